@@ -0,0 +1,26 @@
+//! Monitor configuration (JSON, as services load it) and the string forms accepted in config
+//! files and on the command line: durations, precision modes, and format templates.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use network_speed::{ parse_duration, FormatTemplate, NetworkMonitorConfig, NetworkSpeed, OverheadStats, PrecisionMode };
+
+fuzz_target!(|data: &[u8]| {
+	if let Ok(config) = serde_json::from_slice::<NetworkMonitorConfig>(data) {
+		if config.validate().is_ok() {
+			config.recommended_interval(&OverheadStats::default());
+		}
+	}
+
+	let Ok(text) = std::str::from_utf8(data) else {
+		return;
+	};
+	parse_duration(text);
+	if let Ok(mode) = text.parse::<PrecisionMode>() {
+		mode.measurement_window();
+	}
+	if let Ok(template) = FormatTemplate::parse(text) {
+		template.render(&NetworkSpeed::new(u64::MAX, 0));
+	}
+});
@@ -0,0 +1,19 @@
+//! History/capture files (JSON Lines, optionally gzip) as read by `summary`, `diff`, and `replay`.
+
+#![no_main]
+
+use std::time::Instant;
+
+use libfuzzer_sys::fuzz_target;
+use network_speed::{ read_history_from, records_to_speeds, usage_by_label };
+
+fuzz_target!(|data: &[u8]| {
+	let Ok(records) = read_history_from(data) else {
+		return;
+	};
+	records_to_speeds(&records, Instant::now());
+	usage_by_label(&records, "ssid");
+	for record in &records {
+		record.to_speed();
+	}
+});
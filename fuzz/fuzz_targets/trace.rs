@@ -0,0 +1,19 @@
+//! Counter traces written by `TraceRecorder`.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use network_speed::read_trace_from;
+
+fuzz_target!(|data: &[u8]| {
+	let Ok(snapshots) = read_trace_from(data) else {
+		return;
+	};
+	for pair in snapshots.windows(2) {
+		pair[1].total();
+		for delta in pair[1].diff(&pair[0]) {
+			delta.upload_rate();
+			delta.download_rate();
+		}
+	}
+});
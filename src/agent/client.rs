@@ -0,0 +1,118 @@
+use std::net::{ TcpStream, ToSocketAddrs };
+use std::time::Duration;
+
+use super::protocol::{ io_error, read_message, write_message, Message, RemoteSample };
+#[cfg(feature = "tls")]
+use super::tls::ClientTls;
+use super::Transport;
+use crate::monitor::{ local_time, NetworkMonitor, Shutdown };
+use crate::types::{ NetworkError, NetworkSpeed, Result };
+
+#[derive(Clone, Default)]
+pub struct AgentOptions {
+	pub token: Option<String>,
+	#[cfg(feature = "tls")]
+	pub tls: Option<ClientTls>,
+}
+
+impl AgentOptions {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn with_token(mut self, token: impl Into<String>) -> Self {
+		self.token = Some(token.into());
+		self
+	}
+
+	#[cfg(feature = "tls")]
+	pub fn with_tls(mut self, tls: ClientTls) -> Self {
+		self.tls = Some(tls);
+		self
+	}
+}
+
+/// Streams local samples to a [`Collector`](super::Collector).
+pub struct Agent {
+	stream: Box<dyn Transport>,
+	hostname: String,
+}
+
+impl Agent {
+	pub fn connect(addr: impl ToSocketAddrs, hostname: impl Into<String>) -> Result<Self> {
+		Self::connect_with(addr, hostname, &AgentOptions::default())
+	}
+
+	/// Connects, performs the hello handshake, and waits for the collector to accept the credentials.
+	pub fn connect_with(addr: impl ToSocketAddrs, hostname: impl Into<String>, options: &AgentOptions) -> Result<Self> {
+		let tcp = TcpStream::connect(addr).map_err(io_error)?;
+		tcp.set_nodelay(true).map_err(io_error)?;
+
+		#[cfg(feature = "tls")]
+		let stream: Box<dyn Transport> = match &options.tls {
+			Some(tls) => tls.connect(tcp)?,
+			None => Box::new(tcp),
+		};
+		#[cfg(not(feature = "tls"))]
+		let stream: Box<dyn Transport> = Box::new(tcp);
+
+		let mut agent = Self {
+			stream,
+			hostname: hostname.into(),
+		};
+		write_message(&mut agent.stream, &(Message::Hello {
+			hostname: agent.hostname.clone(),
+			token: options.token.clone(),
+		}))?;
+
+		match read_message(&mut agent.stream)? {
+			Some(Message::Accepted) => Ok(agent),
+			Some(Message::Rejected { reason }) => Err(NetworkError::Protocol { reason: format!("collector rejected agent: {reason}") }),
+			_ =>
+				Err(NetworkError::Protocol {
+					reason: "collector did not acknowledge hello".to_string(),
+				}),
+		}
+	}
+
+	pub fn hostname(&self) -> &str {
+		&self.hostname
+	}
+
+	pub fn send(&mut self, speed: &NetworkSpeed) -> Result<()> {
+		write_message(&mut self.stream, &Message::Sample(RemoteSample::from_speed(speed)))
+	}
+
+	/// Measures with `monitor` every `interval` and forwards each sample until sending fails. The
+	/// monitor config's [`Schedule`](crate::Schedule) stretches the interval while one of its
+	/// windows is active.
+	pub fn run(&mut self, monitor: &mut NetworkMonitor, interval: Duration) -> Result<()> {
+		self.run_until(monitor, interval, &Shutdown::new())
+	}
+
+	/// Like [`run`](Self::run), but returns `Ok(())` once `shutdown` is triggered.
+	pub fn run_until(&mut self, monitor: &mut NetworkMonitor, interval: Duration, shutdown: &Shutdown) -> Result<()> {
+		monitor.measure_speed()?;
+
+		loop {
+			let interval = monitor.get_config().schedule.sampling_interval(interval, local_time());
+			if shutdown.wait_timeout(interval) {
+				return Ok(());
+			}
+
+			match monitor.measure_speed() {
+				Ok(speed) => self.send(&speed)?,
+				Err(NetworkError::InsufficientTimeElapsed { .. }) => {
+					continue;
+				}
+				Err(e) => {
+					return Err(e);
+				}
+			}
+		}
+	}
+
+	pub fn close(mut self) -> Result<()> {
+		write_message(&mut self.stream, &Message::Goodbye)
+	}
+}
@@ -0,0 +1,356 @@
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::net::{ SocketAddr, TcpListener, TcpStream, ToSocketAddrs };
+use std::sync::atomic::{ AtomicUsize, Ordering };
+use std::sync::{ Arc, Mutex };
+use std::thread;
+use std::time::Duration;
+
+use super::protocol::{ io_error, read_message, write_message, Message };
+#[cfg(feature = "tls")]
+use super::tls::ServerTls;
+use super::Transport;
+use crate::monitor::{ NetworkSpeedTracker, Shutdown };
+use crate::types::{ NetworkError, NetworkSpeed, Result };
+
+const ACCEPT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+/// Pause after a failed `accept` (e.g. out of file descriptors) so the loop doesn't spin.
+const ACCEPT_ERROR_BACKOFF: Duration = Duration::from_millis(500);
+const DEFAULT_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+const DEFAULT_MAX_CONNECTIONS: usize = 64;
+
+type HostMap = Arc<Mutex<HashMap<String, NetworkSpeedTracker>>>;
+
+/// Accepts agent connections and keeps one tracker per reporting host.
+///
+/// Listening on a non-loopback address requires a token unless `allow_unauthenticated(true)` is set,
+/// and that token must travel over TLS unless `allow_plaintext_token(true)` is set.
+pub struct Collector {
+	listener: TcpListener,
+	hosts: HostMap,
+	max_history_size: usize,
+	token: Option<Arc<str>>,
+	allow_unauthenticated: bool,
+	allow_plaintext_token: bool,
+	handshake_timeout: Duration,
+	max_connections: usize,
+	connections: Arc<AtomicUsize>,
+	#[cfg(feature = "tls")]
+	tls: Option<ServerTls>,
+}
+
+impl Collector {
+	pub fn bind(addr: impl ToSocketAddrs, max_history_size: usize) -> Result<Self> {
+		let listener = TcpListener::bind(addr).map_err(io_error)?;
+
+		Ok(Self {
+			listener,
+			hosts: Arc::new(Mutex::new(HashMap::new())),
+			max_history_size,
+			token: None,
+			allow_unauthenticated: false,
+			allow_plaintext_token: false,
+			handshake_timeout: DEFAULT_HANDSHAKE_TIMEOUT,
+			max_connections: DEFAULT_MAX_CONNECTIONS,
+			connections: Arc::new(AtomicUsize::new(0)),
+			#[cfg(feature = "tls")]
+			tls: None,
+		})
+	}
+
+	/// Requires agents to present this bearer token in their hello.
+	pub fn with_token(mut self, token: impl Into<String>) -> Self {
+		self.token = Some(Arc::from(token.into()));
+		self
+	}
+
+	pub fn allow_unauthenticated(mut self, allow: bool) -> Self {
+		self.allow_unauthenticated = allow;
+		self
+	}
+
+	/// Accepts a token over an unencrypted connection on a non-loopback address, where anyone on
+	/// the path can read it.
+	pub fn allow_plaintext_token(mut self, allow: bool) -> Self {
+		self.allow_plaintext_token = allow;
+		self
+	}
+
+	/// Time a new connection gets to finish the TLS handshake and hello (default 10 s).
+	pub fn with_handshake_timeout(mut self, timeout: Duration) -> Self {
+		self.handshake_timeout = timeout;
+		self
+	}
+
+	/// Agents served at once (default 64); connections beyond it are closed right away.
+	pub fn with_max_connections(mut self, max_connections: usize) -> Self {
+		self.max_connections = max_connections;
+		self
+	}
+
+	#[cfg(feature = "tls")]
+	pub fn with_tls(mut self, tls: ServerTls) -> Self {
+		self.tls = Some(tls);
+		self
+	}
+
+	pub fn local_addr(&self) -> Result<SocketAddr> {
+		self.listener.local_addr().map_err(io_error)
+	}
+
+	pub fn handle(&self) -> CollectorHandle {
+		CollectorHandle {
+			hosts: Arc::clone(&self.hosts),
+		}
+	}
+
+	/// Accepts connections forever, serving each agent on its own thread.
+	pub fn run(self) -> Result<()> {
+		self.check_exposure()?;
+
+		for stream in self.listener.incoming() {
+			match stream {
+				Ok(stream) => self.serve(stream),
+				Err(_err) => {
+					#[cfg(feature = "tracing")]
+					tracing::warn!(error = %_err, "collector failed to accept a connection");
+					thread::sleep(ACCEPT_ERROR_BACKOFF);
+				}
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Like [`run`](Self::run), but stops accepting new agents once `shutdown` is triggered.
+	/// Connections already being served finish on their own.
+	pub fn run_until(self, shutdown: &Shutdown) -> Result<()> {
+		self.check_exposure()?;
+		self.listener.set_nonblocking(true).map_err(io_error)?;
+
+		while !shutdown.is_triggered() {
+			match self.listener.accept() {
+				Ok((stream, _)) => {
+					if stream.set_nonblocking(false).is_ok() {
+						self.serve(stream);
+					}
+				}
+				Err(err) if err.kind() == ErrorKind::WouldBlock => {
+					shutdown.wait_timeout(ACCEPT_POLL_INTERVAL);
+				}
+				Err(_err) => {
+					#[cfg(feature = "tracing")]
+					tracing::warn!(error = %_err, "collector failed to accept a connection");
+					shutdown.wait_timeout(ACCEPT_ERROR_BACKOFF);
+				}
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Runs the accept loop on a background thread and returns a handle to the collected data.
+	pub fn spawn(self) -> Result<CollectorHandle> {
+		self.check_exposure()?;
+		let handle = self.handle();
+		thread::spawn(move || self.run());
+		Ok(handle)
+	}
+
+	pub fn spawn_until(self, shutdown: &Shutdown) -> Result<CollectorHandle> {
+		self.check_exposure()?;
+		let handle = self.handle();
+		let shutdown = shutdown.clone();
+		thread::spawn(move || self.run_until(&shutdown));
+		Ok(handle)
+	}
+
+	fn serve(&self, stream: TcpStream) {
+		let Some(slot) = ConnectionSlot::acquire(&self.connections, self.max_connections) else {
+			#[cfg(feature = "tracing")]
+			tracing::warn!(max_connections = self.max_connections, "collector at connection limit, closing new connection");
+			return;
+		};
+		let timeout = Some(self.handshake_timeout);
+		if stream.set_read_timeout(timeout).is_err() || stream.set_write_timeout(timeout).is_err() {
+			return;
+		}
+		// Kept to lift the read timeout once the agent is authenticated; the transport owns `stream`.
+		let Ok(control) = stream.try_clone() else {
+			return;
+		};
+
+		let hosts = Arc::clone(&self.hosts);
+		let max_history_size = self.max_history_size;
+		let token = self.token.clone();
+		#[cfg(feature = "tls")]
+		let tls = self.tls.clone();
+
+		thread::spawn(move || {
+			let _slot = slot;
+			#[cfg(feature = "tls")]
+			let transport = match &tls {
+				Some(tls) => tls.accept(stream),
+				None => Ok(Box::new(stream) as Box<dyn Transport>),
+			};
+			#[cfg(not(feature = "tls"))]
+			let transport: Result<Box<dyn Transport>> = Ok(Box::new(stream));
+
+			if let Ok(transport) = transport {
+				let _ = serve_agent(transport, &control, hosts, max_history_size, token.as_deref());
+			}
+		});
+	}
+
+	/// The check every `run`/`spawn` variant performs first: a collector bound to a non-loopback
+	/// address needs a token unless `allow_unauthenticated` is set, and TLS for that token unless
+	/// `allow_plaintext_token` is set.
+	pub fn check_exposure(&self) -> Result<()> {
+		let exposed = !self.local_addr()?.ip().is_loopback();
+		if exposed && self.token.is_none() && !self.allow_unauthenticated {
+			return Err(NetworkError::InvalidConfiguration {
+				field: "collector on a non-loopback address requires a token (or allow_unauthenticated)".to_string(),
+			});
+		}
+		if exposed && self.token.is_some() && !self.has_tls() && !self.allow_plaintext_token {
+			return Err(NetworkError::InvalidConfiguration {
+				field: "collector on a non-loopback address requires TLS to protect the token (or allow_plaintext_token)".to_string(),
+			});
+		}
+		Ok(())
+	}
+
+	#[cfg(feature = "tls")]
+	fn has_tls(&self) -> bool {
+		self.tls.is_some()
+	}
+
+	#[cfg(not(feature = "tls"))]
+	fn has_tls(&self) -> bool {
+		false
+	}
+}
+
+/// One of the collector's `max_connections`, released when the serving thread ends.
+struct ConnectionSlot(Arc<AtomicUsize>);
+
+impl ConnectionSlot {
+	fn acquire(connections: &Arc<AtomicUsize>, max_connections: usize) -> Option<Self> {
+		connections
+			.fetch_update(Ordering::AcqRel, Ordering::Acquire, |active| (active < max_connections).then_some(active + 1))
+			.ok()
+			.map(|_| Self(Arc::clone(connections)))
+	}
+}
+
+impl Drop for ConnectionSlot {
+	fn drop(&mut self) {
+		self.0.fetch_sub(1, Ordering::AcqRel);
+	}
+}
+
+fn serve_agent(
+	mut stream: Box<dyn Transport>,
+	control: &TcpStream,
+	hosts: HostMap,
+	max_history_size: usize,
+	token: Option<&str>
+) -> Result<()> {
+	let hostname = match read_message(&mut stream)? {
+		Some(Message::Hello { hostname, token: presented }) => {
+			if let Some(expected) = token {
+				let authorized = presented.is_some_and(|presented| constant_time_eq(presented.as_bytes(), expected.as_bytes()));
+				if !authorized {
+					write_message(&mut stream, &(Message::Rejected {
+						reason: "invalid or missing token".to_string(),
+					}))?;
+					return Ok(());
+				}
+			}
+			write_message(&mut stream, &Message::Accepted)?;
+			// Authenticated agents may go quiet between samples for as long as they like.
+			control.set_read_timeout(None).map_err(io_error)?;
+			hostname
+		}
+		Some(_) => {
+			return Err(NetworkError::Protocol {
+				reason: "expected hello as first message".to_string(),
+			});
+		}
+		None => {
+			return Ok(());
+		}
+	};
+
+	while let Some(message) = read_message(&mut stream)? {
+		match message {
+			Message::Sample(sample) => {
+				let mut hosts = lock_hosts(&hosts)?;
+				hosts
+					.entry(hostname.clone())
+					.or_insert_with(|| NetworkSpeedTracker::new(max_history_size))
+					.record(sample.to_speed());
+			}
+			Message::Goodbye => {
+				break;
+			}
+			_ => {
+				return Err(NetworkError::Protocol {
+					reason: "unexpected message from agent".to_string(),
+				});
+			}
+		}
+	}
+
+	Ok(())
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+	if a.len() != b.len() {
+		return false;
+	}
+	a.iter()
+		.zip(b)
+		.fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn lock_hosts(hosts: &HostMap) -> Result<std::sync::MutexGuard<'_, HashMap<String, NetworkSpeedTracker>>> {
+	hosts.lock().map_err(|_| NetworkError::LockPoisoned { resource: "Collector" })
+}
+
+/// Cheap, clonable view over the per-host trackers of a running [`Collector`].
+#[derive(Clone)]
+pub struct CollectorHandle {
+	hosts: HostMap,
+}
+
+impl CollectorHandle {
+	pub fn hosts(&self) -> Vec<String> {
+		let mut names: Vec<String> = lock_hosts(&self.hosts)
+			.map(|hosts| hosts.keys().cloned().collect())
+			.unwrap_or_default();
+		names.sort();
+		names
+	}
+
+	pub fn latest(&self, host: &str) -> Option<NetworkSpeed> {
+		self.with_tracker(host, |tracker| tracker.latest().cloned()).flatten()
+	}
+
+	pub fn history(&self, host: &str) -> Vec<NetworkSpeed> {
+		self.with_tracker(host, |tracker| tracker.get_history()).unwrap_or_default()
+	}
+
+	pub fn average_speed(&self, host: &str, duration: Duration) -> Option<NetworkSpeed> {
+		self.with_tracker(host, |tracker| tracker.get_average_speed(duration)).flatten()
+	}
+
+	pub fn peak_speed(&self, host: &str, duration: Duration) -> Option<NetworkSpeed> {
+		self.with_tracker(host, |tracker| tracker.get_peak_speed(duration)).flatten()
+	}
+
+	pub fn with_tracker<T>(&self, host: &str, f: impl FnOnce(&NetworkSpeedTracker) -> T) -> Option<T> {
+		let hosts = lock_hosts(&self.hosts).ok()?;
+		hosts.get(host).map(f)
+	}
+}
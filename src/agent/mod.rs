@@ -0,0 +1,18 @@
+use std::io::{ Read, Write };
+
+pub mod client;
+pub mod collector;
+pub mod protocol;
+#[cfg(feature = "tls")]
+pub mod tls;
+
+pub use client::*;
+pub use collector::*;
+pub use protocol::*;
+#[cfg(feature = "tls")]
+pub use tls::*;
+
+/// Byte stream carrying the protocol: a plain `TcpStream` or a TLS session over one.
+pub(crate) trait Transport: Read + Write + Send {}
+
+impl<T: Read + Write + Send> Transport for T {}
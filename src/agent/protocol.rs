@@ -0,0 +1,219 @@
+use std::io::{ ErrorKind, Read, Write };
+use std::time::{ Duration, Instant, SystemTime, UNIX_EPOCH };
+
+use crate::types::{ NetworkError, NetworkSpeed, Result };
+
+pub const PROTOCOL_VERSION: u8 = 2;
+pub const MAX_FRAME_LEN: usize = 64 * 1024;
+
+const KIND_HELLO: u8 = 1;
+const KIND_SAMPLE: u8 = 2;
+const KIND_GOODBYE: u8 = 3;
+const KIND_ACCEPTED: u8 = 4;
+const KIND_REJECTED: u8 = 5;
+
+/// A sample as sent over the wire, timestamped with wall-clock milliseconds since the Unix epoch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RemoteSample {
+	pub timestamp_ms: u64,
+	pub upload_bytes_per_sec: u64,
+	pub download_bytes_per_sec: u64,
+}
+
+impl RemoteSample {
+	pub fn from_speed(speed: &NetworkSpeed) -> Self {
+		let age = Instant::now().saturating_duration_since(speed.timestamp);
+		let timestamp = SystemTime::now().checked_sub(age).unwrap_or(UNIX_EPOCH);
+
+		Self {
+			timestamp_ms: unix_millis(timestamp),
+			upload_bytes_per_sec: speed.upload_bytes_per_sec.bytes_per_sec(),
+			download_bytes_per_sec: speed.download_bytes_per_sec.bytes_per_sec(),
+		}
+	}
+
+	/// Maps the sample back onto the local monotonic clock; samples from the future are clamped to now.
+	pub fn to_speed(&self) -> NetworkSpeed {
+		let now = Instant::now();
+		let age = Duration::from_millis(unix_millis(SystemTime::now()).saturating_sub(self.timestamp_ms));
+
+		NetworkSpeed {
+			timestamp: now.checked_sub(age).unwrap_or(now),
+			..NetworkSpeed::new(self.upload_bytes_per_sec, self.download_bytes_per_sec)
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Message {
+	Hello {
+		hostname: String,
+		token: Option<String>,
+	},
+	Sample(RemoteSample),
+	Goodbye,
+	/// Collector reply to a hello whose credentials were accepted.
+	Accepted,
+	/// Collector reply to a hello that failed authentication; the connection is closed afterwards.
+	Rejected {
+		reason: String,
+	},
+}
+
+impl Message {
+	/// Encodes the payload (version, kind, body) without the length prefix; fails if it would exceed
+	/// [`MAX_FRAME_LEN`], since the peer would reject the frame anyway.
+	pub fn encode(&self) -> Result<Vec<u8>> {
+		let mut payload = vec![PROTOCOL_VERSION];
+
+		match self {
+			Message::Hello { hostname, token } => {
+				payload.push(KIND_HELLO);
+				put_string(&mut payload, hostname)?;
+				match token {
+					Some(token) => {
+						payload.push(1);
+						put_string(&mut payload, token)?;
+					}
+					None => payload.push(0),
+				}
+			}
+			Message::Sample(sample) => {
+				payload.push(KIND_SAMPLE);
+				payload.extend_from_slice(&sample.timestamp_ms.to_be_bytes());
+				payload.extend_from_slice(&sample.upload_bytes_per_sec.to_be_bytes());
+				payload.extend_from_slice(&sample.download_bytes_per_sec.to_be_bytes());
+			}
+			Message::Goodbye => payload.push(KIND_GOODBYE),
+			Message::Accepted => payload.push(KIND_ACCEPTED),
+			Message::Rejected { reason } => {
+				payload.push(KIND_REJECTED);
+				put_string(&mut payload, reason)?;
+			}
+		}
+
+		if payload.len() > MAX_FRAME_LEN {
+			return Err(protocol_error(&format!("frame of {} bytes exceeds limit", payload.len())));
+		}
+		Ok(payload)
+	}
+
+	pub fn decode(payload: &[u8]) -> Result<Self> {
+		let (&version, rest) = payload.split_first().ok_or_else(|| protocol_error("empty frame"))?;
+		if version != PROTOCOL_VERSION {
+			return Err(protocol_error(&format!("unsupported protocol version {version} (expected {PROTOCOL_VERSION})")));
+		}
+
+		let (&kind, body) = rest.split_first().ok_or_else(|| protocol_error("missing message kind"))?;
+		match kind {
+			KIND_HELLO => {
+				let (hostname, rest) = take_string(body)?;
+				let token = match rest.split_first() {
+					Some((0, [])) => None,
+					Some((1, rest)) => {
+						let (token, rest) = take_string(rest)?;
+						if !rest.is_empty() {
+							return Err(protocol_error("trailing bytes after hello"));
+						}
+						Some(token)
+					}
+					_ => {
+						return Err(protocol_error("malformed hello"));
+					}
+				};
+				Ok(Message::Hello { hostname, token })
+			}
+			KIND_SAMPLE => {
+				if body.len() != 24 {
+					return Err(protocol_error("malformed sample"));
+				}
+				let field = |index: usize| {
+					let mut bytes = [0u8; 8];
+					bytes.copy_from_slice(&body[index * 8..(index + 1) * 8]);
+					u64::from_be_bytes(bytes)
+				};
+				Ok(
+					Message::Sample(RemoteSample {
+						timestamp_ms: field(0),
+						upload_bytes_per_sec: field(1),
+						download_bytes_per_sec: field(2),
+					})
+				)
+			}
+			KIND_GOODBYE => Ok(Message::Goodbye),
+			KIND_ACCEPTED => Ok(Message::Accepted),
+			KIND_REJECTED => {
+				let (reason, _) = take_string(body)?;
+				Ok(Message::Rejected { reason })
+			}
+			other => Err(protocol_error(&format!("unknown message kind {other}"))),
+		}
+	}
+}
+
+pub fn write_message<W: Write>(writer: &mut W, message: &Message) -> Result<()> {
+	let payload = message.encode()?;
+	let len = payload.len() as u32;
+	writer.write_all(&len.to_be_bytes()).map_err(io_error)?;
+	writer.write_all(&payload).map_err(io_error)?;
+	writer.flush().map_err(io_error)
+}
+
+/// Reads one frame; returns `Ok(None)` when the peer closed the connection cleanly.
+pub fn read_message<R: Read>(reader: &mut R) -> Result<Option<Message>> {
+	let mut len_bytes = [0u8; 4];
+	match reader.read_exact(&mut len_bytes) {
+		Ok(()) => {}
+		Err(err) if err.kind() == ErrorKind::UnexpectedEof => {
+			return Ok(None);
+		}
+		Err(err) => {
+			return Err(io_error(err));
+		}
+	}
+
+	let len = u32::from_be_bytes(len_bytes) as usize;
+	if len > MAX_FRAME_LEN {
+		return Err(protocol_error(&format!("frame of {len} bytes exceeds limit")));
+	}
+
+	let mut payload = vec![0u8; len];
+	reader.read_exact(&mut payload).map_err(io_error)?;
+	Message::decode(&payload).map(Some)
+}
+
+fn put_string(payload: &mut Vec<u8>, value: &str) -> Result<()> {
+	let bytes = value.as_bytes();
+	let len = u16::try_from(bytes.len()).map_err(|_| protocol_error("string field too long"))?;
+	payload.extend_from_slice(&len.to_be_bytes());
+	payload.extend_from_slice(bytes);
+	Ok(())
+}
+
+fn take_string(body: &[u8]) -> Result<(String, &[u8])> {
+	let len_bytes: [u8; 2] = body
+		.get(..2)
+		.and_then(|b| b.try_into().ok())
+		.ok_or_else(|| protocol_error("truncated string length"))?;
+	let len = u16::from_be_bytes(len_bytes) as usize;
+	let bytes = body.get(2..2 + len).ok_or_else(|| protocol_error("truncated string"))?;
+	let value = std::str::from_utf8(bytes).map_err(|_| protocol_error("string field is not UTF-8"))?;
+	Ok((value.to_string(), &body[2 + len..]))
+}
+
+fn unix_millis(time: SystemTime) -> u64 {
+	time
+		.duration_since(UNIX_EPOCH)
+		.map(|elapsed| elapsed.as_millis() as u64)
+		.unwrap_or(0)
+}
+
+fn protocol_error(reason: &str) -> NetworkError {
+	NetworkError::Protocol {
+		reason: reason.to_string(),
+	}
+}
+
+pub(crate) fn io_error(err: std::io::Error) -> NetworkError {
+	NetworkError::Io(err)
+}
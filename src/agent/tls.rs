@@ -0,0 +1,115 @@
+use std::fs;
+use std::io::BufReader;
+use std::net::TcpStream;
+use std::path::Path;
+use std::sync::Arc;
+
+use rustls::pki_types::{ CertificateDer, ServerName };
+use rustls::{ ClientConfig, ClientConnection, RootCertStore, ServerConfig, ServerConnection, StreamOwned };
+
+use super::protocol::io_error;
+use super::Transport;
+use crate::types::{ NetworkError, Result };
+
+/// Certificate and key used by a [`Collector`](super::Collector) to terminate TLS.
+#[derive(Clone)]
+pub struct ServerTls {
+	config: Arc<ServerConfig>,
+}
+
+impl ServerTls {
+	pub fn from_pem(cert_chain_pem: &[u8], private_key_pem: &[u8]) -> Result<Self> {
+		let certs = parse_certs(cert_chain_pem)?;
+		let key = rustls_pemfile
+			::private_key(&mut BufReader::new(private_key_pem))
+			.map_err(io_error)?
+			.ok_or_else(|| tls_error("no private key found in PEM"))?;
+
+		let config = ServerConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+			.with_safe_default_protocol_versions()
+			.map_err(tls_error)?
+			.with_no_client_auth()
+			.with_single_cert(certs, key)
+			.map_err(tls_error)?;
+
+		Ok(Self {
+			config: Arc::new(config),
+		})
+	}
+
+	pub fn from_pem_files(cert_chain_path: impl AsRef<Path>, private_key_path: impl AsRef<Path>) -> Result<Self> {
+		let certs = fs::read(cert_chain_path).map_err(io_error)?;
+		let key = fs::read(private_key_path).map_err(io_error)?;
+		Self::from_pem(&certs, &key)
+	}
+
+	pub(crate) fn accept(&self, stream: TcpStream) -> Result<Box<dyn Transport>> {
+		let connection = ServerConnection::new(Arc::clone(&self.config)).map_err(tls_error)?;
+		Ok(Box::new(StreamOwned::new(connection, stream)))
+	}
+}
+
+/// Trust roots and expected server name used by an [`Agent`](super::Agent) connecting over TLS.
+#[derive(Clone)]
+pub struct ClientTls {
+	config: Arc<ClientConfig>,
+	server_name: ServerName<'static>,
+}
+
+impl ClientTls {
+	pub fn from_ca_pem(ca_pem: &[u8], server_name: &str) -> Result<Self> {
+		let mut roots = RootCertStore::empty();
+		for cert in parse_certs(ca_pem)? {
+			roots.add(cert).map_err(tls_error)?;
+		}
+
+		let config = ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+			.with_safe_default_protocol_versions()
+			.map_err(tls_error)?
+			.with_root_certificates(roots)
+			.with_no_client_auth();
+		let server_name = ServerName::try_from(server_name.to_string()).map_err(tls_error)?;
+
+		Ok(Self {
+			config: Arc::new(config),
+			server_name,
+		})
+	}
+
+	pub fn from_ca_pem_file(ca_path: impl AsRef<Path>, server_name: &str) -> Result<Self> {
+		let ca = fs::read(ca_path).map_err(io_error)?;
+		Self::from_ca_pem(&ca, server_name)
+	}
+
+	/// The same trust roots, checking `server_name` instead, for clients that reach several hosts.
+	pub(crate) fn with_server_name(&self, server_name: &str) -> Result<Self> {
+		Ok(Self {
+			config: Arc::clone(&self.config),
+			server_name: ServerName::try_from(server_name.to_string()).map_err(tls_error)?,
+		})
+	}
+
+	pub(crate) fn connect(&self, stream: TcpStream) -> Result<Box<dyn Transport>> {
+		let connection = ClientConnection::new(Arc::clone(&self.config), self.server_name.clone()).map_err(tls_error)?;
+		Ok(Box::new(StreamOwned::new(connection, stream)))
+	}
+}
+
+fn parse_certs(pem: &[u8]) -> Result<Vec<CertificateDer<'static>>> {
+	let certs = rustls_pemfile
+		::certs(&mut BufReader::new(pem))
+		.collect::<std::result::Result<Vec<_>, _>>()
+		.map_err(io_error)?;
+
+	if certs.is_empty() {
+		return Err(tls_error("no certificates found in PEM"));
+	}
+
+	Ok(certs)
+}
+
+fn tls_error(err: impl std::fmt::Display) -> NetworkError {
+	NetworkError::Protocol {
+		reason: format!("TLS: {err}"),
+	}
+}
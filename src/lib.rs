@@ -1,30 +1,45 @@
 #![cfg_attr(docsrs, feature(doc_cfg))]
-#![doc = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/docs/DOCS.md"))]
+#![cfg_attr(feature = "platform", doc = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/docs/DOCS.md")))]
 #![doc(html_root_url = "https://docs.rs/network-speed")]
 
+#[cfg(feature = "agent")]
+pub mod agent;
+#[cfg(feature = "platform")]
 pub mod monitor;
+#[cfg(feature = "platform")]
+pub mod probe;
 pub mod types;
 
+#[cfg(feature = "agent")]
+pub use agent::{ Agent, AgentOptions, Collector, CollectorHandle };
+#[cfg(feature = "platform")]
 pub use monitor::*;
+#[cfg(feature = "platform")]
+pub use probe::{ dns_probe, http_ttfb, public_ip_probe, tcp_connect_time, LinkQuality };
 pub use types::*;
 
+#[cfg(feature = "platform")]
 pub use monitor::sync_monitor::{ NetworkMonitor, NetworkSpeedTracker };
 
 #[cfg(feature = "async")]
-pub use monitor::async_monitor::{ AsyncNetworkMonitor, AsyncNetworkSpeedTracker };
+pub use monitor::async_monitor::{ AsyncNetworkMonitor, AsyncNetworkSpeedTracker, PartialSamples };
 
+#[cfg(feature = "platform")]
 pub fn create_monitor() -> NetworkMonitor {
 	NetworkMonitor::new()
 }
 
+#[cfg(feature = "platform")]
 pub fn create_monitor_with_config(config: NetworkMonitorConfig) -> NetworkMonitor {
 	NetworkMonitor::with_config(config)
 }
 
+#[cfg(feature = "platform")]
 pub fn create_speed_tracker(max_history_size: usize) -> NetworkSpeedTracker {
 	NetworkSpeedTracker::new(max_history_size)
 }
 
+#[cfg(feature = "platform")]
 pub fn create_speed_tracker_with_config(config: NetworkMonitorConfig, max_history_size: usize) -> NetworkSpeedTracker {
 	NetworkSpeedTracker::with_config(config, max_history_size)
 }
@@ -52,10 +67,18 @@ pub fn create_async_speed_tracker_with_config(
 	AsyncNetworkSpeedTracker::with_config(config, max_history_size)
 }
 
+#[cfg(feature = "platform")]
 pub fn list_interfaces() -> Result<Vec<NetworkInterface>> {
 	monitor::interface::list_all_interfaces()
 }
 
+/// See [`SortBy`]; `SortBy::Throughput` blocks for [`THROUGHPUT_SAMPLE_WINDOW`].
+#[cfg(feature = "platform")]
+pub fn list_interfaces_sorted(by: SortBy) -> Result<Vec<NetworkInterface>> {
+	monitor::interface::list_all_interfaces_sorted(by)
+}
+
+#[cfg(feature = "platform")]
 pub fn get_interface_count() -> Result<usize> {
 	monitor::interface::get_interface_count()
 }
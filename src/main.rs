@@ -1,35 +1,375 @@
 #![cfg(feature = "cli")]
 
 use std::env;
+use std::io::{ self, BufRead, Write };
+use std::num::NonZeroUsize;
+use std::path::{ Path, PathBuf };
+use std::process;
 use std::thread;
-use std::time::Duration;
+use std::time::{ Duration, Instant };
 
-use chrono::Local;
-use network_speed::types::format_bytes_per_second;
-use network_speed::{ list_interfaces, NetworkMonitor, NetworkMonitorConfig };
+use chrono::{ DateTime, Local };
+use clap::{ Args, CommandFactory, Parser, Subcommand, ValueEnum };
+use clap_complete::Shell;
+#[cfg(windows)]
+use network_speed::{ probe_capabilities, ProviderKind };
+use network_speed::types::{ format_bytes_per_second, format_bytes_total, format_duration, parse_duration };
+use network_speed::{
+	list_interfaces,
+	read_history,
+	records_to_speeds,
+	summarize,
+	Agent,
+	AgentOptions,
+	Collector,
+	FormatTemplate,
+	HistoryCompression,
+	NetworkInterface,
+	NetworkMonitor,
+	NetworkMonitorConfig,
+	NetworkSpeed,
+	NetworkSpeedTracker,
+	PrecisionMode,
+	Shutdown,
+	SpeedSummary,
+};
+
+/// Samples kept for the exit summary: a day at one line per second.
+const SUMMARY_CAPACITY: usize = 86_400;
+/// Measurements `diag` takes to estimate the sampling overhead.
+const DIAG_SAMPLES: u32 = 5;
+/// Task Scheduler name used by `schedule` when none is given.
+const DEFAULT_TASK_NAME: &str = "network-speed";
+/// Slowest `replay --speed` accepted; anything below would stretch gaps into days.
+const MIN_REPLAY_SPEED: f64 = 0.001;
+
+/// Network Speed Monitor
+#[derive(Parser)]
+#[command(name = "network-speed", version, after_help = "Set NETWORK_SPEED_TOKEN on both sides to authenticate agents.")]
+struct Cli {
+	#[command(subcommand)]
+	command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+	/// Monitor network speed (default)
+	Monitor(MonitorArgs),
+	/// List all network interfaces
+	List,
+	/// Stream samples to a collector
+	Agent {
+		/// Collector address, e.g. collector-host:7878
+		collector: String,
+		/// Time between samples, e.g. 500ms or 2s; a bare number is milliseconds
+		#[arg(default_value = "1s", value_parser = parse_interval)]
+		interval: Duration,
+	},
+	/// Aggregate samples from agents
+	Collector {
+		#[arg(default_value = "0.0.0.0:7878")]
+		bind_addr: String,
+		/// Samples kept per host
+		#[arg(long, default_value = "3600")]
+		history: NonZeroUsize,
+		/// Accept NETWORK_SPEED_TOKEN over unencrypted connections on a non-loopback address
+		#[arg(long)]
+		allow_plaintext_token: bool,
+	},
+	/// Print a diagnostics report to paste into bug reports
+	Diag,
+	/// Compare two recorded sessions (history .jsonl files, optionally gzip-compressed)
+	Diff {
+		before: PathBuf,
+		after: PathBuf,
+	},
+	/// Play back a recorded session (history .jsonl file) through the monitor output
+	Replay {
+		capture: PathBuf,
+		/// Playback rate, e.g. 10x or 0.5x
+		#[arg(long, default_value = "1x", value_parser = parse_replay_speed)]
+		speed: f64,
+		/// Sample line template, see `monitor --format`
+		#[arg(long, value_parser = parse_template)]
+		format: Option<FormatTemplate>,
+	},
+	/// Register or remove a Windows Task Scheduler job for periodic measurements
+	Schedule {
+		#[command(subcommand)]
+		action: ScheduleAction,
+	},
+	/// Answer sample and interface requests on stdin with JSON lines, for the PowerShell module
+	#[command(name = "pwsh-server")]
+	PwshServer,
+	/// Print a shell completion script to stdout
+	Completions {
+		shell: Shell,
+	},
+}
+
+#[derive(Subcommand)]
+enum ScheduleAction {
+	/// Register a task that measures periodically and appends the samples to a file
+	Install(ScheduleArgs),
+	/// Remove a task registered with `schedule install`
+	Remove {
+		/// Task Scheduler task name
+		#[arg(long, default_value = DEFAULT_TASK_NAME)]
+		name: String,
+	},
+}
+
+#[derive(Args)]
+struct ScheduleArgs {
+	/// Time between runs in whole minutes, e.g. 30m, 6h, or 48h
+	#[arg(long, value_parser = parse_positive_duration)]
+	interval: Duration,
+	/// What each run does
+	#[arg(long, value_enum, default_value = "monitor")]
+	command: ScheduledCommand,
+	/// How long each run measures
+	#[arg(long, default_value = "30s", value_parser = parse_positive_duration)]
+	sample_duration: Duration,
+	/// History file each run appends its samples to
+	#[arg(long)]
+	output: PathBuf,
+	/// Task Scheduler task name
+	#[arg(long, default_value = DEFAULT_TASK_NAME)]
+	name: String,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ScheduledCommand {
+	/// Monitor for --sample-duration and record the samples
+	Monitor,
+}
+
+#[derive(Args, Default)]
+struct MonitorArgs {
+	/// instant, windowed:<time>, or samples:<count>x<time>, e.g. samples:5x200ms
+	#[arg(long, default_value = "instant", value_parser = parse_precision)]
+	precision: PrecisionMode,
+	/// Stop after this many samples and print a summary
+	#[arg(long)]
+	count: Option<NonZeroUsize>,
+	/// Stop after this long, e.g. 30s or 5m, and print a summary
+	#[arg(long, value_parser = parse_positive_duration)]
+	duration: Option<Duration>,
+	/// Sample line template, e.g. "{ts:%H:%M:%S} ↑{up:mbps:.1} ↓{down:mbps:.1}"; placeholders are
+	/// up, down, total (with a unit such as mbps, MB, or MiB and a precision such as .1) and ts
+	#[arg(long, value_parser = parse_template)]
+	format: Option<FormatTemplate>,
+	/// Append the samples to this history file when done; a .gz name writes gzip
+	#[arg(long)]
+	output: Option<PathBuf>,
+}
 
 fn main() {
-	let mut args = env::args();
-	let _binary = args.next();
-	match args.next().as_deref() {
-		Some("list") => list_interfaces_command(),
-		Some("monitor") | None => monitor_command(),
-		Some("help") | Some("--help") | Some("-h") => print_help(),
-		Some(other) => {
-			eprintln!("Unknown command: {other}");
-			print_help();
+	match Cli::parse().command.unwrap_or(Command::Monitor(MonitorArgs::default())) {
+		Command::Monitor(args) => monitor_command(&args, &ctrl_c_shutdown()),
+		Command::List => list_interfaces_command(),
+		Command::Diag => diag_command(),
+		Command::Diff { before, after } => diff_command(&before, &after),
+		Command::Replay { capture, speed, format } => replay_command(&capture, speed, format.as_ref(), &ctrl_c_shutdown()),
+		Command::Agent { collector, interval } => agent_command(&collector, interval, &ctrl_c_shutdown()),
+		Command::Collector { bind_addr, history, allow_plaintext_token } =>
+			collector_command(&bind_addr, history.get(), allow_plaintext_token, &ctrl_c_shutdown()),
+		Command::Schedule { action: ScheduleAction::Install(args) } => schedule_install_command(&args),
+		Command::Schedule { action: ScheduleAction::Remove { name } } => schedule_remove_command(&name),
+		Command::PwshServer => pwsh_server_command(),
+		Command::Completions { shell } => {
+			clap_complete::generate(shell, &mut Cli::command(), "network-speed", &mut io::stdout());
 		}
 	}
 }
 
-fn print_help() {
-	println!("Network Speed Monitor");
-	println!("Usage: cargo run --features cli --bin network-speed [COMMAND]");
+/// Shutdown signal for the long-running commands, triggered by Ctrl+C.
+fn ctrl_c_shutdown() -> Shutdown {
+	let shutdown = Shutdown::new();
+	if let Err(err) = shutdown.trigger_on_ctrl_c() {
+		eprintln!("Ctrl+C handler not installed, stopping will skip cleanup: {err}");
+	}
+	shutdown
+}
+
+fn parse_precision(value: &str) -> Result<PrecisionMode, String> {
+	value.parse().map_err(|err: network_speed::NetworkError| err.to_string())
+}
+
+fn parse_positive_duration(value: &str) -> Result<Duration, String> {
+	parse_duration(value)
+		.filter(|duration| !duration.is_zero())
+		.ok_or_else(|| format!("expected a positive duration such as 500ms, 30s, or 5m, got \"{value}\""))
+}
+
+fn parse_interval(value: &str) -> Result<Duration, String> {
+	match value.parse::<u64>() {
+		Ok(0) => Err("interval must be positive".to_string()),
+		Ok(ms) => Ok(Duration::from_millis(ms)),
+		Err(_) => parse_positive_duration(value),
+	}
+}
+
+fn parse_template(value: &str) -> Result<FormatTemplate, String> {
+	value.parse().map_err(|err: network_speed::NetworkError| err.to_string())
+}
+
+fn parse_replay_speed(value: &str) -> Result<f64, String> {
+	let rate = value
+		.strip_suffix(['x', 'X'])
+		.unwrap_or(value)
+		.parse::<f64>()
+		.map_err(|_| format!("invalid replay speed '{value}', expected e.g. 10x"))?;
+	if rate.is_finite() && rate >= MIN_REPLAY_SPEED {
+		Ok(rate)
+	} else {
+		Err(format!("replay speed must be at least {MIN_REPLAY_SPEED}x"))
+	}
+}
+
+fn diag_command() {
+	println!("network-speed {} diagnostics", env!("CARGO_PKG_VERSION"));
+
+	#[cfg(windows)]
+	{
+		println!();
+		println!("Capabilities:");
+		let capabilities = probe_capabilities();
+		for kind in ProviderKind::ALL {
+			let available = if capabilities.is_available(kind) { "available" } else { "missing" };
+			let elevation = if kind.requires_elevation() { " (needs elevation)" } else { "" };
+			println!("  {:<10} {available}{elevation}", kind.as_str());
+		}
+	}
+
+	let config = monitor_config(PrecisionMode::Instant);
+	let interval = config.min_measurement_interval;
+	let mut monitor = NetworkMonitor::with_config(config);
+	let mut failures = Vec::new();
+	for sample in 0..DIAG_SAMPLES {
+		if sample > 0 {
+			thread::sleep(interval);
+		}
+		if let Err(err) = monitor.measure_speed() {
+			failures.push(err);
+		}
+	}
+
+	println!();
+	println!("Provider:");
+	match monitor.provider_info() {
+		Some(info) => {
+			println!("  {} ({}-bit counters)", info.provider.as_str(), info.provider.counter_bits());
+			if info.is_legacy() {
+				println!("  legacy fallback: no physical medium or GUID, counters wrap at 4 GiB");
+			}
+		}
+		None => println!("  none (no measurement succeeded)"),
+	}
+
 	println!();
-	println!("Commands:");
-	println!("  monitor    Monitor network speed (default)");
-	println!("  list       List all network interfaces");
-	println!("  help       Show this help message");
+	println!("Interfaces:");
+	match monitor.explain_interfaces() {
+		Ok(decisions) => {
+			for decision in decisions {
+				let interface = &decision.interface;
+				println!(
+					"  #{:<3} {:<40} {:<10} {:<8} {:<10} {}",
+					interface.index,
+					interface.description.trim(),
+					interface.type_name(),
+					interface.oper_status.as_str(),
+					interface.category().as_str(),
+					decision.reason
+				);
+			}
+		}
+		Err(err) => println!("  enumeration failed: {err}"),
+	}
+	println!("  contributing to totals: {:?}", monitor.contributing_interfaces());
+
+	println!();
+	println!("Sampling overhead:");
+	let overhead = monitor.overhead_stats();
+	println!(
+		"  {} calls, average {:?} wall / {:?} CPU, max {:?}",
+		overhead.calls,
+		overhead.average_wall_time(),
+		overhead.average_cpu_time(),
+		overhead.max_call_wall_time
+	);
+	println!("  recommended interval: {:?}", monitor.recommended_interval());
+	for err in &failures {
+		let hint = err
+			.win32_category()
+			.map(|category| format!(" ({})", category.hint()))
+			.unwrap_or_default();
+		println!("  error {}: {err}{hint}", err.error_code());
+	}
+}
+
+fn diff_command(before: &Path, after: &Path) {
+	let (Some(before_summary), Some(after_summary)) = (load_summary(before), load_summary(after)) else {
+		return;
+	};
+
+	println!("{:<14} {:>16} {:>16} {:>10}", "", before.display(), after.display(), "change");
+	println!("{:-<60}", "");
+	let rows = [
+		("average", &before_summary.average, &after_summary.average),
+		("peak", &before_summary.peak, &after_summary.peak),
+		("p95", &before_summary.p95, &after_summary.p95),
+	];
+	for (label, earlier, later) in rows {
+		print_diff_row(&format!("{label} ↑"), earlier.upload_formatted(), later.upload_formatted(), later.upload_percent_change(earlier));
+		print_diff_row(
+			&format!("{label} ↓"),
+			earlier.download_formatted(),
+			later.download_formatted(),
+			later.download_percent_change(earlier)
+		);
+	}
+
+	let totals = [
+		("total ↑", before_summary.bytes_sent, after_summary.bytes_sent),
+		("total ↓", before_summary.bytes_received, after_summary.bytes_received),
+	];
+	for (label, earlier, later) in totals {
+		print_diff_row(
+			label,
+			format_bytes_total(earlier),
+			format_bytes_total(later),
+			(earlier > 0).then(|| ((later as f64) - (earlier as f64)) / (earlier as f64) * 100.0)
+		);
+	}
+	print_diff_row(
+		"duration",
+		format_duration(before_summary.duration),
+		format_duration(after_summary.duration),
+		None
+	);
+	print_diff_row("samples", before_summary.samples.to_string(), after_summary.samples.to_string(), None);
+}
+
+fn load_summary(path: &Path) -> Option<SpeedSummary> {
+	let records = match read_history(path) {
+		Ok(records) => records,
+		Err(err) => {
+			eprintln!("Failed to read {}: {err}", path.display());
+			return None;
+		}
+	};
+
+	let summary = summarize(&records_to_speeds(&records, Instant::now()));
+	if summary.is_none() {
+		eprintln!("{} contains no samples", path.display());
+	}
+	summary
+}
+
+fn print_diff_row(label: &str, before: String, after: String, change: Option<f64>) {
+	let change = change.map(|percent| format!("{percent:+.1}%")).unwrap_or_else(|| "—".to_string());
+	println!("{label:<14} {before:>16} {after:>16} {change:>10}");
 }
 
 fn list_interfaces_command() {
@@ -61,44 +401,364 @@ fn list_interfaces_command() {
 	}
 }
 
-fn monitor_command() {
-	println!("Network Speed Monitor — press Ctrl+C to stop");
+fn monitor_command(options: &MonitorArgs, shutdown: &Shutdown) {
+	let precision = options.precision.clone();
+
+	println!("Network Speed Monitor ({precision}) — press Ctrl+C to stop");
 	println!("{:-<80}", "");
 
-	let config = NetworkMonitorConfig::builder()
+	// Windowed and sampled measurements block for their window; keep one line per second.
+	let pause = Duration::from_secs(1).saturating_sub(precision.measurement_window());
+	let mut tracker = NetworkSpeedTracker::with_config(monitor_config(precision), SUMMARY_CAPACITY);
+
+	if let Err(err) = tracker.track_speed() {
+		eprintln!("Initial measurement failed: {err}");
+		return;
+	}
+	tracker.clear_history();
+
+	println!("Warm-up...");
+	if shutdown.wait_timeout(Duration::from_secs(1)) {
+		return;
+	}
+
+	let started = Instant::now();
+	let mut measured = 0;
+	loop {
+		match tracker.track_speed() {
+			Ok(speed) => {
+				measured += 1;
+				print_sample(Local::now(), &speed, options.format.as_ref());
+			}
+			Err(err) => {
+				eprintln!("Measurement error: {err}");
+			}
+		}
+
+		let count_reached = options.count.is_some_and(|count| measured >= count.get());
+		let duration_reached = options.duration.is_some_and(|duration| started.elapsed() + pause >= duration);
+		if count_reached || duration_reached || shutdown.wait_timeout(pause) {
+			break;
+		}
+	}
+
+	if let Some(summary) = tracker.summary() {
+		print_summary(&summary);
+	}
+
+	if let Some(path) = &options.output {
+		let compression = if path.extension().is_some_and(|extension| extension == "gz") {
+			HistoryCompression::Gzip
+		} else {
+			HistoryCompression::None
+		};
+		if let Err(err) = tracker.append_history_to(path, compression) {
+			eprintln!("Failed to write {}: {err}", path.display());
+		}
+	}
+}
+
+fn replay_command(capture: &Path, rate: f64, template: Option<&FormatTemplate>, shutdown: &Shutdown) {
+	let records = match read_history(capture) {
+		Ok(records) => records,
+		Err(err) => {
+			eprintln!("Failed to read {}: {err}", capture.display());
+			return;
+		}
+	};
+	let speeds = records_to_speeds(&records, Instant::now());
+
+	println!("Replaying {} at {rate}x — press Ctrl+C to stop", capture.display());
+	println!("{:-<80}", "");
+
+	let mut previous: Option<&NetworkSpeed> = None;
+	let mut replayed = 0;
+	for (record, speed) in records.iter().zip(&speeds) {
+		if let Some(previous) = previous {
+			let recorded = speed.timestamp.saturating_duration_since(previous.timestamp);
+			let gap = Duration::try_from_secs_f64(recorded.as_secs_f64() / rate).unwrap_or(Duration::MAX);
+			if shutdown.wait_timeout(gap) {
+				break;
+			}
+		}
+
+		// Show the recorded wall-clock time, not the playback time.
+		match DateTime::from_timestamp_millis(record.timestamp_ms as i64) {
+			Some(recorded_at) => print_sample(recorded_at.with_timezone(&Local), speed, template),
+			None => print_sample(Local::now(), speed, template),
+		}
+		previous = Some(speed);
+		replayed += 1;
+	}
+
+	match summarize(&speeds[..replayed]) {
+		Some(summary) => print_summary(&summary),
+		None => println!("{} contains no samples", capture.display()),
+	}
+}
+
+fn schedule_install_command(options: &ScheduleArgs) {
+	let (schedule, modifier) = match schedule_trigger(options.interval) {
+		Ok(trigger) => trigger,
+		Err(err) => {
+			eprintln!("{err}");
+			return;
+		}
+	};
+	// Scheduled tasks start in System32, so both paths must be absolute.
+	let (exe, output) = match (env::current_exe(), std::path::absolute(&options.output)) {
+		(Ok(exe), Ok(output)) => (exe, output),
+		(Err(err), _) | (_, Err(err)) => {
+			eprintln!("Failed to resolve the task paths: {err}");
+			return;
+		}
+	};
+
+	let task_command = match options.command {
+		ScheduledCommand::Monitor =>
+			format!(
+				"\"{}\" monitor --duration {}ms --output \"{}\"",
+				exe.display(),
+				options.sample_duration.as_millis(),
+				output.display()
+			),
+	};
+	let modifier = modifier.to_string();
+	let created = run_schtasks(
+		&["/Create", "/TN", &options.name, "/TR", &task_command, "/SC", schedule, "/MO", &modifier, "/F"]
+	);
+	if created {
+		println!(
+			"Registered task \"{}\": every {}, appending to {}",
+			options.name,
+			format_duration(options.interval),
+			output.display()
+		);
+	}
+}
+
+fn schedule_remove_command(name: &str) {
+	if run_schtasks(&["/Delete", "/TN", name, "/F"]) {
+		println!("Removed task \"{name}\"");
+	}
+}
+
+/// Maps an interval onto a `schtasks /SC <schedule> /MO <modifier>` trigger. The tool only
+/// repeats every 1–1439 minutes, 1–23 hours, or 1–365 days.
+fn schedule_trigger(interval: Duration) -> Result<(&'static str, u64), String> {
+	const MINUTE: u64 = 60;
+	const HOUR: u64 = 60 * MINUTE;
+	const DAY: u64 = 24 * HOUR;
+
+	let seconds = interval.as_secs();
+	if seconds == 0 || !seconds.is_multiple_of(MINUTE) || interval.subsec_nanos() != 0 {
+		return Err(format!("Task Scheduler intervals are whole minutes, got {}", format_duration(interval)));
+	}
+	match seconds {
+		seconds if seconds < DAY && seconds.is_multiple_of(HOUR) => Ok(("HOURLY", seconds / HOUR)),
+		seconds if seconds < DAY => Ok(("MINUTE", seconds / MINUTE)),
+		seconds if seconds.is_multiple_of(DAY) && seconds / DAY <= 365 => Ok(("DAILY", seconds / DAY)),
+		_ => Err(format!("intervals of a day or more must be whole days up to 365, got {}", format_duration(interval))),
+	}
+}
+
+/// Runs `schtasks.exe`, which prints its own success or error message.
+fn run_schtasks(args: &[&str]) -> bool {
+	match process::Command::new("schtasks").args(args).status() {
+		Ok(status) => status.success(),
+		Err(err) => {
+			eprintln!("Failed to run schtasks: {err}");
+			false
+		}
+	}
+}
+
+/// Serves the PowerShell module: each stdin line is `sample <window>` or `interfaces`, and each
+/// answer is one JSON line on stdout, `{"error": ...}` on failure. Exits when stdin closes.
+fn pwsh_server_command() {
+	let mut monitor = NetworkMonitor::with_config(monitor_config(PrecisionMode::Instant));
+	let mut stdout = io::stdout().lock();
+
+	for line in io::stdin().lock().lines() {
+		let Ok(line) = line else {
+			break;
+		};
+		let mut words = line.split_whitespace();
+		let response = match (words.next(), words.next()) {
+			(None, _) => continue,
+			(Some("sample"), window) =>
+				match window.map(parse_interval).unwrap_or(Ok(Duration::from_secs(1))) {
+					Ok(window) =>
+						match monitor.measure_speed_blocking(window) {
+							Ok(speed) => sample_json(Local::now(), &speed),
+							Err(err) => error_json(&err.to_string()),
+						}
+					Err(err) => error_json(&err),
+				}
+			(Some("interfaces"), _) =>
+				match list_interfaces() {
+					Ok(interfaces) => serde_json::Value::Array(interfaces.iter().map(interface_json).collect()),
+					Err(err) => error_json(&err.to_string()),
+				}
+			(Some(request), _) => error_json(&format!("unknown request '{request}'")),
+		};
+
+		if writeln!(stdout, "{response}").and_then(|()| stdout.flush()).is_err() {
+			break;
+		}
+	}
+}
+
+fn sample_json(timestamp: DateTime<Local>, speed: &NetworkSpeed) -> serde_json::Value {
+	serde_json::json!({
+		"Timestamp": timestamp.to_rfc3339(),
+		"UploadBytesPerSec": speed.upload_bytes_per_sec.bytes_per_sec(),
+		"DownloadBytesPerSec": speed.download_bytes_per_sec.bytes_per_sec(),
+		"UploadMbps": speed.upload_mbps(),
+		"DownloadMbps": speed.download_mbps(),
+		"Upload": speed.upload_formatted(),
+		"Download": speed.download_formatted(),
+	})
+}
+
+fn interface_json(interface: &NetworkInterface) -> serde_json::Value {
+	serde_json::json!({
+		"Index": interface.index,
+		"Guid": interface.guid,
+		"Name": interface.description.trim(),
+		"Description": interface.driver_description.trim(),
+		"Type": interface.type_name(),
+		"Category": interface.category().as_str(),
+		"Status": interface.oper_status.as_str(),
+		"LinkSpeed": interface.speed,
+		"BytesSent": interface.bytes_sent,
+		"BytesReceived": interface.bytes_received,
+		"IsVirtual": interface.is_virtual(),
+		"IsLoopback": interface.is_loopback(),
+	})
+}
+
+fn error_json(message: &str) -> serde_json::Value {
+	serde_json::json!({ "error": message })
+}
+
+fn print_sample(timestamp: DateTime<Local>, speed: &NetworkSpeed, template: Option<&FormatTemplate>) {
+	if let Some(template) = template {
+		println!("{}", template.render_with(speed, |layout| timestamp.format(layout).to_string()));
+		return;
+	}
+
+	println!(
+		"[{}] ↑ {:<10} ↓ {:<10} Σ {}",
+		timestamp.format("%H:%M:%S"),
+		speed.upload_formatted(),
+		speed.download_formatted(),
+		format_bytes_per_second(speed.total_bytes_per_sec())
+	);
+}
+
+fn print_summary(summary: &SpeedSummary) {
+	println!("{:-<80}", "");
+	println!("Summary: {} samples over {}", summary.samples, format_duration(summary.duration));
+	println!("  {:<8} ↑ {:<12} ↓ {}", "average", summary.average.upload_formatted(), summary.average.download_formatted());
+	println!("  {:<8} ↑ {:<12} ↓ {}", "peak", summary.peak.upload_formatted(), summary.peak.download_formatted());
+	println!("  {:<8} ↑ {:<12} ↓ {}", "p95", summary.p95.upload_formatted(), summary.p95.download_formatted());
+	println!(
+		"  {:<8} ↑ {:<12} ↓ {}",
+		"total",
+		format_bytes_total(summary.bytes_sent),
+		format_bytes_total(summary.bytes_received)
+	);
+}
+
+fn monitor_config(precision: PrecisionMode) -> NetworkMonitorConfig {
+	NetworkMonitorConfig::builder()
 		.exclude_virtual(true)
 		.exclude_loopback(true)
 		.exclude_bluetooth(true)
 		.min_measurement_interval(Duration::from_millis(500))
+		.precision(precision)
 		.build()
-		.expect("valid monitor configuration");
+		.expect("valid monitor configuration")
+}
 
-	let mut monitor = NetworkMonitor::with_config(config);
+fn agent_command(collector: &str, interval: Duration, shutdown: &Shutdown) {
+	let hostname = env::var("COMPUTERNAME")
+		.or_else(|_| env::var("HOSTNAME"))
+		.unwrap_or_else(|_| "unknown".to_string());
 
-	if let Err(err) = monitor.measure_speed() {
-		eprintln!("Initial measurement failed: {err}");
+	let mut options = AgentOptions::new();
+	if let Ok(token) = env::var("NETWORK_SPEED_TOKEN") {
+		options = options.with_token(token);
+	}
+
+	let mut agent = match Agent::connect_with(collector, hostname, &options) {
+		Ok(agent) => agent,
+		Err(err) => {
+			eprintln!("Failed to connect to collector {collector}: {err}");
+			return;
+		}
+	};
+
+	println!("Streaming samples from {} to {collector} every {}ms", agent.hostname(), interval.as_millis());
+	let mut monitor = NetworkMonitor::with_config(monitor_config(PrecisionMode::Instant));
+	if let Err(err) = agent.run_until(&mut monitor, interval, shutdown) {
+		eprintln!("Agent stopped: {err}");
 		return;
 	}
 
-	println!("Warm-up...");
-	thread::sleep(Duration::from_secs(1));
+	if let Err(err) = agent.close() {
+		eprintln!("Failed to close the connection cleanly: {err}");
+	}
+	println!("Agent stopped");
+}
 
-	loop {
-		match monitor.measure_speed() {
-			Ok(speed) => {
-				let timestamp = Local::now().format("%H:%M:%S");
+fn collector_command(bind_addr: &str, max_history_size: usize, allow_plaintext_token: bool, shutdown: &Shutdown) {
+	let mut collector = match Collector::bind(bind_addr, max_history_size) {
+		Ok(collector) => collector,
+		Err(err) => {
+			eprintln!("Failed to bind collector on {bind_addr}: {err}");
+			return;
+		}
+	};
+	if let Ok(token) = env::var("NETWORK_SPEED_TOKEN") {
+		collector = collector.with_token(token);
+	}
+	collector = collector.allow_plaintext_token(allow_plaintext_token);
+
+	if let Err(err) = collector.check_exposure() {
+		eprintln!("Collector not started: {err}");
+		eprintln!(
+			"Set NETWORK_SPEED_TOKEN and pass --allow-plaintext-token (the CLI collector has no TLS), or bind to 127.0.0.1 to accept agents."
+		);
+		return;
+	}
+
+	let handle = collector.handle();
+	let accept_shutdown = shutdown.clone();
+	let accept_loop = thread::spawn(move || collector.run_until(&accept_shutdown));
+
+	println!("Collector listening on {bind_addr} — press Ctrl+C to stop");
+	println!("{:-<80}", "");
+
+	while !shutdown.wait_timeout(Duration::from_secs(1)) && !accept_loop.is_finished() {
+		let timestamp = Local::now().format("%H:%M:%S");
+		for host in handle.hosts() {
+			if let Some(speed) = handle.latest(&host) {
 				println!(
-					"[{timestamp}] ↑ {:<10} ↓ {:<10} Σ {}",
+					"[{timestamp}] {:<24} ↑ {:<10} ↓ {:<10}",
+					host,
 					speed.upload_formatted(),
-					speed.download_formatted(),
-					format_bytes_per_second(speed.total_bytes_per_sec())
+					speed.download_formatted()
 				);
 			}
-			Err(err) => {
-				eprintln!("Measurement error: {err}");
-			}
 		}
+	}
 
-		thread::sleep(Duration::from_secs(1));
+	shutdown.trigger();
+	match accept_loop.join() {
+		Ok(Ok(())) => println!("Collector stopped"),
+		Ok(Err(err)) => eprintln!("Collector stopped: {err}"),
+		Err(_) => eprintln!("Collector thread panicked"),
 	}
 }
@@ -0,0 +1,254 @@
+use std::time::{ Duration, Instant };
+
+#[cfg(feature = "serde")]
+use serde::{ Deserialize, Serialize };
+
+use super::events::Event;
+use crate::types::{ format_bytes_per_second, ByteRate, Direction, LocalTime, NetworkError, NetworkSpeed, Result };
+
+pub const DEFAULT_ALERT_SIGMA: f64 = 3.0;
+pub const DEFAULT_TRAINING_WINDOW: Duration = Duration::from_secs(7 * 86_400);
+/// Samples an hour needs before it is evaluated, so a sparsely trained hour does not alert.
+pub const DEFAULT_MIN_HOUR_SAMPLES: u64 = 30;
+/// Deviations below this never alert, so a near-constant baseline does not turn noise into alerts.
+pub const DEFAULT_MIN_DEVIATION: ByteRate = ByteRate::from_bytes_per_sec(64 * 1024);
+
+/// Running mean and variance of a rate within one hour of the day (Welford's algorithm).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct HourStats {
+	count: u64,
+	mean: f64,
+	m2: f64,
+}
+
+impl HourStats {
+	pub fn count(&self) -> u64 {
+		self.count
+	}
+
+	/// Mean rate in bytes per second.
+	pub fn mean(&self) -> f64 {
+		self.mean
+	}
+
+	/// Sample standard deviation in bytes per second; 0 with fewer than two samples.
+	pub fn std_dev(&self) -> f64 {
+		if self.count < 2 { 0.0 } else { (self.m2 / ((self.count - 1) as f64)).sqrt() }
+	}
+
+	pub fn add(&mut self, value: f64) {
+		self.count += 1;
+		let delta = value - self.mean;
+		self.mean += delta / (self.count as f64);
+		self.m2 += delta * (value - self.mean);
+	}
+}
+
+/// Typical throughput for each hour of the local day.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct HourlyBaseline {
+	hours: [HourStats; 24],
+}
+
+impl HourlyBaseline {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Stats for `hour` (0–23).
+	pub fn hour(&self, hour: usize) -> &HourStats {
+		&self.hours[hour % 24]
+	}
+
+	pub fn learn(&mut self, rate: ByteRate, at: LocalTime) {
+		self.hours[hour_of(at)].add(rate.bytes_per_sec() as f64);
+	}
+}
+
+/// An alert that learns what throughput is normal for each hour of the day and fires when a sample
+/// deviates from it by more than `sigma` standard deviations, in either direction, instead of
+/// against a hand-picked absolute threshold.
+///
+/// For the training window after the first sample it only learns. Afterwards it evaluates every
+/// hour that has at least `min_hour_samples`, and keeps learning from samples that do not deviate, so
+/// the baseline follows gradual change without absorbing the anomalies it reports. It fires after
+/// `consecutive` deviating samples in a row and clears after as many normal ones.
+///
+/// It watches the total rate by default. [`with_direction`](Self::with_direction) makes it learn
+/// and judge upload or download alone, e.g. to catch a sustained upload (a backup or exfiltration)
+/// that a busy download would hide in the total.
+///
+/// Register it with [`EventMonitor::with_adaptive_alert`](super::EventMonitor::with_adaptive_alert),
+/// or call [`observe`](Self::observe) with each sample and publish the returned events yourself.
+#[derive(Debug, Clone)]
+pub struct AdaptiveAlert {
+	name: String,
+	baseline: HourlyBaseline,
+	direction: Direction,
+	sigma: f64,
+	training_window: Duration,
+	min_hour_samples: u64,
+	min_deviation: ByteRate,
+	consecutive: u32,
+	started: Option<Instant>,
+	streak: u32,
+	firing: bool,
+}
+
+impl AdaptiveAlert {
+	pub fn new(name: impl Into<String>) -> Self {
+		Self {
+			name: name.into(),
+			baseline: HourlyBaseline::new(),
+			direction: Direction::Total,
+			sigma: DEFAULT_ALERT_SIGMA,
+			training_window: DEFAULT_TRAINING_WINDOW,
+			min_hour_samples: DEFAULT_MIN_HOUR_SAMPLES,
+			min_deviation: DEFAULT_MIN_DEVIATION,
+			consecutive: 1,
+			started: None,
+			streak: 0,
+			firing: false,
+		}
+	}
+
+	/// Starts from a baseline learned earlier, e.g. saved with the `serde` feature, so a restart
+	/// does not need to train again.
+	pub fn with_baseline(mut self, baseline: HourlyBaseline) -> Self {
+		self.baseline = baseline;
+		self
+	}
+
+	/// The rate the alert learns and judges. A saved baseline only fits the direction it was
+	/// learned for.
+	pub fn with_direction(mut self, direction: Direction) -> Self {
+		self.direction = direction;
+		self
+	}
+
+	pub fn with_sigma(mut self, sigma: f64) -> Self {
+		self.sigma = sigma;
+		self
+	}
+
+	pub fn with_training_window(mut self, window: Duration) -> Self {
+		self.training_window = window;
+		self
+	}
+
+	pub fn with_min_hour_samples(mut self, samples: u64) -> Self {
+		self.min_hour_samples = samples;
+		self
+	}
+
+	pub fn with_min_deviation(mut self, deviation: impl Into<ByteRate>) -> Self {
+		self.min_deviation = deviation.into();
+		self
+	}
+
+	/// Deviating (or, once firing, normal) samples needed in a row to fire (or clear).
+	pub fn with_consecutive(mut self, samples: u32) -> Self {
+		self.consecutive = samples;
+		self
+	}
+
+	pub fn validate(&self) -> Result<()> {
+		if !(self.sigma.is_finite() && self.sigma > 0.0) {
+			return Err(NetworkError::InvalidConfiguration {
+				field: "adaptive alert sigma must be a positive number".to_string(),
+			});
+		}
+		if self.consecutive == 0 {
+			return Err(NetworkError::InvalidConfiguration {
+				field: "adaptive alert consecutive must be > 0".to_string(),
+			});
+		}
+		Ok(())
+	}
+
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	pub fn baseline(&self) -> &HourlyBaseline {
+		&self.baseline
+	}
+
+	pub fn direction(&self) -> Direction {
+		self.direction
+	}
+
+	pub fn is_firing(&self) -> bool {
+		self.firing
+	}
+
+	/// Whether the training window has passed since the first observed sample.
+	pub fn is_trained(&self, now: Instant) -> bool {
+		self.started.is_some_and(|started| now.saturating_duration_since(started) >= self.training_window)
+	}
+
+	/// How many standard deviations `speed` is from the baseline of its hour; `None` while that hour
+	/// has too few samples or the deviation is below the minimum.
+	pub fn deviation(&self, speed: &NetworkSpeed, at: LocalTime) -> Option<f64> {
+		let stats = self.baseline.hour(hour_of(at));
+		if stats.count() < self.min_hour_samples.max(2) {
+			return None;
+		}
+		let difference = (speed.rate(self.direction).bytes_per_sec() as f64) - stats.mean();
+		if difference.abs() < (self.min_deviation.bytes_per_sec() as f64) {
+			return Some(0.0);
+		}
+		// A perfectly flat hour still has a finite scale, set by the minimum deviation.
+		let scale = stats.std_dev().max((self.min_deviation.bytes_per_sec().max(1) as f64) / self.sigma);
+		Some(difference / scale)
+	}
+
+	/// Feeds one sample taken at local time `at`. Returns [`Event::AlertFired`] or
+	/// [`Event::AlertCleared`] when the alert changes state.
+	pub fn observe(&mut self, speed: &NetworkSpeed, at: LocalTime) -> Option<Event> {
+		let started = *self.started.get_or_insert(speed.timestamp);
+		let training = speed.timestamp.saturating_duration_since(started) < self.training_window;
+		let deviation = if training { None } else { self.deviation(speed, at) };
+		let deviating = deviation.is_some_and(|deviation| deviation.abs() > self.sigma);
+		if !deviating {
+			self.baseline.learn(speed.rate(self.direction), at);
+		}
+
+		if deviating != self.firing {
+			self.streak += 1;
+		} else {
+			self.streak = 0;
+		}
+		if self.streak < self.consecutive {
+			return None;
+		}
+		self.streak = 0;
+		self.firing = deviating;
+
+		if !deviating {
+			return Some(Event::AlertCleared {
+				name: self.name.clone(),
+			});
+		}
+		let hour = hour_of(at);
+		let deviation = deviation.unwrap_or_default();
+		Some(Event::AlertFired {
+			name: self.name.clone(),
+			message: format!(
+				"{} throughput {} is {:.1}σ {} the {:02}:00 baseline of {}",
+				self.direction.as_str(),
+				format_bytes_per_second(speed.rate(self.direction)),
+				deviation.abs(),
+				if deviation > 0.0 { "above" } else { "below" },
+				hour,
+				format_bytes_per_second(self.baseline.hour(hour).mean() as u64)
+			),
+		})
+	}
+}
+
+fn hour_of(at: LocalTime) -> usize {
+	((at.minute / 60) as usize) % 24
+}
@@ -0,0 +1,213 @@
+use std::collections::{ BTreeMap, HashMap };
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{ Instant, SystemTime, UNIX_EPOCH };
+
+#[cfg(feature = "persist")]
+use arrow_array::ArrayRef;
+use arrow_array::{ RecordBatch, StringArray, TimestampMillisecondArray, UInt64Array };
+use arrow_schema::{ DataType, Field, Schema, SchemaRef, TimeUnit };
+use parquet::arrow::ArrowWriter;
+
+use super::data_usage::UsageTotals;
+#[cfg(feature = "persist")]
+use super::report::{ UsageReport, UsageStats };
+use crate::types::{ MonitorLabels, NetworkError, NetworkSpeed, Result };
+
+pub fn history_schema() -> SchemaRef {
+	Arc::new(
+		Schema::new(
+			vec![
+				Field::new("timestamp", DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".into())), false),
+				Field::new("upload_bytes_per_sec", DataType::UInt64, false),
+				Field::new("download_bytes_per_sec", DataType::UInt64, false)
+			]
+		)
+	)
+}
+
+/// [`history_schema`] with the tracker name and labels attached as schema metadata
+/// (`monitor.name` and `label.<key>`), which Parquet files carry along.
+pub fn labeled_history_schema(labels: &MonitorLabels) -> SchemaRef {
+	let mut metadata = HashMap::new();
+	if let Some(name) = &labels.name {
+		metadata.insert("monitor.name".to_string(), name.clone());
+	}
+	for (key, value) in &labels.labels {
+		metadata.insert(format!("label.{key}"), value.clone());
+	}
+
+	Arc::new(history_schema().as_ref().clone().with_metadata(metadata))
+}
+
+/// Converts samples into a record batch; `Instant` timestamps are mapped to wall-clock time.
+pub fn history_to_record_batch(history: &[NetworkSpeed]) -> Result<RecordBatch> {
+	labeled_history_to_record_batch(history, &MonitorLabels::default())
+}
+
+pub fn labeled_history_to_record_batch(history: &[NetworkSpeed], labels: &MonitorLabels) -> Result<RecordBatch> {
+	let now_instant = Instant::now();
+	let now_millis = SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|elapsed| elapsed.as_millis() as i64)
+		.unwrap_or(0);
+
+	let timestamps: Vec<i64> = history
+		.iter()
+		.map(|speed| now_millis - (now_instant.saturating_duration_since(speed.timestamp).as_millis() as i64))
+		.collect();
+	let uploads: Vec<u64> = history
+		.iter()
+		.map(|speed| speed.upload_bytes_per_sec.bytes_per_sec())
+		.collect();
+	let downloads: Vec<u64> = history
+		.iter()
+		.map(|speed| speed.download_bytes_per_sec.bytes_per_sec())
+		.collect();
+
+	RecordBatch::try_new(
+		labeled_history_schema(labels),
+		vec![
+			Arc::new(TimestampMillisecondArray::from(timestamps).with_timezone("UTC")),
+			Arc::new(UInt64Array::from(uploads)),
+			Arc::new(UInt64Array::from(downloads))
+		]
+	).map_err(export_error)
+}
+
+pub fn write_history_parquet<W: Write + Send>(history: &[NetworkSpeed], writer: W) -> Result<()> {
+	write_labeled_history_parquet(history, &MonitorLabels::default(), writer)
+}
+
+pub fn write_labeled_history_parquet<W: Write + Send>(
+	history: &[NetworkSpeed],
+	labels: &MonitorLabels,
+	writer: W
+) -> Result<()> {
+	write_parquet(&labeled_history_to_record_batch(history, labels)?, writer)
+}
+
+pub fn write_history_parquet_file(history: &[NetworkSpeed], path: impl AsRef<Path>) -> Result<()> {
+	write_labeled_history_parquet_file(history, &MonitorLabels::default(), path)
+}
+
+pub fn write_labeled_history_parquet_file(
+	history: &[NetworkSpeed],
+	labels: &MonitorLabels,
+	path: impl AsRef<Path>
+) -> Result<()> {
+	let file = File::create(path).map_err(export_error)?;
+	write_labeled_history_parquet(history, labels, file)
+}
+
+/// One row per key (a label value or network name) with the bytes sent and received.
+pub fn usage_schema() -> SchemaRef {
+	Arc::new(
+		Schema::new(
+			vec![
+				Field::new("key", DataType::Utf8, false),
+				Field::new("bytes_sent", DataType::UInt64, false),
+				Field::new("bytes_received", DataType::UInt64, false)
+			]
+		)
+	)
+}
+
+/// Converts per-key usage, such as `usage_by_label` or [`data_usage_by_network`](super::data_usage_by_network)
+/// results, into a record batch.
+pub fn usage_to_record_batch(usage: &BTreeMap<String, UsageTotals>) -> Result<RecordBatch> {
+	let keys: Vec<&str> = usage.keys().map(String::as_str).collect();
+	let sent: Vec<u64> = usage.values().map(|totals| totals.bytes_sent).collect();
+	let received: Vec<u64> = usage.values().map(|totals| totals.bytes_received).collect();
+
+	RecordBatch::try_new(
+		usage_schema(),
+		vec![
+			Arc::new(StringArray::from(keys)),
+			Arc::new(UInt64Array::from(sent)),
+			Arc::new(UInt64Array::from(received))
+		]
+	).map_err(export_error)
+}
+
+pub fn write_usage_parquet<W: Write + Send>(usage: &BTreeMap<String, UsageTotals>, writer: W) -> Result<()> {
+	write_parquet(&usage_to_record_batch(usage)?, writer)
+}
+
+/// Columns of [`UsageReport::to_csv`], plus the report's start and end, so reports for
+/// consecutive periods can be appended to one dataset.
+#[cfg(feature = "persist")]
+pub fn usage_report_schema() -> SchemaRef {
+	let timestamp = DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".into()));
+	Arc::new(
+		Schema::new(
+			vec![
+				Field::new("start", timestamp.clone(), false),
+				Field::new("end", timestamp, false),
+				Field::new("interface", DataType::Utf8, false),
+				Field::new("bytes_sent", DataType::UInt64, false),
+				Field::new("bytes_received", DataType::UInt64, false),
+				Field::new("covered_ms", DataType::UInt64, false),
+				Field::new("average_upload_bytes_per_sec", DataType::UInt64, false),
+				Field::new("average_download_bytes_per_sec", DataType::UInt64, false),
+				Field::new("peak_upload_bytes_per_sec", DataType::UInt64, false),
+				Field::new("peak_download_bytes_per_sec", DataType::UInt64, false),
+				Field::new("samples", DataType::UInt64, false)
+			]
+		)
+	)
+}
+
+/// One row per interface of `report`, then a `total` row, as in [`UsageReport::to_csv`].
+#[cfg(feature = "persist")]
+pub fn usage_report_to_record_batch(report: &UsageReport) -> Result<RecordBatch> {
+	let rows: Vec<_> = report.interfaces
+		.iter()
+		.map(|(name, stats)| (name.as_str(), stats))
+		.chain(std::iter::once(("total", &report.total)))
+		.collect();
+	let column = |value: fn(&UsageStats) -> u64| -> ArrayRef {
+		Arc::new(UInt64Array::from_iter_values(rows.iter().map(|(_, stats)| value(stats))))
+	};
+	let timestamp = |ms: u64| -> ArrayRef {
+		Arc::new(TimestampMillisecondArray::from(vec![ms as i64; rows.len()]).with_timezone("UTC"))
+	};
+
+	RecordBatch::try_new(
+		usage_report_schema(),
+		vec![
+			timestamp(report.start_ms),
+			timestamp(report.end_ms),
+			Arc::new(StringArray::from_iter_values(rows.iter().map(|(name, _)| *name))),
+			column(|stats| stats.bytes_sent),
+			column(|stats| stats.bytes_received),
+			column(|stats| stats.covered_ms),
+			column(|stats| stats.average_upload_bytes_per_sec),
+			column(|stats| stats.average_download_bytes_per_sec),
+			column(|stats| stats.peak_upload_bytes_per_sec),
+			column(|stats| stats.peak_download_bytes_per_sec),
+			column(|stats| stats.samples as u64)
+		]
+	).map_err(export_error)
+}
+
+#[cfg(feature = "persist")]
+pub fn write_usage_report_parquet<W: Write + Send>(report: &UsageReport, writer: W) -> Result<()> {
+	write_parquet(&usage_report_to_record_batch(report)?, writer)
+}
+
+fn write_parquet<W: Write + Send>(batch: &RecordBatch, writer: W) -> Result<()> {
+	let mut parquet_writer = ArrowWriter::try_new(writer, batch.schema(), None).map_err(export_error)?;
+	parquet_writer.write(batch).map_err(export_error)?;
+	parquet_writer.close().map_err(export_error)?;
+	Ok(())
+}
+
+fn export_error(err: impl std::fmt::Display) -> NetworkError {
+	NetworkError::Export {
+		format: "Arrow",
+		reason: err.to_string(),
+	}
+}
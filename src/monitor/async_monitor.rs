@@ -1,72 +1,202 @@
 use std::collections::VecDeque;
+#[cfg(feature = "arrow")]
+use std::path::Path;
 use std::sync::{ Arc, Mutex };
 use std::time::{ Duration, Instant };
 use tokio::sync::mpsc;
 use tokio::time::{ interval, MissedTickBehavior };
 
-use crate::monitor::NetworkMonitor;
-use crate::types::{ NetworkError, NetworkMonitorConfig, NetworkSpeed, PrecisionMode, Result };
+#[cfg(feature = "arrow")]
+use arrow_array::RecordBatch;
+
+#[cfg(feature = "arrow")]
+use crate::monitor::{ labeled_history_to_record_batch, write_labeled_history_parquet_file };
+#[cfg(feature = "charts")]
+use crate::monitor::{ render_chart, ChartOptions };
+use crate::monitor::{ forecast_history, Composition, CounterSource, CountersSnapshot, Forecast, NetworkInterface, NetworkMonitor, ProviderInfo, Shutdown };
+use crate::types::{
+	render_sparkline,
+	MonitorCheckpoint,
+	MonitorLabels,
+	MonitorStats,
+	NetworkError,
+	NetworkMonitorConfig,
+	NetworkSpeed,
+	OverheadStats,
+	PreciseSpeed,
+	Result,
+	SampleTolerance,
+	SpeedSample,
+	time_weighted_average,
+};
+
+/// Samples a collection run gathered before it gave up, and the error that stopped it.
+#[derive(Debug)]
+pub struct PartialSamples {
+	pub samples: Vec<NetworkSpeed>,
+	pub error: NetworkError,
+}
+
+impl From<PartialSamples> for NetworkError {
+	fn from(partial: PartialSamples) -> Self {
+		partial.error
+	}
+}
 
 pub struct AsyncNetworkMonitor {
 	inner: Arc<Mutex<NetworkMonitor>>,
+	coalescing: Option<Coalescing>,
+}
+
+/// Shares one fresh sample between callers that arrive within `window` of each other.
+struct Coalescing {
+	window: Duration,
+	latest: tokio::sync::Mutex<Option<NetworkSpeed>>,
 }
 
 impl AsyncNetworkMonitor {
 	pub fn new() -> Self {
 		Self {
 			inner: Arc::new(Mutex::new(NetworkMonitor::new())),
+			coalescing: None,
 		}
 	}
 
 	pub fn with_config(config: NetworkMonitorConfig) -> Self {
 		Self {
 			inner: Arc::new(Mutex::new(NetworkMonitor::with_config(config))),
+			coalescing: None,
 		}
 	}
 
+	/// See [`NetworkMonitor::with_source`].
+	pub fn with_source(config: NetworkMonitorConfig, source: impl CounterSource + 'static) -> Self {
+		Self {
+			inner: Arc::new(Mutex::new(NetworkMonitor::with_source(config, source))),
+			coalescing: None,
+		}
+	}
+
+	/// Coalesces concurrent `measure_speed` calls: while one call measures, the others wait for it,
+	/// and any call within `window` of the latest sample gets that sample instead of measuring
+	/// again. Useful when several tasks share one monitor; a `window` around
+	/// `min_measurement_interval` avoids `InsufficientTimeElapsed` errors between them.
+	pub fn with_coalescing(mut self, window: Duration) -> Self {
+		self.coalescing = Some(Coalescing {
+			window,
+			latest: tokio::sync::Mutex::new(None),
+		});
+		self
+	}
+
 	pub async fn measure_speed(&self) -> Result<NetworkSpeed> {
+		let Some(coalescing) = &self.coalescing else {
+			return self.measure_speed_uncoalesced().await;
+		};
+
+		let mut latest = coalescing.latest.lock().await;
+		if let Some(speed) = latest.as_ref() {
+			if speed.timestamp.elapsed() < coalescing.window {
+				return Ok(speed.clone());
+			}
+		}
+
+		let speed = self.measure_speed_uncoalesced().await?;
+		*latest = Some(speed.clone());
+		Ok(speed)
+	}
+
+	/// [`measure_speed`](Self::measure_speed) that gives up with [`NetworkError::Timeout`] after `timeout`.
+	pub async fn measure_speed_with_timeout(&self, timeout: Duration) -> Result<NetworkSpeed> {
+		tokio::time::timeout(timeout, self.measure_speed()).await.map_err(|_| NetworkError::Timeout {
+			operation: "measure_speed".to_string(),
+			timeout_ms: timeout.as_millis() as u64,
+		})?
+	}
+
+	/// [`measure_speed`](Self::measure_speed) that returns [`NetworkError::Cancelled`] as soon as
+	/// `shutdown` is triggered.
+	pub async fn measure_speed_until(&self, shutdown: &Shutdown) -> Result<NetworkSpeed> {
+		tokio::select! {
+			_ = shutdown.triggered() => Err(NetworkError::Cancelled),
+			result = self.measure_speed() => result,
+		}
+	}
+
+	async fn measure_speed_uncoalesced(&self) -> Result<NetworkSpeed> {
 		let inner_clone = Arc::clone(&self.inner);
 		tokio::task
 			::spawn_blocking(move || {
-				let mut monitor = inner_clone.lock().map_err(|_| NetworkError::InterfaceOperationFailed {
-					reason: "Monitor mutex poisoned".to_string(),
-				})?;
+				let mut monitor = inner_clone.lock().map_err(|_| NetworkError::LockPoisoned { resource: "Monitor" })?;
 				monitor.measure_speed()
 			}).await
-			.map_err(|_| NetworkError::InterfaceOperationFailed {
-				reason: "Task join error".to_string(),
-			})?
+			.map_err(NetworkError::from)?
+	}
+
+	pub async fn measure_precise_speed(&self) -> Result<PreciseSpeed> {
+		let inner_clone = Arc::clone(&self.inner);
+		tokio::task
+			::spawn_blocking(move || {
+				let mut monitor = inner_clone.lock().map_err(|_| NetworkError::LockPoisoned { resource: "Monitor" })?;
+				monitor.measure_precise_speed()
+			}).await
+			.map_err(NetworkError::from)?
+	}
+
+	/// See [`NetworkMonitor::measure_speed_with_composition`]; bypasses coalescing.
+	pub async fn measure_speed_with_composition(&self) -> Result<(NetworkSpeed, Composition)> {
+		let inner_clone = Arc::clone(&self.inner);
+		tokio::task
+			::spawn_blocking(move || {
+				let mut monitor = inner_clone.lock().map_err(|_| NetworkError::LockPoisoned { resource: "Monitor" })?;
+				monitor.measure_speed_with_composition()
+			}).await
+			.map_err(NetworkError::from)?
+	}
+
+	/// See [`NetworkMonitor::composition`].
+	pub async fn composition(&self) -> Result<Composition> {
+		let inner_clone = Arc::clone(&self.inner);
+		tokio::task
+			::spawn_blocking(move || {
+				let monitor = inner_clone.lock().map_err(|_| NetworkError::LockPoisoned { resource: "Monitor" })?;
+				Ok(monitor.composition())
+			}).await
+			.map_err(NetworkError::from)?
 	}
 
 	pub async fn measure_speed_with_delay(&self, measurement_duration: Duration) -> Result<NetworkSpeed> {
 		let inner_clone = Arc::clone(&self.inner);
 		let result = tokio::task::spawn_blocking(move || {
-			let mut monitor = inner_clone.lock().map_err(|_| NetworkError::InterfaceOperationFailed {
-				reason: "Monitor mutex poisoned".to_string(),
-			})?;
+			let mut monitor = inner_clone.lock().map_err(|_| NetworkError::LockPoisoned { resource: "Monitor" })?;
 			monitor.measure_speed_blocking(measurement_duration)
 		}).await;
 
-		result.map_err(|_| NetworkError::InterfaceOperationFailed {
-			reason: "Task join error".to_string(),
-		})?
+		result.map_err(NetworkError::from)?
+	}
+
+	pub async fn sample(&self) -> Result<Option<SpeedSample>> {
+		let inner_clone = Arc::clone(&self.inner);
+		tokio::task
+			::spawn_blocking(move || {
+				let mut monitor = inner_clone.lock().map_err(|_| NetworkError::LockPoisoned { resource: "Monitor" })?;
+				monitor.sample()
+			}).await
+			.map_err(NetworkError::from)?
 	}
 
 	pub async fn get_instantaneous_speed(&self) -> Result<Option<NetworkSpeed>> {
 		let inner_clone = Arc::clone(&self.inner);
 		tokio::task
 			::spawn_blocking(move || {
-				let mut monitor = inner_clone.lock().map_err(|_| NetworkError::InterfaceOperationFailed {
-					reason: "Monitor mutex poisoned".to_string(),
-				})?;
+				let mut monitor = inner_clone.lock().map_err(|_| NetworkError::LockPoisoned { resource: "Monitor" })?;
 				monitor.get_instantaneous_speed()
 			}).await
-			.map_err(|_| NetworkError::InterfaceOperationFailed {
-				reason: "Task join error".to_string(),
-			})?
+			.map_err(NetworkError::from)?
 	}
 
 	pub async fn reset(&self) {
+		self.clear_coalesced().await;
 		let inner_clone = Arc::clone(&self.inner);
 		tokio::task
 			::spawn_blocking(move || {
@@ -77,28 +207,119 @@ impl AsyncNetworkMonitor {
 			.ok();
 	}
 
+	pub async fn total_traffic(&self) -> Result<(u64, u64)> {
+		let inner_clone = Arc::clone(&self.inner);
+		tokio::task
+			::spawn_blocking(move || {
+				let mut monitor = inner_clone.lock().map_err(|_| NetworkError::LockPoisoned { resource: "Monitor" })?;
+				monitor.total_traffic()
+			}).await
+			.map_err(NetworkError::from)?
+	}
+
+	pub async fn interface_traffic(&self) -> Result<Vec<NetworkInterface>> {
+		let inner_clone = Arc::clone(&self.inner);
+		tokio::task
+			::spawn_blocking(move || {
+				let mut monitor = inner_clone.lock().map_err(|_| NetworkError::LockPoisoned { resource: "Monitor" })?;
+				monitor.interface_traffic()
+			}).await
+			.map_err(NetworkError::from)?
+	}
+
+	/// See [`NetworkMonitor::snapshot`]. Pass the previous snapshot back in to reuse its buffer.
+	pub async fn snapshot(&self, reuse: Option<CountersSnapshot>) -> Result<CountersSnapshot> {
+		let inner_clone = Arc::clone(&self.inner);
+		tokio::task
+			::spawn_blocking(move || {
+				let mut monitor = inner_clone.lock().map_err(|_| NetworkError::LockPoisoned { resource: "Monitor" })?;
+				let mut snapshot = reuse.unwrap_or_default();
+				monitor.snapshot_into(&mut snapshot)?;
+				Ok(snapshot)
+			}).await
+			.map_err(NetworkError::from)?
+	}
+
+	pub async fn checkpoint(&self) -> Option<MonitorCheckpoint> {
+		let inner_clone = Arc::clone(&self.inner);
+		tokio::task
+			::spawn_blocking(move || inner_clone.lock().ok().and_then(|monitor| monitor.checkpoint())).await
+			.ok()
+			.flatten()
+	}
+
+	pub async fn restore_checkpoint(&self, checkpoint: MonitorCheckpoint) -> bool {
+		let inner_clone = Arc::clone(&self.inner);
+		tokio::task
+			::spawn_blocking(move || {
+				inner_clone
+					.lock()
+					.map(|mut monitor| monitor.restore_checkpoint(&checkpoint))
+					.unwrap_or(false)
+			}).await
+			.unwrap_or(false)
+	}
+
 	pub async fn update_config(&self, config: NetworkMonitorConfig) -> Result<()> {
+		self.clear_coalesced().await;
 		let inner_clone = Arc::clone(&self.inner);
 		tokio::task
 			::spawn_blocking(move || {
-				let mut monitor = inner_clone.lock().map_err(|_| NetworkError::InterfaceOperationFailed {
-					reason: "Monitor mutex poisoned".to_string(),
-				})?;
+				let mut monitor = inner_clone.lock().map_err(|_| NetworkError::LockPoisoned { resource: "Monitor" })?;
 				monitor.update_config(config)
 			}).await
-			.map_err(|_| NetworkError::InterfaceOperationFailed {
-				reason: "Task join error".to_string(),
-			})?
+			.map_err(NetworkError::from)?
+	}
+
+	async fn clear_coalesced(&self) {
+		if let Some(coalescing) = &self.coalescing {
+			coalescing.latest.lock().await.take();
+		}
 	}
 
 	pub async fn get_config(&self) -> NetworkMonitorConfig {
 		let inner_clone = Arc::clone(&self.inner);
-		match tokio::task::spawn_blocking(move || { inner_clone.lock().map(|monitor| monitor.get_config().clone()) }).await {
-			Ok(Ok(config)) => config,
+		match tokio::task::spawn_blocking(move || { inner_clone.lock().ok().map(|monitor| monitor.get_config().clone()) }).await {
+			Ok(Some(config)) => config,
 			_ => NetworkMonitorConfig::default(),
 		}
 	}
 
+	pub async fn overhead_stats(&self) -> OverheadStats {
+		let inner_clone = Arc::clone(&self.inner);
+		match tokio::task::spawn_blocking(move || { inner_clone.lock().ok().map(|monitor| monitor.overhead_stats()) }).await {
+			Ok(Some(stats)) => stats,
+			_ => OverheadStats::default(),
+		}
+	}
+
+	/// See [`NetworkMonitor::stats`].
+	pub async fn stats(&self) -> MonitorStats {
+		let inner_clone = Arc::clone(&self.inner);
+		tokio::task
+			::spawn_blocking(move || { inner_clone.lock().ok().map(|monitor| monitor.stats()) }).await
+			.ok()
+			.flatten()
+			.unwrap_or_default()
+	}
+
+	/// See [`NetworkMonitor::provider_info`].
+	pub async fn provider_info(&self) -> Option<ProviderInfo> {
+		let inner_clone = Arc::clone(&self.inner);
+		tokio::task
+			::spawn_blocking(move || { inner_clone.lock().ok().and_then(|monitor| monitor.provider_info()) }).await
+			.unwrap_or_default()
+	}
+
+	/// See [`NetworkMonitor::recommended_interval`].
+	pub async fn recommended_interval(&self) -> Duration {
+		let inner_clone = Arc::clone(&self.inner);
+		match tokio::task::spawn_blocking(move || { inner_clone.lock().ok().map(|monitor| monitor.recommended_interval()) }).await {
+			Ok(Some(interval)) => interval,
+			_ => self.get_config().await.recommended_interval(&OverheadStats::default()),
+		}
+	}
+
 	pub async fn monitor_continuously<F>(&self, interval_duration: Duration, mut callback: F) -> Result<()>
 		where F: FnMut(Result<NetworkSpeed>) + Send + 'static
 	{
@@ -114,6 +335,27 @@ impl AsyncNetworkMonitor {
 		}
 	}
 
+	/// Like [`monitor_continuously`](Self::monitor_continuously), but returns once `shutdown` is triggered.
+	pub async fn monitor_until<F>(&self, interval_duration: Duration, shutdown: &Shutdown, mut callback: F) -> Result<()>
+		where F: FnMut(Result<NetworkSpeed>) + Send + 'static
+	{
+		let config = self.get_config().await;
+		let effective_interval = effective_interval(interval_duration, &config);
+		let mut interval_timer = interval(effective_interval);
+		interval_timer.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+		loop {
+			tokio::select! {
+				_ = shutdown.triggered() => {
+					return Ok(());
+				}
+				_ = interval_timer.tick() => {
+					callback(self.measure_speed().await);
+				}
+			}
+		}
+	}
+
 	pub async fn monitor_with_channel(
 		&self,
 		interval_duration: Duration,
@@ -134,18 +376,12 @@ impl AsyncNetworkMonitor {
 				let result = {
 					let monitor_clone = Arc::clone(&monitor);
 					let result = tokio::task::spawn_blocking(move || {
-						let mut mon = monitor_clone.lock().map_err(|_| {
-							NetworkError::InterfaceOperationFailed {
-								reason: "Monitor mutex poisoned".to_string(),
-							}
-						})?;
+						let mut mon = monitor_clone.lock().map_err(|_| NetworkError::LockPoisoned { resource: "Monitor" })?;
 						mon.measure_speed()
 					}).await;
 
 					result
-						.map_err(|_| NetworkError::InterfaceOperationFailed {
-							reason: "Task join error".to_string(),
-						})
+						.map_err(NetworkError::from)
 						.and_then(|r| r)
 				};
 
@@ -158,36 +394,83 @@ impl AsyncNetworkMonitor {
 		Ok(rx)
 	}
 
+	/// Takes `sample_count` measurements, skipping failures as allowed by
+	/// [`NetworkMonitorConfig::sample_tolerance`].
 	pub async fn collect_samples(&self, sample_count: usize, interval_duration: Duration) -> Result<Vec<NetworkSpeed>> {
 		let config = self.get_config().await;
 		let effective_interval = effective_interval(interval_duration, &config);
-		let mut samples = Vec::with_capacity(sample_count);
+		let mut collector = SampleCollector::new(config.sample_tolerance, sample_count);
 		let mut interval_timer = interval(effective_interval);
 		interval_timer.set_missed_tick_behavior(MissedTickBehavior::Skip);
 
 		for _ in 0..sample_count {
 			interval_timer.tick().await;
-			match self.measure_speed().await {
-				Ok(speed) => samples.push(speed),
-				Err(NetworkError::InsufficientTimeElapsed { .. }) => {
-					continue;
+			collector.push(self.measure_speed().await)?;
+		}
+
+		collector.finish(effective_interval)
+	}
+
+	/// Measures every `interval_duration` until `deadline`, returning what was collected by then.
+	/// A measurement still in flight at the deadline is abandoned rather than awaited.
+	pub async fn collect_samples_until(&self, deadline: Instant, interval_duration: Duration) -> Result<Vec<NetworkSpeed>> {
+		self.try_collect_samples_until(deadline, interval_duration).await.map_err(|partial| partial.error)
+	}
+
+	/// Like [`collect_samples_until`](Self::collect_samples_until), but a run that stops on too many
+	/// failures hands back the samples gathered up to that point along with the error.
+	pub async fn try_collect_samples_until(
+		&self,
+		deadline: Instant,
+		interval_duration: Duration
+	) -> std::result::Result<Vec<NetworkSpeed>, PartialSamples> {
+		let config = self.get_config().await;
+		let effective_interval = effective_interval(interval_duration, &config);
+		let deadline = tokio::time::Instant::from_std(deadline);
+		let expected = deadline.saturating_duration_since(tokio::time::Instant::now()).as_millis() /
+			effective_interval.as_millis().max(1);
+		let mut collector = SampleCollector::new(config.sample_tolerance, expected as usize);
+		let mut interval_timer = interval(effective_interval);
+		interval_timer.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+		loop {
+			let measured = tokio::time::timeout_at(deadline, async {
+				interval_timer.tick().await;
+				self.measure_speed().await
+			}).await;
+
+			match measured {
+				Ok(result) => {
+					if let Err(error) = collector.push(result) {
+						return Err(PartialSamples {
+							samples: collector.samples,
+							error,
+						});
+					}
 				}
-				Err(e) => {
-					return Err(e);
+				Err(_) => {
+					break;
 				}
 			}
 		}
 
-		if samples.is_empty() {
-			return Err(NetworkError::InsufficientTimeElapsed {
-				min_ms: effective_interval.as_millis() as u64,
-				actual_ms: 0,
-			});
+		match collector.check(effective_interval) {
+			Ok(()) => Ok(collector.samples),
+			Err(error) =>
+				Err(PartialSamples {
+					samples: collector.samples,
+					error,
+				}),
 		}
+	}
 
-		Ok(samples)
+	/// [`collect_samples_until`](Self::collect_samples_until) with a deadline `duration` from now.
+	pub async fn collect_samples_for(&self, duration: Duration, interval_duration: Duration) -> Result<Vec<NetworkSpeed>> {
+		self.collect_samples_until(Instant::now() + duration, interval_duration).await
 	}
 
+	/// Samples for `measurement_duration` and returns their time-weighted average, so ticks that
+	/// were skipped or delayed count for the time they actually covered.
 	pub async fn measure_average_speed(
 		&self,
 		measurement_duration: Duration,
@@ -202,19 +485,10 @@ impl AsyncNetworkMonitor {
 
 		let samples = self.collect_samples(sample_count, sample_interval).await?;
 
-		let avg_upload =
-			samples
-				.iter()
-				.map(|s| s.upload_bytes_per_sec)
-				.sum::<u64>() / (samples.len() as u64);
-
-		let avg_download =
-			samples
-				.iter()
-				.map(|s| s.download_bytes_per_sec)
-				.sum::<u64>() / (samples.len() as u64);
-
-		Ok(NetworkSpeed::new(avg_upload, avg_download))
+		time_weighted_average(&samples).ok_or(NetworkError::InsufficientTimeElapsed {
+			min_ms: sample_interval.as_millis() as u64,
+			actual_ms: 0,
+		})
 	}
 }
 
@@ -228,6 +502,7 @@ pub struct AsyncNetworkSpeedTracker {
 	monitor: AsyncNetworkMonitor,
 	history: Arc<Mutex<VecDeque<NetworkSpeed>>>,
 	max_history_size: usize,
+	labels: MonitorLabels,
 }
 
 impl AsyncNetworkSpeedTracker {
@@ -236,6 +511,7 @@ impl AsyncNetworkSpeedTracker {
 			monitor: AsyncNetworkMonitor::new(),
 			history: Arc::new(Mutex::new(VecDeque::with_capacity(max_history_size))),
 			max_history_size,
+			labels: MonitorLabels::default(),
 		}
 	}
 
@@ -244,9 +520,26 @@ impl AsyncNetworkSpeedTracker {
 			monitor: AsyncNetworkMonitor::with_config(config),
 			history: Arc::new(Mutex::new(VecDeque::with_capacity(max_history_size))),
 			max_history_size,
+			labels: MonitorLabels::default(),
 		}
 	}
 
+	/// Names the tracker; the name is written into Parquet exports.
+	pub fn with_name(mut self, name: impl Into<String>) -> Self {
+		self.labels.name = Some(name.into());
+		self
+	}
+
+	/// Adds a key/value label that is written into Parquet exports.
+	pub fn with_label(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+		self.labels.labels.insert(key.into(), value.into());
+		self
+	}
+
+	pub fn labels(&self) -> &MonitorLabels {
+		&self.labels
+	}
+
 	pub async fn track_speed(&self) -> Result<NetworkSpeed> {
 		let speed = self.monitor.measure_speed().await?;
 
@@ -256,10 +549,8 @@ impl AsyncNetworkSpeedTracker {
 
 		tokio::task
 			::spawn_blocking(
-				move || -> Result<(), NetworkError> {
-					let mut history = history_clone.lock().map_err(|_| NetworkError::InterfaceOperationFailed {
-						reason: "History mutex poisoned".to_string(),
-					})?;
+				move || -> Result<()> {
+					let mut history = history_clone.lock().map_err(|_| NetworkError::LockPoisoned { resource: "History" })?;
 					history.push_back(speed_clone);
 
 					if history.len() > max_size {
@@ -269,21 +560,25 @@ impl AsyncNetworkSpeedTracker {
 					Ok(())
 				}
 			).await
-			.map_err(|_| NetworkError::InterfaceOperationFailed {
-				reason: "Task join error".to_string(),
-			})??;
+			.map_err(NetworkError::from)??;
 
 		Ok(speed)
 	}
 
+	pub async fn overhead_stats(&self) -> OverheadStats {
+		self.monitor.overhead_stats().await
+	}
+
+	pub async fn stats(&self) -> MonitorStats {
+		self.monitor.stats().await
+	}
+
 	pub async fn get_history(&self) -> Vec<NetworkSpeed> {
 		let history_clone = Arc::clone(&self.history);
 		match
 			tokio::task::spawn_blocking(move || {
-				let history = history_clone.lock().map_err(|_| NetworkError::InterfaceOperationFailed {
-					reason: "History mutex poisoned".to_string(),
-				})?;
-				Ok(history.iter().cloned().collect::<Vec<_>>())
+				let history = history_clone.lock().map_err(|_| NetworkError::LockPoisoned { resource: "History" })?;
+				Ok::<_, NetworkError>(history.iter().cloned().collect::<Vec<_>>())
 			}).await
 		{
 			Ok(Ok(history)) => history,
@@ -291,41 +586,16 @@ impl AsyncNetworkSpeedTracker {
 		}
 	}
 
+	/// Time-weighted average of the samples within `duration` (see [`time_weighted_average`]).
 	pub async fn get_average_speed(&self, duration: Duration) -> Option<NetworkSpeed> {
 		let history_clone = Arc::clone(&self.history);
 		tokio::task
-			::spawn_blocking(move || {
-				let history = history_clone.lock().map_err(|_| NetworkError::InterfaceOperationFailed {
-					reason: "History mutex poisoned".to_string(),
-				})?;
-
-				if history.is_empty() {
-					return Ok(None);
-				}
+			::spawn_blocking(move || -> Result<Option<NetworkSpeed>> {
+				let history = history_clone.lock().map_err(|_| NetworkError::LockPoisoned { resource: "History" })?;
 
-				let cutoff_time = Instant::now() - duration;
-				let recent_speeds: Vec<_> = history
-					.iter()
-					.filter(|speed| speed.timestamp >= cutoff_time)
-					.collect();
-
-				if recent_speeds.is_empty() {
-					return Ok(None);
-				}
-
-				let avg_upload =
-					recent_speeds
-						.iter()
-						.map(|s| s.upload_bytes_per_sec)
-						.sum::<u64>() / (recent_speeds.len() as u64);
-
-				let avg_download =
-					recent_speeds
-						.iter()
-						.map(|s| s.download_bytes_per_sec)
-						.sum::<u64>() / (recent_speeds.len() as u64);
-
-				Ok(Some(NetworkSpeed::new(avg_upload, avg_download)))
+				let cutoff_time = Instant::now().checked_sub(duration);
+				let samples = history.iter().filter(|speed| cutoff_time.is_none_or(|cutoff| speed.timestamp >= cutoff));
+				Ok(time_weighted_average(samples))
 			}).await
 			.ok()
 			.and_then(|result| result.ok())
@@ -335,19 +605,17 @@ impl AsyncNetworkSpeedTracker {
 	pub async fn get_peak_speed(&self, duration: Duration) -> Option<NetworkSpeed> {
 		let history_clone = Arc::clone(&self.history);
 		tokio::task
-			::spawn_blocking(move || {
-				let history = history_clone.lock().map_err(|_| NetworkError::InterfaceOperationFailed {
-					reason: "History mutex poisoned".to_string(),
-				})?;
+			::spawn_blocking(move || -> Result<Option<NetworkSpeed>> {
+				let history = history_clone.lock().map_err(|_| NetworkError::LockPoisoned { resource: "History" })?;
 
 				if history.is_empty() {
 					return Ok(None);
 				}
 
-				let cutoff_time = Instant::now() - duration;
+				let cutoff_time = Instant::now().checked_sub(duration);
 				let peak = history
 					.iter()
-					.filter(|speed| speed.timestamp >= cutoff_time)
+					.filter(|speed| cutoff_time.is_none_or(|cutoff| speed.timestamp >= cutoff))
 					.max_by_key(|speed| speed.total_bytes_per_sec())
 					.cloned();
 
@@ -358,6 +626,53 @@ impl AsyncNetworkSpeedTracker {
 			.flatten()
 	}
 
+	pub async fn forecast(&self, horizon: Duration) -> Option<Forecast> {
+		forecast_history(&self.get_history().await, horizon)
+	}
+
+	pub async fn sparkline(&self, duration: Duration, width: usize) -> String {
+		let history_clone = Arc::clone(&self.history);
+		tokio::task
+			::spawn_blocking(move || {
+				let history = history_clone.lock().ok()?;
+				let cutoff_time = Instant::now().checked_sub(duration);
+				let values: Vec<u64> = history
+					.iter()
+					.filter(|speed| cutoff_time.is_none_or(|cutoff| speed.timestamp >= cutoff))
+					.map(|speed| speed.total_bytes_per_sec().bytes_per_sec())
+					.collect();
+
+				Some(render_sparkline(&values, width))
+			}).await
+			.ok()
+			.flatten()
+			.unwrap_or_default()
+	}
+
+	#[cfg(feature = "charts")]
+	pub async fn render_chart(&self, options: &ChartOptions) -> Result<Vec<u8>> {
+		let history = self.get_history().await;
+		let options = options.clone();
+		tokio::task
+			::spawn_blocking(move || render_chart(&history, &options)).await
+			.map_err(NetworkError::from)?
+	}
+
+	#[cfg(feature = "arrow")]
+	pub async fn history_record_batch(&self) -> Result<RecordBatch> {
+		labeled_history_to_record_batch(&self.get_history().await, &self.labels)
+	}
+
+	#[cfg(feature = "arrow")]
+	pub async fn write_parquet(&self, path: impl AsRef<Path>) -> Result<()> {
+		let history = self.get_history().await;
+		let labels = self.labels.clone();
+		let path = path.as_ref().to_path_buf();
+		tokio::task
+			::spawn_blocking(move || write_labeled_history_parquet_file(&history, &labels, path)).await
+			.map_err(NetworkError::from)?
+	}
+
 	pub async fn clear_history(&self) {
 		let history_clone = Arc::clone(&self.history);
 		tokio::task
@@ -417,20 +732,68 @@ impl AsyncNetworkSpeedTracker {
 	}
 }
 
-fn effective_interval(requested: Duration, config: &NetworkMonitorConfig) -> Duration {
-	let mut interval = requested.max(config.min_measurement_interval);
-
-	match &config.precision {
-		PrecisionMode::Instant => interval,
-		PrecisionMode::Windowed { duration } => interval.max(*duration),
-		PrecisionMode::Samples { samples, interval: sample_interval } => {
-			let sample_count: u32 = samples.get().into();
-			let total_sample_window = saturating_mul_duration(*sample_interval, sample_count);
-			interval.max(total_sample_window)
+/// Accumulates sub-sample results under a [`SampleTolerance`].
+struct SampleCollector {
+	tolerance: SampleTolerance,
+	samples: Vec<NetworkSpeed>,
+	failed: usize,
+	last_error: Option<NetworkError>,
+}
+
+impl SampleCollector {
+	fn new(tolerance: SampleTolerance, expected: usize) -> Self {
+		Self {
+			tolerance,
+			samples: Vec::with_capacity(expected),
+			failed: 0,
+			last_error: None,
 		}
 	}
+
+	/// Records one measurement; fails once more samples failed than the tolerance allows.
+	fn push(&mut self, result: Result<NetworkSpeed>) -> Result<()> {
+		match result {
+			Ok(speed) => self.samples.push(speed),
+			Err(NetworkError::InsufficientTimeElapsed { .. }) => {}
+			Err(e) => {
+				self.failed += 1;
+				if self.failed > self.tolerance.max_failed_samples {
+					return Err(e);
+				}
+				self.last_error = Some(e);
+			}
+		}
+		Ok(())
+	}
+
+	fn finish(mut self, effective_interval: Duration) -> Result<Vec<NetworkSpeed>> {
+		self.check(effective_interval)?;
+		Ok(self.samples)
+	}
+
+	/// Fails if the run ended with no samples or fewer than the tolerance requires.
+	fn check(&mut self, effective_interval: Duration) -> Result<()> {
+		if self.samples.is_empty() {
+			return Err(
+				self.last_error.take().unwrap_or(NetworkError::InsufficientTimeElapsed {
+					min_ms: effective_interval.as_millis() as u64,
+					actual_ms: 0,
+				})
+			);
+		}
+
+		if self.samples.len() < self.tolerance.min_successful_samples {
+			return Err(NetworkError::InsufficientSamples {
+				succeeded: self.samples.len(),
+				required: self.tolerance.min_successful_samples,
+				failed: self.failed,
+			});
+		}
+
+		Ok(())
+	}
 }
 
-fn saturating_mul_duration(duration: Duration, factor: u32) -> Duration {
-	duration.checked_mul(factor).unwrap_or(Duration::MAX)
+fn effective_interval(requested: Duration, config: &NetworkMonitorConfig) -> Duration {
+	requested.max(config.min_measurement_interval).max(config.precision.measurement_window())
 }
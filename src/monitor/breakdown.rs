@@ -0,0 +1,245 @@
+use std::collections::HashMap;
+use std::time::{ Duration, Instant };
+
+#[cfg(windows)]
+use windows::Win32::NetworkManagement::IpHelper::{ FreeMibTable, GetIfTable2, MIB_IF_ROW2, MIB_IF_TABLE2 };
+
+use super::interface::NetworkInterface;
+#[cfg(windows)]
+use crate::types::NetworkError;
+use crate::types::{ CongestionLevel, NetworkStatsDelta, PacketCounters, Result };
+
+/// Counters for one direction, split by destination type.
+///
+/// Windows reports multicast and broadcast bytes separately but only a combined non-unicast packet count.
+/// Linux counts neither: all bytes are reported as unicast, and only received multicast packets as
+/// non-unicast.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CastCounters {
+	pub unicast_bytes: u64,
+	pub multicast_bytes: u64,
+	pub broadcast_bytes: u64,
+	pub unicast_packets: u64,
+	pub non_unicast_packets: u64,
+}
+
+impl CastCounters {
+	/// Per-field difference; counters that went backwards (adapter reset) yield zero.
+	pub fn delta_since(&self, earlier: &CastCounters) -> CastCounters {
+		CastCounters {
+			unicast_bytes: self.unicast_bytes.saturating_sub(earlier.unicast_bytes),
+			multicast_bytes: self.multicast_bytes.saturating_sub(earlier.multicast_bytes),
+			broadcast_bytes: self.broadcast_bytes.saturating_sub(earlier.broadcast_bytes),
+			unicast_packets: self.unicast_packets.saturating_sub(earlier.unicast_packets),
+			non_unicast_packets: self.non_unicast_packets.saturating_sub(earlier.non_unicast_packets),
+		}
+	}
+
+	pub fn total_bytes(&self) -> u64 {
+		self.unicast_bytes.saturating_add(self.multicast_bytes).saturating_add(self.broadcast_bytes)
+	}
+
+	/// Fraction of bytes that were multicast or broadcast, or `None` when nothing was counted.
+	pub fn non_unicast_share(&self) -> Option<f64> {
+		let total = self.total_bytes();
+		if total == 0 {
+			return None;
+		}
+		Some(((self.multicast_bytes.saturating_add(self.broadcast_bytes)) as f64) / (total as f64))
+	}
+}
+
+/// Cumulative per-cast counters of one interface.
+#[derive(Debug, Clone)]
+pub struct InterfaceCounters {
+	pub interface: NetworkInterface,
+	pub sent: CastCounters,
+	pub received: CastCounters,
+	/// Outbound packets dropped without an error, typically because the send queue was full.
+	pub outbound_discards: u64,
+	/// Packets waiting in the output queue at read time. Many drivers always report zero.
+	pub output_queue_length: u64,
+}
+
+/// Per-cast counter deltas of one interface over `elapsed`.
+#[derive(Debug, Clone)]
+pub struct InterfaceBreakdown {
+	pub interface: NetworkInterface,
+	pub elapsed: Duration,
+	pub sent: CastCounters,
+	pub received: CastCounters,
+	pub outbound_discards: u64,
+	/// Output queue length at the end of the interval (a level, not a delta).
+	pub output_queue_length: u64,
+}
+
+impl InterfaceBreakdown {
+	pub fn between(earlier: &InterfaceCounters, later: &InterfaceCounters, elapsed: Duration) -> Self {
+		Self {
+			interface: later.interface.clone(),
+			elapsed,
+			sent: later.sent.delta_since(&earlier.sent),
+			received: later.received.delta_since(&earlier.received),
+			outbound_discards: later.outbound_discards.saturating_sub(earlier.outbound_discards),
+			output_queue_length: later.output_queue_length,
+		}
+	}
+
+	pub fn congestion(&self) -> CongestionLevel {
+		let packets_sent = self.sent.unicast_packets.saturating_add(self.sent.non_unicast_packets);
+		CongestionLevel::classify(packets_sent, self.outbound_discards, self.output_queue_length)
+	}
+
+	/// Received multicast + broadcast bytes per second; the usual signature of chatty discovery protocols.
+	pub fn non_unicast_receive_rate(&self) -> f64 {
+		let seconds = self.elapsed.as_secs_f64();
+		if seconds <= 0.0 {
+			return 0.0;
+		}
+		(self.received.multicast_bytes.saturating_add(self.received.broadcast_bytes) as f64) / seconds
+	}
+}
+
+/// Worst congestion level across `breakdowns`; `Clear` when empty.
+pub fn overall_congestion(breakdowns: &[InterfaceBreakdown]) -> CongestionLevel {
+	breakdowns
+		.iter()
+		.map(InterfaceBreakdown::congestion)
+		.max()
+		.unwrap_or_default()
+}
+
+/// Keeps the previous counter snapshot so successive calls yield deltas.
+pub(crate) struct BreakdownState {
+	previous: HashMap<u32, InterfaceCounters>,
+	taken_at: Instant,
+}
+
+impl BreakdownState {
+	pub(crate) fn new(counters: Vec<InterfaceCounters>) -> Self {
+		Self {
+			previous: counters
+				.into_iter()
+				.map(|counters| (counters.interface.index, counters))
+				.collect(),
+			taken_at: Instant::now(),
+		}
+	}
+
+	/// Diffs `counters` against the stored snapshot, then replaces it. Interfaces that are new since
+	/// the last call have no baseline yet and are omitted.
+	pub(crate) fn advance(&mut self, counters: Vec<InterfaceCounters>) -> Vec<InterfaceBreakdown> {
+		let now = Instant::now();
+		let elapsed = now.saturating_duration_since(self.taken_at);
+		let breakdowns = counters
+			.iter()
+			.filter_map(|later| {
+				self.previous
+					.get(&later.interface.index)
+					.map(|earlier| InterfaceBreakdown::between(earlier, later, elapsed))
+			})
+			.collect();
+
+		*self = Self {
+			previous: counters
+				.into_iter()
+				.map(|counters| (counters.interface.index, counters))
+				.collect(),
+			taken_at: now,
+		};
+		breakdowns
+	}
+}
+
+/// Keeps the previous per-interface packet counters so successive calls yield an aggregate delta.
+pub(crate) struct PacketState {
+	previous: HashMap<u32, PacketCounters>,
+	taken_at: Instant,
+}
+
+impl PacketState {
+	pub(crate) fn new(interfaces: &[NetworkInterface]) -> Self {
+		Self {
+			previous: interfaces
+				.iter()
+				.map(|interface| (interface.index, interface.packets))
+				.collect(),
+			taken_at: Instant::now(),
+		}
+	}
+
+	/// Sums the per-interface deltas against the stored snapshot, then replaces it. Interfaces that
+	/// are new since the last call have no baseline yet and are left out, so an adapter appearing
+	/// does not count its whole history as one interval.
+	pub(crate) fn advance(&mut self, interfaces: &[NetworkInterface]) -> NetworkStatsDelta {
+		let now = Instant::now();
+		let counters = interfaces
+			.iter()
+			.filter_map(|interface| {
+				self.previous.get(&interface.index).map(|earlier| interface.packets.delta_since(earlier))
+			})
+			.fold(PacketCounters::default(), |total, delta| total.saturating_add(&delta));
+		let delta = NetworkStatsDelta {
+			counters,
+			elapsed: now.saturating_duration_since(self.taken_at),
+		};
+
+		*self = Self::new(interfaces);
+		self.taken_at = now;
+		delta
+	}
+}
+
+/// Reads per-cast counters for every interface. Requires `GetIfTable2` (Windows Vista and later);
+/// the legacy `GetIfTable` does not report multicast or broadcast octets.
+pub fn list_interface_counters() -> Result<Vec<InterfaceCounters>> {
+	#[cfg(windows)]
+	return unsafe { collect_counters_v2() };
+	#[cfg(target_os = "linux")]
+	return super::linux::list_interface_counters();
+	#[cfg(target_os = "macos")]
+	return super::macos::list_interface_counters();
+}
+
+#[cfg(windows)]
+unsafe fn collect_counters_v2() -> Result<Vec<InterfaceCounters>> {
+	let mut table_ptr: *mut MIB_IF_TABLE2 = std::ptr::null_mut();
+	GetIfTable2(&mut table_ptr).map_err(NetworkError::WindowsApi)?;
+
+	let table = &*table_ptr;
+	let slice = std::slice::from_raw_parts(table.Table.as_ptr(), table.NumEntries as usize);
+	let mut counters = Vec::with_capacity(slice.len());
+
+	for row in slice {
+		counters.push(counters_from_row(row)?);
+	}
+
+	if let Err(err) = FreeMibTable(table_ptr as _) {
+		return Err(NetworkError::WindowsApi(err));
+	}
+
+	Ok(counters)
+}
+
+#[cfg(windows)]
+fn counters_from_row(row: &MIB_IF_ROW2) -> Result<InterfaceCounters> {
+	Ok(InterfaceCounters {
+		interface: NetworkInterface::from_mib_ifrow(row)?,
+		sent: CastCounters {
+			unicast_bytes: row.OutUcastOctets,
+			multicast_bytes: row.OutMulticastOctets,
+			broadcast_bytes: row.OutBroadcastOctets,
+			unicast_packets: row.OutUcastPkts,
+			non_unicast_packets: row.OutNUcastPkts,
+		},
+		received: CastCounters {
+			unicast_bytes: row.InUcastOctets,
+			multicast_bytes: row.InMulticastOctets,
+			broadcast_bytes: row.InBroadcastOctets,
+			unicast_packets: row.InUcastPkts,
+			non_unicast_packets: row.InNUcastPkts,
+		},
+		outbound_discards: row.OutDiscards,
+		output_queue_length: row.OutQLen,
+	})
+}
@@ -0,0 +1,154 @@
+use windows::{
+	core::{ PCSTR, PCWSTR },
+	Win32::Foundation::{ FreeLibrary, HANDLE },
+	Win32::System::LibraryLoader::{ GetProcAddress, LoadLibraryExW, LOAD_LIBRARY_SEARCH_SYSTEM32 },
+};
+
+use super::interface::CounterProvider;
+use crate::types::NetworkError;
+
+/// A data source the crate reads from or can be built on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProviderKind {
+	/// `GetIfTable2`, the default counter source.
+	IfTable2,
+	/// The legacy `GetIfTable` fallback with 32-bit counters.
+	LegacyIfTable,
+	/// Event Tracing for Windows, for per-process and per-connection traffic.
+	Etw,
+	/// Performance Data Helper counters (`\Network Interface(*)\...`).
+	Pdh,
+	/// The Native Wifi API, for signal quality and link rates.
+	Wlan,
+	/// The Mobile Broadband API, for cellular adapters.
+	Mbn,
+}
+
+impl ProviderKind {
+	pub const ALL: [ProviderKind; 6] = [
+		ProviderKind::IfTable2,
+		ProviderKind::LegacyIfTable,
+		ProviderKind::Etw,
+		ProviderKind::Pdh,
+		ProviderKind::Wlan,
+		ProviderKind::Mbn,
+	];
+
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			ProviderKind::IfTable2 => "IF_TABLE2",
+			ProviderKind::LegacyIfTable => "legacy",
+			ProviderKind::Etw => "ETW",
+			ProviderKind::Pdh => "PDH",
+			ProviderKind::Wlan => "WLAN",
+			ProviderKind::Mbn => "MBN",
+		}
+	}
+
+	/// Whether the provider only works from an elevated process. Real-time ETW sessions need
+	/// administrator rights (or membership in Performance Log Users); the others do not.
+	pub fn requires_elevation(&self) -> bool {
+		matches!(self, ProviderKind::Etw)
+	}
+
+	/// The error to report when the provider is used without the privileges it needs.
+	pub fn privilege_error(&self) -> NetworkError {
+		NetworkError::InsufficientPrivileges {
+			provider: self.as_str(),
+		}
+	}
+
+	/// System library and export whose presence means the provider can be used.
+	fn entry_point(&self) -> (&'static str, Option<&'static str>) {
+		match self {
+			ProviderKind::IfTable2 => ("iphlpapi.dll", Some("GetIfTable2")),
+			ProviderKind::LegacyIfTable => ("iphlpapi.dll", Some("GetIfTable")),
+			ProviderKind::Etw => ("advapi32.dll", Some("StartTraceW")),
+			ProviderKind::Pdh => ("pdh.dll", Some("PdhOpenQueryW")),
+			// Missing on Server SKUs without the Wireless LAN Service feature.
+			ProviderKind::Wlan => ("wlanapi.dll", Some("WlanOpenHandle")),
+			// The Mobile Broadband API is COM; its presence follows the WWAN service library.
+			ProviderKind::Mbn => ("wwapi.dll", None),
+		}
+	}
+}
+
+impl From<CounterProvider> for ProviderKind {
+	fn from(provider: CounterProvider) -> Self {
+		match provider {
+			CounterProvider::IfTable2 => ProviderKind::IfTable2,
+			CounterProvider::LegacyIfTable => ProviderKind::LegacyIfTable,
+			// An application-supplied source stands in for the default provider.
+			CounterProvider::Custom(_) => ProviderKind::IfTable2,
+		}
+	}
+}
+
+/// Which providers are available on this system, see [`probe_capabilities`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Capabilities {
+	pub if_table2: bool,
+	pub legacy_if_table: bool,
+	pub etw: bool,
+	pub pdh: bool,
+	pub wlan: bool,
+	pub mbn: bool,
+}
+
+impl Capabilities {
+	pub fn is_available(&self, kind: ProviderKind) -> bool {
+		match kind {
+			ProviderKind::IfTable2 => self.if_table2,
+			ProviderKind::LegacyIfTable => self.legacy_if_table,
+			ProviderKind::Etw => self.etw,
+			ProviderKind::Pdh => self.pdh,
+			ProviderKind::Wlan => self.wlan,
+			ProviderKind::Mbn => self.mbn,
+		}
+	}
+
+	pub fn available(&self) -> impl Iterator<Item = ProviderKind> + '_ {
+		ProviderKind::ALL.into_iter().filter(|kind| self.is_available(*kind))
+	}
+
+	/// Whether interface counters can be read at all, through either table API.
+	pub fn can_monitor(&self) -> bool {
+		self.if_table2 || self.legacy_if_table
+	}
+}
+
+/// Checks which providers this system offers, without constructing a monitor. Only looks for
+/// the system libraries and their exports in `System32`, so a provider reported as available
+/// can still fail later, e.g. for lack of privileges or an absent adapter.
+pub fn probe_capabilities() -> Capabilities {
+	Capabilities {
+		if_table2: is_provider_present(ProviderKind::IfTable2),
+		legacy_if_table: is_provider_present(ProviderKind::LegacyIfTable),
+		etw: is_provider_present(ProviderKind::Etw),
+		pdh: is_provider_present(ProviderKind::Pdh),
+		wlan: is_provider_present(ProviderKind::Wlan),
+		mbn: is_provider_present(ProviderKind::Mbn),
+	}
+}
+
+fn is_provider_present(kind: ProviderKind) -> bool {
+	let (library, export) = kind.entry_point();
+	let wide: Vec<u16> = library.encode_utf16().chain(std::iter::once(0)).collect();
+
+	unsafe {
+		let Ok(module) = LoadLibraryExW(PCWSTR(wide.as_ptr()), HANDLE::default(), LOAD_LIBRARY_SEARCH_SYSTEM32) else {
+			return false;
+		};
+
+		let present = match export {
+			Some(name) => {
+				let name = format!("{name}\0");
+				GetProcAddress(module, PCSTR(name.as_ptr())).is_some()
+			}
+			None => true,
+		};
+
+		let _ = FreeLibrary(module);
+		present
+	}
+}
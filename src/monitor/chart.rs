@@ -0,0 +1,167 @@
+use std::io::Cursor;
+use std::time::{ Duration, Instant };
+
+use image::{ ImageFormat, RgbImage };
+use plotters::coord::Shift;
+use plotters::prelude::*;
+
+use crate::types::{ NetworkError, NetworkSpeed, Result };
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChartFormat {
+	Svg,
+	/// PNG output is rendered without text (captions, axis labels), since no font backend is bundled.
+	Png,
+}
+
+#[derive(Debug, Clone)]
+pub struct ChartOptions {
+	pub format: ChartFormat,
+	pub width: u32,
+	pub height: u32,
+	pub window: Duration,
+	pub title: Option<String>,
+}
+
+impl ChartOptions {
+	pub fn new(format: ChartFormat) -> Self {
+		Self {
+			format,
+			..Self::default()
+		}
+	}
+
+	pub fn with_size(mut self, width: u32, height: u32) -> Self {
+		self.width = width;
+		self.height = height;
+		self
+	}
+
+	pub fn with_window(mut self, window: Duration) -> Self {
+		self.window = window;
+		self
+	}
+
+	pub fn with_title(mut self, title: impl Into<String>) -> Self {
+		self.title = Some(title.into());
+		self
+	}
+}
+
+impl Default for ChartOptions {
+	fn default() -> Self {
+		Self {
+			format: ChartFormat::Svg,
+			width: 800,
+			height: 400,
+			window: Duration::from_secs(300),
+			title: None,
+		}
+	}
+}
+
+/// Renders upload/download throughput (Mbps) over time for the samples inside `options.window`.
+pub fn render_chart(history: &[NetworkSpeed], options: &ChartOptions) -> Result<Vec<u8>> {
+	if options.width == 0 || options.height == 0 {
+		return Err(NetworkError::InvalidConfiguration {
+			field: "chart width and height must be > 0".to_string(),
+		});
+	}
+
+	let cutoff_time = Instant::now().checked_sub(options.window);
+	let samples: Vec<&NetworkSpeed> = history
+		.iter()
+		.filter(|speed| cutoff_time.is_none_or(|cutoff| speed.timestamp >= cutoff))
+		.collect();
+
+	match options.format {
+		ChartFormat::Svg => {
+			let mut svg = String::new();
+			{
+				let root = SVGBackend::with_string(&mut svg, (options.width, options.height)).into_drawing_area();
+				draw_chart(root, &samples, options, true)?;
+			}
+			Ok(svg.into_bytes())
+		}
+		ChartFormat::Png => {
+			let mut pixels = vec![0u8; (options.width as usize) * (options.height as usize) * 3];
+			{
+				let root = BitMapBackend::with_buffer(&mut pixels, (options.width, options.height)).into_drawing_area();
+				draw_chart(root, &samples, options, false)?;
+			}
+
+			let image = RgbImage::from_raw(options.width, options.height, pixels).ok_or_else(|| chart_error("invalid buffer"))?;
+			let mut png = Cursor::new(Vec::new());
+			image.write_to(&mut png, ImageFormat::Png).map_err(chart_error)?;
+			Ok(png.into_inner())
+		}
+	}
+}
+
+fn draw_chart<DB: DrawingBackend>(
+	root: DrawingArea<DB, Shift>,
+	samples: &[&NetworkSpeed],
+	options: &ChartOptions,
+	with_text: bool
+) -> Result<()>
+	where DB::ErrorType: 'static
+{
+	let start = samples.first().map(|speed| speed.timestamp);
+	let point = |speed: &NetworkSpeed, mbps: f64| {
+		let x = start.map(|start| speed.timestamp.duration_since(start).as_secs_f64()).unwrap_or(0.0);
+		(x, mbps)
+	};
+	let upload: Vec<(f64, f64)> = samples
+		.iter()
+		.map(|speed| point(speed, speed.upload_mbps()))
+		.collect();
+	let download: Vec<(f64, f64)> = samples
+		.iter()
+		.map(|speed| point(speed, speed.download_mbps()))
+		.collect();
+
+	let max_x = upload
+		.last()
+		.map(|(x, _)| *x)
+		.unwrap_or(0.0)
+		.max(1.0);
+	let max_y = upload
+		.iter()
+		.chain(download.iter())
+		.map(|(_, y)| *y)
+		.fold(0.0, f64::max)
+		.max(0.001) * 1.1;
+
+	root.fill(&WHITE).map_err(chart_error)?;
+
+	let label_area = if with_text { 40 } else { 0 };
+	let mut builder = ChartBuilder::on(&root);
+	builder.margin(10).x_label_area_size(label_area).y_label_area_size(label_area);
+	if with_text {
+		if let Some(title) = &options.title {
+			builder.caption(title, ("sans-serif", 20));
+		}
+	}
+	let mut chart = builder.build_cartesian_2d(0f64..max_x, 0f64..max_y).map_err(chart_error)?;
+
+	let mut mesh = chart.configure_mesh();
+	if with_text {
+		mesh.x_desc("seconds").y_desc("Mbps");
+	} else {
+		mesh.x_labels(0).y_labels(0);
+	}
+	mesh.draw().map_err(chart_error)?;
+
+	chart.draw_series(LineSeries::new(upload, &RED)).map_err(chart_error)?;
+	chart.draw_series(LineSeries::new(download, &BLUE)).map_err(chart_error)?;
+	root.present().map_err(chart_error)?;
+
+	Ok(())
+}
+
+fn chart_error(err: impl std::fmt::Display) -> NetworkError {
+	NetworkError::Export {
+		format: "Chart",
+		reason: err.to_string(),
+	}
+}
@@ -0,0 +1,149 @@
+use std::sync::OnceLock;
+
+use super::interface::{
+	DetectionSource,
+	InterfaceCategory,
+	NetworkInterface,
+	BLUETOOTH_PHYSICAL_MEDIUM,
+	CONTAINER_KEYWORDS,
+	VIRTUAL_KEYWORDS,
+};
+use crate::types::InterfaceKind;
+
+/// What a [`ClassificationRule`] looks at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RuleMatcher {
+	/// Case-insensitive substring of the alias or the driver description.
+	Keyword(String),
+	/// Case-insensitive substring of the alias only.
+	Alias(String),
+	/// Case-insensitive substring of the driver description only; driver strings are not
+	/// localized, which makes this safer than [`Alias`](Self::Alias) across locales.
+	DriverDescription(String),
+	Kind(InterfaceKind),
+	/// Raw `NDIS_PHYSICAL_MEDIUM` value.
+	PhysicalMedium(u32),
+	/// Interface GUID, compared case-insensitively with or without braces; pins one adapter.
+	Guid(String),
+}
+
+impl RuleMatcher {
+	pub fn matches(&self, interface: &NetworkInterface) -> bool {
+		match self {
+			RuleMatcher::Keyword(keyword) => interface.matches_keyword(&keyword.to_lowercase()),
+			RuleMatcher::Alias(text) => interface.description.to_lowercase().contains(&text.to_lowercase()),
+			RuleMatcher::DriverDescription(text) =>
+				interface.driver_description.to_lowercase().contains(&text.to_lowercase()),
+			RuleMatcher::Kind(kind) => interface.interface_type == *kind,
+			RuleMatcher::PhysicalMedium(medium) => interface.physical_medium == *medium,
+			RuleMatcher::Guid(guid) => {
+				let trim = |guid: &str| guid.trim_matches(|c| c == '{' || c == '}').to_ascii_lowercase();
+				!interface.guid.is_empty() && trim(&interface.guid) == trim(guid)
+			}
+		}
+	}
+
+	pub fn source(&self) -> DetectionSource {
+		match self {
+			RuleMatcher::Keyword(_) | RuleMatcher::Alias(_) | RuleMatcher::DriverDescription(_) =>
+				DetectionSource::Description,
+			RuleMatcher::Kind(_) => DetectionSource::InterfaceType,
+			RuleMatcher::PhysicalMedium(_) => DetectionSource::PhysicalMedium,
+			RuleMatcher::Guid(_) => DetectionSource::Guid,
+		}
+	}
+}
+
+/// Assigns `category` to every interface `matcher` accepts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassificationRule {
+	pub matcher: RuleMatcher,
+	pub category: InterfaceCategory,
+}
+
+impl ClassificationRule {
+	pub fn new(matcher: RuleMatcher, category: InterfaceCategory) -> Self {
+		Self { matcher, category }
+	}
+}
+
+/// Outcome of [`Classifier::classify`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Classification {
+	pub category: InterfaceCategory,
+	pub source: DetectionSource,
+	/// Whether a user rule (rather than a built-in one) decided.
+	pub user_rule: bool,
+}
+
+/// Rule table that maps adapters to an [`InterfaceCategory`].
+///
+/// User rules are tried first, in the order they were added, then the built-in rules; the first
+/// match wins and adapters matching nothing are [`InterfaceCategory::Physical`]. Built-in rules
+/// prefer locale-independent signals (interface type, physical medium) and fall back to keywords.
+#[derive(Debug, Clone, Default)]
+pub struct Classifier {
+	user_rules: Vec<ClassificationRule>,
+}
+
+impl Classifier {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn with_rule(mut self, rule: ClassificationRule) -> Self {
+		self.user_rules.push(rule);
+		self
+	}
+
+	pub fn add_rule(&mut self, rule: ClassificationRule) {
+		self.user_rules.push(rule);
+	}
+
+	pub fn user_rules(&self) -> &[ClassificationRule] {
+		&self.user_rules
+	}
+
+	pub fn builtin_rules() -> &'static [ClassificationRule] {
+		static RULES: OnceLock<Vec<ClassificationRule>> = OnceLock::new();
+		RULES.get_or_init(|| {
+			let keyword = |keyword: &str, category| ClassificationRule::new(RuleMatcher::Keyword(keyword.to_string()), category);
+
+			let mut rules = vec![
+				ClassificationRule::new(RuleMatcher::Kind(InterfaceKind::Loopback), InterfaceCategory::Loopback),
+				ClassificationRule::new(
+					RuleMatcher::PhysicalMedium(BLUETOOTH_PHYSICAL_MEDIUM),
+					InterfaceCategory::Bluetooth
+				),
+				keyword("bluetooth", InterfaceCategory::Bluetooth)
+			];
+			rules.extend(CONTAINER_KEYWORDS.iter().map(|&word| keyword(word, InterfaceCategory::Container)));
+			// Tunnel and PPP interface types are virtual whatever the adapter is called.
+			rules.push(ClassificationRule::new(RuleMatcher::Kind(InterfaceKind::Tunnel), InterfaceCategory::Virtual));
+			rules.push(ClassificationRule::new(RuleMatcher::Kind(InterfaceKind::Other(23)), InterfaceCategory::Virtual));
+			rules.extend(VIRTUAL_KEYWORDS.iter().map(|&word| keyword(word, InterfaceCategory::Virtual)));
+			rules
+		})
+	}
+
+	pub fn classify(&self, interface: &NetworkInterface) -> Classification {
+		let user = self.user_rules.iter().map(|rule| (rule, true));
+		let builtin = Self::builtin_rules()
+			.iter()
+			.map(|rule| (rule, false));
+
+		user
+			.chain(builtin)
+			.find(|(rule, _)| rule.matcher.matches(interface))
+			.map(|(rule, user_rule)| Classification {
+				category: rule.category,
+				source: rule.matcher.source(),
+				user_rule,
+			})
+			.unwrap_or(Classification {
+				category: InterfaceCategory::Physical,
+				source: DetectionSource::Default,
+				user_rule: false,
+			})
+	}
+}
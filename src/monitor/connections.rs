@@ -0,0 +1,532 @@
+use std::collections::HashMap;
+#[cfg(windows)]
+use std::ffi::c_void;
+#[cfg(windows)]
+use std::net::Ipv6Addr;
+use std::net::{ IpAddr, Ipv4Addr, SocketAddr };
+use std::time::{ Duration, Instant };
+
+#[cfg(windows)]
+use windows::{
+	core::HRESULT,
+	Win32::Foundation::{ BOOLEAN, ERROR_INSUFFICIENT_BUFFER, FALSE, NO_ERROR },
+	Win32::NetworkManagement::IpHelper::{
+		GetExtendedTcpTable,
+		GetExtendedUdpTable,
+		GetPerTcp6ConnectionEStats,
+		GetPerTcpConnectionEStats,
+		SetPerTcp6ConnectionEStats,
+		SetPerTcpConnectionEStats,
+		TcpConnectionEstatsData,
+		MIB_TCP6ROW,
+		MIB_TCP6ROW_OWNER_PID,
+		MIB_TCP6TABLE_OWNER_PID,
+		MIB_TCPROW_LH,
+		MIB_TCPROW_OWNER_PID,
+		MIB_TCPTABLE_OWNER_PID,
+		MIB_UDP6ROW_OWNER_PID,
+		MIB_UDP6TABLE_OWNER_PID,
+		MIB_UDPROW_OWNER_PID,
+		MIB_UDPTABLE_OWNER_PID,
+		TCP_ESTATS_DATA_ROD_v0,
+		TCP_ESTATS_DATA_RW_v0,
+		TCP_TABLE_OWNER_PID_ALL,
+		UDP_TABLE_OWNER_PID,
+	},
+	Win32::Networking::WinSock::{ AF_INET, AF_INET6, IN6_ADDR, IN6_ADDR_0 },
+};
+
+use crate::types::PreciseSpeed;
+#[cfg(windows)]
+use crate::types::{ NetworkError, Result };
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConnectionProtocol {
+	Tcp,
+	Udp,
+}
+
+impl ConnectionProtocol {
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			ConnectionProtocol::Tcp => "tcp",
+			ConnectionProtocol::Udp => "udp",
+		}
+	}
+}
+
+/// State of a TCP connection (`MIB_TCP_STATE`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TcpState {
+	Closed,
+	Listen,
+	SynSent,
+	SynReceived,
+	Established,
+	FinWait1,
+	FinWait2,
+	CloseWait,
+	Closing,
+	LastAck,
+	TimeWait,
+	DeleteTcb,
+	Unknown(u32),
+}
+
+impl TcpState {
+	pub fn from_raw(value: u32) -> Self {
+		match value {
+			1 => TcpState::Closed,
+			2 => TcpState::Listen,
+			3 => TcpState::SynSent,
+			4 => TcpState::SynReceived,
+			5 => TcpState::Established,
+			6 => TcpState::FinWait1,
+			7 => TcpState::FinWait2,
+			8 => TcpState::CloseWait,
+			9 => TcpState::Closing,
+			10 => TcpState::LastAck,
+			11 => TcpState::TimeWait,
+			12 => TcpState::DeleteTcb,
+			other => TcpState::Unknown(other),
+		}
+	}
+
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			TcpState::Closed => "closed",
+			TcpState::Listen => "listen",
+			TcpState::SynSent => "syn-sent",
+			TcpState::SynReceived => "syn-received",
+			TcpState::Established => "established",
+			TcpState::FinWait1 => "fin-wait-1",
+			TcpState::FinWait2 => "fin-wait-2",
+			TcpState::CloseWait => "close-wait",
+			TcpState::Closing => "closing",
+			TcpState::LastAck => "last-ack",
+			TcpState::TimeWait => "time-wait",
+			TcpState::DeleteTcb => "delete-tcb",
+			TcpState::Unknown(_) => "unknown",
+		}
+	}
+}
+
+/// Payload bytes a TCP connection carried since extended statistics collection was enabled for
+/// it, from `TCP_ESTATS_DATA_ROD_v0`. Retransmissions and headers are not counted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ConnectionBytes {
+	pub bytes_sent: u64,
+	pub bytes_received: u64,
+}
+
+/// One TCP connection or UDP endpoint with the process that owns it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Connection {
+	pub protocol: ConnectionProtocol,
+	pub local: SocketAddr,
+	/// Remote endpoint; `None` for UDP and for listening TCP sockets.
+	pub remote: Option<SocketAddr>,
+	/// `None` for UDP, which has no connection state.
+	pub state: Option<TcpState>,
+	/// Owning process ID; 0 for the System Idle Process (e.g. `TIME_WAIT` entries), 4 for the kernel.
+	pub pid: u32,
+	/// Byte counters, for TCP connections whose statistics collection was turned on with
+	/// `enable_connection_stats` (Windows).
+	pub bytes: Option<ConnectionBytes>,
+}
+
+impl Connection {
+	/// Identifies the same socket across two listings.
+	pub fn key(&self) -> ConnectionKey {
+		ConnectionKey {
+			protocol: self.protocol,
+			local: self.local,
+			remote: self.remote,
+			pid: self.pid,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ConnectionKey {
+	pub protocol: ConnectionProtocol,
+	pub local: SocketAddr,
+	pub remote: Option<SocketAddr>,
+	pub pid: u32,
+}
+
+/// A connection's rate between two listings, from [`connection_speeds`].
+#[derive(Debug, Clone)]
+pub struct ConnectionSpeed {
+	pub connection: Connection,
+	pub speed: PreciseSpeed,
+}
+
+#[cfg(windows)]
+/// Lists IPv4 and IPv6 TCP connections (`GetExtendedTcpTable`) and UDP endpoints
+/// (`GetExtendedUdpTable`) with their owning process. Byte counters are filled in for TCP
+/// connections whose statistics collection is enabled; reading them needs no elevation.
+pub fn list_connections() -> Result<Vec<Connection>> {
+	let mut connections = Vec::new();
+
+	unsafe {
+		let table = read_table(|buffer, size| GetExtendedTcpTable(buffer, size, FALSE, AF_INET.0 as u32, TCP_TABLE_OWNER_PID_ALL, 0))?;
+		let table = &*(table.as_ptr() as *const MIB_TCPTABLE_OWNER_PID);
+		let rows = std::slice::from_raw_parts(table.table.as_ptr(), table.dwNumEntries as usize);
+		connections.extend(rows.iter().map(|row| tcp_connection(row, read_tcp_bytes(&tcp_row(row)))));
+
+		let table = read_table(|buffer, size| GetExtendedTcpTable(buffer, size, FALSE, AF_INET6.0 as u32, TCP_TABLE_OWNER_PID_ALL, 0))?;
+		let table = &*(table.as_ptr() as *const MIB_TCP6TABLE_OWNER_PID);
+		let rows = std::slice::from_raw_parts(table.table.as_ptr(), table.dwNumEntries as usize);
+		connections.extend(rows.iter().map(|row| tcp6_connection(row, read_tcp6_bytes(&tcp6_row(row)))));
+
+		let table = read_table(|buffer, size| GetExtendedUdpTable(buffer, size, FALSE, AF_INET.0 as u32, UDP_TABLE_OWNER_PID, 0))?;
+		let table = &*(table.as_ptr() as *const MIB_UDPTABLE_OWNER_PID);
+		let rows = std::slice::from_raw_parts(table.table.as_ptr(), table.dwNumEntries as usize);
+		connections.extend(rows.iter().map(udp_connection));
+
+		let table = read_table(|buffer, size| GetExtendedUdpTable(buffer, size, FALSE, AF_INET6.0 as u32, UDP_TABLE_OWNER_PID, 0))?;
+		let table = &*(table.as_ptr() as *const MIB_UDP6TABLE_OWNER_PID);
+		let rows = std::slice::from_raw_parts(table.table.as_ptr(), table.dwNumEntries as usize);
+		connections.extend(rows.iter().map(udp6_connection));
+	}
+
+	Ok(connections)
+}
+
+#[cfg(windows)]
+/// The connections owned by process `pid`.
+pub fn connections_of(pid: u32) -> Result<Vec<Connection>> {
+	let mut connections = list_connections()?;
+	connections.retain(|connection| connection.pid == pid);
+	Ok(connections)
+}
+
+#[cfg(windows)]
+/// Turns on byte counting (`SetPerTcpConnectionEStats`) for every established TCP connection and
+/// returns how many were enabled. Counting starts at this call and only covers connections that
+/// exist now, so call it again before each listing to pick up new ones. Requires an elevated
+/// process; otherwise fails with an `ERROR_ACCESS_DENIED` error (see `needs_elevation`).
+pub fn enable_connection_stats() -> Result<usize> {
+	let rw = TCP_ESTATS_DATA_RW_v0 {
+		EnableCollection: BOOLEAN(1),
+	};
+	let rw = unsafe { as_bytes(&rw) };
+	let mut enabled = 0;
+
+	unsafe {
+		let table = read_table(|buffer, size| GetExtendedTcpTable(buffer, size, FALSE, AF_INET.0 as u32, TCP_TABLE_OWNER_PID_ALL, 0))?;
+		let table = &*(table.as_ptr() as *const MIB_TCPTABLE_OWNER_PID);
+		for row in std::slice::from_raw_parts(table.table.as_ptr(), table.dwNumEntries as usize) {
+			if TcpState::from_raw(row.dwState) == TcpState::Established {
+				let status = SetPerTcpConnectionEStats(&tcp_row(row), TcpConnectionEstatsData, rw, 0, 0);
+				enabled += count_enabled(status)?;
+			}
+		}
+
+		let table = read_table(|buffer, size| GetExtendedTcpTable(buffer, size, FALSE, AF_INET6.0 as u32, TCP_TABLE_OWNER_PID_ALL, 0))?;
+		let table = &*(table.as_ptr() as *const MIB_TCP6TABLE_OWNER_PID);
+		for row in std::slice::from_raw_parts(table.table.as_ptr(), table.dwNumEntries as usize) {
+			if TcpState::from_raw(row.dwState) == TcpState::Established {
+				let status = SetPerTcp6ConnectionEStats(&tcp6_row(row), TcpConnectionEstatsData, rw, 0, 0);
+				enabled += count_enabled(status)?;
+			}
+		}
+	}
+
+	Ok(enabled)
+}
+
+/// Per-connection rates between two listings taken `elapsed` apart, fastest first. Only connections
+/// with byte counters in both listings are included; a counter that went backwards (a reused
+/// socket address) counts as zero.
+pub fn connection_speeds(earlier: &[Connection], later: &[Connection], elapsed: Duration) -> Vec<ConnectionSpeed> {
+	let earlier: HashMap<ConnectionKey, ConnectionBytes> = earlier
+		.iter()
+		.filter_map(|connection| Some((connection.key(), connection.bytes?)))
+		.collect();
+	let timestamp = Instant::now();
+
+	let mut speeds: Vec<ConnectionSpeed> = later
+		.iter()
+		.filter_map(|connection| {
+			let before = earlier.get(&connection.key())?;
+			let after = connection.bytes?;
+			Some(ConnectionSpeed {
+				connection: connection.clone(),
+				speed: PreciseSpeed::from_deltas(
+					after.bytes_sent.saturating_sub(before.bytes_sent),
+					after.bytes_received.saturating_sub(before.bytes_received),
+					elapsed,
+					timestamp
+				),
+			})
+		})
+		.collect();
+	speeds.sort_by(|a, b| b.speed.total_bytes_per_sec().total_cmp(&a.speed.total_bytes_per_sec()));
+	speeds
+}
+
+/// Combined rate of the connections to one remote endpoint, or to one remote IP on any port.
+#[derive(Debug, Clone)]
+pub struct RemoteSpeed {
+	pub ip: IpAddr,
+	/// `None` when connections to every port of `ip` are combined.
+	pub port: Option<u16>,
+	/// Connections that contributed to `speed`.
+	pub connections: usize,
+	pub speed: PreciseSpeed,
+}
+
+impl RemoteSpeed {
+	pub fn addr(&self) -> Option<SocketAddr> {
+		self.port.map(|port| SocketAddr::new(self.ip, port))
+	}
+}
+
+/// Destinations that carried the most traffic, from `top_remotes` (Windows) or [`TopRemotes::from_speeds`].
+#[derive(Debug, Clone, Default)]
+pub struct TopRemotes {
+	/// Per remote IP and port, fastest first.
+	pub endpoints: Vec<RemoteSpeed>,
+	/// Per remote IP across all its ports, fastest first.
+	pub ips: Vec<RemoteSpeed>,
+}
+
+impl TopRemotes {
+	/// Sums the rates of [`connection_speeds`] per remote endpoint and per remote IP, keeping the
+	/// `n` fastest of each. Connections without a remote endpoint (listening sockets) are left out.
+	pub fn from_speeds(speeds: &[ConnectionSpeed], n: usize) -> Self {
+		Self {
+			endpoints: aggregate_remotes(speeds, n, |addr| (addr.ip(), Some(addr.port()))),
+			ips: aggregate_remotes(speeds, n, |addr| (addr.ip(), None)),
+		}
+	}
+}
+
+/// Samples every connection over `window` and returns the `n` remote endpoints and IPs that moved
+/// the most bytes. Blocks for `window`. Turns on byte counting first (see
+/// [`enable_connection_stats`]), so it needs an elevated process; only TCP connections that
+/// existed when the window started are counted.
+#[cfg(windows)]
+pub fn top_remotes(n: usize, window: Duration) -> Result<TopRemotes> {
+	enable_connection_stats()?;
+	let started = Instant::now();
+	let earlier = list_connections()?;
+	std::thread::sleep(window);
+	let later = list_connections()?;
+	Ok(TopRemotes::from_speeds(&connection_speeds(&earlier, &later, started.elapsed()), n))
+}
+
+fn aggregate_remotes(speeds: &[ConnectionSpeed], n: usize, key: impl Fn(SocketAddr) -> (IpAddr, Option<u16>)) -> Vec<RemoteSpeed> {
+	let mut remotes: HashMap<(IpAddr, Option<u16>), RemoteSpeed> = HashMap::new();
+	for entry in speeds {
+		let Some(remote) = entry.connection.remote else {
+			continue;
+		};
+		let (ip, port) = key(remote);
+		let total = remotes.entry((ip, port)).or_insert_with(|| RemoteSpeed {
+			ip,
+			port,
+			connections: 0,
+			speed: PreciseSpeed {
+				upload_bytes_per_sec: 0.0,
+				download_bytes_per_sec: 0.0,
+				..entry.speed
+			},
+		});
+		total.connections += 1;
+		total.speed.upload_bytes_per_sec += entry.speed.upload_bytes_per_sec;
+		total.speed.download_bytes_per_sec += entry.speed.download_bytes_per_sec;
+	}
+
+	let mut remotes: Vec<RemoteSpeed> = remotes.into_values().collect();
+	remotes.sort_by(|a, b| {
+		b.speed
+			.total_bytes_per_sec()
+			.total_cmp(&a.speed.total_bytes_per_sec())
+			.then_with(|| (a.ip, a.port).cmp(&(b.ip, b.port)))
+	});
+	remotes.truncate(n);
+	remotes
+}
+
+#[cfg(windows)]
+/// Calls a sizing IP Helper function twice: once for the size, once to fill the buffer. Retries
+/// while the table grows between the calls. The `u64` backing keeps the rows aligned.
+unsafe fn read_table(mut query: impl FnMut(Option<*mut c_void>, *mut u32) -> u32) -> Result<Vec<u64>> {
+	let mut size = 0u32;
+	let mut status = query(None, &mut size);
+	loop {
+		if status != ERROR_INSUFFICIENT_BUFFER.0 && status != NO_ERROR.0 {
+			return Err(win32_error(status));
+		}
+		let mut buffer = vec![0u64; (size as usize).div_ceil(8).max(1)];
+		status = query(Some(buffer.as_mut_ptr() as *mut c_void), &mut size);
+		if status == NO_ERROR.0 {
+			return Ok(buffer);
+		}
+	}
+}
+
+#[cfg(windows)]
+fn count_enabled(status: u32) -> Result<usize> {
+	if status == NO_ERROR.0 {
+		return Ok(1);
+	}
+	// ERROR_NOT_FOUND: the connection closed since the table was read.
+	if status == 1168 {
+		return Ok(0);
+	}
+	Err(win32_error(status))
+}
+
+#[cfg(windows)]
+fn win32_error(status: u32) -> NetworkError {
+	NetworkError::WindowsApi(windows::core::Error::from(HRESULT::from_win32(status)))
+}
+
+#[cfg(windows)]
+unsafe fn as_bytes<T>(value: &T) -> &[u8] {
+	std::slice::from_raw_parts(value as *const T as *const u8, std::mem::size_of::<T>())
+}
+
+#[cfg(windows)]
+unsafe fn as_bytes_mut<T>(value: &mut T) -> &mut [u8] {
+	std::slice::from_raw_parts_mut(value as *mut T as *mut u8, std::mem::size_of::<T>())
+}
+
+/// Port of an IP Helper row, stored in network byte order in the low 16 bits.
+pub fn port_from_raw(raw: u32) -> u16 {
+	u16::from_be(raw as u16)
+}
+
+/// IPv4 address of an IP Helper row, stored in network byte order.
+pub fn ipv4_from_raw(raw: u32) -> Ipv4Addr {
+	Ipv4Addr::from(raw.to_ne_bytes())
+}
+
+#[cfg(windows)]
+fn socket_addr_v4(addr: u32, port: u32) -> SocketAddr {
+	SocketAddr::new(IpAddr::V4(ipv4_from_raw(addr)), port_from_raw(port))
+}
+
+#[cfg(windows)]
+fn ipv6(bytes: [u8; 16]) -> IpAddr {
+	IpAddr::V6(Ipv6Addr::from(bytes))
+}
+
+#[cfg(windows)]
+fn tcp_connection(row: &MIB_TCPROW_OWNER_PID, bytes: Option<ConnectionBytes>) -> Connection {
+	let state = TcpState::from_raw(row.dwState);
+	Connection {
+		protocol: ConnectionProtocol::Tcp,
+		local: socket_addr_v4(row.dwLocalAddr, row.dwLocalPort),
+		remote: (state != TcpState::Listen).then(|| socket_addr_v4(row.dwRemoteAddr, row.dwRemotePort)),
+		state: Some(state),
+		pid: row.dwOwningPid,
+		bytes,
+	}
+}
+
+#[cfg(windows)]
+fn tcp6_connection(row: &MIB_TCP6ROW_OWNER_PID, bytes: Option<ConnectionBytes>) -> Connection {
+	let state = TcpState::from_raw(row.dwState);
+	Connection {
+		protocol: ConnectionProtocol::Tcp,
+		local: SocketAddr::new(ipv6(row.ucLocalAddr), port_from_raw(row.dwLocalPort)),
+		remote: (state != TcpState::Listen).then(|| SocketAddr::new(ipv6(row.ucRemoteAddr), port_from_raw(row.dwRemotePort))),
+		state: Some(state),
+		pid: row.dwOwningPid,
+		bytes,
+	}
+}
+
+#[cfg(windows)]
+fn udp_connection(row: &MIB_UDPROW_OWNER_PID) -> Connection {
+	Connection {
+		protocol: ConnectionProtocol::Udp,
+		local: socket_addr_v4(row.dwLocalAddr, row.dwLocalPort),
+		remote: None,
+		state: None,
+		pid: row.dwOwningPid,
+		bytes: None,
+	}
+}
+
+#[cfg(windows)]
+fn udp6_connection(row: &MIB_UDP6ROW_OWNER_PID) -> Connection {
+	Connection {
+		protocol: ConnectionProtocol::Udp,
+		local: SocketAddr::new(ipv6(row.ucLocalAddr), port_from_raw(row.dwLocalPort)),
+		remote: None,
+		state: None,
+		pid: row.dwOwningPid,
+		bytes: None,
+	}
+}
+
+#[cfg(windows)]
+/// The row the EStats functions take; they ignore the state.
+fn tcp_row(row: &MIB_TCPROW_OWNER_PID) -> MIB_TCPROW_LH {
+	MIB_TCPROW_LH {
+		dwLocalAddr: row.dwLocalAddr,
+		dwLocalPort: row.dwLocalPort,
+		dwRemoteAddr: row.dwRemoteAddr,
+		dwRemotePort: row.dwRemotePort,
+		..Default::default()
+	}
+}
+
+#[cfg(windows)]
+fn tcp6_row(row: &MIB_TCP6ROW_OWNER_PID) -> MIB_TCP6ROW {
+	MIB_TCP6ROW {
+		LocalAddr: IN6_ADDR {
+			u: IN6_ADDR_0 {
+				Byte: row.ucLocalAddr,
+			},
+		},
+		dwLocalScopeId: row.dwLocalScopeId,
+		dwLocalPort: row.dwLocalPort,
+		RemoteAddr: IN6_ADDR {
+			u: IN6_ADDR_0 {
+				Byte: row.ucRemoteAddr,
+			},
+		},
+		dwRemoteScopeId: row.dwRemoteScopeId,
+		dwRemotePort: row.dwRemotePort,
+		..Default::default()
+	}
+}
+
+#[cfg(windows)]
+/// `None` when collection is off for the connection or it closed in the meantime.
+fn read_tcp_bytes(row: &MIB_TCPROW_LH) -> Option<ConnectionBytes> {
+	let mut rw = TCP_ESTATS_DATA_RW_v0::default();
+	let mut rod = TCP_ESTATS_DATA_ROD_v0::default();
+	let status = unsafe {
+		GetPerTcpConnectionEStats(row, TcpConnectionEstatsData, Some(as_bytes_mut(&mut rw)), 0, None, 0, Some(as_bytes_mut(&mut rod)), 0)
+	};
+	estats_bytes(status, &rw, &rod)
+}
+
+#[cfg(windows)]
+fn read_tcp6_bytes(row: &MIB_TCP6ROW) -> Option<ConnectionBytes> {
+	let mut rw = TCP_ESTATS_DATA_RW_v0::default();
+	let mut rod = TCP_ESTATS_DATA_ROD_v0::default();
+	let status = unsafe {
+		GetPerTcp6ConnectionEStats(row, TcpConnectionEstatsData, Some(as_bytes_mut(&mut rw)), 0, None, 0, Some(as_bytes_mut(&mut rod)), 0)
+	};
+	estats_bytes(status, &rw, &rod)
+}
+
+#[cfg(windows)]
+fn estats_bytes(status: u32, rw: &TCP_ESTATS_DATA_RW_v0, rod: &TCP_ESTATS_DATA_ROD_v0) -> Option<ConnectionBytes> {
+	if status != NO_ERROR.0 || rw.EnableCollection.0 == 0 {
+		return None;
+	}
+	Some(ConnectionBytes {
+		bytes_sent: rod.DataBytesOut,
+		bytes_received: rod.DataBytesIn,
+	})
+}
@@ -0,0 +1,129 @@
+use std::collections::BTreeMap;
+use std::time::{ Duration, SystemTime };
+#[cfg(all(feature = "usage-import", windows))]
+use std::time::UNIX_EPOCH;
+
+#[cfg(all(feature = "usage-import", windows))]
+use windows::{
+	Foundation::DateTime,
+	Networking::Connectivity::{ DataUsageGranularity, NetworkInformation, NetworkUsageStates, TriStates },
+	Win32::System::WinRT::{ RoInitialize, RO_INIT_MULTITHREADED },
+};
+
+#[cfg(all(feature = "usage-import", windows))]
+use super::interface::format_guid;
+#[cfg(all(feature = "usage-import", windows))]
+use crate::types::Result;
+
+/// How far back the Settings → Data usage page looks, and a sensible default for `import_data_usage`.
+pub const DATA_USAGE_HISTORY_DAYS: u32 = 30;
+
+#[cfg(all(feature = "usage-import", windows))]
+const DAY: Duration = Duration::from_secs(86_400);
+/// WinRT `DateTime` ticks (100 ns since 1601-01-01) at the Unix epoch.
+#[cfg(all(feature = "usage-import", windows))]
+const UNIX_EPOCH_TICKS: i64 = 116_444_736_000_000_000;
+
+/// One day of traffic on one network, as recorded by Windows' own usage accounting.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataUsageRecord {
+	/// Network profile name: the SSID for Wi-Fi, e.g. "Ethernet 2" for wired networks.
+	pub profile_name: String,
+	/// GUID of the adapter the profile belongs to, formatted like [`NetworkInterface::guid`](super::interface::NetworkInterface::guid).
+	/// `None` when the adapter is not present anymore.
+	pub interface_guid: Option<String>,
+	/// Start of the day the record covers.
+	pub start: SystemTime,
+	pub bytes_sent: u64,
+	pub bytes_received: u64,
+	/// How long the network was connected during that day.
+	pub connected: Duration,
+}
+
+impl DataUsageRecord {
+	pub fn total_bytes(&self) -> u64 {
+		self.bytes_sent.saturating_add(self.bytes_received)
+	}
+}
+
+/// Bytes moved on one network, see [`data_usage_by_network`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UsageTotals {
+	pub bytes_sent: u64,
+	pub bytes_received: u64,
+}
+
+impl UsageTotals {
+	pub fn total_bytes(&self) -> u64 {
+		self.bytes_sent.saturating_add(self.bytes_received)
+	}
+}
+
+/// Sums imported records per network profile, which for Wi-Fi is the SSID, so usage can be split
+/// between e.g. home, office, and a metered hotspot.
+pub fn data_usage_by_network(records: &[DataUsageRecord]) -> BTreeMap<String, UsageTotals> {
+	let mut usage: BTreeMap<String, UsageTotals> = BTreeMap::new();
+	for record in records {
+		let totals = usage.entry(record.profile_name.clone()).or_default();
+		totals.bytes_sent = totals.bytes_sent.saturating_add(record.bytes_sent);
+		totals.bytes_received = totals.bytes_received.saturating_add(record.bytes_received);
+	}
+	usage
+}
+
+/// Reads the per-network daily usage Windows has already collected for the last `days` days, so
+/// usage tracking can start with history instead of from zero. Oldest days come first within
+/// each network; days without traffic are included with zero bytes.
+#[cfg(all(feature = "usage-import", windows))]
+pub fn import_data_usage(days: u32) -> Result<Vec<DataUsageRecord>> {
+	// WinRT needs the thread in an apartment; an existing one (S_FALSE or RPC_E_CHANGED_MODE) works as well.
+	unsafe {
+		let _ = RoInitialize(RO_INIT_MULTITHREADED);
+	}
+
+	let end = SystemTime::now();
+	let start = end.checked_sub(DAY * days).unwrap_or(UNIX_EPOCH);
+	let states = NetworkUsageStates {
+		Roaming: TriStates::DoNotCare,
+		Shared: TriStates::DoNotCare,
+	};
+
+	let mut records = Vec::new();
+	for profile in NetworkInformation::GetConnectionProfiles()? {
+		let usage = profile
+			.GetNetworkUsageAsync(to_datetime(start), to_datetime(end), DataUsageGranularity::PerDay, states)?
+			.get()?;
+		let profile_name = profile.ProfileName()?.to_string();
+		let interface_guid = profile
+			.NetworkAdapter()
+			.and_then(|adapter| adapter.NetworkAdapterId())
+			.ok()
+			.map(|guid| format_guid(&guid));
+
+		for (day, entry) in usage.into_iter().enumerate() {
+			records.push(DataUsageRecord {
+				profile_name: profile_name.clone(),
+				interface_guid: interface_guid.clone(),
+				start: start + DAY * (day as u32),
+				bytes_sent: entry.BytesSent()?,
+				bytes_received: entry.BytesReceived()?,
+				connected: from_timespan(entry.ConnectionDuration()?.Duration),
+			});
+		}
+	}
+
+	Ok(records)
+}
+
+#[cfg(all(feature = "usage-import", windows))]
+fn to_datetime(time: SystemTime) -> DateTime {
+	let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+	DateTime {
+		UniversalTime: UNIX_EPOCH_TICKS + ((since_epoch.as_nanos() / 100) as i64),
+	}
+}
+
+#[cfg(all(feature = "usage-import", windows))]
+fn from_timespan(ticks: i64) -> Duration {
+	Duration::from_nanos((ticks.max(0) as u64).saturating_mul(100))
+}
@@ -0,0 +1,218 @@
+use windows::{
+	core::{ PCWSTR, PWSTR },
+	Win32::NetworkManagement::IpHelper::{ GetIfEntry2, GetIpInterfaceEntry, MIB_IF_ROW2, MIB_IPINTERFACE_ROW },
+	Win32::Networking::WinSock::{ AF_INET, AF_INET6 },
+	Win32::System::Registry::{
+		RegCloseKey,
+		RegEnumKeyExW,
+		RegGetValueW,
+		RegOpenKeyExW,
+		HKEY,
+		HKEY_LOCAL_MACHINE,
+		KEY_READ,
+		RRF_RT_REG_SZ,
+	},
+};
+
+use super::interface::{ find_by_guid, NetworkInterface };
+use crate::types::{ NetworkError, Result };
+
+/// Device class key of network adapters; each numbered subkey holds one adapter's driver settings.
+const NET_CLASS_KEY: &str = "SYSTEM\\CurrentControlSet\\Control\\Class\\{4d36e972-e325-11ce-bfc1-08002be10318}";
+
+/// State of a checksum offload, from the standardized `*...ChecksumOffload...` keywords.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OffloadState {
+	Disabled,
+	TxEnabled,
+	RxEnabled,
+	TxRxEnabled,
+}
+
+impl OffloadState {
+	/// Parses a keyword value (`"0"` to `"3"`); anything else is `None`.
+	pub fn from_keyword(value: &str) -> Option<Self> {
+		match value.trim() {
+			"0" => Some(OffloadState::Disabled),
+			"1" => Some(OffloadState::TxEnabled),
+			"2" => Some(OffloadState::RxEnabled),
+			"3" => Some(OffloadState::TxRxEnabled),
+			_ => None,
+		}
+	}
+
+	pub fn transmit(&self) -> bool {
+		matches!(self, OffloadState::TxEnabled | OffloadState::TxRxEnabled)
+	}
+
+	pub fn receive(&self) -> bool {
+		matches!(self, OffloadState::RxEnabled | OffloadState::TxRxEnabled)
+	}
+}
+
+/// Offload settings of an adapter. `None` means the driver does not expose the keyword, which
+/// usually means it does not support the offload.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OffloadSettings {
+	pub ipv4_checksum: Option<OffloadState>,
+	pub tcp_checksum_ipv4: Option<OffloadState>,
+	pub tcp_checksum_ipv6: Option<OffloadState>,
+	pub udp_checksum_ipv4: Option<OffloadState>,
+	pub udp_checksum_ipv6: Option<OffloadState>,
+	/// Large Send Offload v2.
+	pub lso_ipv4: Option<bool>,
+	pub lso_ipv6: Option<bool>,
+}
+
+/// Capability and configuration info of one adapter, from [`interface_details`].
+#[derive(Debug, Clone)]
+pub struct InterfaceDetails {
+	pub interface: NetworkInterface,
+	/// MTU the IP stack uses, IPv4 when bound, else IPv6; `None` when neither is bound.
+	pub mtu: Option<u32>,
+	/// Largest packet the adapter accepts at the link layer.
+	pub max_mtu: u32,
+	/// Configured `*JumboPacket` frame size (including the Ethernet header).
+	pub jumbo_packet: Option<u32>,
+	pub rss_enabled: Option<bool>,
+	/// Configured `*NumRssQueues`; drivers that leave it unset use their own default.
+	pub rss_queues: Option<u32>,
+	pub offload: OffloadSettings,
+}
+
+impl InterfaceDetails {
+	/// Whether the IP MTU is below what the adapter could carry, e.g. a jumbo-capable NIC still
+	/// using 1500.
+	pub fn mtu_below_max(&self) -> bool {
+		self.mtu.is_some_and(|mtu| mtu < self.max_mtu)
+	}
+}
+
+/// Reads MTU, RSS, and offload info of the adapter with interface index `index`.
+///
+/// The MTUs come from IP Helper; RSS and offload state are the standardized `*` keywords in the
+/// adapter's driver registry key, i.e. what is configured rather than what the miniport negotiated.
+/// Virtual adapters often have no driver key, in which case those fields are `None`.
+pub fn interface_details(index: u32) -> Result<InterfaceDetails> {
+	let mut row = MIB_IF_ROW2 {
+		InterfaceIndex: index,
+		..Default::default()
+	};
+	unsafe {
+		GetIfEntry2(&mut row)?;
+	}
+	let interface = NetworkInterface::from_mib_ifrow(&row)?;
+	let mtu = ip_mtu(index);
+	let keywords = AdapterKeywords::open(&interface.guid);
+	let keyword = |name: &str| keywords.as_ref().and_then(|keywords| keywords.get(name));
+	let number = |name: &str| keyword(name).and_then(|value| value.trim().parse::<u32>().ok());
+	let flag = |name: &str| number(name).map(|value| value != 0);
+	let offload = |name: &str| keyword(name).and_then(|value| OffloadState::from_keyword(&value));
+
+	Ok(InterfaceDetails {
+		mtu,
+		max_mtu: row.Mtu,
+		jumbo_packet: number("*JumboPacket"),
+		rss_enabled: flag("*RSS"),
+		rss_queues: number("*NumRssQueues"),
+		offload: OffloadSettings {
+			ipv4_checksum: offload("*IPChecksumOffloadIPv4"),
+			tcp_checksum_ipv4: offload("*TCPChecksumOffloadIPv4"),
+			tcp_checksum_ipv6: offload("*TCPChecksumOffloadIPv6"),
+			udp_checksum_ipv4: offload("*UDPChecksumOffloadIPv4"),
+			udp_checksum_ipv6: offload("*UDPChecksumOffloadIPv6"),
+			lso_ipv4: flag("*LsoV2IPv4"),
+			lso_ipv6: flag("*LsoV2IPv6"),
+		},
+		interface,
+	})
+}
+
+/// Like [`interface_details`], looking the adapter up by GUID.
+pub fn interface_details_by_guid(guid: &str) -> Result<InterfaceDetails> {
+	let interface = find_by_guid(guid)?.ok_or(NetworkError::InvalidInterface)?;
+	interface_details(interface.index)
+}
+
+fn ip_mtu(index: u32) -> Option<u32> {
+	[AF_INET, AF_INET6].into_iter().find_map(|family| {
+		let mut row = MIB_IPINTERFACE_ROW {
+			Family: family,
+			InterfaceIndex: index,
+			..Default::default()
+		};
+		unsafe { GetIpInterfaceEntry(&mut row).ok().map(|_| row.NlMtu) }
+	})
+}
+
+/// The driver key (`<class>\NNNN`) of one adapter.
+struct AdapterKeywords {
+	class: HKEY,
+	subkey: Vec<u16>,
+}
+
+impl AdapterKeywords {
+	/// Finds the subkey whose `NetCfgInstanceId` is `guid`.
+	fn open(guid: &str) -> Option<Self> {
+		if guid.is_empty() {
+			return None;
+		}
+		let path = wide(NET_CLASS_KEY);
+		let mut class = HKEY::default();
+		unsafe {
+			RegOpenKeyExW(HKEY_LOCAL_MACHINE, PCWSTR(path.as_ptr()), 0, KEY_READ, &mut class).ok()?;
+		}
+
+		let mut keywords = Self { class, subkey: Vec::new() };
+		for position in 0u32.. {
+			let mut name = [0u16; 256];
+			let mut len = name.len() as u32;
+			let enumerated = unsafe {
+				RegEnumKeyExW(class, position, PWSTR(name.as_mut_ptr()), &mut len, None, PWSTR::null(), None, None)
+			};
+			if enumerated.is_err() {
+				return None;
+			}
+			keywords.subkey = name[..len as usize].iter().copied().chain(std::iter::once(0)).collect();
+			if keywords.get("NetCfgInstanceId").is_some_and(|id| id.eq_ignore_ascii_case(guid)) {
+				return Some(keywords);
+			}
+		}
+		None
+	}
+
+	/// A `REG_SZ` value of the adapter key; standardized keywords are stored as strings.
+	fn get(&self, name: &str) -> Option<String> {
+		let value = wide(name);
+		let mut buffer = [0u16; 256];
+		let mut size = std::mem::size_of_val(&buffer) as u32;
+		unsafe {
+			RegGetValueW(
+				self.class,
+				PCWSTR(self.subkey.as_ptr()),
+				PCWSTR(value.as_ptr()),
+				RRF_RT_REG_SZ,
+				None,
+				Some(buffer.as_mut_ptr().cast()),
+				Some(&mut size)
+			).ok()?;
+		}
+		let len = buffer
+			.iter()
+			.position(|&c| c == 0)
+			.unwrap_or(buffer.len());
+		Some(String::from_utf16_lossy(&buffer[..len]))
+	}
+}
+
+impl Drop for AdapterKeywords {
+	fn drop(&mut self) {
+		unsafe {
+			let _ = RegCloseKey(self.class);
+		}
+	}
+}
+
+fn wide(value: &str) -> Vec<u16> {
+	value.encode_utf16().chain(std::iter::once(0)).collect()
+}
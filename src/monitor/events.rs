@@ -0,0 +1,363 @@
+use std::collections::HashMap;
+use std::sync::mpsc::{ self, Receiver, Sender };
+use std::sync::atomic::{ AtomicU64, Ordering };
+use std::sync::{ Arc, Mutex };
+use std::time::Duration;
+#[cfg(feature = "network-watch")]
+use std::time::Instant;
+
+use super::adaptive::AdaptiveAlert;
+use super::interface::{ local_time, NetworkInterface };
+#[cfg(all(feature = "network-watch", windows))]
+use super::network_profile::{ connected_networks, network_profile_events };
+use super::network_profile::{ NetworkCategory, NetworkProfile };
+use super::shutdown::Shutdown;
+use super::sync_monitor::NetworkMonitor;
+use crate::types::{ MonitorStats, NetworkError, NetworkSpeed, Result, Schedule };
+
+/// Everything the library reports, delivered over one subscription.
+#[derive(Debug, Clone)]
+pub enum Event {
+	Sample(NetworkSpeed),
+	InterfaceAdded(NetworkInterface),
+	InterfaceRemoved(NetworkInterface),
+	LinkSpeedChanged {
+		interface: NetworkInterface,
+		previous_bps: u64,
+	},
+	AlertFired {
+		name: String,
+		message: String,
+	},
+	AlertCleared {
+		name: String,
+	},
+	Anomaly {
+		description: String,
+		speed: NetworkSpeed,
+	},
+	NetworkConnected(NetworkProfile),
+	NetworkDisconnected(NetworkProfile),
+	/// The network moved between the public, private, and domain profiles.
+	NetworkCategoryChanged {
+		network: NetworkProfile,
+		previous: NetworkCategory,
+	},
+}
+
+enum Subscriber {
+	Sync(Sender<Event>),
+	#[cfg(feature = "async")] Async(tokio::sync::mpsc::UnboundedSender<Event>),
+}
+
+impl Subscriber {
+	fn send(&self, event: Event) -> bool {
+		match self {
+			Subscriber::Sync(sender) => sender.send(event).is_ok(),
+			#[cfg(feature = "async")]
+			Subscriber::Async(sender) => sender.send(event).is_ok(),
+		}
+	}
+}
+
+impl Event {
+	/// Whether this is an [`AlertFired`](Event::AlertFired) or [`AlertCleared`](Event::AlertCleared)
+	/// event, which a quiet-hours [`Schedule`] suppresses.
+	pub fn is_alert(&self) -> bool {
+		matches!(self, Event::AlertFired { .. } | Event::AlertCleared { .. })
+	}
+}
+
+/// Fan-out of [`Event`]s to any number of subscribers. Clones share the same subscriber list, so
+/// alerting or anomaly code elsewhere in the application can publish onto the same stream.
+#[derive(Clone, Default)]
+pub struct EventBus {
+	subscribers: Arc<Mutex<Vec<Subscriber>>>,
+	dropped: Arc<AtomicU64>,
+	schedule: Arc<Mutex<Schedule>>,
+	suppressed: Arc<AtomicU64>,
+}
+
+impl EventBus {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn subscribe(&self) -> Receiver<Event> {
+		let (sender, receiver) = mpsc::channel();
+		self.add(Subscriber::Sync(sender));
+		receiver
+	}
+
+	#[cfg(feature = "async")]
+	pub fn subscribe_async(&self) -> tokio::sync::mpsc::UnboundedReceiver<Event> {
+		let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+		self.add(Subscriber::Async(sender));
+		receiver
+	}
+
+	/// Mutes alert events during the schedule's quiet hours, for every clone of this bus.
+	pub fn set_schedule(&self, schedule: Schedule) {
+		if let Ok(mut current) = self.schedule.lock() {
+			*current = schedule;
+		}
+	}
+
+	/// Delivers `event` to every live subscriber; dropped receivers are pruned. Alerts published
+	/// while the [schedule](Self::set_schedule) mutes them are discarded.
+	pub fn publish(&self, event: Event) {
+		if event.is_alert() && self.alerts_muted() {
+			self.suppressed.fetch_add(1, Ordering::Relaxed);
+			return;
+		}
+		if let Ok(mut subscribers) = self.subscribers.lock() {
+			subscribers.retain(|subscriber| {
+				let delivered = subscriber.send(event.clone());
+				if !delivered {
+					self.dropped.fetch_add(1, Ordering::Relaxed);
+				}
+				delivered
+			});
+		}
+	}
+
+	/// Events that could not be delivered because the receiving end had been dropped.
+	pub fn dropped_events(&self) -> u64 {
+		self.dropped.load(Ordering::Relaxed)
+	}
+
+	/// Alert events discarded during quiet hours.
+	pub fn suppressed_alerts(&self) -> u64 {
+		self.suppressed.load(Ordering::Relaxed)
+	}
+
+	pub fn subscriber_count(&self) -> usize {
+		self.subscribers
+			.lock()
+			.map(|subscribers| subscribers.len())
+			.unwrap_or(0)
+	}
+
+	fn alerts_muted(&self) -> bool {
+		self.schedule
+			.lock()
+			.is_ok_and(|schedule| !schedule.is_empty() && schedule.alerts_muted(local_time()))
+	}
+
+	fn add(&self, subscriber: Subscriber) {
+		if let Ok(mut subscribers) = self.subscribers.lock() {
+			subscribers.push(subscriber);
+		}
+	}
+}
+
+/// Diffs two interface snapshots into added/removed/link-speed events.
+///
+/// Interfaces are matched by GUID, falling back to the index when no GUID was reported.
+pub fn interface_events(previous: &[NetworkInterface], current: &[NetworkInterface]) -> Vec<Event> {
+	let key = |interface: &NetworkInterface| {
+		if interface.guid.is_empty() { format!("#{}", interface.index) } else { interface.guid.clone() }
+	};
+	let before: HashMap<String, &NetworkInterface> = previous
+		.iter()
+		.map(|interface| (key(interface), interface))
+		.collect();
+	let after: HashMap<String, &NetworkInterface> = current
+		.iter()
+		.map(|interface| (key(interface), interface))
+		.collect();
+
+	let mut events = Vec::new();
+	for interface in current {
+		match before.get(&key(interface)) {
+			None => events.push(Event::InterfaceAdded(interface.clone())),
+			Some(old) if old.speed != interface.speed => {
+				events.push(Event::LinkSpeedChanged {
+					interface: interface.clone(),
+					previous_bps: old.speed,
+				});
+			}
+			Some(_) => {}
+		}
+	}
+	for interface in previous {
+		if !after.contains_key(&key(interface)) {
+			events.push(Event::InterfaceRemoved(interface.clone()));
+		}
+	}
+
+	events
+}
+
+/// Drives a [`NetworkMonitor`] and publishes samples and interface changes onto an [`EventBus`].
+pub struct EventMonitor {
+	monitor: NetworkMonitor,
+	bus: EventBus,
+	interfaces: Vec<NetworkInterface>,
+	#[cfg(feature = "network-watch")]
+	network_watch: Option<NetworkWatch>,
+	adaptive_alerts: Vec<AdaptiveAlert>,
+}
+
+/// Network state last seen by [`EventMonitor::with_network_watch`].
+#[cfg(feature = "network-watch")]
+struct NetworkWatch {
+	interval: Duration,
+	last_check: Option<Instant>,
+	#[cfg(windows)]
+	networks: Vec<NetworkProfile>,
+}
+
+impl EventMonitor {
+	pub fn new(monitor: NetworkMonitor) -> Self {
+		Self::with_bus(monitor, EventBus::new())
+	}
+
+	/// Uses `bus` for publishing. A non-empty [`schedule`](crate::NetworkMonitorConfig::schedule)
+	/// in the monitor's config replaces the bus's schedule.
+	pub fn with_bus(monitor: NetworkMonitor, bus: EventBus) -> Self {
+		let schedule = &monitor.get_config().schedule;
+		if !schedule.is_empty() {
+			bus.set_schedule(schedule.clone());
+		}
+		Self {
+			monitor,
+			bus,
+			interfaces: Vec::new(),
+			#[cfg(feature = "network-watch")]
+			network_watch: None,
+			adaptive_alerts: Vec::new(),
+		}
+	}
+
+	/// Also publishes network connect/disconnect and category changes, checking at most once per `interval`. Like interfaces, the first check reports every
+	/// connected network. Failed checks are skipped without affecting sampling. Windows only; on
+	/// other systems no network events are published.
+	#[cfg(feature = "network-watch")]
+	pub fn with_network_watch(mut self, interval: Duration) -> Self {
+		self.network_watch = Some(NetworkWatch {
+			interval,
+			last_check: None,
+			#[cfg(windows)]
+			networks: Vec::new(),
+		});
+		self
+	}
+
+	/// Feeds every sample to `alert` and publishes the alert events it returns.
+	pub fn with_adaptive_alert(mut self, alert: AdaptiveAlert) -> Result<Self> {
+		alert.validate()?;
+		self.adaptive_alerts.push(alert);
+		Ok(self)
+	}
+
+	/// The registered adaptive alerts, e.g. to save their learned baselines on shutdown.
+	pub fn adaptive_alerts(&self) -> &[AdaptiveAlert] {
+		&self.adaptive_alerts
+	}
+
+	pub fn bus(&self) -> &EventBus {
+		&self.bus
+	}
+
+	pub fn subscribe(&self) -> Receiver<Event> {
+		self.bus.subscribe()
+	}
+
+	pub fn monitor(&self) -> &NetworkMonitor {
+		&self.monitor
+	}
+
+	/// The monitor's [`stats`](NetworkMonitor::stats) plus the bus's undelivered events.
+	pub fn stats(&self) -> MonitorStats {
+		MonitorStats {
+			dropped_events: self.bus.dropped_events(),
+			..self.monitor.stats()
+		}
+	}
+
+	/// Takes one measurement and publishes the resulting events. The first poll reports every
+	/// selected interface as added.
+	pub fn poll(&mut self) -> Result<NetworkSpeed> {
+		#[cfg(feature = "network-watch")]
+		self.check_networks();
+
+		let speed = match self.monitor.measure_speed() {
+			Ok(speed) => speed,
+			Err(NetworkError::NoInterfacesFound) => {
+				for event in interface_events(&self.interfaces, &[]) {
+					self.bus.publish(event);
+				}
+				self.interfaces.clear();
+				return Err(NetworkError::NoInterfacesFound);
+			}
+			Err(e) => {
+				return Err(e);
+			}
+		};
+
+		if let Some((interfaces, _)) = self.monitor.last_snapshot() {
+			for event in interface_events(&self.interfaces, &interfaces) {
+				self.bus.publish(event);
+			}
+			self.interfaces = interfaces;
+		}
+
+		self.bus.publish(Event::Sample(speed.clone()));
+		if !self.adaptive_alerts.is_empty() {
+			let at = local_time();
+			for alert in &mut self.adaptive_alerts {
+				if let Some(event) = alert.observe(&speed, at) {
+					self.bus.publish(event);
+				}
+			}
+		}
+		Ok(speed)
+	}
+
+	#[cfg(feature = "network-watch")]
+	fn check_networks(&mut self) {
+		let Some(watch) = self.network_watch.as_mut() else {
+			return;
+		};
+		if watch.last_check.is_some_and(|last_check| last_check.elapsed() < watch.interval) {
+			return;
+		}
+		watch.last_check = Some(Instant::now());
+
+		#[cfg(windows)]
+		if let Ok(networks) = connected_networks() {
+			for event in network_profile_events(&watch.networks, &networks) {
+				self.bus.publish(event);
+			}
+			watch.networks = networks;
+		}
+	}
+
+	/// Polls every `interval` until every subscriber has gone away or a non-recoverable error occurs.
+	/// The config's [`Schedule`] stretches the interval while one of its windows is active.
+	pub fn run(&mut self, interval: Duration) -> Result<()> {
+		self.run_until(interval, &Shutdown::new())
+	}
+
+	/// Like [`run`](Self::run), but also returns as soon as `shutdown` is triggered.
+	pub fn run_until(&mut self, interval: Duration, shutdown: &Shutdown) -> Result<()> {
+		while !shutdown.is_triggered() {
+			match self.poll() {
+				Ok(_) => {}
+				Err(e) if e.is_recoverable() => {}
+				Err(NetworkError::NoInterfacesFound) => {}
+				Err(e) => {
+					return Err(e);
+				}
+			}
+
+			let interval = self.monitor.get_config().schedule.sampling_interval(interval, local_time());
+			if self.bus.subscriber_count() == 0 || shutdown.wait_timeout(interval) {
+				return Ok(());
+			}
+		}
+
+		Ok(())
+	}
+}
@@ -0,0 +1,146 @@
+use std::time::Duration;
+
+use super::data_usage::UsageTotals;
+use crate::types::{ ByteRate, NetworkSpeed };
+
+/// Linear-trend projection of throughput from recent history.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Forecast {
+	pub horizon: Duration,
+	/// Rates expected at the end of the horizon.
+	pub upload_bytes_per_sec: ByteRate,
+	pub download_bytes_per_sec: ByteRate,
+	/// Bytes expected to be transferred between the latest sample and the end of the horizon.
+	pub projected_upload_bytes: u64,
+	pub projected_download_bytes: u64,
+	/// Trend in bytes/s per second; negative when traffic is declining.
+	pub upload_trend: f64,
+	pub download_trend: f64,
+}
+
+impl Forecast {
+	pub fn projected_total_bytes(&self) -> u64 {
+		self.projected_upload_bytes.saturating_add(self.projected_download_bytes)
+	}
+}
+
+/// Fits a least-squares line through the samples (rate against time) and extrapolates it
+/// `horizon` past the newest sample. Projected rates are clamped at zero.
+///
+/// Returns `None` with fewer than two samples or when all samples share one timestamp.
+pub fn forecast_history(history: &[NetworkSpeed], horizon: Duration) -> Option<Forecast> {
+	let latest = history.iter().map(|speed| speed.timestamp).max()?;
+	let xs: Vec<f64> = history
+		.iter()
+		.map(|speed| -latest.duration_since(speed.timestamp).as_secs_f64())
+		.collect();
+
+	let (upload_level, upload_trend) = fit_line(
+		&xs,
+		history
+			.iter()
+			.map(|speed| speed.upload_bytes_per_sec.bytes_per_sec() as f64)
+	)?;
+	let (download_level, download_trend) = fit_line(
+		&xs,
+		history
+			.iter()
+			.map(|speed| speed.download_bytes_per_sec.bytes_per_sec() as f64)
+	)?;
+
+	let h = horizon.as_secs_f64();
+	let rate_at = |level: f64, trend: f64| ByteRate::from_bytes_per_sec((level + trend * h).max(0.0) as u64);
+
+	Some(Forecast {
+		horizon,
+		upload_bytes_per_sec: rate_at(upload_level, upload_trend),
+		download_bytes_per_sec: rate_at(download_level, download_trend),
+		projected_upload_bytes: positive_area(upload_level, upload_trend, h) as u64,
+		projected_download_bytes: positive_area(download_level, download_trend, h) as u64,
+		upload_trend,
+		download_trend,
+	})
+}
+
+/// End-of-cycle projection of usage so far (e.g. "1.2 TB this month"), see [`forecast_cycle_end`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CycleForecast {
+	/// Time since the cycle started.
+	pub elapsed: Duration,
+	/// Time left until the cycle ends.
+	pub remaining: Duration,
+	pub used: UsageTotals,
+	/// Bytes expected by the end of the cycle, including [`used`](Self::used).
+	pub projected: UsageTotals,
+}
+
+impl CycleForecast {
+	pub fn projected_total_bytes(&self) -> u64 {
+		self.projected.total_bytes()
+	}
+
+	/// Whether the cycle is on track to go over `cap_bytes`, e.g. a data plan's allowance.
+	pub fn exceeds(&self, cap_bytes: u64) -> bool {
+		self.projected_total_bytes() > cap_bytes
+	}
+}
+
+/// Extends `used`, moved during the first `elapsed` of a cycle, over the `remaining` time at the
+/// same average pace. Returns `None` when no time has elapsed yet.
+pub fn forecast_cycle_end(used: UsageTotals, elapsed: Duration, remaining: Duration) -> Option<CycleForecast> {
+	if elapsed.is_zero() {
+		return None;
+	}
+
+	let scale = remaining.as_secs_f64() / elapsed.as_secs_f64();
+	let project = |bytes: u64| bytes.saturating_add(((bytes as f64) * scale) as u64);
+	Some(CycleForecast {
+		elapsed,
+		remaining,
+		used,
+		projected: UsageTotals {
+			bytes_sent: project(used.bytes_sent),
+			bytes_received: project(used.bytes_received),
+		},
+	})
+}
+
+/// Returns `(intercept at x = 0, slope)`.
+fn fit_line(xs: &[f64], ys: impl Iterator<Item = f64>) -> Option<(f64, f64)> {
+	let ys: Vec<f64> = ys.collect();
+	let n = xs.len() as f64;
+	if xs.len() < 2 {
+		return None;
+	}
+
+	let mean_x = xs.iter().sum::<f64>() / n;
+	let mean_y = ys.iter().sum::<f64>() / n;
+	let (mut covariance, mut variance) = (0.0, 0.0);
+	for (x, y) in xs.iter().zip(&ys) {
+		covariance += (x - mean_x) * (y - mean_y);
+		variance += (x - mean_x) * (x - mean_x);
+	}
+	if variance <= f64::EPSILON {
+		return None;
+	}
+
+	let slope = covariance / variance;
+	Some((mean_y - slope * mean_x, slope))
+}
+
+/// Integral of `max(0, level + trend * x)` over `[0, h]`.
+fn positive_area(level: f64, trend: f64, h: f64) -> f64 {
+	let end = level + trend * h;
+	match (level > 0.0, end > 0.0) {
+		(true, true) => ((level + end) / 2.0) * h,
+		(false, false) => 0.0,
+		(true, false) => {
+			let zero = -level / trend;
+			(level * zero) / 2.0
+		}
+		(false, true) => {
+			let zero = -level / trend;
+			(end * (h - zero)) / 2.0
+		}
+	}
+}
@@ -0,0 +1,131 @@
+use std::borrow::Cow;
+use std::time::{ Duration, Instant };
+
+use egui::{ pos2, vec2, Align2, Color32, FontId, Response, Sense, Shape, Stroke, Ui, Widget };
+
+use super::sync_monitor::NetworkSpeedTracker;
+use crate::types::{ format_bytes_per_second, NetworkSpeed };
+
+/// Scrolling upload/download graph for egui.
+///
+/// The newest sample sits at the right edge; the vertical scale follows the peak inside the window.
+#[derive(Debug, Clone)]
+pub struct SpeedGraph<'a> {
+	history: Cow<'a, [NetworkSpeed]>,
+	window: Duration,
+	size: egui::Vec2,
+	upload_color: Color32,
+	download_color: Color32,
+	show_labels: bool,
+}
+
+impl<'a> SpeedGraph<'a> {
+	pub fn new(history: &'a [NetworkSpeed]) -> Self {
+		Self::from_cow(Cow::Borrowed(history))
+	}
+
+	pub fn from_tracker(tracker: &NetworkSpeedTracker) -> SpeedGraph<'static> {
+		SpeedGraph::from_cow(Cow::Owned(tracker.get_history()))
+	}
+
+	fn from_cow(history: Cow<'a, [NetworkSpeed]>) -> Self {
+		Self {
+			history,
+			window: Duration::from_secs(60),
+			size: vec2(240.0, 80.0),
+			upload_color: Color32::from_rgb(230, 90, 70),
+			download_color: Color32::from_rgb(70, 140, 230),
+			show_labels: true,
+		}
+	}
+
+	pub fn window(mut self, window: Duration) -> Self {
+		self.window = window;
+		self
+	}
+
+	/// Width is a minimum: the graph expands to the available width when there is more room.
+	pub fn size(mut self, width: f32, height: f32) -> Self {
+		self.size = vec2(width, height);
+		self
+	}
+
+	pub fn colors(mut self, upload: Color32, download: Color32) -> Self {
+		self.upload_color = upload;
+		self.download_color = download;
+		self
+	}
+
+	pub fn show_labels(mut self, show: bool) -> Self {
+		self.show_labels = show;
+		self
+	}
+}
+
+impl Widget for SpeedGraph<'_> {
+	fn ui(self, ui: &mut Ui) -> Response {
+		let width = ui.available_width().max(self.size.x);
+		let (rect, response) = ui.allocate_exact_size(vec2(width, self.size.y), Sense::hover());
+		if !ui.is_rect_visible(rect) {
+			return response;
+		}
+
+		let visuals = ui.visuals();
+		let painter = ui.painter_at(rect);
+		painter.rect_filled(rect, visuals.widgets.noninteractive.rounding, visuals.extreme_bg_color);
+
+		let now = Instant::now();
+		let window_secs = self.window.as_secs_f32().max(f32::EPSILON);
+		let samples: Vec<&NetworkSpeed> = self.history
+			.iter()
+			.filter(|speed| now.saturating_duration_since(speed.timestamp) <= self.window)
+			.collect();
+
+		let peak = samples
+			.iter()
+			.map(|speed| speed.upload_bytes_per_sec.max(speed.download_bytes_per_sec).bytes_per_sec())
+			.max()
+			.unwrap_or(0)
+			.max(1);
+
+		let to_point = |speed: &NetworkSpeed, rate: u64| {
+			let age = now.saturating_duration_since(speed.timestamp).as_secs_f32();
+			let x = rect.right() - (age / window_secs) * rect.width();
+			let y = rect.bottom() - ((rate as f32) / (peak as f32)) * rect.height() * 0.9;
+			pos2(x, y)
+		};
+
+		let download: Vec<_> = samples
+			.iter()
+			.map(|speed| to_point(speed, speed.download_bytes_per_sec.bytes_per_sec()))
+			.collect();
+		let upload: Vec<_> = samples
+			.iter()
+			.map(|speed| to_point(speed, speed.upload_bytes_per_sec.bytes_per_sec()))
+			.collect();
+		if samples.len() >= 2 {
+			painter.add(Shape::line(download, Stroke::new(1.5, self.download_color)));
+			painter.add(Shape::line(upload, Stroke::new(1.5, self.upload_color)));
+		}
+
+		if self.show_labels {
+			let font = FontId::monospace(10.0);
+			let text_pos = rect.left_top() + vec2(4.0, 2.0);
+			painter.text(text_pos, Align2::LEFT_TOP, format_bytes_per_second(peak), font.clone(), visuals.weak_text_color());
+
+			if let Some(latest) = samples.last() {
+				let bottom_right = rect.right_bottom() - vec2(4.0, 2.0);
+				painter.text(
+					bottom_right - vec2(0.0, 12.0),
+					Align2::RIGHT_BOTTOM,
+					format!("↑ {}", latest.upload_formatted()),
+					font.clone(),
+					self.upload_color
+				);
+				painter.text(bottom_right, Align2::RIGHT_BOTTOM, format!("↓ {}", latest.download_formatted()), font, self.download_color);
+			}
+		}
+
+		response
+	}
+}
@@ -1,7 +1,9 @@
-use std::collections::{ HashMap, HashSet };
+use std::collections::{ BTreeMap, HashMap, HashSet };
+use std::time::{ Duration, Instant };
+#[cfg(windows)]
 use windows::{
 	core::HRESULT,
-	Win32::Foundation::{ ERROR_INSUFFICIENT_BUFFER, ERROR_INVALID_FUNCTION, FALSE, NO_ERROR },
+	Win32::Foundation::{ ERROR_INSUFFICIENT_BUFFER, ERROR_INVALID_FUNCTION, FALSE, FILETIME, NO_ERROR },
 	Win32::NetworkManagement::IpHelper::{
 		FreeMibTable,
 		GetIfTable,
@@ -11,77 +13,285 @@ use windows::{
 		MIB_IF_ROW2,
 		MIB_IF_TABLE2,
 	},
+	Win32::System::SystemInformation::{ GetLocalTime, GetTickCount64 },
+	Win32::System::Threading::{ GetCurrentThread, GetThreadTimes },
 };
 
-use crate::types::{ format_bits_per_second, NetworkError, NetworkMonitorConfig, Result };
+use super::breakdown::{ list_interface_counters, InterfaceCounters };
+use super::classify::Classifier;
+use super::snapshot::{ CounterEntry, CountersSnapshot };
+use super::source::CounterSource;
+use super::stack::{ interface_stack, InterfaceStack };
+#[cfg(target_os = "linux")]
+pub(crate) use super::linux::{ current_thread_cpu_time, system_uptime_ms };
+#[cfg(target_os = "macos")]
+pub(crate) use super::macos::{ current_thread_cpu_time, system_uptime_ms };
+use crate::types::{
+	format_bits_per_second,
+	format_bytes_total,
+	FilterPriority,
+	InterfaceKind,
+	TunnelAccounting,
+	NetworkError,
+	NetworkMonitorConfig,
+	PacketCounters,
+	Result,
+	RowErrorPolicy,
+	StackAccounting,
+};
+use crate::types::{ LocalTime, Weekday };
+
+/// Coarse grouping of adapters for aggregation and filtering.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum InterfaceCategory {
+	Physical,
+	/// Hyper-V vSwitch / vEthernet, WSL, and container NICs: virtual, but carrying real guest traffic.
+	Container,
+	/// VPNs, tunnels, and other virtual adapters.
+	Virtual,
+	Bluetooth,
+	Loopback,
+}
+
+impl InterfaceCategory {
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			InterfaceCategory::Physical => "physical",
+			InterfaceCategory::Container => "container",
+			InterfaceCategory::Virtual => "virtual",
+			InterfaceCategory::Bluetooth => "bluetooth",
+			InterfaceCategory::Loopback => "loopback",
+		}
+	}
+}
+
+/// What [`NetworkInterface::category`] based its decision on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DetectionSource {
+	/// The Windows interface type (e.g. software loopback).
+	InterfaceType,
+	/// The NDIS physical medium reported by the driver; locale-independent.
+	PhysicalMedium,
+	/// A keyword in the alias or driver description; may miss localized names.
+	Description,
+	/// A rule pinned to the adapter's GUID.
+	Guid,
+	/// Nothing matched, so the adapter is assumed physical.
+	Default,
+}
+
+/// Operational state of an interface (`IF_OPER_STATUS`, RFC 2863).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OperStatus {
+	Up,
+	Down,
+	Testing,
+	Unknown,
+	/// Waiting for an external event, e.g. a dial-up connection or a Wi-Fi association.
+	Dormant,
+	/// A component (typically hardware) is missing.
+	NotPresent,
+	/// Down because a layer beneath it (e.g. the physical NIC under a VPN) is down.
+	LowerLayerDown,
+}
+
+impl OperStatus {
+	/// Maps a raw `IF_OPER_STATUS` value as reported by `GetIfTable2`.
+	pub fn from_raw(status: u32) -> Self {
+		match status {
+			1 => OperStatus::Up,
+			2 => OperStatus::Down,
+			3 => OperStatus::Testing,
+			5 => OperStatus::Dormant,
+			6 => OperStatus::NotPresent,
+			7 => OperStatus::LowerLayerDown,
+			_ => OperStatus::Unknown,
+		}
+	}
+
+	/// Maps the legacy `GetIfTable` `INTERNAL_IF_OPER_STATUS`, which uses different numbering.
+	pub fn from_legacy(status: u32) -> Self {
+		match status {
+			// IF_OPER_STATUS_CONNECTED / IF_OPER_STATUS_OPERATIONAL
+			4 | 5 => OperStatus::Up,
+			// IF_OPER_STATUS_CONNECTING
+			3 => OperStatus::Dormant,
+			// IF_OPER_STATUS_NON_OPERATIONAL / UNREACHABLE / DISCONNECTED
+			0..=2 => OperStatus::Down,
+			_ => OperStatus::Unknown,
+		}
+	}
+
+	pub fn is_up(&self) -> bool {
+		*self == OperStatus::Up
+	}
+
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			OperStatus::Up => "up",
+			OperStatus::Down => "down",
+			OperStatus::Testing => "testing",
+			OperStatus::Unknown => "unknown",
+			OperStatus::Dormant => "dormant",
+			OperStatus::NotPresent => "not present",
+			OperStatus::LowerLayerDown => "lower layer down",
+		}
+	}
+}
 
 #[derive(Debug, Clone)]
 pub struct NetworkInterface {
 	pub index: u32,
-	pub interface_type: u32,
+	/// Interface GUID in registry form (`{XXXXXXXX-XXXX-...}`); stable across reboots, unlike `index`.
+	/// Empty when the OS did not report one.
+	pub guid: String,
+	pub interface_type: InterfaceKind,
+	/// Raw `NDIS_PHYSICAL_MEDIUM` value; 0 (unspecified) when the OS did not report one, as with
+	/// the legacy `GetIfTable` fallback.
+	pub physical_medium: u32,
+	/// Friendly name: the alias (e.g. "Wi-Fi", or a localized "WLAN") when set, else the driver description.
 	pub description: String,
+	/// Description reported by the driver (e.g. "Intel(R) Wi-Fi 6 AX201 160MHz"); not localized.
+	pub driver_description: String,
+	/// Shorthand for `oper_status.is_up()`.
 	pub is_operational: bool,
+	pub oper_status: OperStatus,
 	pub bytes_sent: u64,
 	pub bytes_received: u64,
+	/// Cumulative packet, error, and discard counters; zero where the source has none.
+	pub packets: PacketCounters,
 	pub speed: u64,
 }
 
 impl NetworkInterface {
+	#[cfg(windows)]
 	pub fn from_mib_ifrow(row: &MIB_IF_ROW2) -> Result<Self> {
 		let description = utf16_to_string(&row.Description);
 		let alias = utf16_to_string(&row.Alias);
 		let friendly = if !alias.is_empty() { alias } else { description.clone() };
 
 		let transmit_speed = if row.TransmitLinkSpeed == 0 { row.ReceiveLinkSpeed } else { row.TransmitLinkSpeed };
+		let oper_status = OperStatus::from_raw(row.OperStatus.0 as u32);
 
 		Ok(NetworkInterface {
 			index: row.InterfaceIndex,
-			interface_type: row.Type,
+			guid: format_guid(&row.InterfaceGuid),
+			interface_type: InterfaceKind::from(row.Type),
+			physical_medium: row.PhysicalMediumType.0 as u32,
 			description: if friendly.is_empty() {
-				description
+				description.clone()
 			} else {
 				friendly
 			},
-			// NET_IF_OPER_STATUS_UP is defined as 1.
-			is_operational: row.OperStatus.0 == 1,
+			driver_description: description,
+			is_operational: oper_status.is_up(),
+			oper_status,
 			bytes_sent: row.OutOctets,
 			bytes_received: row.InOctets,
+			packets: PacketCounters {
+				packets_sent: row.OutUcastPkts.saturating_add(row.OutNUcastPkts),
+				packets_received: row.InUcastPkts.saturating_add(row.InNUcastPkts),
+				send_errors: row.OutErrors,
+				receive_errors: row.InErrors,
+				send_discards: row.OutDiscards,
+				receive_discards: row.InDiscards,
+			},
 			speed: transmit_speed,
 		})
 	}
 
+	#[cfg(windows)]
 	pub fn from_legacy_mib_ifrow(row: &MIB_IFROW) -> Result<Self> {
 		let desc_len = (row.dwDescrLen as usize).min(row.bDescr.len());
 		let description = String::from_utf8_lossy(&row.bDescr[..desc_len])
 			.trim()
 			.to_string();
 		let friendly = description.clone();
+		let oper_status = OperStatus::from_legacy(row.dwOperStatus.0 as u32);
+
+		// wszName looks like `\DEVICE\TCPIP_{GUID}`.
+		let name = utf16_to_string(&row.wszName);
+		let guid = match (name.find('{'), name.rfind('}')) {
+			(Some(start), Some(end)) if start < end => name[start..=end].to_uppercase(),
+			_ => String::new(),
+		};
 
 		Ok(NetworkInterface {
 			index: row.dwIndex,
-			interface_type: row.dwType,
+			guid,
+			interface_type: InterfaceKind::from(row.dwType),
+			physical_medium: 0,
 			description: if friendly.is_empty() {
-				description
+				description.clone()
 			} else {
 				friendly
 			},
-			is_operational: row.dwOperStatus.0 == 1,
+			driver_description: description,
+			is_operational: oper_status.is_up(),
+			oper_status,
 			bytes_sent: row.dwOutOctets as u64,
 			bytes_received: row.dwInOctets as u64,
+			packets: PacketCounters {
+				packets_sent: (row.dwOutUcastPkts as u64) + (row.dwOutNUcastPkts as u64),
+				packets_received: (row.dwInUcastPkts as u64) + (row.dwInNUcastPkts as u64),
+				send_errors: row.dwOutErrors as u64,
+				receive_errors: row.dwInErrors as u64,
+				send_discards: row.dwOutDiscards as u64,
+				receive_discards: row.dwInDiscards as u64,
+			},
 			speed: row.dwSpeed as u64,
 		})
 	}
 
 	pub fn is_virtual(&self) -> bool {
-		is_virtual_interface_by_description(&self.description)
+		self.matches_any_keyword(VIRTUAL_KEYWORDS) || self.is_container()
 	}
 
 	pub fn is_loopback(&self) -> bool {
-		self.interface_type == 24
+		self.interface_type == InterfaceKind::Loopback
+	}
+
+	/// Hyper-V vSwitch / vEthernet, WSL, and container adapters. These also count as virtual.
+	pub fn is_container(&self) -> bool {
+		self.matches_any_keyword(CONTAINER_KEYWORDS)
+	}
+
+	/// VPN and tunnel adapters, whose traffic is also counted on the physical NIC carrying it.
+	pub fn is_tunnel(&self) -> bool {
+		// 23 is PPP, 53 the proprietary virtual type used by Wintun/WireGuard and many VPN clients.
+		let tunnel_type = matches!(
+			self.interface_type,
+			InterfaceKind::Tunnel | InterfaceKind::Other(23) | InterfaceKind::Other(53)
+		);
+		(tunnel_type || self.matches_any_keyword(TUNNEL_KEYWORDS)) && !self.is_loopback()
 	}
 
+	/// Category according to the built-in [`Classifier`] rules.
+	pub fn category(&self) -> InterfaceCategory {
+		Classifier::new().classify(self).category
+	}
+
+	/// Why [`category`](Self::category) classified the adapter the way it did.
+	pub fn detection_source(&self) -> DetectionSource {
+		Classifier::new().classify(self).source
+	}
+
+	/// Bluetooth PAN adapters report the Bluetooth physical medium; the keyword check only covers
+	/// drivers that leave the medium unspecified.
 	pub fn is_bluetooth(&self) -> bool {
-		self.description.to_lowercase().contains("bluetooth")
+		self.physical_medium == BLUETOOTH_PHYSICAL_MEDIUM || self.matches_keyword("bluetooth")
+	}
+
+	/// Case-insensitive substring match against the alias and the driver description;
+	/// `keyword` must be lowercase.
+	pub fn matches_keyword(&self, keyword: &str) -> bool {
+		self.description.to_lowercase().contains(keyword) || self.driver_description.to_lowercase().contains(keyword)
+	}
+
+	fn matches_any_keyword(&self, keywords: &[&str]) -> bool {
+		let alias = self.description.to_lowercase();
+		let driver = self.driver_description.to_lowercase();
+		keywords.iter().any(|&keyword| alias.contains(keyword) || driver.contains(keyword))
 	}
 
 	pub fn total_bytes(&self) -> u64 {
@@ -89,29 +299,244 @@ impl NetworkInterface {
 	}
 
 	pub fn type_name(&self) -> &'static str {
-		match self.interface_type {
-			1 => "Other",
-			6 => "Ethernet",
-			9 => "Token Ring",
-			23 => "PPP",
-			24 => "Loopback",
-			37 => "Serial",
-			71 => "Wi-Fi",
-			131 => "Tunnel",
-			144 => "WWAN",
-			145 => "WiMAX",
-			_ => "Unknown",
-		}
+		self.interface_type.name()
 	}
 
 	pub fn formatted_speed(&self) -> String {
 		format_bits_per_second(self.speed)
 	}
+
+	pub fn formatted_total(&self) -> String {
+		format_bytes_total(self.total_bytes())
+	}
+}
+
+/// An entry of the [`InterfaceManager`] cache.
+#[derive(Debug, Clone)]
+pub struct CachedInterface {
+	/// The interface with the counters read when it was last enumerated.
+	pub interface: NetworkInterface,
+	pub last_seen: Instant,
+	/// When the interface first went missing from enumeration; `None` while it is present.
+	pub missing_since: Option<Instant>,
+}
+
+impl CachedInterface {
+	pub fn is_present(&self) -> bool {
+		self.missing_since.is_none()
+	}
+
+	/// Whether a missing entry has outlived `retention` at `now` and should be evicted. With zero
+	/// retention it expires as soon as it goes missing.
+	pub fn is_expired(&self, now: Instant, retention: Duration) -> bool {
+		self.missing_since.is_some_and(|since| retention.is_zero() || now.saturating_duration_since(since) > retention)
+	}
+}
+
+/// Why the configured filters kept or dropped an interface, see [`InterfaceManager::filter_reason`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FilterReason {
+	/// Passed every filter.
+	Selected,
+	/// Kept by `include_interface_indices` / `include_interface_name_patterns` despite matching
+	/// an exclusion (`FilterPriority::IncludeWins`).
+	IncludeList,
+	/// Include lists are set and did not match.
+	NotInIncludeList,
+	/// `oper_status` is dormant and `include_dormant_interfaces` is off.
+	Dormant,
+	Loopback,
+	Virtual,
+	Bluetooth,
+	/// Listed in `interface_type_filters`.
+	InterfaceType(InterfaceKind),
+	/// Matched this entry of `interface_name_filters`.
+	NameFilter(String),
+	/// Passed the filters but dropped by `tunnel_accounting`.
+	TunnelAccounting,
+	/// Passed the filters but dropped by `stack_accounting`, because another layer of its stack is
+	/// counted instead.
+	StackAccounting,
+}
+
+impl FilterReason {
+	pub fn is_included(&self) -> bool {
+		matches!(self, FilterReason::Selected | FilterReason::IncludeList)
+	}
+}
+
+impl std::fmt::Display for FilterReason {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			FilterReason::Selected => f.write_str("selected"),
+			FilterReason::IncludeList => f.write_str("selected by include list"),
+			FilterReason::NotInIncludeList => f.write_str("not in include list"),
+			FilterReason::Dormant => f.write_str("excluded: dormant"),
+			FilterReason::Loopback => f.write_str("excluded: loopback"),
+			FilterReason::Virtual => f.write_str("excluded: virtual"),
+			FilterReason::Bluetooth => f.write_str("excluded: bluetooth"),
+			FilterReason::InterfaceType(kind) => write!(f, "excluded: type {kind}"),
+			FilterReason::NameFilter(filter) => write!(f, "excluded: name filter \"{filter}\""),
+			FilterReason::TunnelAccounting => f.write_str("excluded: tunnel accounting"),
+			FilterReason::StackAccounting => f.write_str("excluded: stack accounting"),
+		}
+	}
+}
+
+/// An interface together with the filter verdict for it.
+#[derive(Debug, Clone)]
+pub struct FilterDecision {
+	pub interface: NetworkInterface,
+	pub reason: FilterReason,
+}
+
+/// OS API, or application-supplied [`CounterSource`], the interface counters were read from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CounterProvider {
+	/// `GetIfTable2`: 64-bit counters plus physical medium, driver description, and GUID.
+	IfTable2,
+	/// The legacy `GetIfTable` fallback, used when `GetIfTable2` is unavailable. Counters are
+	/// 32-bit and wrap after 4 GiB, and the physical medium and GUID are not reported, so
+	/// Bluetooth detection and GUID rules fall back to keywords.
+	LegacyIfTable,
+	/// `/proc/net/dev` on Linux, with type, status, and link speed from `/sys/class/net`. Counters
+	/// are 64-bit; interfaces have no GUID, and the driver name stands in for the description.
+	#[cfg(target_os = "linux")]
+	ProcNetDev,
+	/// `sysctl(NET_RT_IFLIST2)` on macOS, with 64-bit counters and the link speed the driver
+	/// reports. Interfaces have no GUID, and Wi-Fi classifies as Ethernet.
+	#[cfg(target_os = "macos")]
+	SysctlIfList2,
+	/// A [`CounterSource`] supplied by the application, with the name it gave itself. Assumed to
+	/// report 64-bit counters.
+	Custom(&'static str),
+}
+
+impl CounterProvider {
+	pub fn is_legacy(&self) -> bool {
+		*self == CounterProvider::LegacyIfTable
+	}
+
+	/// Width of the byte counters in bits.
+	pub fn counter_bits(&self) -> u32 {
+		match self {
+			CounterProvider::IfTable2 => 64,
+			CounterProvider::LegacyIfTable => 32,
+			#[cfg(target_os = "linux")]
+			CounterProvider::ProcNetDev => 64,
+			#[cfg(target_os = "macos")]
+			CounterProvider::SysctlIfList2 => 64,
+			CounterProvider::Custom(_) => 64,
+		}
+	}
+
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			CounterProvider::IfTable2 => "GetIfTable2",
+			CounterProvider::LegacyIfTable => "GetIfTable",
+			#[cfg(target_os = "linux")]
+			CounterProvider::ProcNetDev => "/proc/net/dev",
+			#[cfg(target_os = "macos")]
+			CounterProvider::SysctlIfList2 => "NET_RT_IFLIST2",
+			CounterProvider::Custom(name) => name,
+		}
+	}
+}
+
+/// Where a monitor's counters currently come from, see [`CounterProvider`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProviderInfo {
+	pub provider: CounterProvider,
+	/// Number of reads served by the legacy fallback since the monitor was created.
+	pub legacy_reads: u64,
+}
+
+impl ProviderInfo {
+	pub fn is_legacy(&self) -> bool {
+		self.provider.is_legacy()
+	}
+}
+
+/// Interfaces whose counters made up an aggregate measurement.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Composition {
+	/// Incremented every time the set of contributing interfaces changes.
+	pub version: u64,
+	/// Interface indices, sorted.
+	pub indices: Vec<u32>,
+}
+
+impl Composition {
+	pub fn changed_since(&self, earlier: &Composition) -> bool {
+		self.version != earlier.version
+	}
+
+	/// Indices contributing now that did not in `earlier`.
+	pub fn added<'a>(&'a self, earlier: &'a Composition) -> impl Iterator<Item = u32> + 'a {
+		self.indices
+			.iter()
+			.copied()
+			.filter(move |index| earlier.indices.binary_search(index).is_err())
+	}
+
+	/// Indices that contributed in `earlier` but no longer do.
+	pub fn removed<'a>(&'a self, earlier: &'a Composition) -> impl Iterator<Item = u32> + 'a {
+		earlier.added(self)
+	}
+}
+
+/// An interface table row skipped under [`RowErrorPolicy::Skip`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedRow {
+	/// Position of the row in the table.
+	pub row: usize,
+	/// [`NetworkError::error_code`] of the conversion failure.
+	pub error_code: u32,
+	pub message: String,
+}
+
+impl SkippedRow {
+	fn new(row: usize, err: &NetworkError) -> Self {
+		#[cfg(feature = "tracing")]
+		tracing::warn!(row, error_code = err.error_code(), error = %err, "skipping interface row that failed to convert");
+		Self {
+			row,
+			error_code: err.error_code(),
+			message: err.to_string(),
+		}
+	}
 }
 
 pub struct InterfaceManager {
 	config: NetworkMonitorConfig,
-	interface_cache: HashMap<u32, NetworkInterface>,
+	interface_cache: HashMap<u32, CachedInterface>,
+	/// Status-independent filter verdicts by interface index, so snapshots skip building
+	/// `NetworkInterface`s.
+	#[cfg(windows)]
+	include_decisions: HashMap<u32, CachedDecision>,
+	/// Whether a selected tunnel adapter was up at the last enumeration (for `PreferTunnel`).
+	tunnel_active: bool,
+	/// Scratch list of `(index, is_up)` for the tunnel entries of the snapshot being filled.
+	snapshot_tunnels: Vec<(u32, bool)>,
+	/// Interfaces summed by the latest `get_total_traffic`.
+	composition: Composition,
+	composition_scratch: Vec<u32>,
+	/// API used by the latest read; `None` until the first one.
+	provider: Option<CounterProvider>,
+	legacy_reads: u64,
+	/// Rows skipped by the latest read under `RowErrorPolicy::Skip`.
+	skipped_rows: Vec<SkippedRow>,
+	/// Stack table of the latest read; only refreshed when `stack_accounting` needs it.
+	stack: InterfaceStack,
+	/// Replaces the OS counters when set.
+	source: Option<Box<dyn CounterSource>>,
+}
+
+#[cfg(windows)]
+#[derive(Debug, Clone, Copy)]
+struct CachedDecision {
+	include: bool,
+	tunnel: bool,
 }
 
 impl InterfaceManager {
@@ -119,198 +544,988 @@ impl InterfaceManager {
 		Self {
 			config,
 			interface_cache: HashMap::new(),
+			#[cfg(windows)]
+			include_decisions: HashMap::new(),
+			tunnel_active: false,
+			snapshot_tunnels: Vec::new(),
+			composition: Composition::default(),
+			composition_scratch: Vec::new(),
+			provider: None,
+			legacy_reads: 0,
+			skipped_rows: Vec::new(),
+			stack: InterfaceStack::default(),
+			source: None,
+		}
+	}
+
+	/// Reads counters from `source` instead of the OS.
+	pub fn with_source(config: NetworkMonitorConfig, source: impl CounterSource + 'static) -> Self {
+		Self {
+			source: Some(Box::new(source)),
+			..Self::new(config)
 		}
 	}
 
+	/// Starts over with `config`, keeping the counter source.
+	pub(crate) fn reconfigure(&mut self, config: NetworkMonitorConfig) {
+		let source = self.source.take();
+		*self = Self::new(config);
+		self.source = source;
+	}
+
+	/// Every interface from the counter source, or from the OS when there is none.
+	fn read_interfaces(&mut self) -> Result<Vec<Result<NetworkInterface>>> {
+		let (provider, rows) = match self.source.as_mut() {
+			Some(source) => {
+				let rows = source.read_interfaces().map_err(|err| err.in_provider(source.provider().as_str()))?;
+				(source.provider(), rows)
+			}
+			None => {
+				let iter = interfaces_iter()?;
+				(iter.provider(), iter.collect())
+			}
+		};
+		self.record_provider(provider);
+		Ok(rows)
+	}
+
+	/// Enumerates the interfaces that pass the configured filters. Interfaces that vanish stay in
+	/// the cache for `interface_retention` so a brief enumeration glitch keeps their last counters.
 	pub fn get_active_interfaces(&mut self) -> Result<Vec<NetworkInterface>> {
-		let enumerated = get_raw_interfaces()?;
+		let rows = self.read_interfaces()?;
+		self.skipped_rows.clear();
+		let mut raw = Vec::with_capacity(rows.len());
+		for (row, interface) in rows.into_iter().enumerate() {
+			match interface {
+				Ok(interface) => raw.push(interface),
+				Err(err) => self.skip_row(row, err)?,
+			}
+		}
+		let selected: Vec<NetworkInterface> = raw
+			.into_iter()
+			.filter(|interface| self.should_include_interface(interface))
+			.collect();
+		self.tunnel_active = selected.iter().any(|interface| interface.is_tunnel() && interface.oper_status.is_up());
+
+		let candidates: Vec<u32> = selected
+			.iter()
+			.filter(|interface| self.keeps_after_tunnel_dedup(interface.is_tunnel()))
+			.map(|interface| interface.index)
+			.collect();
+		let shadowed = self.stack_shadowed(&candidates);
+
+		let now = Instant::now();
 		let mut active_interfaces = Vec::new();
 		let mut active_indices = HashSet::new();
 
-		for interface in enumerated {
-			if self.should_include_interface(&interface) {
-				self.interface_cache.insert(interface.index, interface.clone());
+		for interface in selected {
+			if self.keeps_after_tunnel_dedup(interface.is_tunnel()) && !shadowed.contains(&interface.index) {
+				self.interface_cache.insert(interface.index, CachedInterface {
+					interface: interface.clone(),
+					last_seen: now,
+					missing_since: None,
+				});
 				active_indices.insert(interface.index);
 				active_interfaces.push(interface);
 			}
 		}
 
+		let retention = self.config.interface_retention;
+		for (index, entry) in self.interface_cache.iter_mut() {
+			if !active_indices.contains(index) && entry.missing_since.is_none() {
+				#[cfg(feature = "tracing")]
+				tracing::warn!(
+					index,
+					name = %entry.interface.description,
+					bytes_sent = entry.interface.bytes_sent,
+					bytes_received = entry.interface.bytes_received,
+					since_last_seen_ms = now.saturating_duration_since(entry.last_seen).as_millis() as u64,
+					retention_ms = retention.as_millis() as u64,
+					"interface vanished from enumeration, keeping its last counters"
+				);
+				entry.missing_since = Some(now);
+			}
+		}
+		self.interface_cache.retain(|_, entry| {
+			let expired = entry.is_expired(now, retention);
+			#[cfg(feature = "tracing")]
+			if expired {
+				tracing::warn!(
+					index = entry.interface.index,
+					name = %entry.interface.description,
+					bytes_sent = entry.interface.bytes_sent,
+					bytes_received = entry.interface.bytes_received,
+					"interface missing for longer than its retention, dropping it from the totals"
+				);
+			}
+			!expired
+		});
+
 		if active_indices.is_empty() {
-			self.interface_cache.clear();
 			return Err(NetworkError::NoInterfacesFound);
 		}
 
-		self.interface_cache.retain(|index, _| active_indices.contains(index));
-
 		Ok(active_interfaces)
 	}
 
+	/// Summed counters of the active interfaces plus the last known counters of interfaces still
+	/// within `interface_retention`, so a briefly missing adapter does not make the total dip.
 	pub fn get_total_traffic(&mut self) -> Result<(u64, u64)> {
-		let interfaces = self.get_active_interfaces()?;
+		self.get_active_interfaces()?;
 
-		let total_sent = interfaces
-			.iter()
-			.map(|i| i.bytes_sent)
-			.sum();
-		let total_received = interfaces
-			.iter()
-			.map(|i| i.bytes_received)
-			.sum();
+		let mut contributors = std::mem::take(&mut self.composition_scratch);
+		contributors.clear();
+		let (mut total_sent, mut total_received) = (0u64, 0u64);
+		for entry in self.interface_cache.values() {
+			if self.counts_in_total(entry.interface.is_tunnel()) {
+				total_sent += entry.interface.bytes_sent;
+				total_received += entry.interface.bytes_received;
+				contributors.push(entry.interface.index);
+			}
+		}
+
+		contributors.sort_unstable();
+		if contributors != self.composition.indices {
+			std::mem::swap(&mut contributors, &mut self.composition.indices);
+			self.composition.version += 1;
+		}
+		self.composition_scratch = contributors;
 
 		Ok((total_sent, total_received))
 	}
 
+	/// Sorted indices of the interfaces summed by the latest aggregate; does not allocate.
+	pub fn contributing_interfaces(&self) -> &[u32] {
+		&self.composition.indices
+	}
+
+	pub fn composition(&self) -> &Composition {
+		&self.composition
+	}
+
+	/// The counter API used by the latest read, or `None` before the first one.
+	pub fn provider_info(&self) -> Option<ProviderInfo> {
+		self.provider.map(|provider| ProviderInfo {
+			provider,
+			legacy_reads: self.legacy_reads,
+		})
+	}
+
+	/// Rows of the latest read that failed to convert and were skipped under
+	/// [`RowErrorPolicy::Skip`]; empty when the read was complete.
+	pub fn skipped_rows(&self) -> &[SkippedRow] {
+		&self.skipped_rows
+	}
+
+	/// The interface stack read by the latest enumeration; empty under `StackAccounting::CountAll`,
+	/// which does not read it. See [`interface_stack`] for an on-demand read.
+	pub fn stack(&self) -> &InterfaceStack {
+		&self.stack
+	}
+
+	/// Which of `candidates` `stack_accounting` leaves to another layer of their stack. Refreshes
+	/// the stack table; if that read fails, the previous table is used.
+	fn stack_shadowed(&mut self, candidates: &[u32]) -> Vec<u32> {
+		if self.config.stack_accounting == StackAccounting::CountAll {
+			return Vec::new();
+		}
+		let stack = match self.source.as_mut() {
+			Some(source) => source.interface_stack(),
+			None => interface_stack(),
+		};
+		if let Ok(stack) = stack {
+			self.stack = stack;
+		}
+		self.stack.shadowed(candidates, self.config.stack_accounting)
+	}
+
+	/// Records a row that failed to convert, or returns its error under `RowErrorPolicy::Fail`.
+	fn skip_row(&mut self, row: usize, err: NetworkError) -> Result<()> {
+		match self.config.row_errors {
+			RowErrorPolicy::Fail => Err(err),
+			RowErrorPolicy::Skip => {
+				self.skipped_rows.push(SkippedRow::new(row, &err));
+				Ok(())
+			}
+		}
+	}
+
+	fn record_provider(&mut self, provider: CounterProvider) {
+		if provider.is_legacy() {
+			#[cfg(feature = "tracing")]
+			if self.provider != Some(provider) {
+				tracing::warn!(
+					previous = ?self.provider.as_ref().map(CounterProvider::as_str),
+					legacy_reads = self.legacy_reads,
+					"GetIfTable2 unavailable, falling back to 32-bit GetIfTable counters"
+				);
+			}
+			self.legacy_reads += 1;
+		}
+		self.provider = Some(provider);
+	}
+
+	/// Per-cast counters of the interfaces that pass the configured filters.
+	pub fn get_active_interface_counters(&mut self) -> Result<Vec<InterfaceCounters>> {
+		let counters = match self.source.as_mut() {
+			Some(source) => source.interface_counters()?,
+			None => list_interface_counters()?,
+		};
+		let counters: Vec<InterfaceCounters> = counters
+			.into_iter()
+			.filter(|counters| self.should_include_interface(&counters.interface))
+			.collect();
+
+		if counters.is_empty() {
+			return Err(NetworkError::NoInterfacesFound);
+		}
+
+		Ok(counters)
+	}
+
 	pub fn get_interface_by_index(&self, index: u32) -> Option<&NetworkInterface> {
-		self.interface_cache.get(&index)
+		self.interface_cache.get(&index).map(|entry| &entry.interface)
+	}
+
+	/// Interfaces present in the most recent enumeration, with the counters read at that time.
+	pub fn cached_interfaces(&self) -> Vec<NetworkInterface> {
+		self.cache_entries()
+			.into_iter()
+			.filter(CachedInterface::is_present)
+			.map(|entry| entry.interface)
+			.collect()
+	}
+
+	/// Every cache entry, including missing interfaces still within `interface_retention`.
+	pub fn cache_entries(&self) -> Vec<CachedInterface> {
+		let mut entries: Vec<CachedInterface> = self.interface_cache.values().cloned().collect();
+		entries.sort_by_key(|entry| entry.interface.index);
+		entries
 	}
 
 	pub fn refresh_cache(&mut self) -> Result<()> {
 		self.interface_cache.clear();
+		#[cfg(windows)]
+		self.include_decisions.clear();
 		self.get_active_interfaces()?;
 		Ok(())
 	}
 
-	fn should_include_interface(&self, interface: &NetworkInterface) -> bool {
-		if
-			!self.config.include_interface_indices.is_empty() &&
-			!self.config.include_interface_indices.contains(&interface.index)
-		{
-			return false;
+	/// Refills `snapshot` with the counters of the selected interfaces, reusing its buffer.
+	pub fn fill_snapshot(&mut self, snapshot: &mut CountersSnapshot) -> Result<()> {
+		snapshot.clear();
+		self.snapshot_tunnels.clear();
+		self.skipped_rows.clear();
+
+		if self.source.is_some() {
+			let rows = self.read_interfaces()?;
+			self.fill_snapshot_from(rows.into_iter(), snapshot)?;
+		} else {
+			self.fill_system_snapshot(snapshot)?;
 		}
 
-		let desc_lower = interface.description.to_lowercase();
+		self.tunnel_active = self.snapshot_tunnels.iter().any(|&(_, up)| up);
+		if matches!(self.config.tunnel_accounting, TunnelAccounting::PreferPhysical | TunnelAccounting::PreferTunnel) {
+			let tunnels = &self.snapshot_tunnels;
+			let tunnel_active = self.tunnel_active;
+			let accounting = self.config.tunnel_accounting;
+			snapshot.retain(|entry| {
+				let is_tunnel = tunnels.iter().any(|&(index, _)| index == entry.index);
+				keeps_after_tunnel_dedup(accounting, tunnel_active, is_tunnel)
+			});
+		}
+
+		if self.config.stack_accounting != StackAccounting::CountAll {
+			let candidates: Vec<u32> = snapshot.iter().map(|entry| entry.index).collect();
+			let shadowed = self.stack_shadowed(&candidates);
+			if !shadowed.is_empty() {
+				snapshot.retain(|entry| !shadowed.contains(&entry.index));
+			}
+		}
+
+		if snapshot.is_empty() {
+			return Err(NetworkError::NoInterfacesFound);
+		}
+
+		snapshot.finish(Instant::now());
+		Ok(())
+	}
 
-		if
-			!self.config.include_interface_name_patterns.is_empty() &&
-			!self.config.include_interface_name_patterns.iter().any(|pattern| desc_lower.contains(&pattern.to_lowercase()))
+	/// Fills `snapshot` from the OS counters, through the `GetIfTable2` row fast path on Windows.
+	fn fill_system_snapshot(&mut self, snapshot: &mut CountersSnapshot) -> Result<()> {
+		#[cfg(windows)]
+		match unsafe { self.fill_snapshot_v2(snapshot) } {
+			Ok(()) => self.record_provider(CounterProvider::IfTable2),
+			Err(NetworkError::WindowsApi(err)) if err.code() == HRESULT::from_win32(ERROR_INVALID_FUNCTION.0) => {
+				self.record_provider(CounterProvider::LegacyIfTable);
+				snapshot.clear();
+				self.snapshot_tunnels.clear();
+				self.skipped_rows.clear();
+				self.fill_snapshot_from(unsafe { InterfaceIter::open_legacy()? }, snapshot)?;
+			}
+			Err(e) => {
+				return Err(e);
+			}
+		}
+		#[cfg(not(windows))]
 		{
-			return false;
+			let iter = interfaces_iter()?;
+			self.record_provider(iter.provider());
+			self.fill_snapshot_from(iter, snapshot)?;
 		}
+		Ok(())
+	}
 
-		if self.config.exclude_loopback && interface.is_loopback() {
-			return false;
+	/// Fills `snapshot` from already converted interfaces, for providers without a row fast path.
+	fn fill_snapshot_from(
+		&mut self,
+		rows: impl Iterator<Item = Result<NetworkInterface>>,
+		snapshot: &mut CountersSnapshot
+	) -> Result<()> {
+		for (row, interface) in rows.enumerate() {
+			let interface = match interface {
+				Ok(interface) => interface,
+				Err(err) => {
+					self.skip_row(row, err)?;
+					continue;
+				}
+			};
+			if self.should_include_interface(&interface) {
+				if interface.is_tunnel() {
+					self.snapshot_tunnels.push((interface.index, interface.oper_status.is_up()));
+				}
+				snapshot.push(CounterEntry {
+					index: interface.index,
+					interface_type: interface.interface_type,
+					bytes_sent: interface.bytes_sent,
+					bytes_received: interface.bytes_received,
+				});
+			}
 		}
+		Ok(())
+	}
+
+	#[cfg(windows)]
+	unsafe fn fill_snapshot_v2(&mut self, snapshot: &mut CountersSnapshot) -> Result<()> {
+		let mut table_ptr: *mut MIB_IF_TABLE2 = std::ptr::null_mut();
+		GetIfTable2(&mut table_ptr).map_err(NetworkError::WindowsApi)?;
+
+		let table = &*table_ptr;
+		let slice = std::slice::from_raw_parts(table.Table.as_ptr(), table.NumEntries as usize);
+		let mut result = Ok(());
+
+		for (position, row) in slice.iter().enumerate() {
+			let decision = match self.include_decisions.get(&row.InterfaceIndex) {
+				Some(decision) => *decision,
+				None => {
+					match NetworkInterface::from_mib_ifrow(row) {
+						Ok(interface) => {
+							let decision = CachedDecision {
+								include: self.passes_filters(&interface),
+								tunnel: interface.is_tunnel(),
+							};
+							self.include_decisions.insert(row.InterfaceIndex, decision);
+							decision
+						}
+						Err(e) => {
+							if let Err(e) = self.skip_row(position, e) {
+								result = Err(e);
+								break;
+							}
+							continue;
+						}
+					}
+				}
+			};
 
-		if self.config.exclude_virtual && interface.is_virtual() {
-			return false;
+			let status = OperStatus::from_raw(row.OperStatus.0 as u32);
+			if decision.include && self.is_status_included(status) {
+				if decision.tunnel {
+					self.snapshot_tunnels.push((row.InterfaceIndex, status.is_up()));
+				}
+				snapshot.push(CounterEntry {
+					index: row.InterfaceIndex,
+					interface_type: InterfaceKind::from(row.Type),
+					bytes_sent: row.OutOctets,
+					bytes_received: row.InOctets,
+				});
+			}
 		}
 
-		if self.config.exclude_bluetooth && interface.is_bluetooth() {
-			return false;
+		if let Err(err) = FreeMibTable(table_ptr as _) {
+			return Err(NetworkError::WindowsApi(err));
 		}
 
-		if self.config.interface_type_filters.contains(&interface.interface_type) {
-			return false;
+		result
+	}
+
+	/// Whether `interface` passes the configured filters, combined per `filter_priority`.
+	pub fn should_include_interface(&self, interface: &NetworkInterface) -> bool {
+		self.is_status_included(interface.oper_status) && self.passes_filters(interface)
+	}
+
+	fn is_status_included(&self, status: OperStatus) -> bool {
+		self.config.include_dormant_interfaces || status != OperStatus::Dormant
+	}
+
+	/// Whether an interface passing the filters stays selected under `tunnel_accounting`.
+	fn keeps_after_tunnel_dedup(&self, is_tunnel: bool) -> bool {
+		keeps_after_tunnel_dedup(self.config.tunnel_accounting, self.tunnel_active, is_tunnel)
+	}
+
+	/// Whether a selected interface contributes to aggregate totals and speeds.
+	fn counts_in_total(&self, is_tunnel: bool) -> bool {
+		match self.config.tunnel_accounting {
+			TunnelAccounting::Separate => !is_tunnel,
+			_ => self.keeps_after_tunnel_dedup(is_tunnel),
 		}
+	}
 
-		if !self.config.interface_name_filters.is_empty() {
-			let should_exclude = self.config.interface_name_filters
+	/// The status-independent part of [`should_include_interface`](Self::should_include_interface).
+	fn passes_filters(&self, interface: &NetworkInterface) -> bool {
+		self.static_filter_reason(interface).is_included()
+	}
+
+	/// Why `interface` is or is not measured under the current configuration. Tunnel
+	/// deduplication uses the tunnel state of the latest enumeration.
+	pub fn filter_reason(&self, interface: &NetworkInterface) -> FilterReason {
+		if !self.is_status_included(interface.oper_status) {
+			return FilterReason::Dormant;
+		}
+
+		let reason = self.static_filter_reason(interface);
+		if reason.is_included() && !self.keeps_after_tunnel_dedup(interface.is_tunnel()) {
+			return FilterReason::TunnelAccounting;
+		}
+		reason
+	}
+
+	/// Enumerates every interface with its [`FilterReason`], for diagnosing unexpected totals.
+	pub fn explain_interfaces(&mut self) -> Result<Vec<FilterDecision>> {
+		let interfaces = match self.source.as_mut() {
+			Some(source) =>
+				source
+					.read_interfaces()
+					.map_err(|err| err.in_provider(source.provider().as_str()))?
+					.into_iter()
+					.collect::<Result<Vec<_>>>()?,
+			None => get_raw_interfaces()?,
+		};
+		self.tunnel_active = interfaces
+			.iter()
+			.any(|interface| self.should_include_interface(interface) && interface.is_tunnel() && interface.oper_status.is_up());
+
+		let mut decisions: Vec<FilterDecision> = interfaces
+			.into_iter()
+			.map(|interface| FilterDecision {
+				reason: self.filter_reason(&interface),
+				interface,
+			})
+			.collect();
+
+		let included: Vec<u32> = decisions
+			.iter()
+			.filter(|decision| decision.reason.is_included())
+			.map(|decision| decision.interface.index)
+			.collect();
+		let shadowed = self.stack_shadowed(&included);
+		for decision in &mut decisions {
+			if shadowed.contains(&decision.interface.index) {
+				decision.reason = FilterReason::StackAccounting;
+			}
+		}
+
+		Ok(decisions)
+	}
+
+	fn static_filter_reason(&self, interface: &NetworkInterface) -> FilterReason {
+		let desc_lower = interface.description.to_lowercase();
+		let in_include_list =
+			self.config.include_interface_indices.contains(&interface.index) ||
+			self.config.include_interface_name_patterns
 				.iter()
-				.any(|filter| desc_lower.contains(&filter.to_lowercase()));
+				.any(|pattern| desc_lower.contains(&pattern.to_lowercase()));
+
+		match self.config.filter_priority {
+			FilterPriority::ExcludeWins => {
+				if
+					!self.config.include_interface_indices.is_empty() &&
+					!self.config.include_interface_indices.contains(&interface.index)
+				{
+					return FilterReason::NotInIncludeList;
+				}
 
-			if should_exclude {
-				return false;
+				if
+					!self.config.include_interface_name_patterns.is_empty() &&
+					!self.config.include_interface_name_patterns
+						.iter()
+						.any(|pattern| desc_lower.contains(&pattern.to_lowercase()))
+				{
+					return FilterReason::NotInIncludeList;
+				}
+
+				self.exclusion(interface, &desc_lower).unwrap_or(FilterReason::Selected)
+			}
+			FilterPriority::IncludeWins => {
+				match self.exclusion(interface, &desc_lower) {
+					None => FilterReason::Selected,
+					Some(_) if in_include_list => FilterReason::IncludeList,
+					Some(reason) => reason,
+				}
 			}
 		}
+	}
 
-		true
+	fn exclusion(&self, interface: &NetworkInterface, desc_lower: &str) -> Option<FilterReason> {
+		if self.config.exclude_loopback && interface.is_loopback() {
+			return Some(FilterReason::Loopback);
+		}
+
+		let kept_container = self.config.include_container_interfaces && interface.is_container();
+		if self.config.exclude_virtual && interface.is_virtual() && !kept_container {
+			return Some(FilterReason::Virtual);
+		}
+
+		if self.config.exclude_bluetooth && interface.is_bluetooth() {
+			return Some(FilterReason::Bluetooth);
+		}
+
+		if self.config.interface_type_filters.contains(&interface.interface_type) {
+			return Some(FilterReason::InterfaceType(interface.interface_type));
+		}
+
+		self.config.interface_name_filters
+			.iter()
+			.find(|filter| desc_lower.contains(&filter.to_lowercase()))
+			.map(|filter| FilterReason::NameFilter(filter.clone()))
 	}
 }
 
 fn get_raw_interfaces() -> Result<Vec<NetworkInterface>> {
-	let result = unsafe { collect_interfaces_v2() };
+	interfaces_iter()?.collect()
+}
 
-	match result {
-		Ok(interfaces) => Ok(interfaces),
-		Err(NetworkError::WindowsApi(err)) if err.code() == HRESULT::from_win32(ERROR_INVALID_FUNCTION.0 as u32) => unsafe {
-			collect_interfaces_v1()
-		}
-		Err(e) => Err(e),
-	}
+/// Lazily converts the rows of an interface table, see [`interfaces_iter`]. The table is freed
+/// when the iterator is dropped.
+pub struct InterfaceIter {
+	table: RawInterfaceTable,
+	position: usize,
+	len: usize,
 }
 
-unsafe fn collect_interfaces_v2() -> Result<Vec<NetworkInterface>> {
-	let mut table_ptr: *mut MIB_IF_TABLE2 = std::ptr::null_mut();
-	GetIfTable2(&mut table_ptr).map_err(NetworkError::WindowsApi)?;
+enum RawInterfaceTable {
+	#[cfg(windows)]
+	IfTable2(*mut MIB_IF_TABLE2),
+	/// `MIB_IFTABLE` in a buffer sized by `GetIfTable`.
+	#[cfg(windows)]
+	Legacy(Vec<u8>),
+	/// Rows of `/proc/net/dev` converted up front, so devices that left `/sys/class/net` since
+	/// (veth/docker churn) are dropped instead of failing the read.
+	#[cfg(target_os = "linux")]
+	ProcNetDev(Vec<NetworkInterface>),
+	/// Entries of `sysctl(NET_RT_IFLIST2)`, read up front.
+	#[cfg(target_os = "macos")]
+	IfList2(Vec<super::macos::LinkCounters>),
+}
+
+impl InterfaceIter {
+	#[cfg(windows)]
+	unsafe fn open_v2() -> Result<Self> {
+		let mut table_ptr: *mut MIB_IF_TABLE2 = std::ptr::null_mut();
+		GetIfTable2(&mut table_ptr).map_err(NetworkError::WindowsApi)?;
+
+		Ok(Self {
+			len: (*table_ptr).NumEntries as usize,
+			table: RawInterfaceTable::IfTable2(table_ptr),
+			position: 0,
+		})
+	}
+
+	#[cfg(windows)]
+	unsafe fn open_legacy() -> Result<Self> {
+		let mut size = 0u32;
+		let mut status = GetIfTable(None, &mut size, FALSE);
+		if status != ERROR_INSUFFICIENT_BUFFER.0 {
+			let err = windows::core::Error::from(HRESULT::from_win32(status));
+			return Err(NetworkError::WindowsApi(err));
+		}
 
-	let table = &*table_ptr;
-	let slice = std::slice::from_raw_parts(table.Table.as_ptr(), table.NumEntries as usize);
-	let mut interfaces = Vec::with_capacity(slice.len());
+		let mut buffer = vec![0u8; size as usize];
+		status = GetIfTable(Some(buffer.as_mut_ptr() as *mut MIB_IFTABLE), &mut size, FALSE);
+		if status != NO_ERROR.0 {
+			let err = windows::core::Error::from(HRESULT::from_win32(status));
+			return Err(NetworkError::WindowsApi(err));
+		}
+
+		Ok(Self {
+			len: (*(buffer.as_ptr() as *const MIB_IFTABLE)).dwNumEntries as usize,
+			table: RawInterfaceTable::Legacy(buffer),
+			position: 0,
+		})
+	}
 
-	for row in slice {
-		interfaces.push(NetworkInterface::from_mib_ifrow(row)?);
+	#[cfg(target_os = "linux")]
+	fn open_proc_net_dev() -> Result<Self> {
+		let rows: Vec<NetworkInterface> = super::linux
+			::read_proc_net_dev()?
+			.iter()
+			.filter_map(|counters| NetworkInterface::from_device_counters(counters).ok())
+			.collect();
+		Ok(Self {
+			len: rows.len(),
+			table: RawInterfaceTable::ProcNetDev(rows),
+			position: 0,
+		})
 	}
 
-	if let Err(err) = FreeMibTable(table_ptr as _) {
-		return Err(NetworkError::WindowsApi(err));
+	#[cfg(target_os = "macos")]
+	fn open_if_list2() -> Result<Self> {
+		let rows = super::macos::read_interface_list()?;
+		Ok(Self {
+			len: rows.len(),
+			table: RawInterfaceTable::IfList2(rows),
+			position: 0,
+		})
 	}
 
-	Ok(interfaces)
+	pub fn provider(&self) -> CounterProvider {
+		match self.table {
+			#[cfg(windows)]
+			RawInterfaceTable::IfTable2(_) => CounterProvider::IfTable2,
+			#[cfg(windows)]
+			RawInterfaceTable::Legacy(_) => CounterProvider::LegacyIfTable,
+			#[cfg(target_os = "linux")]
+			RawInterfaceTable::ProcNetDev(_) => CounterProvider::ProcNetDev,
+			#[cfg(target_os = "macos")]
+			RawInterfaceTable::IfList2(_) => CounterProvider::SysctlIfList2,
+		}
+	}
 }
 
-unsafe fn collect_interfaces_v1() -> Result<Vec<NetworkInterface>> {
-	let mut size = 0u32;
-	let mut status = GetIfTable(None, &mut size, FALSE);
-	if status != ERROR_INSUFFICIENT_BUFFER.0 {
-		let err = windows::core::Error::from(HRESULT::from_win32(status));
-		return Err(NetworkError::WindowsApi(err));
+impl Iterator for InterfaceIter {
+	type Item = Result<NetworkInterface>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.position >= self.len {
+			return None;
+		}
+
+		let position = self.position;
+		self.position += 1;
+
+		// `position < len`, and `len` came from the table header.
+		Some(match &self.table {
+			#[cfg(windows)]
+			RawInterfaceTable::IfTable2(table_ptr) => unsafe {
+				let row = &*(**table_ptr).Table.as_ptr().add(position);
+				NetworkInterface::from_mib_ifrow(row)
+			}
+			#[cfg(windows)]
+			RawInterfaceTable::Legacy(buffer) => unsafe {
+				let table = &*(buffer.as_ptr() as *const MIB_IFTABLE);
+				NetworkInterface::from_legacy_mib_ifrow(&*table.table.as_ptr().add(position))
+			}
+			#[cfg(target_os = "linux")]
+			RawInterfaceTable::ProcNetDev(rows) => Ok(rows[position].clone()),
+			#[cfg(target_os = "macos")]
+			RawInterfaceTable::IfList2(rows) => NetworkInterface::from_link_counters(&rows[position]),
+		})
 	}
 
-	let mut buffer = vec![0u8; size as usize];
-	let table_ptr = buffer.as_mut_ptr() as *mut MIB_IFTABLE;
-	status = GetIfTable(Some(table_ptr), &mut size, FALSE);
-	if status != NO_ERROR.0 {
-		let err = windows::core::Error::from(HRESULT::from_win32(status));
-		return Err(NetworkError::WindowsApi(err));
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		let remaining = self.len - self.position;
+		(remaining, Some(remaining))
 	}
+}
 
-	let table = &*table_ptr;
-	let rows = std::slice::from_raw_parts(table.table.as_ptr(), table.dwNumEntries as usize);
-	let mut interfaces = Vec::with_capacity(rows.len());
+impl ExactSizeIterator for InterfaceIter {}
 
-	for row in rows {
-		interfaces.push(NetworkInterface::from_legacy_mib_ifrow(row)?);
+#[cfg(windows)]
+impl Drop for InterfaceIter {
+	fn drop(&mut self) {
+		if let RawInterfaceTable::IfTable2(table_ptr) = self.table {
+			unsafe {
+				let _ = FreeMibTable(table_ptr as _);
+			}
+		}
 	}
+}
 
-	Ok(interfaces)
+fn keeps_after_tunnel_dedup(accounting: TunnelAccounting, tunnel_active: bool, is_tunnel: bool) -> bool {
+	match accounting {
+		TunnelAccounting::CountBoth | TunnelAccounting::Separate => true,
+		TunnelAccounting::PreferPhysical => !is_tunnel,
+		TunnelAccounting::PreferTunnel => is_tunnel || !tunnel_active,
+	}
 }
 
-fn is_virtual_interface_by_description(description: &str) -> bool {
-	const VIRTUAL_KEYWORDS: &[&str] = &[
-		"virtual",
-		"vpn",
-		"tunnel",
-		"tap",
-		"tun",
-		"vmware",
-		"virtualbox",
-		"hyper-v",
-		"teredo",
-		"6to4",
-		"microsoft wi-fi direct virtual adapter",
-		"isatap",
-		"wan miniport",
-		"ras async adapter",
-		"pptp",
-		"l2tp",
-		"sstp",
-		"ikev2",
-		"ppp",
-		"dial-up",
-	];
+/// `NdisPhysicalMediumBluetooth`.
+pub(crate) const BLUETOOTH_PHYSICAL_MEDIUM: u32 = 10;
+
+pub(crate) const CONTAINER_KEYWORDS: &[&str] = &[
+	"vethernet",
+	"hyper-v virtual ethernet",
+	"hyper-v virtual switch",
+	"wsl",
+	"docker",
+	"container",
+];
+
+const TUNNEL_KEYWORDS: &[&str] = &[
+	"vpn",
+	"tunnel",
+	"wireguard",
+	"wintun",
+	"tap-windows",
+	"ikev2",
+	"l2tp",
+	"pptp",
+	"sstp",
+	"anyconnect",
+	"globalprotect",
+	"fortinet",
+	"zerotier",
+	"tailscale",
+];
 
-	let desc_lower = description.to_lowercase();
-	VIRTUAL_KEYWORDS.iter().any(|&keyword| desc_lower.contains(keyword))
+pub(crate) const VIRTUAL_KEYWORDS: &[&str] = &[
+	"virtual",
+	"vpn",
+	"tunnel",
+	"tap",
+	"tun",
+	"vmware",
+	"virtualbox",
+	"hyper-v",
+	"teredo",
+	"6to4",
+	"microsoft wi-fi direct virtual adapter",
+	"isatap",
+	"wan miniport",
+	"ras async adapter",
+	"pptp",
+	"l2tp",
+	"sstp",
+	"ikev2",
+	"ppp",
+	"dial-up",
+];
+
+/// Sums cumulative `(sent, received)` bytes per [`InterfaceCategory`].
+pub fn traffic_by_category(interfaces: &[NetworkInterface]) -> BTreeMap<InterfaceCategory, (u64, u64)> {
+	traffic_by_category_with(interfaces, &Classifier::new())
+}
+
+/// [`traffic_by_category`] using `classifier`, e.g. one extended with site-specific rules.
+pub fn traffic_by_category_with(
+	interfaces: &[NetworkInterface],
+	classifier: &Classifier
+) -> BTreeMap<InterfaceCategory, (u64, u64)> {
+	let mut totals = BTreeMap::new();
+	for interface in interfaces {
+		let entry: &mut (u64, u64) = totals.entry(classifier.classify(interface).category).or_default();
+		entry.0 = entry.0.saturating_add(interface.bytes_sent);
+		entry.1 = entry.1.saturating_add(interface.bytes_received);
+	}
+	totals
 }
 
 pub fn list_all_interfaces() -> Result<Vec<NetworkInterface>> {
 	get_raw_interfaces()
 }
 
+/// All interfaces without filtering, converted one row at a time, so a search can stop at the
+/// first match instead of building every `NetworkInterface`. Falls back to `GetIfTable` like
+/// [`list_all_interfaces`].
+#[cfg(windows)]
+pub fn interfaces_iter() -> Result<InterfaceIter> {
+	match unsafe { InterfaceIter::open_v2() } {
+		Ok(iter) => Ok(iter),
+		Err(NetworkError::WindowsApi(err)) if err.code() == HRESULT::from_win32(ERROR_INVALID_FUNCTION.0) => unsafe {
+			InterfaceIter::open_legacy()
+		}
+		Err(e) => Err(e),
+	}
+}
+
+/// All interfaces without filtering, from `/proc/net/dev`, converted one row at a time.
+#[cfg(target_os = "linux")]
+pub fn interfaces_iter() -> Result<InterfaceIter> {
+	InterfaceIter::open_proc_net_dev()
+}
+
+/// All interfaces without filtering, from `sysctl(NET_RT_IFLIST2)`, converted one row at a time.
+#[cfg(target_os = "macos")]
+pub fn interfaces_iter() -> Result<InterfaceIter> {
+	InterfaceIter::open_if_list2()
+}
+
+/// Order for [`list_all_interfaces_sorted`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SortBy {
+	/// Busiest first, by bytes moved during [`THROUGHPUT_SAMPLE_WINDOW`].
+	Throughput,
+	/// Fastest reported link first.
+	LinkSpeed,
+	/// Alphabetical by friendly name, ignoring case.
+	Name,
+}
+
+/// How long [`SortBy::Throughput`] waits between its two enumerations.
+pub const THROUGHPUT_SAMPLE_WINDOW: Duration = Duration::from_millis(250);
+
+/// All interfaces in the given order. [`SortBy::Throughput`] enumerates twice,
+/// [`THROUGHPUT_SAMPLE_WINDOW`] apart, and blocks meanwhile.
+pub fn list_all_interfaces_sorted(by: SortBy) -> Result<Vec<NetworkInterface>> {
+	let earlier = if by == SortBy::Throughput {
+		let earlier = get_raw_interfaces()?;
+		std::thread::sleep(THROUGHPUT_SAMPLE_WINDOW);
+		earlier
+	} else {
+		Vec::new()
+	};
+
+	Ok(rank_interfaces(get_raw_interfaces()?, &earlier, by))
+}
+
+/// Sorts `current` by `by`. For [`SortBy::Throughput`], each interface is ranked by how many bytes
+/// it moved since its entry (matched by index) in `earlier`; interfaces absent from `earlier` rank
+/// last. Ties keep their enumeration order.
+pub fn rank_interfaces(mut current: Vec<NetworkInterface>, earlier: &[NetworkInterface], by: SortBy) -> Vec<NetworkInterface> {
+	match by {
+		SortBy::Throughput => {
+			let baseline: HashMap<u32, u64> = earlier
+				.iter()
+				.map(|interface| (interface.index, interface.total_bytes()))
+				.collect();
+			current.sort_by_cached_key(|interface| {
+				let moved = baseline
+					.get(&interface.index)
+					.map(|&before| interface.total_bytes().saturating_sub(before));
+				std::cmp::Reverse(moved)
+			});
+		}
+		SortBy::LinkSpeed => current.sort_by_key(|interface| std::cmp::Reverse(interface.speed)),
+		SortBy::Name => current.sort_by_cached_key(|interface| interface.description.to_lowercase()),
+	}
+	current
+}
+
+/// First interface whose alias or driver description contains `pattern`, ignoring case.
+pub fn find_interface_by_name(pattern: &str) -> Result<Option<NetworkInterface>> {
+	let pattern = pattern.to_lowercase();
+	for interface in interfaces_iter()? {
+		let interface = interface?;
+		if interface.matches_keyword(&pattern) {
+			return Ok(Some(interface));
+		}
+	}
+	Ok(None)
+}
+
+/// The interface with the given GUID; case and surrounding braces are ignored.
+pub fn find_by_guid(guid: &str) -> Result<Option<NetworkInterface>> {
+	for interface in interfaces_iter()? {
+		let interface = interface?;
+		if guid_eq(&interface.guid, guid) {
+			return Ok(Some(interface));
+		}
+	}
+	Ok(None)
+}
+
+/// The adapter most likely carrying the machine's traffic, see [`select_primary`].
+pub fn primary_interface() -> Result<Option<NetworkInterface>> {
+	let interfaces = get_raw_interfaces()?;
+	Ok(select_primary(&interfaces).cloned())
+}
+
+/// Picks the primary adapter: an operational physical interface, preferring the one with the
+/// most traffic since boot and then the fastest link. Returns `None` when no physical adapter
+/// is up. This is a heuristic and ignores the routing table, so with several active NICs the
+/// busiest one wins rather than the default route; `route_for` (Windows) gives the routing
+/// decision itself.
+pub fn select_primary(interfaces: &[NetworkInterface]) -> Option<&NetworkInterface> {
+	interfaces
+		.iter()
+		.filter(|interface| interface.oper_status.is_up() && interface.category() == InterfaceCategory::Physical)
+		.max_by_key(|interface| (interface.total_bytes(), interface.speed))
+}
+
+fn guid_eq(a: &str, b: &str) -> bool {
+	let trim = |guid: &str| guid.trim().trim_start_matches('{').trim_end_matches('}').to_string();
+	!a.is_empty() && trim(a).eq_ignore_ascii_case(&trim(b))
+}
+
 pub fn get_interface_count() -> Result<usize> {
-	Ok(get_raw_interfaces()?.len())
+	Ok(interfaces_iter()?.len())
+}
+
+/// Milliseconds since the system booted.
+#[cfg(windows)]
+pub(crate) fn system_uptime_ms() -> u64 {
+	unsafe { GetTickCount64() }
+}
+
+/// The current local wall-clock time, for evaluating a [`Schedule`](crate::Schedule).
+#[cfg(windows)]
+pub fn local_time() -> LocalTime {
+	let now = unsafe { GetLocalTime() };
+	LocalTime::new(Weekday::from_days_since_sunday(now.wDayOfWeek.into()), now.wHour as u8, now.wMinute as u8)
+}
+
+/// The current local wall-clock time, for evaluating a [`Schedule`](crate::Schedule). Falls back
+/// to UTC when the time zone cannot be determined.
+#[cfg(unix)]
+pub fn local_time() -> LocalTime {
+	let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+	unsafe {
+		let now = libc::time(std::ptr::null_mut());
+		if libc::localtime_r(&now, &mut tm).is_null() {
+			libc::gmtime_r(&now, &mut tm);
+		}
+	}
+	LocalTime::new(Weekday::from_days_since_sunday(tm.tm_wday as u32), tm.tm_hour as u8, tm.tm_min as u8)
+}
+
+/// User + kernel CPU time consumed by the calling thread; zero if the query fails.
+#[cfg(windows)]
+pub(crate) fn current_thread_cpu_time() -> std::time::Duration {
+	let mut creation = FILETIME::default();
+	let mut exit = FILETIME::default();
+	let mut kernel = FILETIME::default();
+	let mut user = FILETIME::default();
+
+	let queried = unsafe { GetThreadTimes(GetCurrentThread(), &mut creation, &mut exit, &mut kernel, &mut user) };
+	if queried.is_err() {
+		return std::time::Duration::ZERO;
+	}
+
+	// FILETIME counts 100ns ticks.
+	let ticks = |time: FILETIME| ((time.dwHighDateTime as u64) << 32) | (time.dwLowDateTime as u64);
+	std::time::Duration::from_nanos(ticks(kernel).saturating_add(ticks(user)).saturating_mul(100))
+}
+
+#[cfg(windows)]
+pub(crate) fn format_guid(guid: &windows::core::GUID) -> String {
+	let d4 = guid.data4;
+	format!(
+		"{{{:08X}-{:04X}-{:04X}-{:02X}{:02X}-{:02X}{:02X}{:02X}{:02X}{:02X}{:02X}}}",
+		guid.data1,
+		guid.data2,
+		guid.data3,
+		d4[0],
+		d4[1],
+		d4[2],
+		d4[3],
+		d4[4],
+		d4[5],
+		d4[6],
+		d4[7]
+	)
 }
 
+#[cfg(windows)]
 fn utf16_to_string(buf: &[u16]) -> String {
 	let len = buf
 		.iter()
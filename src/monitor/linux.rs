@@ -0,0 +1,264 @@
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use super::breakdown::{ CastCounters, InterfaceCounters };
+use super::interface::{ NetworkInterface, OperStatus };
+use super::stack::{ InterfaceStack, StackLink };
+use crate::types::{ InterfaceKind, NetworkError, PacketCounters, Result };
+
+const PROC_NET_DEV: &str = "/proc/net/dev";
+const SYS_CLASS_NET: &str = "/sys/class/net";
+
+/// `IFF_UP` from `<net/if.h>`.
+const IFF_UP: u32 = 0x1;
+
+/// `NdisPhysicalMediumNative802_11`, reported for wireless devices so they classify like Wi-Fi
+/// adapters on Windows.
+const NATIVE_802_11_PHYSICAL_MEDIUM: u32 = 9;
+
+/// One line of `/proc/net/dev`: the cumulative counters of one network device.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DeviceCounters {
+	/// Kernel device name, e.g. `eth0` or `wlp2s0`.
+	pub name: String,
+	pub bytes_received: u64,
+	pub packets_received: u64,
+	pub receive_errors: u64,
+	pub receive_drops: u64,
+	/// Received multicast packets; the kernel keeps no multicast byte count.
+	pub multicast_received: u64,
+	pub bytes_sent: u64,
+	pub packets_sent: u64,
+	pub send_errors: u64,
+	pub send_drops: u64,
+}
+
+/// Parses the contents of `/proc/net/dev`. The two header lines and lines without a `name:`
+/// prefix are skipped, as are missing or malformed trailing columns, which read as zero.
+pub fn parse_proc_net_dev(contents: &str) -> Vec<DeviceCounters> {
+	contents
+		.lines()
+		.filter_map(|line| {
+			let (name, fields) = line.split_once(':')?;
+			let name = name.trim();
+			if name.is_empty() || name.contains('|') {
+				return None;
+			}
+			let values: Vec<u64> = fields
+				.split_whitespace()
+				.map(|field| field.parse().unwrap_or(0))
+				.collect();
+			let field = |position: usize| values.get(position).copied().unwrap_or(0);
+			Some(DeviceCounters {
+				name: name.to_string(),
+				bytes_received: field(0),
+				packets_received: field(1),
+				receive_errors: field(2),
+				receive_drops: field(3),
+				multicast_received: field(7),
+				bytes_sent: field(8),
+				packets_sent: field(9),
+				send_errors: field(10),
+				send_drops: field(11),
+			})
+		})
+		.collect()
+}
+
+/// Reads and parses `/proc/net/dev`.
+pub fn read_proc_net_dev() -> Result<Vec<DeviceCounters>> {
+	let contents = fs::read_to_string(PROC_NET_DEV).map_err(|err| NetworkError::from(err).in_provider(PROC_NET_DEV))?;
+	Ok(parse_proc_net_dev(&contents))
+}
+
+impl NetworkInterface {
+	/// Builds an interface from a `/proc/net/dev` row plus the device's attributes in
+	/// `/sys/class/net`. Fails with [`NetworkError::InvalidInterface`] when the device disappeared
+	/// in between.
+	pub fn from_device_counters(counters: &DeviceCounters) -> Result<Self> {
+		let device = Path::new(SYS_CLASS_NET).join(&counters.name);
+		let index = read_attribute(&device, "ifindex")
+			.and_then(|index| index.parse().ok())
+			.ok_or(NetworkError::InvalidInterface)?;
+
+		let wireless = device.join("wireless").exists() || device.join("phy80211").exists();
+		let oper_status = oper_status(&device);
+		// Reading `speed` fails with EINVAL while the link is down; virtual devices report -1.
+		let megabits = read_attribute(&device, "speed")
+			.and_then(|speed| speed.parse::<i64>().ok())
+			.filter(|&speed| speed > 0)
+			.unwrap_or(0) as u64;
+
+		Ok(NetworkInterface {
+			index,
+			guid: String::new(),
+			interface_type: interface_kind(&device, wireless),
+			physical_medium: if wireless { NATIVE_802_11_PHYSICAL_MEDIUM } else { 0 },
+			description: counters.name.clone(),
+			driver_description: driver_description(&device),
+			is_operational: oper_status.is_up(),
+			oper_status,
+			bytes_sent: counters.bytes_sent,
+			bytes_received: counters.bytes_received,
+			packets: PacketCounters {
+				packets_sent: counters.packets_sent,
+				packets_received: counters.packets_received,
+				send_errors: counters.send_errors,
+				receive_errors: counters.receive_errors,
+				send_discards: counters.send_drops,
+				receive_discards: counters.receive_drops,
+			},
+			speed: megabits.saturating_mul(1_000_000),
+		})
+	}
+}
+
+/// Per-cast counters for [`list_interface_counters`](super::breakdown::list_interface_counters).
+/// `/proc/net/dev` has no multicast or broadcast byte counts, so every byte counts as unicast and
+/// only received multicast packets are split out.
+pub(crate) fn list_interface_counters() -> Result<Vec<InterfaceCounters>> {
+	let mut counters = Vec::new();
+	for device in read_proc_net_dev()? {
+		let interface = match NetworkInterface::from_device_counters(&device) {
+			Ok(interface) => interface,
+			Err(NetworkError::InvalidInterface) => {
+				continue;
+			}
+			Err(e) => {
+				return Err(e);
+			}
+		};
+		counters.push(InterfaceCounters {
+			interface,
+			sent: CastCounters {
+				unicast_bytes: device.bytes_sent,
+				unicast_packets: device.packets_sent,
+				..Default::default()
+			},
+			received: CastCounters {
+				unicast_bytes: device.bytes_received,
+				unicast_packets: device.packets_received.saturating_sub(device.multicast_received),
+				non_unicast_packets: device.multicast_received,
+				..Default::default()
+			},
+			outbound_discards: device.send_drops,
+			output_queue_length: 0,
+		});
+	}
+	Ok(counters)
+}
+
+/// Builds the stack from the `lower_<name>` links sysfs keeps for bonds, bridges, VLANs, and
+/// macvlans.
+pub(crate) fn interface_stack() -> Result<InterfaceStack> {
+	let mut links = Vec::new();
+	for entry in fs::read_dir(SYS_CLASS_NET)? {
+		let device = entry?.path();
+		let Some(upper) = read_attribute(&device, "ifindex").and_then(|index| index.parse().ok()) else {
+			continue;
+		};
+		let Ok(children) = fs::read_dir(&device) else {
+			continue;
+		};
+		for child in children.flatten() {
+			if !child.file_name().to_string_lossy().starts_with("lower_") {
+				continue;
+			}
+			if let Some(lower) = read_attribute(&child.path(), "ifindex").and_then(|index| index.parse().ok()) {
+				links.push(StackLink { upper, lower });
+			}
+		}
+	}
+	Ok(InterfaceStack::from_links(links))
+}
+
+/// Milliseconds since boot, from the first field of `/proc/uptime`; zero if it cannot be read.
+pub(crate) fn system_uptime_ms() -> u64 {
+	fs::read_to_string("/proc/uptime")
+		.ok()
+		.and_then(|uptime| uptime.split_whitespace().next()?.parse::<f64>().ok())
+		.map_or(0, |seconds| (seconds * 1000.0) as u64)
+}
+
+/// CPU time consumed by the calling thread, from the first field (nanoseconds on CPU) of its
+/// `schedstat`; zero if it cannot be read.
+pub(crate) fn current_thread_cpu_time() -> Duration {
+	fs::read_to_string("/proc/thread-self/schedstat")
+		.ok()
+		.and_then(|schedstat| schedstat.split_whitespace().next()?.parse().ok())
+		.map_or(Duration::ZERO, Duration::from_nanos)
+}
+
+fn read_attribute(device: &Path, attribute: &str) -> Option<String> {
+	fs::read_to_string(device.join(attribute))
+		.ok()
+		.map(|value| value.trim().to_string())
+}
+
+/// Maps `operstate`. Loopback and many tunnel drivers never leave `unknown`, so an `unknown`
+/// device that is administratively up and has a carrier counts as up.
+fn oper_status(device: &Path) -> OperStatus {
+	match read_attribute(device, "operstate").as_deref() {
+		Some("up") => OperStatus::Up,
+		Some("down") => OperStatus::Down,
+		Some("testing") => OperStatus::Testing,
+		Some("dormant") => OperStatus::Dormant,
+		Some("notpresent") => OperStatus::NotPresent,
+		Some("lowerlayerdown") => OperStatus::LowerLayerDown,
+		_ => {
+			let flags = read_attribute(device, "flags")
+				.and_then(|flags| u32::from_str_radix(flags.trim_start_matches("0x"), 16).ok())
+				.unwrap_or(0);
+			// Reading `carrier` fails while the device is administratively down.
+			if flags & IFF_UP != 0 && read_attribute(device, "carrier").as_deref() == Some("1") {
+				OperStatus::Up
+			} else {
+				OperStatus::Unknown
+			}
+		}
+	}
+}
+
+/// Maps the `ARPHRD_*` link type in `type` to the IANA interface type Windows reports.
+fn interface_kind(device: &Path, wireless: bool) -> InterfaceKind {
+	let arphrd = read_attribute(device, "type").and_then(|kind| kind.parse::<u32>().ok());
+	match arphrd {
+		Some(1) if wireless => InterfaceKind::Wifi,
+		Some(1) => InterfaceKind::Ethernet,
+		Some(772) => InterfaceKind::Loopback,
+		// ARPHRD_PPP
+		Some(512) => InterfaceKind::Other(23),
+		// IPIP, IPv6-in-IPv6, SIT, GRE, and ARPHRD_NONE, which tun and WireGuard devices use.
+		Some(768 | 769 | 776 | 778 | 65534) => InterfaceKind::Tunnel,
+		Some(801..=803) => InterfaceKind::Wifi,
+		_ => InterfaceKind::Other(1),
+	}
+}
+
+/// The kernel driver bound to the device (e.g. `e1000e`, `iwlwifi`), or a description of the
+/// virtual device type for software devices such as bridges, veths, and tun devices.
+fn driver_description(device: &Path) -> String {
+	let driver = fs::read_link(device.join("device/driver"))
+		.ok()
+		.and_then(|driver| Some(driver.file_name()?.to_string_lossy().into_owned()));
+	if let Some(driver) = driver {
+		return driver;
+	}
+
+	let kind = if device.join("bridge").exists() {
+		"bridge"
+	} else if device.join("bonding").exists() {
+		"bond"
+	} else if device.join("tun_flags").exists() {
+		"tun"
+	} else {
+		"device"
+	};
+	let is_virtual = fs::canonicalize(device).is_ok_and(|path| path.starts_with("/sys/devices/virtual"));
+	if is_virtual {
+		format!("virtual {kind}")
+	} else {
+		kind.to_string()
+	}
+}
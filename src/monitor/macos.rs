@@ -0,0 +1,263 @@
+use std::ffi::CStr;
+use std::time::Duration;
+
+use super::breakdown::{ CastCounters, InterfaceCounters };
+use super::interface::{ NetworkInterface, OperStatus };
+use super::stack::InterfaceStack;
+use crate::types::{ InterfaceKind, NetworkError, PacketCounters, Result };
+
+/// `IFT_*` link types from `<net/if_types.h>`.
+const IFT_ETHER: u8 = 0x06;
+const IFT_PPP: u8 = 0x17;
+const IFT_LOOP: u8 = 0x18;
+const IFT_GIF: u8 = 0x37;
+const IFT_STF: u8 = 0x39;
+const IFT_IEEE80211: u8 = 0x47;
+const IFT_BRIDGE: u8 = 0xd1;
+const IFT_CELLULAR: u8 = 0xff;
+
+/// `NdisPhysicalMediumNative802_11`, matching [`super::linux`] for wireless devices.
+const NATIVE_802_11_PHYSICAL_MEDIUM: u32 = 9;
+
+/// One `RTM_IFINFO2` message of `sysctl(NET_RT_IFLIST2)`: the 64-bit counters of one interface.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LinkCounters {
+	pub index: u32,
+	/// BSD interface name, e.g. `en0` or `utun3`.
+	pub name: String,
+	/// `IFF_*` flags.
+	pub flags: u32,
+	/// `IFT_*` link type.
+	pub link_type: u8,
+	/// Link speed in bits per second; 0 when the driver does not report one.
+	pub baudrate: u64,
+	pub bytes_received: u64,
+	pub packets_received: u64,
+	pub receive_errors: u64,
+	pub receive_drops: u64,
+	pub multicast_received: u64,
+	pub bytes_sent: u64,
+	pub packets_sent: u64,
+	pub send_errors: u64,
+	pub send_drops: u64,
+	pub multicast_sent: u64,
+}
+
+/// Reads every interface's counters with `sysctl(NET_RT_IFLIST2)`. Unlike `getifaddrs`, which
+/// reports 32-bit counters, `if_data64` does not wrap after 4 GiB.
+pub fn read_interface_list() -> Result<Vec<LinkCounters>> {
+	let mut mib = [libc::CTL_NET, libc::PF_ROUTE, 0, 0, libc::NET_RT_IFLIST2, 0];
+	let mut buffer: Vec<u8> = Vec::new();
+	// Interfaces can appear between the size query and the read; retry with the new size.
+	for _ in 0..3 {
+		let mut size = 0usize;
+		let status = unsafe {
+			libc::sysctl(mib.as_mut_ptr(), mib.len() as u32, std::ptr::null_mut(), &mut size, std::ptr::null_mut(), 0)
+		};
+		if status != 0 {
+			return Err(sysctl_error(std::io::Error::last_os_error()));
+		}
+		buffer.resize(size, 0);
+		let status = unsafe {
+			libc::sysctl(mib.as_mut_ptr(), mib.len() as u32, buffer.as_mut_ptr().cast(), &mut size, std::ptr::null_mut(), 0)
+		};
+		if status == 0 {
+			buffer.truncate(size);
+			return Ok(parse_interface_list(&buffer));
+		}
+		let err = std::io::Error::last_os_error();
+		if err.raw_os_error() != Some(libc::ENOMEM) {
+			return Err(sysctl_error(err));
+		}
+	}
+	Err(NetworkError::MemoryAllocation)
+}
+
+fn sysctl_error(err: std::io::Error) -> NetworkError {
+	NetworkError::from(err).in_provider("NET_RT_IFLIST2")
+}
+
+/// Walks the routing messages in `buffer`, keeping the `RTM_IFINFO2` ones; the address messages
+/// in between are skipped.
+fn parse_interface_list(buffer: &[u8]) -> Vec<LinkCounters> {
+	let header_len = std::mem::size_of::<libc::if_msghdr2>();
+	let mut interfaces = Vec::new();
+	let mut offset = 0;
+	while offset + 4 <= buffer.len() {
+		// Every routing message starts with its u16 length, version, and type.
+		let length = u16::from_ne_bytes([buffer[offset], buffer[offset + 1]]) as usize;
+		let message_type = buffer[offset + 3] as i32;
+		if length == 0 || offset + length > buffer.len() {
+			break;
+		}
+		if message_type == libc::RTM_IFINFO2 && length >= header_len {
+			// `if_msghdr2` is packed, so the unaligned read is required.
+			let header = unsafe { std::ptr::read_unaligned(buffer[offset..].as_ptr() as *const libc::if_msghdr2) };
+			interfaces.push(link_counters(&header));
+		}
+		offset += length;
+	}
+	interfaces
+}
+
+fn link_counters(header: &libc::if_msghdr2) -> LinkCounters {
+	let data = header.ifm_data;
+	let index = header.ifm_index as u32;
+	LinkCounters {
+		index,
+		name: interface_name(index).unwrap_or_else(|| format!("if{index}")),
+		flags: header.ifm_flags as u32,
+		link_type: data.ifi_type,
+		baudrate: data.ifi_baudrate,
+		bytes_received: data.ifi_ibytes,
+		packets_received: data.ifi_ipackets,
+		receive_errors: data.ifi_ierrors,
+		receive_drops: data.ifi_iqdrops,
+		multicast_received: data.ifi_imcasts,
+		bytes_sent: data.ifi_obytes,
+		packets_sent: data.ifi_opackets,
+		send_errors: data.ifi_oerrors,
+		send_drops: header.ifm_snd_drops.max(0) as u64,
+		multicast_sent: data.ifi_omcasts,
+	}
+}
+
+fn interface_name(index: u32) -> Option<String> {
+	let mut name = [0 as libc::c_char; libc::IF_NAMESIZE];
+	let result = unsafe { libc::if_indextoname(index, name.as_mut_ptr()) };
+	if result.is_null() {
+		return None;
+	}
+	Some(unsafe { CStr::from_ptr(name.as_ptr()) }.to_string_lossy().into_owned())
+}
+
+impl NetworkInterface {
+	/// Builds an interface from one `NET_RT_IFLIST2` entry. The name doubles as the description,
+	/// and the driver description names the kind of virtual interface, if any, so the virtual and
+	/// tunnel filters apply.
+	pub fn from_link_counters(counters: &LinkCounters) -> Result<Self> {
+		let up = counters.flags & (libc::IFF_UP as u32) != 0;
+		let running = counters.flags & (libc::IFF_RUNNING as u32) != 0;
+		let oper_status = match (up, running) {
+			(true, true) => OperStatus::Up,
+			(true, false) => OperStatus::LowerLayerDown,
+			_ => OperStatus::Down,
+		};
+
+		Ok(NetworkInterface {
+			index: counters.index,
+			guid: String::new(),
+			interface_type: interface_kind(counters),
+			physical_medium: if counters.link_type == IFT_IEEE80211 { NATIVE_802_11_PHYSICAL_MEDIUM } else { 0 },
+			description: counters.name.clone(),
+			driver_description: driver_description(&counters.name).to_string(),
+			is_operational: oper_status.is_up(),
+			oper_status,
+			bytes_sent: counters.bytes_sent,
+			bytes_received: counters.bytes_received,
+			packets: PacketCounters {
+				packets_sent: counters.packets_sent,
+				packets_received: counters.packets_received,
+				send_errors: counters.send_errors,
+				receive_errors: counters.receive_errors,
+				send_discards: counters.send_drops,
+				receive_discards: counters.receive_drops,
+			},
+			speed: counters.baudrate,
+		})
+	}
+}
+
+/// Per-cast counters for [`list_interface_counters`](super::breakdown::list_interface_counters).
+/// The kernel counts multicast packets in both directions but no multicast or broadcast bytes,
+/// so every byte counts as unicast.
+pub(crate) fn list_interface_counters() -> Result<Vec<InterfaceCounters>> {
+	read_interface_list()?
+		.iter()
+		.map(|link| {
+			Ok(InterfaceCounters {
+				interface: NetworkInterface::from_link_counters(link)?,
+				sent: CastCounters {
+					unicast_bytes: link.bytes_sent,
+					unicast_packets: link.packets_sent.saturating_sub(link.multicast_sent),
+					non_unicast_packets: link.multicast_sent,
+					..Default::default()
+				},
+				received: CastCounters {
+					unicast_bytes: link.bytes_received,
+					unicast_packets: link.packets_received.saturating_sub(link.multicast_received),
+					non_unicast_packets: link.multicast_received,
+					..Default::default()
+				},
+				outbound_discards: link.send_drops,
+				output_queue_length: 0,
+			})
+		})
+		.collect()
+}
+
+/// macOS exposes bridge and bond membership only through per-interface ioctls, so the stack is
+/// reported empty and `StackAccounting` has nothing to drop.
+pub(crate) fn interface_stack() -> Result<InterfaceStack> {
+	Ok(InterfaceStack::default())
+}
+
+/// Milliseconds since boot, from `kern.boottime`; zero if it cannot be read.
+pub(crate) fn system_uptime_ms() -> u64 {
+	let mut mib = [libc::CTL_KERN, libc::KERN_BOOTTIME];
+	let mut boot_time: libc::timeval = unsafe { std::mem::zeroed() };
+	let mut size = std::mem::size_of::<libc::timeval>();
+	let status = unsafe {
+		libc::sysctl(mib.as_mut_ptr(), 2, (&mut boot_time as *mut libc::timeval).cast(), &mut size, std::ptr::null_mut(), 0)
+	};
+	if status != 0 {
+		return 0;
+	}
+	let boot = Duration::new(boot_time.tv_sec as u64, (boot_time.tv_usec as u32) * 1000);
+	std::time::SystemTime::now()
+		.duration_since(std::time::UNIX_EPOCH)
+		.map_or(0, |now| now.saturating_sub(boot).as_millis() as u64)
+}
+
+/// CPU time consumed by the calling thread; zero if the clock cannot be read.
+pub(crate) fn current_thread_cpu_time() -> Duration {
+	let mut time: libc::timespec = unsafe { std::mem::zeroed() };
+	if unsafe { libc::clock_gettime(libc::CLOCK_THREAD_CPUTIME_ID, &mut time) } != 0 {
+		return Duration::ZERO;
+	}
+	Duration::new(time.tv_sec as u64, time.tv_nsec as u32)
+}
+
+/// Maps the `IFT_*` link type to the IANA interface type Windows reports. Wi-Fi interfaces report
+/// `IFT_ETHER` like wired ones, so they classify as Ethernet.
+fn interface_kind(counters: &LinkCounters) -> InterfaceKind {
+	match counters.link_type {
+		IFT_LOOP => InterfaceKind::Loopback,
+		IFT_IEEE80211 => InterfaceKind::Wifi,
+		IFT_ETHER | IFT_BRIDGE => InterfaceKind::Ethernet,
+		IFT_PPP => InterfaceKind::Other(23),
+		IFT_GIF | IFT_STF => InterfaceKind::Tunnel,
+		IFT_CELLULAR => InterfaceKind::Wwan,
+		// utun and ipsec interfaces use IFT_OTHER.
+		_ if counters.name.starts_with("utun") || counters.name.starts_with("ipsec") => InterfaceKind::Tunnel,
+		other => InterfaceKind::Other(other as u32),
+	}
+}
+
+/// What a software interface is, from the name prefixes the kernel assigns; physical interfaces
+/// get an empty description.
+fn driver_description(name: &str) -> &'static str {
+	let prefix = name.trim_end_matches(|c: char| c.is_ascii_digit());
+	match prefix {
+		"utun" | "ipsec" => "virtual tunnel",
+		"gif" | "stf" => "virtual tunnel (IPv6 transition)",
+		"bridge" => "virtual bridge",
+		"bond" => "virtual bond",
+		"vlan" => "virtual vlan",
+		"feth" => "virtual fake ethernet",
+		"awdl" | "llw" => "virtual wireless direct link",
+		"ap" => "virtual access point",
+		"anpi" => "virtual apple network processor interface",
+		_ => "",
+	}
+}
@@ -0,0 +1,148 @@
+use std::collections::VecDeque;
+use std::sync::{ Arc, Mutex };
+
+use super::interface::{ CounterProvider, NetworkInterface, OperStatus };
+use super::source::CounterSource;
+use crate::types::{ InterfaceKind, NetworkError, PacketCounters, Result };
+
+enum MockStep {
+	Counters(Vec<(u64, u64)>),
+	Error(NetworkError),
+}
+
+struct MockState {
+	interfaces: Vec<NetworkInterface>,
+	script: VecDeque<MockStep>,
+	reads: u64,
+}
+
+/// A [`CounterSource`] that returns scripted byte counters, so code built on
+/// [`NetworkMonitor`](super::NetworkMonitor) can be unit-tested without real traffic.
+///
+/// Each read consumes one scripted step: `(bytes_sent, bytes_received)` for every interface, in
+/// the order they were added, or an error. Once the script runs out, the last counters repeat, as
+/// on an idle link. Clones share the script, so a test can keep a handle and push more steps after
+/// moving the source into a monitor.
+///
+/// Only the counters are scripted. Rates still divide by the real time between reads, so sleep for
+/// the configured `min_measurement_interval` between measurements and compare rates with a tolerance,
+/// or assert on `total_traffic` and the byte deltas instead.
+#[derive(Clone)]
+pub struct MockCounterSource {
+	state: Arc<Mutex<MockState>>,
+}
+
+impl MockCounterSource {
+	/// One up Ethernet interface, `mock0` at index 1 with a 1 Gbps link, starting at zero bytes.
+	pub fn new() -> Self {
+		Self::with_interfaces(vec![Self::interface(1, InterfaceKind::Ethernet, "mock0")])
+	}
+
+	/// Scripts counters for `interfaces`; their own byte counts are used until the first step.
+	pub fn with_interfaces(interfaces: Vec<NetworkInterface>) -> Self {
+		Self {
+			state: Arc::new(
+				Mutex::new(MockState {
+					interfaces,
+					script: VecDeque::new(),
+					reads: 0,
+				})
+			),
+		}
+	}
+
+	/// An up interface with a 1 Gbps link and zero counters, for [`with_interfaces`](Self::with_interfaces).
+	pub fn interface(index: u32, interface_type: InterfaceKind, description: &str) -> NetworkInterface {
+		NetworkInterface {
+			index,
+			guid: String::new(),
+			interface_type,
+			physical_medium: 0,
+			description: description.to_string(),
+			driver_description: String::new(),
+			is_operational: true,
+			oper_status: OperStatus::Up,
+			bytes_sent: 0,
+			bytes_received: 0,
+			packets: PacketCounters::default(),
+			speed: 1_000_000_000,
+		}
+	}
+
+	/// Queues one read returning `counters`, one `(bytes_sent, bytes_received)` per interface.
+	/// Interfaces without an entry keep their previous counters.
+	pub fn push_counters(&self, counters: &[(u64, u64)]) {
+		self.lock().script.push_back(MockStep::Counters(counters.to_vec()));
+	}
+
+	/// Queues one read whose counters are the last queued ones (or the current ones) advanced by
+	/// `deltas`, e.g. `push_deltas(&[(1_000, 5_000)])` for 1 kB up and 5 kB down since the previous read.
+	pub fn push_deltas(&self, deltas: &[(u64, u64)]) {
+		let mut state = self.lock();
+		let mut counters: Vec<(u64, u64)> = state.interfaces
+			.iter()
+			.map(|interface| (interface.bytes_sent, interface.bytes_received))
+			.collect();
+		for step in &state.script {
+			if let MockStep::Counters(step) = step {
+				for (counter, value) in counters.iter_mut().zip(step) {
+					*counter = *value;
+				}
+			}
+		}
+		for (counter, (sent, received)) in counters.iter_mut().zip(deltas) {
+			*counter = (counter.0.wrapping_add(*sent), counter.1.wrapping_add(*received));
+		}
+		state.script.push_back(MockStep::Counters(counters));
+	}
+
+	/// Queues one read that fails with `error`, e.g. to exercise retry or alert paths.
+	pub fn push_error(&self, error: NetworkError) {
+		self.lock().script.push_back(MockStep::Error(error));
+	}
+
+	/// Reads served so far.
+	pub fn reads(&self) -> u64 {
+		self.lock().reads
+	}
+
+	/// Scripted steps not read yet.
+	pub fn remaining(&self) -> usize {
+		self.lock().script.len()
+	}
+
+	fn lock(&self) -> std::sync::MutexGuard<'_, MockState> {
+		// A panicking test thread must not hide the script from the others.
+		self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+	}
+}
+
+impl Default for MockCounterSource {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl CounterSource for MockCounterSource {
+	fn read_interfaces(&mut self) -> Result<Vec<Result<NetworkInterface>>> {
+		let mut state = self.lock();
+		state.reads += 1;
+		match state.script.pop_front() {
+			Some(MockStep::Counters(counters)) => {
+				for (interface, (sent, received)) in state.interfaces.iter_mut().zip(counters) {
+					interface.bytes_sent = sent;
+					interface.bytes_received = received;
+				}
+			}
+			Some(MockStep::Error(error)) => {
+				return Err(error);
+			}
+			None => {}
+		}
+		Ok(state.interfaces.iter().cloned().map(Ok).collect())
+	}
+
+	fn provider(&self) -> CounterProvider {
+		CounterProvider::Custom("mock")
+	}
+}
@@ -1,11 +1,122 @@
+#[cfg(not(any(windows, target_os = "linux", target_os = "macos")))]
+compile_error!("the `platform` feature supports Windows, Linux, and macOS; build with `default-features = false` for the shared types only");
+
+pub mod adaptive;
+pub mod breakdown;
+#[cfg(windows)]
+pub mod capabilities;
+pub mod classify;
+pub mod connections;
+pub mod data_usage;
+#[cfg(windows)]
+pub mod details;
+pub mod events;
+pub mod forecast;
 pub mod interface;
+#[cfg(target_os = "linux")]
+pub mod linux;
+#[cfg(target_os = "macos")]
+pub mod macos;
+#[cfg(feature = "test-util")]
+pub mod mock;
+#[cfg(windows)]
+pub mod neighbors;
+pub mod network_profile;
+pub mod registry;
+#[cfg(windows)]
+pub mod routes;
+pub mod shutdown;
+pub mod sink;
+pub mod snapshot;
+pub mod source;
+pub mod stack;
 pub mod sync_monitor;
+pub mod utilization;
+
+
+#[cfg(all(feature = "qos", windows))]
+pub mod qos;
 
 #[cfg(feature = "async")]
 pub mod async_monitor;
 
+#[cfg(feature = "charts")]
+pub mod chart;
+
+#[cfg(feature = "arrow")]
+pub mod arrow;
+
+#[cfg(feature = "egui")]
+pub mod graph;
+
+#[cfg(feature = "persist")]
+pub mod persist;
+
+#[cfg(feature = "persist")]
+pub mod report;
+
+#[cfg(feature = "persist")]
+pub mod report_scheduler;
+
+#[cfg(feature = "persist")]
+pub mod trace;
+
+pub use adaptive::*;
+pub use breakdown::*;
+#[cfg(windows)]
+pub use capabilities::*;
+pub use classify::*;
+pub use connections::*;
+pub use data_usage::*;
+#[cfg(windows)]
+pub use details::*;
+pub use events::*;
+pub use forecast::*;
 pub use interface::*;
+#[cfg(target_os = "linux")]
+pub use linux::{ parse_proc_net_dev, read_proc_net_dev, DeviceCounters };
+#[cfg(target_os = "macos")]
+pub use macos::{ read_interface_list, LinkCounters };
+#[cfg(feature = "test-util")]
+pub use mock::MockCounterSource;
+#[cfg(windows)]
+pub use neighbors::*;
+pub use network_profile::*;
+pub use registry::TrackerRegistry;
+#[cfg(windows)]
+pub use routes::*;
+pub use shutdown::Shutdown;
+pub use sink::*;
+pub use snapshot::*;
+pub use source::*;
+pub use stack::*;
 pub use sync_monitor::*;
+pub use utilization::UtilizationSample;
+
+
+#[cfg(all(feature = "qos", windows))]
+pub use qos::*;
 
 #[cfg(feature = "async")]
 pub use async_monitor::*;
+
+#[cfg(feature = "charts")]
+pub use chart::*;
+
+#[cfg(feature = "arrow")]
+pub use self::arrow::*;
+
+#[cfg(feature = "egui")]
+pub use graph::*;
+
+#[cfg(feature = "persist")]
+pub use persist::*;
+
+#[cfg(feature = "persist")]
+pub use report::*;
+
+#[cfg(feature = "persist")]
+pub use report_scheduler::*;
+
+#[cfg(feature = "persist")]
+pub use trace::*;
@@ -0,0 +1,144 @@
+use std::net::{ IpAddr, Ipv4Addr, Ipv6Addr };
+
+use windows::Win32::{
+	NetworkManagement::IpHelper::{ FreeMibTable, GetIpNetTable2, MIB_IPNET_TABLE2 },
+	Networking::WinSock::{ AF_INET, AF_INET6, AF_UNSPEC, SOCKADDR_INET },
+};
+
+use crate::types::{ NetworkError, Result };
+
+/// Reachability of a neighbor cache entry (`NL_NEIGHBOR_STATE`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NeighborState {
+	Unreachable,
+	Incomplete,
+	Probe,
+	Delay,
+	Stale,
+	Reachable,
+	/// A static entry, added by hand or by the system (broadcast and multicast addresses).
+	Permanent,
+	Unknown(i32),
+}
+
+impl NeighborState {
+	pub fn from_raw(value: i32) -> Self {
+		match value {
+			0 => NeighborState::Unreachable,
+			1 => NeighborState::Incomplete,
+			2 => NeighborState::Probe,
+			3 => NeighborState::Delay,
+			4 => NeighborState::Stale,
+			5 => NeighborState::Reachable,
+			6 => NeighborState::Permanent,
+			other => NeighborState::Unknown(other),
+		}
+	}
+
+	/// Whether the entry has a resolved link-layer address that was recently confirmed or can still
+	/// be used. Stale entries are included: Windows keeps them until the next send needs them.
+	pub fn is_resolved(&self) -> bool {
+		matches!(
+			self,
+			NeighborState::Reachable | NeighborState::Stale | NeighborState::Delay | NeighborState::Probe | NeighborState::Permanent
+		)
+	}
+
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			NeighborState::Unreachable => "unreachable",
+			NeighborState::Incomplete => "incomplete",
+			NeighborState::Probe => "probe",
+			NeighborState::Delay => "delay",
+			NeighborState::Stale => "stale",
+			NeighborState::Reachable => "reachable",
+			NeighborState::Permanent => "permanent",
+			NeighborState::Unknown(_) => "unknown",
+		}
+	}
+}
+
+/// One entry of the ARP (IPv4) or neighbor discovery (IPv6) cache.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Neighbor {
+	pub address: IpAddr,
+	/// Index of the interface the neighbor was seen on, matching [`NetworkInterface::index`](super::interface::NetworkInterface::index).
+	pub interface_index: u32,
+	/// Link-layer address; empty while resolution is incomplete.
+	pub physical_address: Vec<u8>,
+	pub state: NeighborState,
+	pub is_router: bool,
+}
+
+impl Neighbor {
+	/// The link-layer address as `AA-BB-CC-DD-EE-FF`, the form `ipconfig` and `arp -a` print.
+	pub fn mac(&self) -> Option<String> {
+		format_mac(&self.physical_address)
+	}
+}
+
+/// Formats a link-layer address as dash-separated uppercase hex; `None` for an empty or all-zero
+/// address.
+pub fn format_mac(address: &[u8]) -> Option<String> {
+	if address.iter().all(|&byte| byte == 0) {
+		return None;
+	}
+	Some(
+		address
+			.iter()
+			.map(|byte| format!("{byte:02X}"))
+			.collect::<Vec<_>>()
+			.join("-")
+	)
+}
+
+/// Lists the IPv4 and IPv6 neighbor cache (`GetIpNetTable2`), i.e. the devices this machine has
+/// recently talked to on its local segments.
+pub fn list_neighbors() -> Result<Vec<Neighbor>> {
+	let mut neighbors = Vec::new();
+
+	unsafe {
+		let mut table: *mut MIB_IPNET_TABLE2 = std::ptr::null_mut();
+		GetIpNetTable2(AF_UNSPEC, &mut table)?;
+		let rows = std::slice::from_raw_parts((*table).Table.as_ptr(), (*table).NumEntries as usize);
+
+		for row in rows {
+			let Some(address) = sockaddr_to_ip(&row.Address) else {
+				continue;
+			};
+			let length = (row.PhysicalAddressLength as usize).min(row.PhysicalAddress.len());
+			neighbors.push(Neighbor {
+				address,
+				interface_index: row.InterfaceIndex,
+				physical_address: row.PhysicalAddress[..length].to_vec(),
+				state: NeighborState::from_raw(row.State.0),
+				// Bit 0 is IsRouter, bit 1 IsUnreachable.
+				is_router: row.Anonymous.Flags & 0x1 != 0,
+			});
+		}
+
+		FreeMibTable(table as _).map_err(NetworkError::WindowsApi)?;
+	}
+
+	Ok(neighbors)
+}
+
+/// The neighbors seen on the interface with the given index.
+pub fn list_neighbors_on(interface_index: u32) -> Result<Vec<Neighbor>> {
+	let mut neighbors = list_neighbors()?;
+	neighbors.retain(|neighbor| neighbor.interface_index == interface_index);
+	Ok(neighbors)
+}
+
+pub(crate) fn sockaddr_to_ip(address: &SOCKADDR_INET) -> Option<IpAddr> {
+	unsafe {
+		match address.si_family {
+			AF_INET => {
+				let bytes = address.Ipv4.sin_addr.S_un.S_un_b;
+				Some(IpAddr::V4(Ipv4Addr::new(bytes.s_b1, bytes.s_b2, bytes.s_b3, bytes.s_b4)))
+			}
+			AF_INET6 => Some(IpAddr::V6(Ipv6Addr::from(address.Ipv6.sin6_addr.u.Byte))),
+			_ => None,
+		}
+	}
+}
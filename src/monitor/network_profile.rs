@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+
+#[cfg(all(feature = "network-watch", windows))]
+use windows::Win32::{
+	Networking::NetworkListManager::{
+		INetwork,
+		INetworkListManager,
+		NetworkListManager,
+		NLM_ENUM_NETWORK_CONNECTED,
+		NLM_NETWORK_CATEGORY,
+		NLM_NETWORK_CATEGORY_DOMAIN_AUTHENTICATED,
+		NLM_NETWORK_CATEGORY_PRIVATE,
+	},
+	System::Com::{ CoCreateInstance, CoInitializeEx, CLSCTX_ALL, COINIT_MULTITHREADED },
+};
+
+use super::events::Event;
+#[cfg(all(feature = "network-watch", windows))]
+use crate::types::Result;
+
+/// The firewall profile Windows assigned to a network.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NetworkCategory {
+	Public,
+	Private,
+	/// Authenticated against the machine's domain.
+	Domain,
+}
+
+impl NetworkCategory {
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			NetworkCategory::Public => "public",
+			NetworkCategory::Private => "private",
+			NetworkCategory::Domain => "domain",
+		}
+	}
+
+	#[cfg(all(feature = "network-watch", windows))]
+	fn from_nlm(category: NLM_NETWORK_CATEGORY) -> Self {
+		match category {
+			NLM_NETWORK_CATEGORY_PRIVATE => NetworkCategory::Private,
+			NLM_NETWORK_CATEGORY_DOMAIN_AUTHENTICATED => NetworkCategory::Domain,
+			_ => NetworkCategory::Public,
+		}
+	}
+}
+
+/// A connected network as Windows' Network List Manager names it, e.g. an SSID or `Network 3`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NetworkProfile {
+	pub name: String,
+	pub category: NetworkCategory,
+}
+
+/// Lists the networks this machine is connected to right now.
+#[cfg(all(feature = "network-watch", windows))]
+pub fn connected_networks() -> Result<Vec<NetworkProfile>> {
+	unsafe {
+		// Any apartment works for the Network List Manager; an existing one is fine.
+		let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+		let manager: INetworkListManager = CoCreateInstance(&NetworkListManager, None, CLSCTX_ALL)?;
+		let networks = manager.GetNetworks(NLM_ENUM_NETWORK_CONNECTED)?;
+
+		let mut profiles = Vec::new();
+		loop {
+			let mut batch: [Option<INetwork>; 1] = [None];
+			let mut fetched = 0u32;
+			networks.Next(&mut batch, Some(&mut fetched))?;
+			let Some(network) = batch[0].take().filter(|_| fetched == 1) else {
+				break;
+			};
+			profiles.push(NetworkProfile {
+				name: network.GetName()?.to_string(),
+				category: NetworkCategory::from_nlm(network.GetCategory()?),
+			});
+		}
+		Ok(profiles)
+	}
+}
+
+/// Diffs two network lists into connected/disconnected/category events, matching networks by name.
+pub fn network_profile_events(previous: &[NetworkProfile], current: &[NetworkProfile]) -> Vec<Event> {
+	let before: HashMap<&str, &NetworkProfile> = previous
+		.iter()
+		.map(|network| (network.name.as_str(), network))
+		.collect();
+	let after: HashMap<&str, &NetworkProfile> = current
+		.iter()
+		.map(|network| (network.name.as_str(), network))
+		.collect();
+
+	let mut events = Vec::new();
+	for network in current {
+		match before.get(network.name.as_str()) {
+			None => events.push(Event::NetworkConnected(network.clone())),
+			Some(old) if old.category != network.category => {
+				events.push(Event::NetworkCategoryChanged {
+					network: network.clone(),
+					previous: old.category,
+				});
+			}
+			Some(_) => {}
+		}
+	}
+	for network in previous {
+		if !after.contains_key(network.name.as_str()) {
+			events.push(Event::NetworkDisconnected(network.clone()));
+		}
+	}
+
+	events
+}
@@ -0,0 +1,244 @@
+use std::collections::BTreeMap;
+use std::fs::{ File, OpenOptions };
+use std::io::{ BufRead, BufReader, BufWriter, Read, Write };
+use std::path::Path;
+use std::time::{ Duration, Instant, SystemTime, UNIX_EPOCH };
+
+use flate2::read::MultiGzDecoder;
+use flate2::write::GzEncoder;
+use serde::{ Deserialize, Serialize };
+
+use super::data_usage::UsageTotals;
+use crate::types::{ MonitorLabels, NetworkError, NetworkSpeed, Result };
+
+pub const DEFAULT_CHUNK_SIZE: usize = 256;
+/// Records further apart than this are treated as separate sessions by [`usage_by_label`].
+pub const MAX_USAGE_GAP: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryCompression {
+	None,
+	/// Each flushed chunk is a separate gzip member, so appending never rewrites earlier data.
+	Gzip,
+}
+
+/// One persisted sample (a JSON line), timestamped with Unix epoch milliseconds.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryRecord {
+	pub timestamp_ms: u64,
+	pub upload_bytes_per_sec: u64,
+	pub download_bytes_per_sec: u64,
+	/// Name and labels of the tracker that produced the sample; omitted from the line when empty.
+	#[serde(flatten)]
+	pub labels: MonitorLabels,
+}
+
+impl HistoryRecord {
+	pub fn from_speed(speed: &NetworkSpeed) -> Self {
+		let age = Instant::now().saturating_duration_since(speed.timestamp);
+		let timestamp = SystemTime::now().checked_sub(age).unwrap_or(UNIX_EPOCH);
+
+		Self {
+			timestamp_ms: timestamp
+				.duration_since(UNIX_EPOCH)
+				.map(|elapsed| elapsed.as_millis() as u64)
+				.unwrap_or(0),
+			upload_bytes_per_sec: speed.upload_bytes_per_sec.bytes_per_sec(),
+			download_bytes_per_sec: speed.download_bytes_per_sec.bytes_per_sec(),
+			labels: MonitorLabels::default(),
+		}
+	}
+
+	/// Maps the record back onto the monotonic clock; records older than the clock allows are pinned to now.
+	pub fn to_speed(&self) -> NetworkSpeed {
+		let now = Instant::now();
+		let age = SystemTime::now()
+			.duration_since(UNIX_EPOCH.checked_add(Duration::from_millis(self.timestamp_ms)).unwrap_or(UNIX_EPOCH))
+			.unwrap_or_default();
+
+		NetworkSpeed {
+			timestamp: now.checked_sub(age).unwrap_or(now),
+			..NetworkSpeed::new(self.upload_bytes_per_sec, self.download_bytes_per_sec)
+		}
+	}
+}
+
+/// Appends samples to a JSON Lines history file in chunks, optionally gzip-compressed.
+///
+/// Pending samples are flushed when the chunk fills, on [`flush`](Self::flush), and on drop.
+pub struct HistoryWriter {
+	file: File,
+	compression: HistoryCompression,
+	chunk_size: usize,
+	labels: MonitorLabels,
+	pending: Vec<HistoryRecord>,
+}
+
+impl HistoryWriter {
+	pub fn open(path: impl AsRef<Path>, compression: HistoryCompression) -> Result<Self> {
+		let file = OpenOptions::new().create(true).append(true).open(path).map_err(persist_error)?;
+
+		Ok(Self {
+			file,
+			compression,
+			chunk_size: DEFAULT_CHUNK_SIZE,
+			labels: MonitorLabels::default(),
+			pending: Vec::new(),
+		})
+	}
+
+	/// Larger chunks compress better; smaller chunks lose less data if the process dies.
+	pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+		self.chunk_size = chunk_size.max(1);
+		self
+	}
+
+	/// Stamps every record written from now on with `labels`.
+	pub fn with_labels(mut self, labels: MonitorLabels) -> Self {
+		self.labels = labels;
+		self
+	}
+
+	/// Labels for the records written from now on, e.g. to retag them when the network changes.
+	pub fn labels_mut(&mut self) -> &mut MonitorLabels {
+		&mut self.labels
+	}
+
+	pub fn push(&mut self, speed: &NetworkSpeed) -> Result<()> {
+		self.pending.push(HistoryRecord {
+			labels: self.labels.clone(),
+			..HistoryRecord::from_speed(speed)
+		});
+		if self.pending.len() >= self.chunk_size {
+			self.flush()?;
+		}
+		Ok(())
+	}
+
+	pub fn extend<'a>(&mut self, speeds: impl IntoIterator<Item = &'a NetworkSpeed>) -> Result<()> {
+		for speed in speeds {
+			self.push(speed)?;
+		}
+		Ok(())
+	}
+
+	pub fn flush(&mut self) -> Result<()> {
+		if self.pending.is_empty() {
+			return Ok(());
+		}
+
+		let mut lines = Vec::with_capacity(self.pending.len() * 80);
+		for record in &self.pending {
+			serde_json::to_writer(&mut lines, record).map_err(persist_error)?;
+			lines.push(b'\n');
+		}
+
+		let mut out = BufWriter::new(&self.file);
+		match self.compression {
+			HistoryCompression::None => out.write_all(&lines).map_err(persist_error)?,
+			HistoryCompression::Gzip => {
+				let mut encoder = GzEncoder::new(&mut out, flate2::Compression::default());
+				encoder.write_all(&lines).map_err(persist_error)?;
+				encoder.finish().map_err(persist_error)?;
+			}
+		}
+		out.flush().map_err(persist_error)?;
+
+		self.pending.clear();
+		Ok(())
+	}
+}
+
+impl Drop for HistoryWriter {
+	fn drop(&mut self) {
+		let _ = self.flush();
+	}
+}
+
+pub fn append_history(path: impl AsRef<Path>, history: &[NetworkSpeed], compression: HistoryCompression) -> Result<()> {
+	append_labeled_history(path, history, compression, &MonitorLabels::default())
+}
+
+pub fn append_labeled_history(
+	path: impl AsRef<Path>,
+	history: &[NetworkSpeed],
+	compression: HistoryCompression,
+	labels: &MonitorLabels
+) -> Result<()> {
+	let mut writer = HistoryWriter::open(path, compression)?
+		.with_chunk_size(history.len())
+		.with_labels(labels.clone());
+	writer.extend(history)?;
+	writer.flush()
+}
+
+/// Places records on the monotonic clock with their original spacing, the first one at `origin`.
+/// Unlike [`HistoryRecord::to_speed`], this also works for files older than the system uptime,
+/// so recorded sessions can be summarized or replayed.
+pub fn records_to_speeds(records: &[HistoryRecord], origin: Instant) -> Vec<NetworkSpeed> {
+	let Some(first) = records.first() else {
+		return Vec::new();
+	};
+
+	records
+		.iter()
+		.map(|record| NetworkSpeed {
+			timestamp: origin + Duration::from_millis(record.timestamp_ms.saturating_sub(first.timestamp_ms)),
+			..NetworkSpeed::new(record.upload_bytes_per_sec, record.download_bytes_per_sec)
+		})
+		.collect()
+}
+
+/// Splits the traffic in `records` by the value of the `key` label, e.g. `"network"` for files
+/// whose writer relabels on network changes. Each record's rate is applied to the time since the previous record; the
+/// first record of a session (after a gap over [`MAX_USAGE_GAP`]) and unlabeled records are skipped.
+pub fn usage_by_label(records: &[HistoryRecord], key: &str) -> BTreeMap<String, UsageTotals> {
+	let mut usage: BTreeMap<String, UsageTotals> = BTreeMap::new();
+	for pair in records.windows(2) {
+		let (previous, record) = (&pair[0], &pair[1]);
+		let Some(value) = record.labels.get(key) else {
+			continue;
+		};
+		let gap = Duration::from_millis(record.timestamp_ms.saturating_sub(previous.timestamp_ms));
+		if gap > MAX_USAGE_GAP {
+			continue;
+		}
+
+		let seconds = gap.as_secs_f64();
+		let totals = usage.entry(value.to_string()).or_default();
+		totals.bytes_sent = totals.bytes_sent.saturating_add(((record.upload_bytes_per_sec as f64) * seconds) as u64);
+		totals.bytes_received = totals.bytes_received.saturating_add(((record.download_bytes_per_sec as f64) * seconds) as u64);
+	}
+	usage
+}
+
+/// Reads a history file written by [`HistoryWriter`]; compression is detected from the content.
+pub fn read_history(path: impl AsRef<Path>) -> Result<Vec<HistoryRecord>> {
+	read_history_from(File::open(path).map_err(persist_error)?)
+}
+
+/// [`read_history`] for an already open file or an in-memory buffer.
+pub fn read_history_from(reader: impl Read) -> Result<Vec<HistoryRecord>> {
+	let mut reader = BufReader::new(reader);
+	let is_gzip = reader.fill_buf().map_err(persist_error)?.starts_with(&[0x1f, 0x8b]);
+
+	let reader: Box<dyn Read> = if is_gzip { Box::new(MultiGzDecoder::new(reader)) } else { Box::new(reader) };
+
+	let mut records = Vec::new();
+	for line in BufReader::new(reader).lines() {
+		let line = line.map_err(persist_error)?;
+		if line.trim().is_empty() {
+			continue;
+		}
+		records.push(serde_json::from_str(&line).map_err(persist_error)?);
+	}
+
+	Ok(records)
+}
+
+fn persist_error(err: impl std::fmt::Display) -> NetworkError {
+	NetworkError::Persistence {
+		target: "History",
+		reason: err.to_string(),
+	}
+}
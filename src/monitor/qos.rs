@@ -0,0 +1,150 @@
+use std::time::Instant;
+
+use windows::{
+	core::{ HRESULT, PCWSTR },
+	Win32::System::Performance::{
+		PdhAddEnglishCounterW,
+		PdhCloseQuery,
+		PdhCollectQueryData,
+		PdhGetRawCounterArrayW,
+		PdhOpenQueryW,
+		PDH_CSTATUS_NO_INSTANCE,
+		PDH_MORE_DATA,
+		PDH_NO_DATA,
+		PDH_RAW_COUNTER_ITEM_W,
+	},
+};
+
+use crate::types::{ NetworkError, QosBreakdown, Result, TrafficClassCounters, TrafficClassUsage };
+
+const BYTES_PATH: &str = "\\Pacer Flow(*)\\Bytes transmitted";
+const PACKETS_PATH: &str = "\\Pacer Flow(*)\\Packets transmitted";
+
+/// Reads per-traffic-class counters from the QoS packet scheduler (`\Pacer Flow(*)\...`).
+///
+/// The scheduler only creates a flow for traffic that matched a QoS policy (Group Policy or
+/// `New-NetQosPolicy`), so the classes are the prioritized traffic; whatever the interfaces sent
+/// beyond that is best-effort, see [`QosBreakdown::best_effort_bytes`].
+pub struct QosMonitor {
+	query: isize,
+	bytes: isize,
+	packets: isize,
+	last: Option<(Instant, Vec<TrafficClassCounters>)>,
+}
+
+impl QosMonitor {
+	/// Opens the counter query. Fails when the Pacer counters are not registered, e.g. because the
+	/// QoS Packet Scheduler is not bound to any adapter.
+	pub fn new() -> Result<Self> {
+		let mut query = 0isize;
+		unsafe {
+			check(PdhOpenQueryW(PCWSTR::null(), 0, &mut query))?;
+		}
+		// Closes the query if adding a counter fails below.
+		let mut monitor = Self {
+			query,
+			bytes: 0,
+			packets: 0,
+			last: None,
+		};
+		monitor.bytes = monitor.add_counter(BYTES_PATH)?;
+		monitor.packets = monitor.add_counter(PACKETS_PATH)?;
+		Ok(monitor)
+	}
+
+	/// Current cumulative counters of every active flow; empty when no flow exists.
+	pub fn counters(&mut self) -> Result<Vec<TrafficClassCounters>> {
+		let status = unsafe { PdhCollectQueryData(self.query) };
+		if status == PDH_NO_DATA {
+			return Ok(Vec::new());
+		}
+		check(status)?;
+
+		let bytes = read_raw_array(self.bytes)?;
+		let packets = read_raw_array(self.packets)?;
+		Ok(
+			bytes
+				.into_iter()
+				.map(|(class, bytes_transmitted)| {
+					let packets_transmitted = packets
+						.iter()
+						.find(|(name, _)| *name == class)
+						.map_or(0, |(_, packets)| *packets);
+					TrafficClassCounters {
+						class,
+						bytes_transmitted,
+						packets_transmitted,
+					}
+				})
+				.collect()
+		)
+	}
+
+	/// Per-class traffic since the previous call, or `None` on the first call, which only records
+	/// the starting counters.
+	pub fn measure(&mut self) -> Result<Option<QosBreakdown>> {
+		let counters = self.counters()?;
+		let now = Instant::now();
+		let breakdown = self.last
+			.as_ref()
+			.map(|(taken_at, earlier)| TrafficClassUsage::between(earlier, &counters, now.duration_since(*taken_at)));
+		self.last = Some((now, counters));
+		Ok(breakdown)
+	}
+
+	fn add_counter(&self, path: &str) -> Result<isize> {
+		let wide: Vec<u16> = path.encode_utf16().chain(std::iter::once(0)).collect();
+		let mut counter = 0isize;
+		unsafe {
+			check(PdhAddEnglishCounterW(self.query, PCWSTR(wide.as_ptr()), 0, &mut counter))?;
+		}
+		Ok(counter)
+	}
+}
+
+impl Drop for QosMonitor {
+	fn drop(&mut self) {
+		unsafe {
+			PdhCloseQuery(self.query);
+		}
+	}
+}
+
+/// Instance names and raw first values of a wildcard counter.
+fn read_raw_array(counter: isize) -> Result<Vec<(String, u64)>> {
+	let mut size = 0u32;
+	let mut count = 0u32;
+	let status = unsafe { PdhGetRawCounterArrayW(counter, &mut size, &mut count, None) };
+	if status == PDH_CSTATUS_NO_INSTANCE || status == PDH_NO_DATA || (status == 0 && count == 0) {
+		return Ok(Vec::new());
+	}
+	if status != PDH_MORE_DATA {
+		check(status)?;
+	}
+
+	// The item array is followed by the instance name strings in the same buffer.
+	let item_size = std::mem::size_of::<PDH_RAW_COUNTER_ITEM_W>();
+	let mut buffer: Vec<PDH_RAW_COUNTER_ITEM_W> = Vec::with_capacity((size as usize).div_ceil(item_size));
+	unsafe {
+		check(PdhGetRawCounterArrayW(counter, &mut size, &mut count, Some(buffer.as_mut_ptr())))?;
+		let items = std::slice::from_raw_parts(buffer.as_ptr(), count as usize);
+		Ok(
+			items
+				.iter()
+				.map(|item| {
+					let name = item.szName.to_string().unwrap_or_default();
+					(name, item.RawValue.FirstValue.max(0) as u64)
+				})
+				.collect()
+		)
+	}
+}
+
+fn check(status: u32) -> Result<()> {
+	if status == 0 {
+		Ok(())
+	} else {
+		// PDH status codes are already HRESULT-shaped.
+		Err(NetworkError::WindowsApi(windows::core::Error::from(HRESULT(status as i32))))
+	}
+}
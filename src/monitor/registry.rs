@@ -0,0 +1,110 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use super::shutdown::Shutdown;
+use super::sync_monitor::NetworkSpeedTracker;
+use crate::types::{ NetworkError, NetworkMonitorConfig, NetworkSpeed, Result };
+
+/// Named [`NetworkSpeedTracker`]s, each with its own config and history length, sampled together
+/// by one loop. A server process monitoring several logical services registers one tracker per
+/// service and looks it up by name.
+#[derive(Default)]
+pub struct TrackerRegistry {
+	trackers: BTreeMap<String, NetworkSpeedTracker>,
+}
+
+impl TrackerRegistry {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Creates and adds a tracker named `name`, which also becomes its [label
+	/// name](crate::MonitorLabels::name). Fails if the name is taken or `config` is invalid.
+	pub fn register(
+		&mut self,
+		name: impl Into<String>,
+		config: NetworkMonitorConfig,
+		max_history_size: usize
+	) -> Result<&mut NetworkSpeedTracker> {
+		config.validate()?;
+		let name = name.into();
+		let tracker = NetworkSpeedTracker::with_config(config, max_history_size).with_name(name.clone());
+		self.insert(tracker)
+	}
+
+	/// Adds an existing tracker under its label name. Fails if it has none or the name is taken.
+	pub fn insert(&mut self, tracker: NetworkSpeedTracker) -> Result<&mut NetworkSpeedTracker> {
+		let Some(name) = tracker.labels().name.clone() else {
+			return Err(NetworkError::InvalidConfiguration {
+				field: "registered trackers must be named with with_name".to_string(),
+			});
+		};
+		if self.trackers.contains_key(&name) {
+			return Err(NetworkError::InvalidConfiguration {
+				field: format!("tracker {name:?} is already registered"),
+			});
+		}
+		Ok(self.trackers.entry(name).or_insert(tracker))
+	}
+
+	pub fn remove(&mut self, name: &str) -> Option<NetworkSpeedTracker> {
+		self.trackers.remove(name)
+	}
+
+	pub fn get(&self, name: &str) -> Option<&NetworkSpeedTracker> {
+		self.trackers.get(name)
+	}
+
+	pub fn get_mut(&mut self, name: &str) -> Option<&mut NetworkSpeedTracker> {
+		self.trackers.get_mut(name)
+	}
+
+	pub fn contains(&self, name: &str) -> bool {
+		self.trackers.contains_key(name)
+	}
+
+	/// Registered names, in sorted order.
+	pub fn names(&self) -> impl Iterator<Item = &str> {
+		self.trackers.keys().map(String::as_str)
+	}
+
+	pub fn iter(&self) -> impl Iterator<Item = (&str, &NetworkSpeedTracker)> {
+		self.trackers.iter().map(|(name, tracker)| (name.as_str(), tracker))
+	}
+
+	pub fn len(&self) -> usize {
+		self.trackers.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.trackers.is_empty()
+	}
+
+	/// Takes one measurement with every tracker. One tracker failing does not stop the others;
+	/// failures are also counted in that tracker's [`stats`](NetworkSpeedTracker::stats).
+	pub fn track_all(&mut self) -> BTreeMap<String, Result<NetworkSpeed>> {
+		self.trackers
+			.iter_mut()
+			.map(|(name, tracker)| (name.clone(), tracker.track_speed()))
+			.collect()
+	}
+
+	/// Largest [`recommended_interval`](NetworkSpeedTracker::recommended_interval) among the
+	/// trackers, so the shared loop does not outpace any of them; `None` when empty.
+	pub fn recommended_interval(&self) -> Option<Duration> {
+		self.trackers
+			.values()
+			.map(NetworkSpeedTracker::recommended_interval)
+			.max()
+	}
+
+	/// Calls [`track_all`](Self::track_all) every `interval` until `shutdown` is triggered.
+	pub fn run_until(&mut self, interval: Duration, shutdown: &Shutdown) {
+		while !shutdown.is_triggered() {
+			self.track_all();
+			if shutdown.wait_timeout(interval) {
+				return;
+			}
+		}
+	}
+}
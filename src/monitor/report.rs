@@ -0,0 +1,410 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::Path;
+use std::time::{ Duration, SystemTime, UNIX_EPOCH };
+
+use serde::{ Deserialize, Serialize };
+
+use super::data_usage::UsageTotals;
+use super::forecast::{ forecast_cycle_end, CycleForecast };
+use super::persist::{ read_history, HistoryRecord, MAX_USAGE_GAP };
+use crate::types::template::format_utc;
+use crate::types::{ format_bytes_per_second, format_bytes_total, format_duration, NetworkError, Result };
+
+/// Label naming the interface a tracker measured; [`UsageReport::interfaces`] is keyed by it.
+pub const INTERFACE_LABEL: &str = "interface";
+/// Key for records with neither an [`INTERFACE_LABEL`] label nor a tracker name.
+pub const UNLABELED_SERIES: &str = "unlabeled";
+
+const HOUR_MS: u64 = 3_600_000;
+
+/// Traffic of one series (or all of them) within a [`UsageReport`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsageStats {
+	pub bytes_sent: u64,
+	pub bytes_received: u64,
+	/// Time covered by samples. Gaps over [`MAX_USAGE_GAP`] are not counted, so the averages are
+	/// over the time the tracker was running.
+	pub covered_ms: u64,
+	pub average_upload_bytes_per_sec: u64,
+	pub average_download_bytes_per_sec: u64,
+	pub peak_upload_bytes_per_sec: u64,
+	pub peak_download_bytes_per_sec: u64,
+	pub samples: usize,
+}
+
+impl UsageStats {
+	pub fn total_bytes(&self) -> u64 {
+		self.bytes_sent.saturating_add(self.bytes_received)
+	}
+
+	fn add_interval(&mut self, record: &HistoryRecord, interval_ms: u64) -> (u64, u64) {
+		let seconds = (interval_ms as f64) / 1000.0;
+		let sent = ((record.upload_bytes_per_sec as f64) * seconds) as u64;
+		let received = ((record.download_bytes_per_sec as f64) * seconds) as u64;
+		self.bytes_sent = self.bytes_sent.saturating_add(sent);
+		self.bytes_received = self.bytes_received.saturating_add(received);
+		self.covered_ms = self.covered_ms.saturating_add(interval_ms);
+		(sent, received)
+	}
+
+	fn add_sample(&mut self, record: &HistoryRecord) {
+		self.peak_upload_bytes_per_sec = self.peak_upload_bytes_per_sec.max(record.upload_bytes_per_sec);
+		self.peak_download_bytes_per_sec = self.peak_download_bytes_per_sec.max(record.download_bytes_per_sec);
+		self.samples += 1;
+	}
+
+	fn merge(&mut self, other: &UsageStats) {
+		self.bytes_sent = self.bytes_sent.saturating_add(other.bytes_sent);
+		self.bytes_received = self.bytes_received.saturating_add(other.bytes_received);
+		// Series run side by side, so together they cover as long as the longest one.
+		self.covered_ms = self.covered_ms.max(other.covered_ms);
+		self.peak_upload_bytes_per_sec = self.peak_upload_bytes_per_sec.max(other.peak_upload_bytes_per_sec);
+		self.peak_download_bytes_per_sec = self.peak_download_bytes_per_sec.max(other.peak_download_bytes_per_sec);
+		self.samples += other.samples;
+	}
+
+	fn finish(&mut self) {
+		self.average_upload_bytes_per_sec = self.bytes_sent.saturating_mul(1000).checked_div(self.covered_ms).unwrap_or(0);
+		self.average_download_bytes_per_sec = self.bytes_received.saturating_mul(1000).checked_div(self.covered_ms).unwrap_or(0);
+	}
+}
+
+/// The clock hour (UTC-aligned) that moved the most bytes across all series.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HourUsage {
+	/// Start of the hour, in Unix epoch milliseconds.
+	pub start_ms: u64,
+	pub bytes_sent: u64,
+	pub bytes_received: u64,
+}
+
+impl HourUsage {
+	pub fn total_bytes(&self) -> u64 {
+		self.bytes_sent.saturating_add(self.bytes_received)
+	}
+}
+
+/// How [`UsageReport::render`] lays out a report.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReportFormat {
+	/// Aligned plain text, for e-mail bodies and chat messages.
+	#[default]
+	Text,
+	/// A standalone HTML document with one table row per interface.
+	Html,
+	/// [`UsageReport::to_json`].
+	Json,
+}
+
+impl ReportFormat {
+	/// MIME type of the rendered report, for webhook requests and e-mail bodies.
+	pub fn content_type(&self) -> &'static str {
+		match self {
+			ReportFormat::Text => "text/plain; charset=utf-8",
+			ReportFormat::Html => "text/html; charset=utf-8",
+			ReportFormat::Json => "application/json",
+		}
+	}
+}
+
+/// Totals, averages, busiest hour, and per-interface breakdown of persisted history between two
+/// points in time, for capacity reports.
+///
+/// Every record's rate is applied to the time since the previous record of the same series, as in
+/// [`usage_by_label`](super::persist::usage_by_label). Series are told apart by the
+/// [`INTERFACE_LABEL`] label, else the tracker name, so files written by several trackers are
+/// accounted separately. The total adds every series: a file holding both an all-interfaces tracker
+/// and per-interface ones counts their traffic twice unless the records are filtered first.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsageReport {
+	/// Start of the report, in Unix epoch milliseconds.
+	pub start_ms: u64,
+	/// End (exclusive) of the report, in Unix epoch milliseconds.
+	pub end_ms: u64,
+	pub total: UsageStats,
+	/// `None` when no traffic was recorded in the range.
+	pub busiest_hour: Option<HourUsage>,
+	pub interfaces: BTreeMap<String, UsageStats>,
+}
+
+impl UsageReport {
+	/// Builds the report from `records` (in any order) for `start..end`. Intervals that begin before
+	/// `start` are clipped to it.
+	pub fn between(records: &[HistoryRecord], start: SystemTime, end: SystemTime) -> Self {
+		let start_ms = epoch_ms(start);
+		let end_ms = epoch_ms(end).max(start_ms);
+
+		let mut series: BTreeMap<&str, Vec<&HistoryRecord>> = BTreeMap::new();
+		for record in records {
+			series.entry(series_name(record)).or_default().push(record);
+		}
+
+		let mut interfaces = BTreeMap::new();
+		let mut hours: BTreeMap<u64, HourUsage> = BTreeMap::new();
+		for (name, mut records) in series {
+			records.sort_by_key(|record| record.timestamp_ms);
+			let mut stats = UsageStats::default();
+			let mut previous: Option<&HistoryRecord> = None;
+			for record in records {
+				if record.timestamp_ms >= end_ms {
+					break;
+				}
+				let last = previous.replace(record);
+				if record.timestamp_ms < start_ms {
+					continue;
+				}
+				stats.add_sample(record);
+
+				let Some(last) = last else {
+					continue;
+				};
+				let gap = record.timestamp_ms.saturating_sub(last.timestamp_ms);
+				if gap > (MAX_USAGE_GAP.as_millis() as u64) {
+					continue;
+				}
+				let interval = record.timestamp_ms - last.timestamp_ms.max(start_ms);
+				let (sent, received) = stats.add_interval(record, interval);
+				let hour_start = (record.timestamp_ms / HOUR_MS) * HOUR_MS;
+				let hour = hours.entry(hour_start).or_insert(HourUsage {
+					start_ms: hour_start,
+					..Default::default()
+				});
+				hour.bytes_sent = hour.bytes_sent.saturating_add(sent);
+				hour.bytes_received = hour.bytes_received.saturating_add(received);
+			}
+			if stats.samples > 0 {
+				interfaces.insert(name.to_string(), stats);
+			}
+		}
+
+		let mut total = UsageStats::default();
+		for stats in interfaces.values_mut() {
+			stats.finish();
+			total.merge(stats);
+		}
+		total.finish();
+
+		// The first of equally busy hours wins.
+		let busiest_hour = hours
+			.into_values()
+			.filter(|hour| hour.total_bytes() > 0)
+			.fold(None, |busiest: Option<HourUsage>, hour| match busiest {
+				Some(busiest) if busiest.total_bytes() >= hour.total_bytes() => Some(busiest),
+				_ => Some(hour),
+			});
+
+		Self {
+			start_ms,
+			end_ms,
+			total,
+			busiest_hour,
+			interfaces,
+		}
+	}
+
+	/// [`between`](Self::between) over a history file written by
+	/// [`HistoryWriter`](super::persist::HistoryWriter).
+	pub fn from_history(path: impl AsRef<Path>, start: SystemTime, end: SystemTime) -> Result<Self> {
+		Ok(Self::between(&read_history(path)?, start, end))
+	}
+
+	/// The report for the `duration` ending now, e.g. the last week.
+	pub fn last(records: &[HistoryRecord], duration: Duration) -> Self {
+		let end = SystemTime::now();
+		Self::between(records, end.checked_sub(duration).unwrap_or(UNIX_EPOCH), end)
+	}
+
+	/// Projects the total to [`end_ms`](Self::end_ms), for a report built over a whole billing
+	/// cycle while it is still running: the traffic so far continues at its average pace since
+	/// [`start_ms`](Self::start_ms). `None` before the cycle starts.
+	pub fn forecast_cycle_end(&self) -> Option<CycleForecast> {
+		self.forecast_cycle_end_at(SystemTime::now())
+	}
+
+	/// [`forecast_cycle_end`](Self::forecast_cycle_end) as of `now`.
+	pub fn forecast_cycle_end_at(&self, now: SystemTime) -> Option<CycleForecast> {
+		let now_ms = epoch_ms(now).clamp(self.start_ms, self.end_ms);
+		let used = UsageTotals {
+			bytes_sent: self.total.bytes_sent,
+			bytes_received: self.total.bytes_received,
+		};
+		forecast_cycle_end(
+			used,
+			Duration::from_millis(now_ms - self.start_ms),
+			Duration::from_millis(self.end_ms - now_ms)
+		)
+	}
+
+	pub fn to_json(&self) -> Result<String> {
+		serde_json::to_string_pretty(self).map_err(|err| NetworkError::Persistence {
+			target: "Report",
+			reason: err.to_string(),
+		})
+	}
+
+	/// `Network usage <start> - <end>`, in UTC; the e-mail subject of scheduled reports.
+	pub fn title(&self) -> String {
+		format!("Network usage {} - {}", format_utc_ms(self.start_ms), format_utc_ms(self.end_ms))
+	}
+
+	pub fn render(&self, format: ReportFormat) -> Result<String> {
+		match format {
+			ReportFormat::Text => Ok(self.to_text()),
+			ReportFormat::Html => Ok(self.to_html()),
+			ReportFormat::Json => self.to_json(),
+		}
+	}
+
+	pub fn to_text(&self) -> String {
+		let mut text = format!("{}\n\n", self.title());
+		let total = &self.total;
+		let _ = writeln!(
+			text,
+			"Total:        {} sent, {} received",
+			format_bytes_total(total.bytes_sent),
+			format_bytes_total(total.bytes_received)
+		);
+		let _ = writeln!(
+			text,
+			"Average:      {} up, {} down over {}",
+			format_bytes_per_second(total.average_upload_bytes_per_sec),
+			format_bytes_per_second(total.average_download_bytes_per_sec),
+			format_duration(Duration::from_millis(total.covered_ms))
+		);
+		let _ = writeln!(
+			text,
+			"Peak:         {} up, {} down",
+			format_bytes_per_second(total.peak_upload_bytes_per_sec),
+			format_bytes_per_second(total.peak_download_bytes_per_sec)
+		);
+		if let Some(hour) = &self.busiest_hour {
+			let _ = writeln!(text, "Busiest hour: {}, {}", format_utc_ms(hour.start_ms), format_bytes_total(hour.total_bytes()));
+		}
+
+		if !self.interfaces.is_empty() {
+			let width = self.interfaces.keys().map(|name| name.chars().count()).max().unwrap_or(0).max("Interface".len());
+			let _ = writeln!(
+				text,
+				"\n{:<width$}  {:>12}  {:>12}  {:>12}  {:>12}",
+				"Interface",
+				"Sent",
+				"Received",
+				"Avg up",
+				"Avg down"
+			);
+			for (name, stats) in &self.interfaces {
+				let _ = writeln!(
+					text,
+					"{:<width$}  {:>12}  {:>12}  {:>12}  {:>12}",
+					name,
+					format_bytes_total(stats.bytes_sent),
+					format_bytes_total(stats.bytes_received),
+					format_bytes_per_second(stats.average_upload_bytes_per_sec),
+					format_bytes_per_second(stats.average_download_bytes_per_sec)
+				);
+			}
+		}
+		text
+	}
+
+	pub fn to_html(&self) -> String {
+		let title = html_escape(&self.title());
+		let total = &self.total;
+		let mut html = format!(
+			"<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"><title>{title}</title></head>\n<body>\n<h1>{title}</h1>\n<ul>\n"
+		);
+		let _ = writeln!(
+			html,
+			"<li>Total: {} sent, {} received</li>",
+			format_bytes_total(total.bytes_sent),
+			format_bytes_total(total.bytes_received)
+		);
+		let _ = writeln!(
+			html,
+			"<li>Average: {} up, {} down over {}</li>",
+			format_bytes_per_second(total.average_upload_bytes_per_sec),
+			format_bytes_per_second(total.average_download_bytes_per_sec),
+			format_duration(Duration::from_millis(total.covered_ms))
+		);
+		let _ = writeln!(
+			html,
+			"<li>Peak: {} up, {} down</li>",
+			format_bytes_per_second(total.peak_upload_bytes_per_sec),
+			format_bytes_per_second(total.peak_download_bytes_per_sec)
+		);
+		if let Some(hour) = &self.busiest_hour {
+			let _ = writeln!(html, "<li>Busiest hour: {}, {}</li>", format_utc_ms(hour.start_ms), format_bytes_total(hour.total_bytes()));
+		}
+		html.push_str(
+			"</ul>\n<table>\n<tr><th>Interface</th><th>Sent</th><th>Received</th><th>Avg up</th><th>Avg down</th><th>Peak up</th><th>Peak down</th></tr>\n"
+		);
+		for (name, stats) in &self.interfaces {
+			let _ = writeln!(
+				html,
+				"<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+				html_escape(name),
+				format_bytes_total(stats.bytes_sent),
+				format_bytes_total(stats.bytes_received),
+				format_bytes_per_second(stats.average_upload_bytes_per_sec),
+				format_bytes_per_second(stats.average_download_bytes_per_sec),
+				format_bytes_per_second(stats.peak_upload_bytes_per_sec),
+				format_bytes_per_second(stats.peak_download_bytes_per_sec)
+			);
+		}
+		html.push_str("</table>\n</body>\n</html>\n");
+		html
+	}
+
+	/// One row per interface followed by a `total` row. The busiest hour is only part of
+	/// [`to_json`](Self::to_json), as it does not fit the per-interface rows.
+	pub fn to_csv(&self) -> String {
+		let mut csv = String::from(
+			"interface,bytes_sent,bytes_received,covered_secs,average_upload_bytes_per_sec,average_download_bytes_per_sec,peak_upload_bytes_per_sec,peak_download_bytes_per_sec,samples\n"
+		);
+		for (name, stats) in self.interfaces.iter().chain(std::iter::once((&"total".to_string(), &self.total))) {
+			let _ = writeln!(
+				csv,
+				"{},{},{},{},{},{},{},{},{}",
+				csv_field(name),
+				stats.bytes_sent,
+				stats.bytes_received,
+				stats.covered_ms / 1000,
+				stats.average_upload_bytes_per_sec,
+				stats.average_download_bytes_per_sec,
+				stats.peak_upload_bytes_per_sec,
+				stats.peak_download_bytes_per_sec,
+				stats.samples
+			);
+		}
+		csv
+	}
+}
+
+fn series_name(record: &HistoryRecord) -> &str {
+	record.labels
+		.get(INTERFACE_LABEL)
+		.or(record.labels.name.as_deref())
+		.unwrap_or(UNLABELED_SERIES)
+}
+
+fn epoch_ms(time: SystemTime) -> u64 {
+	time.duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_millis() as u64)
+}
+
+fn format_utc_ms(ms: u64) -> String {
+	format_utc(UNIX_EPOCH + Duration::from_millis(ms), "%Y-%m-%d %H:%M UTC")
+}
+
+fn html_escape(value: &str) -> String {
+	value.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Quotes a field containing a separator, quote, or line break, doubling embedded quotes.
+fn csv_field(value: &str) -> String {
+	if value.contains([',', '"', '\n', '\r']) {
+		format!("\"{}\"", value.replace('"', "\"\""))
+	} else {
+		value.to_string()
+	}
+}
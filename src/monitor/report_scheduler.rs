@@ -0,0 +1,545 @@
+#[cfg(feature = "smtp")]
+use std::io::{ BufRead, BufReader };
+#[cfg(feature = "webhook")]
+use std::io::Read;
+#[cfg(any(feature = "webhook", feature = "smtp"))]
+use std::io::Write;
+#[cfg(any(feature = "webhook", feature = "smtp"))]
+use std::net::{ SocketAddr, TcpStream, ToSocketAddrs };
+use std::path::PathBuf;
+use std::time::{ Duration, SystemTime };
+
+use super::interface::local_time;
+use super::report::{ ReportFormat, UsageReport };
+use super::shutdown::Shutdown;
+#[cfg(all(feature = "webhook", feature = "tls"))]
+use crate::agent::ClientTls;
+#[cfg(feature = "webhook")]
+use crate::probe::http::{ parse_status, HttpTarget };
+use crate::types::schedule::parse_time_of_day;
+#[cfg(any(feature = "webhook", feature = "smtp"))]
+use crate::types::NetworkError;
+use crate::types::{ LocalTime, Result, Weekday };
+
+/// How often [`ReportScheduler::run_until`] checks whether a report is due.
+pub const REPORT_POLL_INTERVAL: Duration = Duration::from_secs(60);
+/// How long after the send time a missed report (e.g. a failed delivery) is still sent.
+pub const REPORT_CATCH_UP_WINDOW: Duration = Duration::from_secs(3600);
+#[cfg(any(feature = "webhook", feature = "smtp"))]
+pub const DEFAULT_DELIVERY_TIMEOUT: Duration = Duration::from_secs(30);
+
+const DAY: Duration = Duration::from_secs(86_400);
+
+/// How often a [`ReportScheduler`] sends, each report covering the period since the previous one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportCadence {
+	Daily,
+	Weekly(Weekday),
+}
+
+impl ReportCadence {
+	pub fn period(&self) -> Duration {
+		match self {
+			ReportCadence::Daily => DAY,
+			ReportCadence::Weekly(_) => DAY * 7,
+		}
+	}
+
+	fn sends_on(&self, weekday: Weekday) -> bool {
+		match self {
+			ReportCadence::Daily => true,
+			ReportCadence::Weekly(day) => *day == weekday,
+		}
+	}
+}
+
+/// A report rendered for delivery.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderedReport {
+	/// [`UsageReport::title`], used as the e-mail subject.
+	pub subject: String,
+	pub body: String,
+	pub format: ReportFormat,
+}
+
+/// Where a [`ReportScheduler`] sends its reports.
+pub trait ReportDelivery: Send {
+	fn deliver(&mut self, report: &RenderedReport) -> Result<()>;
+}
+
+impl<F> ReportDelivery for F where F: FnMut(&RenderedReport) -> Result<()> + Send {
+	fn deliver(&mut self, report: &RenderedReport) -> Result<()> {
+		self(report)
+	}
+}
+
+/// Sends a [`UsageReport`] of a history file daily or weekly at a local time, e.g. every Monday at
+/// 08:00 covering the past week, so small deployments get summaries without a reporting stack.
+///
+/// A failed delivery is retried on the next poll until [`REPORT_CATCH_UP_WINDOW`] after the send
+/// time has passed. The last send time is kept in memory only, so a restart within that window
+/// sends the report again.
+pub struct ReportScheduler {
+	history_path: PathBuf,
+	cadence: ReportCadence,
+	send_at: u16,
+	format: ReportFormat,
+	deliveries: Vec<Box<dyn ReportDelivery>>,
+	last_sent: Option<SystemTime>,
+	failed_attempts: u64,
+}
+
+impl ReportScheduler {
+	/// Reports on `history_path` (written by [`HistoryWriter`](super::persist::HistoryWriter)) at
+	/// `send_at`, `HH:MM` local time, as plain text and without deliveries yet.
+	pub fn new(history_path: impl Into<PathBuf>, cadence: ReportCadence, send_at: &str) -> Result<Self> {
+		Ok(Self {
+			history_path: history_path.into(),
+			cadence,
+			send_at: parse_time_of_day(send_at)?,
+			format: ReportFormat::Text,
+			deliveries: Vec::new(),
+			last_sent: None,
+			failed_attempts: 0,
+		})
+	}
+
+	pub fn with_format(mut self, format: ReportFormat) -> Self {
+		self.format = format;
+		self
+	}
+
+	/// Adds a destination; every report goes to all of them.
+	pub fn with_delivery(mut self, delivery: impl ReportDelivery + 'static) -> Self {
+		self.deliveries.push(Box::new(delivery));
+		self
+	}
+
+	pub fn last_sent(&self) -> Option<SystemTime> {
+		self.last_sent
+	}
+
+	/// Reports that could not be built or delivered, including retries.
+	pub fn failed_attempts(&self) -> u64 {
+		self.failed_attempts
+	}
+
+	/// Whether a report is due at local time `at` when the current time is `now`: on a send day,
+	/// within the catch-up window after the send time, and not sent for this period yet.
+	pub fn is_due(&self, at: LocalTime, now: SystemTime) -> bool {
+		let catch_up = (REPORT_CATCH_UP_WINDOW.as_secs() / 60) as u16;
+		let in_window = at.minute >= self.send_at && at.minute < self.send_at.saturating_add(catch_up);
+		let sent_recently = self.last_sent.is_some_and(|sent| {
+			now.duration_since(sent).map_or(true, |since| since < self.cadence.period() / 2)
+		});
+		self.cadence.sends_on(at.weekday) && in_window && !sent_recently
+	}
+
+	/// Builds the report for the period ending now and hands it to every delivery, regardless of
+	/// the schedule. Returns the first error once every delivery has been tried.
+	pub fn send_now(&mut self) -> Result<UsageReport> {
+		let result = self.build_and_deliver();
+		match &result {
+			Ok(_) => {
+				self.last_sent = Some(SystemTime::now());
+			}
+			Err(_err) => {
+				self.failed_attempts += 1;
+				#[cfg(feature = "tracing")]
+				tracing::warn!(error = %_err, path = %self.history_path.display(), "scheduled usage report failed");
+			}
+		}
+		result
+	}
+
+	/// Sends the report if it [`is_due`](Self::is_due) now; `Ok(None)` when it is not.
+	pub fn poll(&mut self) -> Result<Option<UsageReport>> {
+		if !self.is_due(local_time(), SystemTime::now()) {
+			return Ok(None);
+		}
+		self.send_now().map(Some)
+	}
+
+	/// Calls [`poll`](Self::poll) every [`REPORT_POLL_INTERVAL`] until `shutdown` is triggered.
+	/// Failures are counted in [`failed_attempts`](Self::failed_attempts) and retried.
+	pub fn run_until(&mut self, shutdown: &Shutdown) {
+		while !shutdown.is_triggered() {
+			let _ = self.poll();
+			if shutdown.wait_timeout(REPORT_POLL_INTERVAL) {
+				return;
+			}
+		}
+	}
+
+	fn build_and_deliver(&mut self) -> Result<UsageReport> {
+		let end = SystemTime::now();
+		let start = end.checked_sub(self.cadence.period()).unwrap_or(SystemTime::UNIX_EPOCH);
+		let report = UsageReport::from_history(&self.history_path, start, end)?;
+		let rendered = RenderedReport {
+			subject: report.title(),
+			body: report.render(self.format)?,
+			format: self.format,
+		};
+
+		let mut first_error = None;
+		for delivery in &mut self.deliveries {
+			if let Err(err) = delivery.deliver(&rendered) {
+				first_error.get_or_insert(err);
+			}
+		}
+		match first_error {
+			Some(err) => Err(err),
+			None => Ok(report),
+		}
+	}
+}
+
+/// POSTs the rendered report to an `http://` URL, or with the `tls` feature an `https://` one,
+/// with the format's content type. Any 2xx status counts as delivered.
+///
+/// An `Authorization` header is refused over plain `http://` to hosts off the loopback interface
+/// unless `allow_plaintext_auth(true)` is set.
+#[cfg(feature = "webhook")]
+pub struct WebhookDelivery {
+	target: HttpTarget,
+	headers: Vec<(String, String)>,
+	allow_plaintext_auth: bool,
+	timeout: Duration,
+	#[cfg(feature = "tls")]
+	tls: Option<ClientTls>,
+}
+
+#[cfg(feature = "webhook")]
+impl WebhookDelivery {
+	pub fn new(url: &str) -> Result<Self> {
+		#[cfg(feature = "tls")]
+		let target = if url.starts_with("https://") { HttpTarget::parse_https(url)? } else { HttpTarget::parse(url)? };
+		#[cfg(not(feature = "tls"))]
+		let target = HttpTarget::parse(url)?;
+
+		Ok(Self {
+			target,
+			headers: Vec::new(),
+			allow_plaintext_auth: false,
+			timeout: DEFAULT_DELIVERY_TIMEOUT,
+			#[cfg(feature = "tls")]
+			tls: None,
+		})
+	}
+
+	/// Adds a request header, e.g. `Authorization`. Line breaks are replaced with spaces.
+	pub fn with_header(mut self, name: &str, value: &str) -> Self {
+		self.headers.push((single_line(name), single_line(value)));
+		self
+	}
+
+	pub fn allow_plaintext_auth(mut self, allow: bool) -> Self {
+		self.allow_plaintext_auth = allow;
+		self
+	}
+
+	/// Trust roots for an `https://` URL; its server name must match the URL's host.
+	#[cfg(feature = "tls")]
+	pub fn with_tls(mut self, tls: ClientTls) -> Self {
+		self.tls = Some(tls);
+		self
+	}
+
+	/// Bounds the connect and each read or write separately.
+	pub fn with_timeout(mut self, timeout: Duration) -> Self {
+		self.timeout = timeout;
+		self
+	}
+
+	fn post(&self, report: &RenderedReport) -> Result<u16> {
+		#[cfg(feature = "tls")]
+		if self.target.secure {
+			let tls = self.tls.as_ref().ok_or_else(|| NetworkError::InvalidConfiguration {
+				field: "https:// webhook needs trust roots; set with_tls".to_string(),
+			})?;
+			let stream = connect((self.target.host.as_str(), self.target.port), self.timeout).map_err(webhook_error)?;
+			return self.exchange(tls.connect(stream)?, report).map_err(webhook_error);
+		}
+
+		let stream = connect((self.target.host.as_str(), self.target.port), self.timeout).map_err(webhook_error)?;
+
+		let peer = stream.peer_addr().map_err(webhook_error)?;
+		let authorization = self.headers.iter().any(|(name, _)| name.trim().eq_ignore_ascii_case("authorization"));
+		if authorization && !self.allow_plaintext_auth && !peer.ip().is_loopback() {
+			return Err(NetworkError::InvalidConfiguration {
+				field: format!("refusing to send the Authorization header unencrypted to {peer}; use https:// or set allow_plaintext_auth(true)"),
+			});
+		}
+		self.exchange(stream, report).map_err(webhook_error)
+	}
+
+	fn exchange(&self, mut stream: impl Read + Write, report: &RenderedReport) -> std::io::Result<u16> {
+		let mut request = format!(
+			"POST {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: network-speed/{}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+			self.target.path,
+			self.target.host_header(),
+			env!("CARGO_PKG_VERSION"),
+			report.format.content_type(),
+			report.body.len()
+		);
+		for (name, value) in &self.headers {
+			request.push_str(&format!("{name}: {value}\r\n"));
+		}
+		request.push_str("\r\n");
+		stream.write_all(request.as_bytes())?;
+		stream.write_all(report.body.as_bytes())?;
+
+		let mut head = Vec::with_capacity(64);
+		let mut buffer = [0u8; 512];
+		while !head.contains(&b'\n') {
+			let read = stream.read(&mut buffer)?;
+			if read == 0 {
+				break;
+			}
+			head.extend_from_slice(&buffer[..read]);
+		}
+		parse_status(&head).ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidData, "malformed status line"))
+	}
+}
+
+#[cfg(feature = "webhook")]
+impl ReportDelivery for WebhookDelivery {
+	fn deliver(&mut self, report: &RenderedReport) -> Result<()> {
+		let status = self.post(report)?;
+		if !(200..300).contains(&status) {
+			return Err(delivery_error("Webhook", format!("HTTP status {status}")));
+		}
+		Ok(())
+	}
+}
+
+/// Mails the rendered report through an SMTP relay, such as the local MTA or an internal relay on
+/// port 25 or 587.
+///
+/// The connection is not encrypted. Credentials are sent with `AUTH PLAIN` and are refused for
+/// relays off the loopback interface unless `allow_plaintext_auth(true)` is set.
+#[cfg(feature = "smtp")]
+pub struct SmtpDelivery {
+	server: String,
+	from: String,
+	to: Vec<String>,
+	credentials: Option<(String, String)>,
+	allow_plaintext_auth: bool,
+	helo_name: String,
+	timeout: Duration,
+}
+
+#[cfg(feature = "smtp")]
+impl SmtpDelivery {
+	/// `server` is `host:port`, e.g. `localhost:25`. Fails without recipients or on an address
+	/// that is not a plain `user@host`.
+	pub fn new(server: impl Into<String>, from: &str, to: &[&str]) -> Result<Self> {
+		if to.is_empty() {
+			return Err(NetworkError::InvalidConfiguration {
+				field: "smtp delivery needs at least one recipient".to_string(),
+			});
+		}
+		let address = |value: &str| {
+			let valid = value.contains('@') && !value.contains(|c: char| c.is_control() || c.is_whitespace() || c == '<' || c == '>');
+			if valid {
+				Ok(value.to_string())
+			} else {
+				Err(NetworkError::InvalidConfiguration {
+					field: format!("invalid e-mail address {value:?}"),
+				})
+			}
+		};
+
+		Ok(Self {
+			server: server.into(),
+			from: address(from)?,
+			to: to.iter().map(|to| address(to)).collect::<Result<_>>()?,
+			credentials: None,
+			allow_plaintext_auth: false,
+			helo_name: "localhost".to_string(),
+			timeout: DEFAULT_DELIVERY_TIMEOUT,
+		})
+	}
+
+	pub fn with_credentials(mut self, username: impl Into<String>, password: impl Into<String>) -> Self {
+		self.credentials = Some((username.into(), password.into()));
+		self
+	}
+
+	pub fn allow_plaintext_auth(mut self, allow: bool) -> Self {
+		self.allow_plaintext_auth = allow;
+		self
+	}
+
+	/// Name sent with `EHLO`; some relays require a fully qualified host name.
+	pub fn with_helo_name(mut self, name: &str) -> Self {
+		self.helo_name = single_line(name);
+		self
+	}
+
+	pub fn with_timeout(mut self, timeout: Duration) -> Self {
+		self.timeout = timeout;
+		self
+	}
+
+	fn send(&self, report: &RenderedReport) -> Result<()> {
+		let stream = connect(self.server.as_str(), self.timeout).map_err(smtp_error)?;
+		let peer = stream.peer_addr().map_err(smtp_error)?;
+		if self.credentials.is_some() && !self.allow_plaintext_auth && !peer.ip().is_loopback() {
+			return Err(NetworkError::InvalidConfiguration {
+				field: format!("refusing to send SMTP credentials unencrypted to {peer}; set allow_plaintext_auth(true)"),
+			});
+		}
+
+		let mut session = SmtpSession {
+			reader: BufReader::new(stream.try_clone().map_err(smtp_error)?),
+			writer: stream,
+		};
+		session.reply(&[220])?;
+		session.command(&format!("EHLO {}", self.helo_name), &[250])?;
+		if let Some((username, password)) = &self.credentials {
+			let token = base64_encode(format!("\0{username}\0{password}").as_bytes());
+			session.command(&format!("AUTH PLAIN {token}"), &[235])?;
+		}
+		session.command(&format!("MAIL FROM:<{}>", self.from), &[250])?;
+		for to in &self.to {
+			session.command(&format!("RCPT TO:<{to}>"), &[250, 251])?;
+		}
+		session.command("DATA", &[354])?;
+		session.send_data(&self.message(report))?;
+		session.reply(&[250])?;
+		let _ = session.command("QUIT", &[221]);
+		Ok(())
+	}
+
+	fn message(&self, report: &RenderedReport) -> String {
+		let mut message = format!(
+			"From: <{}>\r\nTo: {}\r\nSubject: {}\r\nMIME-Version: 1.0\r\nContent-Type: {}\r\nContent-Transfer-Encoding: 8bit\r\n\r\n",
+			self.from,
+			self.to
+				.iter()
+				.map(|to| format!("<{to}>"))
+				.collect::<Vec<_>>()
+				.join(", "),
+			single_line(&report.subject),
+			report.format.content_type()
+		);
+		for line in report.body.lines() {
+			// Dot-stuffing, so a line consisting of "." does not end the message early.
+			if line.starts_with('.') {
+				message.push('.');
+			}
+			message.push_str(line);
+			message.push_str("\r\n");
+		}
+		message
+	}
+}
+
+#[cfg(feature = "smtp")]
+impl ReportDelivery for SmtpDelivery {
+	fn deliver(&mut self, report: &RenderedReport) -> Result<()> {
+		self.send(report)
+	}
+}
+
+#[cfg(feature = "smtp")]
+struct SmtpSession {
+	reader: BufReader<TcpStream>,
+	writer: TcpStream,
+}
+
+#[cfg(feature = "smtp")]
+impl SmtpSession {
+	fn command(&mut self, line: &str, expected: &[u16]) -> Result<()> {
+		self.writer.write_all(format!("{line}\r\n").as_bytes()).map_err(smtp_error)?;
+		self.reply(expected)
+	}
+
+	fn send_data(&mut self, message: &str) -> Result<()> {
+		self.writer.write_all(message.as_bytes()).map_err(smtp_error)?;
+		self.writer.write_all(b".\r\n").map_err(smtp_error)
+	}
+
+	/// Reads one (possibly multi-line) reply and checks its code.
+	fn reply(&mut self, expected: &[u16]) -> Result<()> {
+		loop {
+			let mut line = String::new();
+			if self.reader.read_line(&mut line).map_err(smtp_error)? == 0 {
+				return Err(smtp_error("connection closed"));
+			}
+			let code: u16 = line
+				.get(..3)
+				.and_then(|code| code.parse().ok())
+				.ok_or_else(|| smtp_error(format!("malformed reply {:?}", line.trim_end())))?;
+			if line.as_bytes().get(3) == Some(&b'-') {
+				continue;
+			}
+			if !expected.contains(&code) {
+				return Err(smtp_error(format!("server replied {}", line.trim_end())));
+			}
+			return Ok(());
+		}
+	}
+}
+
+/// Tries each resolved address in turn, with `timeout` for the connect and for each read or write.
+#[cfg(any(feature = "webhook", feature = "smtp"))]
+fn connect(addr: impl ToSocketAddrs, timeout: Duration) -> std::io::Result<TcpStream> {
+	let addrs: Vec<SocketAddr> = addr.to_socket_addrs()?.collect();
+	let mut last_error = std::io::Error::new(std::io::ErrorKind::NotFound, "no addresses resolved");
+	for addr in addrs {
+		match TcpStream::connect_timeout(&addr, timeout) {
+			Ok(stream) => {
+				stream.set_read_timeout(Some(timeout))?;
+				stream.set_write_timeout(Some(timeout))?;
+				return Ok(stream);
+			}
+			Err(err) => {
+				last_error = err;
+			}
+		}
+	}
+	Err(last_error)
+}
+
+#[cfg(any(feature = "webhook", feature = "smtp"))]
+fn single_line(value: &str) -> String {
+	value.replace(['\r', '\n'], " ")
+}
+
+#[cfg(any(feature = "webhook", feature = "smtp"))]
+fn delivery_error(channel: &'static str, reason: impl std::fmt::Display) -> NetworkError {
+	NetworkError::Delivery {
+		channel,
+		reason: reason.to_string(),
+	}
+}
+
+#[cfg(feature = "webhook")]
+fn webhook_error(reason: impl std::fmt::Display) -> NetworkError {
+	delivery_error("Webhook", reason)
+}
+
+#[cfg(feature = "smtp")]
+fn smtp_error(reason: impl std::fmt::Display) -> NetworkError {
+	delivery_error("SMTP", reason)
+}
+
+#[cfg(feature = "smtp")]
+fn base64_encode(input: &[u8]) -> String {
+	const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+	let mut output = String::with_capacity(input.len().div_ceil(3) * 4);
+	for chunk in input.chunks(3) {
+		let bytes = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+		let triple = ((bytes[0] as u32) << 16) | ((bytes[1] as u32) << 8) | (bytes[2] as u32);
+		for (i, shift) in [18, 12, 6, 0].into_iter().enumerate() {
+			if i <= chunk.len() {
+				output.push(ALPHABET[((triple >> shift) & 0x3f) as usize] as char);
+			} else {
+				output.push('=');
+			}
+		}
+	}
+	output
+}
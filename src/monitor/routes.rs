@@ -0,0 +1,144 @@
+use std::net::IpAddr;
+
+use windows::Win32::{
+	NetworkManagement::IpHelper::{ FreeMibTable, GetBestRoute2, GetIpForwardTable2, MIB_IPFORWARD_ROW2, MIB_IPFORWARD_TABLE2 },
+	Networking::WinSock::{ AF_INET, AF_INET6, AF_UNSPEC, IN6_ADDR, IN6_ADDR_0, IN_ADDR, IN_ADDR_0, SOCKADDR_IN, SOCKADDR_IN6, SOCKADDR_INET },
+};
+
+use super::interface::{ interfaces_iter, NetworkInterface };
+use super::neighbors::sockaddr_to_ip;
+use crate::types::{ NetworkError, Result };
+
+/// One entry of the IPv4 or IPv6 routing table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Route {
+	pub destination: IpAddr,
+	pub prefix_length: u8,
+	/// Gateway, or `None` for on-link routes.
+	pub next_hop: Option<IpAddr>,
+	/// Index of the outgoing interface, matching [`NetworkInterface::index`].
+	pub interface_index: u32,
+	/// Route metric; Windows adds the interface metric to it when choosing between routes.
+	pub metric: u32,
+}
+
+impl Route {
+	/// `0.0.0.0/0` or `::/0`.
+	pub fn is_default(&self) -> bool {
+		self.prefix_length == 0
+	}
+
+	/// Whether `address` falls within the route's destination prefix.
+	pub fn contains(&self, address: IpAddr) -> bool {
+		match (self.destination, address) {
+			(IpAddr::V4(network), IpAddr::V4(address)) => {
+				prefix_matches(&network.octets(), &address.octets(), self.prefix_length)
+			}
+			(IpAddr::V6(network), IpAddr::V6(address)) => {
+				prefix_matches(&network.octets(), &address.octets(), self.prefix_length)
+			}
+			_ => false,
+		}
+	}
+
+	fn from_row(row: &MIB_IPFORWARD_ROW2) -> Option<Self> {
+		let next_hop = sockaddr_to_ip(&row.NextHop).filter(|hop| !hop.is_unspecified());
+		Some(Route {
+			destination: sockaddr_to_ip(&row.DestinationPrefix.Prefix)?,
+			prefix_length: row.DestinationPrefix.PrefixLength,
+			next_hop,
+			interface_index: row.InterfaceIndex,
+			metric: row.Metric,
+		})
+	}
+}
+
+/// The route Windows would use for a destination, from [`route_for`].
+#[derive(Debug, Clone)]
+pub struct RouteLookup {
+	pub route: Route,
+	/// Local address the traffic would be sent from.
+	pub source: IpAddr,
+	/// The outgoing interface; `None` if it disappeared between the two lookups.
+	pub interface: Option<NetworkInterface>,
+}
+
+/// Lists the IPv4 and IPv6 routing tables (`GetIpForwardTable2`).
+pub fn list_routes() -> Result<Vec<Route>> {
+	let mut routes = Vec::new();
+
+	unsafe {
+		let mut table: *mut MIB_IPFORWARD_TABLE2 = std::ptr::null_mut();
+		GetIpForwardTable2(AF_UNSPEC, &mut table)?;
+		let rows = std::slice::from_raw_parts((*table).Table.as_ptr(), (*table).NumEntries as usize);
+		routes.extend(rows.iter().filter_map(Route::from_row));
+		FreeMibTable(table as _).map_err(NetworkError::WindowsApi)?;
+	}
+
+	Ok(routes)
+}
+
+/// The route, source address, and interface Windows picks for traffic to `destination`
+/// (`GetBestRoute2`), including interface metrics. For example, `route_for("8.8.8.8".parse()?)`
+/// tells which adapter carries Internet traffic. Fails when no route matches.
+pub fn route_for(destination: IpAddr) -> Result<RouteLookup> {
+	let destination = ip_to_sockaddr(destination);
+	let mut row = MIB_IPFORWARD_ROW2::default();
+	let mut source = SOCKADDR_INET::default();
+	unsafe {
+		GetBestRoute2(None, 0, None, &destination, 0, &mut row, &mut source)?;
+	}
+
+	let route = Route::from_row(&row).ok_or(NetworkError::InvalidInterface)?;
+	let source = sockaddr_to_ip(&source).ok_or(NetworkError::InvalidInterface)?;
+	let mut interface = None;
+	for candidate in interfaces_iter()? {
+		let candidate = candidate?;
+		if candidate.index == route.interface_index {
+			interface = Some(candidate);
+			break;
+		}
+	}
+
+	Ok(RouteLookup { route, source, interface })
+}
+
+fn prefix_matches(network: &[u8], address: &[u8], prefix_length: u8) -> bool {
+	let bits = (prefix_length as usize).min(network.len() * 8);
+	let (whole, rest) = (bits / 8, bits % 8);
+	if network[..whole] != address[..whole] {
+		return false;
+	}
+	if rest == 0 {
+		return true;
+	}
+	let mask = 0xffu8 << (8 - rest);
+	network[whole] & mask == address[whole] & mask
+}
+
+fn ip_to_sockaddr(address: IpAddr) -> SOCKADDR_INET {
+	match address {
+		IpAddr::V4(address) => SOCKADDR_INET {
+			Ipv4: SOCKADDR_IN {
+				sin_family: AF_INET,
+				sin_addr: IN_ADDR {
+					S_un: IN_ADDR_0 {
+						S_addr: u32::from_ne_bytes(address.octets()),
+					},
+				},
+				..Default::default()
+			},
+		},
+		IpAddr::V6(address) => SOCKADDR_INET {
+			Ipv6: SOCKADDR_IN6 {
+				sin6_family: AF_INET6,
+				sin6_addr: IN6_ADDR {
+					u: IN6_ADDR_0 {
+						Byte: address.octets(),
+					},
+				},
+				..Default::default()
+			},
+		},
+	}
+}
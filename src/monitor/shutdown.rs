@@ -0,0 +1,209 @@
+use std::sync::{ Arc, Condvar, Mutex };
+use std::time::Duration;
+#[cfg(unix)]
+use std::sync::atomic::{ AtomicI32, Ordering };
+#[cfg(windows)]
+use windows::Win32::Foundation::{ BOOL, FALSE, TRUE };
+#[cfg(windows)]
+use windows::Win32::System::Console::{ SetConsoleCtrlHandler, CTRL_BREAK_EVENT, CTRL_C_EVENT };
+
+use crate::types::{ NetworkError, Result };
+
+/// Signals triggered by the console control handler, see [`Shutdown::trigger_on_ctrl_c`].
+static CTRL_C_TARGETS: Mutex<Vec<Shutdown>> = Mutex::new(Vec::new());
+
+/// Write end of the pipe the Unix signal handler reports signal numbers through.
+#[cfg(unix)]
+static SIGNAL_PIPE: AtomicI32 = AtomicI32::new(-1);
+
+type Hook = Box<dyn FnOnce() + Send>;
+
+#[derive(Default)]
+struct ShutdownState {
+	triggered: bool,
+	hooks: Vec<Hook>,
+}
+
+#[derive(Default)]
+struct ShutdownInner {
+	state: Mutex<ShutdownState>,
+	condvar: Condvar,
+	#[cfg(feature = "async")]
+	notify: tokio::sync::Notify,
+}
+
+/// Cloneable stop signal shared by every background loop in the crate.
+///
+/// Triggering it wakes sleeping loops immediately and runs the registered hooks (e.g. flushing a
+/// `HistoryWriter`) exactly once.
+#[derive(Clone, Default)]
+pub struct Shutdown {
+	inner: Arc<ShutdownInner>,
+}
+
+impl Shutdown {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn trigger(&self) {
+		let hooks = {
+			let Ok(mut state) = self.inner.state.lock() else {
+				return;
+			};
+			if state.triggered {
+				return;
+			}
+			state.triggered = true;
+			std::mem::take(&mut state.hooks)
+		};
+
+		self.inner.condvar.notify_all();
+		#[cfg(feature = "async")]
+		self.inner.notify.notify_waiters();
+
+		for hook in hooks {
+			hook();
+		}
+	}
+
+	pub fn is_triggered(&self) -> bool {
+		self.inner.state
+			.lock()
+			.map(|state| state.triggered)
+			.unwrap_or(true)
+	}
+
+	/// Runs `hook` when shutdown is triggered, or right away if it already was.
+	pub fn on_shutdown(&self, hook: impl FnOnce() + Send + 'static) {
+		if let Ok(mut state) = self.inner.state.lock() {
+			if !state.triggered {
+				state.hooks.push(Box::new(hook));
+				return;
+			}
+		}
+		hook();
+	}
+
+	/// Sleeps for up to `timeout`; returns `true` if shutdown was triggered (possibly earlier).
+	pub fn wait_timeout(&self, timeout: Duration) -> bool {
+		let Ok(state) = self.inner.state.lock() else {
+			return true;
+		};
+		self.inner.condvar
+			.wait_timeout_while(state, timeout, |state| !state.triggered)
+			.map(|(state, _)| state.triggered)
+			.unwrap_or(true)
+	}
+
+	pub fn wait(&self) {
+		let Ok(state) = self.inner.state.lock() else {
+			return;
+		};
+		drop(self.inner.condvar.wait_while(state, |state| !state.triggered));
+	}
+
+	/// Triggers this signal on Ctrl+C or Ctrl+Break (SIGINT or SIGTERM on Unix) instead of
+	/// terminating the process, so loops can flush and exit cleanly. A second Ctrl+C after every
+	/// registered signal has been triggered falls through to the default handler and ends the
+	/// process, in case cleanup hangs.
+	pub fn trigger_on_ctrl_c(&self) -> Result<()> {
+		let Ok(mut targets) = CTRL_C_TARGETS.lock() else {
+			return Err(NetworkError::LockPoisoned { resource: "Ctrl+C handler" });
+		};
+		if targets.is_empty() {
+			#[cfg(windows)]
+			unsafe { SetConsoleCtrlHandler(Some(console_ctrl_handler), TRUE)? };
+			#[cfg(unix)]
+			install_signal_handlers()?;
+		}
+		targets.push(self.clone());
+		Ok(())
+	}
+
+	/// Resolves once shutdown is triggered.
+	#[cfg(feature = "async")]
+	pub async fn triggered(&self) {
+		loop {
+			let notified = self.inner.notify.notified();
+			if self.is_triggered() {
+				return;
+			}
+			notified.await;
+		}
+	}
+}
+
+#[cfg(windows)]
+unsafe extern "system" fn console_ctrl_handler(ctrl_type: u32) -> BOOL {
+	if ctrl_type != CTRL_C_EVENT && ctrl_type != CTRL_BREAK_EVENT {
+		return FALSE;
+	}
+
+	// Runs on a thread the console creates, so hooks may block briefly without stalling the loops.
+	if trigger_pending() { TRUE } else { FALSE }
+}
+
+/// Triggers every registered signal that has not been triggered yet; `false` if there was none.
+fn trigger_pending() -> bool {
+	let pending: Vec<Shutdown> = match CTRL_C_TARGETS.lock() {
+		Ok(targets) => targets
+			.iter()
+			.filter(|shutdown| !shutdown.is_triggered())
+			.cloned()
+			.collect(),
+		Err(_) => Vec::new(),
+	};
+	if pending.is_empty() {
+		return false;
+	}
+
+	for shutdown in pending {
+		shutdown.trigger();
+	}
+	true
+}
+
+/// Routes SIGINT and SIGTERM through a pipe to a watcher thread, since hooks may lock and allocate,
+/// which a signal handler must not.
+#[cfg(unix)]
+fn install_signal_handlers() -> Result<()> {
+	let mut fds = [0; 2];
+	if unsafe { libc::pipe(fds.as_mut_ptr()) } != 0 {
+		return Err(std::io::Error::last_os_error().into());
+	}
+	let [reader, writer] = fds;
+	SIGNAL_PIPE.store(writer, Ordering::SeqCst);
+
+	std::thread::Builder::new()
+		.name("shutdown-signals".into())
+		.spawn(move || {
+			let mut signal = 0u8;
+			while unsafe { libc::read(reader, (&mut signal as *mut u8).cast(), 1) } == 1 {
+				if !trigger_pending() {
+					// Nothing left to stop: let the signal end the process as it normally would.
+					unsafe {
+						libc::signal(signal.into(), libc::SIG_DFL);
+						libc::raise(signal.into());
+					}
+				}
+			}
+		})?;
+
+	for signal in [libc::SIGINT, libc::SIGTERM] {
+		let handler = signal_handler as extern "C" fn(libc::c_int);
+		if unsafe { libc::signal(signal, handler as libc::sighandler_t) } == libc::SIG_ERR {
+			return Err(std::io::Error::last_os_error().into());
+		}
+	}
+	Ok(())
+}
+
+#[cfg(unix)]
+extern "C" fn signal_handler(signal: libc::c_int) {
+	// write(2) is async-signal-safe; a full pipe just drops the repeated signal.
+	let byte = signal as u8;
+	unsafe {
+		libc::write(SIGNAL_PIPE.load(Ordering::SeqCst), (&byte as *const u8).cast(), 1);
+	}
+}
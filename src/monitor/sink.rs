@@ -0,0 +1,155 @@
+use std::time::{ Duration, Instant };
+
+use crate::types::speed::sample_durations;
+use crate::types::{ time_weighted_average, ByteRate, NetworkSpeed, Result };
+
+/// Destination for measured samples (a history file, an exporter, a channel, ...).
+pub trait SpeedSink {
+	fn accept(&mut self, speed: &NetworkSpeed) -> Result<()>;
+
+	/// Pushes out anything buffered. Called on shutdown or whenever the caller wants durability.
+	fn flush(&mut self) -> Result<()> {
+		Ok(())
+	}
+}
+
+impl<F> SpeedSink for F where F: FnMut(&NetworkSpeed) -> Result<()> {
+	fn accept(&mut self, speed: &NetworkSpeed) -> Result<()> {
+		self(speed)
+	}
+}
+
+#[cfg(feature = "persist")]
+impl SpeedSink for crate::monitor::HistoryWriter {
+	fn accept(&mut self, speed: &NetworkSpeed) -> Result<()> {
+		self.push(speed)
+	}
+
+	fn flush(&mut self) -> Result<()> {
+		crate::monitor::HistoryWriter::flush(self)
+	}
+}
+
+/// How [`RateLimitedSink`] condenses the samples of one window into a single sample.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregation {
+	/// Time-weighted average rate (see [`time_weighted_average`]).
+	Average,
+	/// Highest upload and highest download rate, taken independently.
+	Max,
+	/// Bytes transferred during the window. The emitted sample's "rates" hold byte totals, which suits
+	/// counter-style series in time-series databases.
+	Sum,
+}
+
+impl Aggregation {
+	/// Condenses `samples` (oldest first); the result carries the newest sample's timestamp.
+	pub fn apply(&self, samples: &[NetworkSpeed]) -> Option<NetworkSpeed> {
+		self.apply_after(None, samples)
+	}
+
+	/// Like [`apply`](Self::apply), where `previous` is the timestamp of the sample just before
+	/// `samples` (e.g. the last one of the preceding window). [`Sum`](Self::Sum) then weights the
+	/// first sample by the gap since `previous` instead of borrowing the next gap, so a window with a
+	/// single sample still counts its bytes.
+	pub fn apply_after(&self, previous: Option<Instant>, samples: &[NetworkSpeed]) -> Option<NetworkSpeed> {
+		let last = samples.last()?;
+		let aggregated = match self {
+			Aggregation::Average => time_weighted_average(samples)?,
+			Aggregation::Max => {
+				let upload = samples.iter().map(|speed| speed.upload_bytes_per_sec).max()?;
+				let download = samples.iter().map(|speed| speed.download_bytes_per_sec).max()?;
+				NetworkSpeed::new(upload, download)
+			}
+			Aggregation::Sum => {
+				let refs: Vec<&NetworkSpeed> = samples.iter().collect();
+				let mut durations = sample_durations(&refs);
+				if let Some(previous) = previous {
+					durations[0] = samples[0].timestamp.saturating_duration_since(previous).as_secs_f64();
+				}
+				let total = |rate: fn(&NetworkSpeed) -> ByteRate| {
+					let bytes: f64 = samples
+						.iter()
+						.zip(&durations)
+						.map(|(speed, seconds)| (rate(speed).bytes_per_sec() as f64) * seconds)
+						.sum();
+					bytes as u64
+				};
+				NetworkSpeed::new(total(|s| s.upload_bytes_per_sec), total(|s| s.download_bytes_per_sec))
+			}
+		};
+
+		Some(NetworkSpeed {
+			timestamp: last.timestamp,
+			..aggregated
+		})
+	}
+}
+
+/// Forwards at most one aggregated sample per `interval` to the wrapped sink, so a slow exporter
+/// can receive e.g. 10 s aggregates while the monitor samples locally every 250 ms.
+///
+/// Windows are measured on sample timestamps: a window closes with the first sample at least
+/// `interval` after the window's first sample. [`flush`](SpeedSink::flush) emits a partial window.
+/// Windows after the first are aggregated as continuing from the previous window's last sample.
+pub struct RateLimitedSink<S> {
+	inner: S,
+	interval: Duration,
+	aggregation: Aggregation,
+	pending: Vec<NetworkSpeed>,
+	last_emitted: Option<Instant>,
+}
+
+impl<S: SpeedSink> RateLimitedSink<S> {
+	pub fn new(inner: S, interval: Duration, aggregation: Aggregation) -> Self {
+		Self {
+			inner,
+			interval,
+			aggregation,
+			pending: Vec::new(),
+			last_emitted: None,
+		}
+	}
+
+	pub fn inner(&self) -> &S {
+		&self.inner
+	}
+
+	pub fn into_inner(self) -> S {
+		self.inner
+	}
+
+	/// Samples buffered for the current window.
+	pub fn pending(&self) -> usize {
+		self.pending.len()
+	}
+
+	fn emit(&mut self) -> Result<()> {
+		let aggregated = self.aggregation.apply_after(self.last_emitted, &self.pending);
+		if let Some(last) = self.pending.last() {
+			self.last_emitted = Some(last.timestamp);
+		}
+		self.pending.clear();
+		match aggregated {
+			Some(speed) => self.inner.accept(&speed),
+			None => Ok(()),
+		}
+	}
+}
+
+impl<S: SpeedSink> SpeedSink for RateLimitedSink<S> {
+	fn accept(&mut self, speed: &NetworkSpeed) -> Result<()> {
+		self.pending.push(speed.clone());
+
+		let window_start = self.pending[0].timestamp;
+		if speed.timestamp.saturating_duration_since(window_start) >= self.interval {
+			self.emit()?;
+		}
+		Ok(())
+	}
+
+	fn flush(&mut self) -> Result<()> {
+		self.emit()?;
+		self.inner.flush()
+	}
+}
@@ -0,0 +1,143 @@
+use std::time::{ Duration, Instant };
+
+#[cfg(feature = "serde")]
+use serde::{ Deserialize, Serialize };
+
+use crate::types::{ ByteRate, InterfaceKind };
+
+/// Cumulative counters of one interface inside a [`CountersSnapshot`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CounterEntry {
+	pub index: u32,
+	pub interface_type: InterfaceKind,
+	pub bytes_sent: u64,
+	pub bytes_received: u64,
+}
+
+/// Counter change of one interface between two snapshots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CounterDelta {
+	pub index: u32,
+	pub interface_type: InterfaceKind,
+	pub elapsed: Duration,
+	pub bytes_sent: u64,
+	pub bytes_received: u64,
+}
+
+impl CounterDelta {
+	pub fn upload_rate(&self) -> ByteRate {
+		rate(self.bytes_sent, self.elapsed)
+	}
+
+	pub fn download_rate(&self) -> ByteRate {
+		rate(self.bytes_received, self.elapsed)
+	}
+}
+
+/// Raw per-interface counters of the selected interfaces at one instant.
+///
+/// Refill an existing snapshot with `NetworkMonitor::snapshot_into` to reuse its buffer; after
+/// the first few calls no allocation happens per reading. Entries are sorted by interface index.
+#[derive(Debug, Clone)]
+pub struct CountersSnapshot {
+	entries: Vec<CounterEntry>,
+	taken_at: Instant,
+}
+
+impl CountersSnapshot {
+	pub fn new() -> Self {
+		Self {
+			entries: Vec::new(),
+			taken_at: Instant::now(),
+		}
+	}
+
+	/// Builds a snapshot from entries read elsewhere (e.g. replayed from a log).
+	pub fn from_entries(entries: impl IntoIterator<Item = CounterEntry>, taken_at: Instant) -> Self {
+		let mut snapshot = Self {
+			entries: entries.into_iter().collect(),
+			taken_at,
+		};
+		snapshot.finish(taken_at);
+		snapshot
+	}
+
+	pub fn taken_at(&self) -> Instant {
+		self.taken_at
+	}
+
+	pub fn len(&self) -> usize {
+		self.entries.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.entries.is_empty()
+	}
+
+	pub fn iter(&self) -> impl Iterator<Item = &CounterEntry> {
+		self.entries.iter()
+	}
+
+	pub fn get(&self, index: u32) -> Option<&CounterEntry> {
+		self.entries
+			.binary_search_by_key(&index, |entry| entry.index)
+			.ok()
+			.map(|position| &self.entries[position])
+	}
+
+	/// Summed `(sent, received)` bytes across all entries.
+	pub fn total(&self) -> (u64, u64) {
+		self.entries.iter().fold((0u64, 0u64), |(sent, received), entry| {
+			(sent.saturating_add(entry.bytes_sent), received.saturating_add(entry.bytes_received))
+		})
+	}
+
+	/// Per-interface changes since `earlier`, for interfaces present in both snapshots. Counters
+	/// that went backwards (adapter reset) report zero. Does not allocate.
+	pub fn diff<'a>(&'a self, earlier: &'a CountersSnapshot) -> impl Iterator<Item = CounterDelta> + 'a {
+		let elapsed = self.taken_at.saturating_duration_since(earlier.taken_at);
+
+		self.entries.iter().filter_map(move |entry| {
+			let previous = earlier.get(entry.index)?;
+			Some(CounterDelta {
+				index: entry.index,
+				interface_type: entry.interface_type,
+				elapsed,
+				bytes_sent: entry.bytes_sent.saturating_sub(previous.bytes_sent),
+				bytes_received: entry.bytes_received.saturating_sub(previous.bytes_received),
+			})
+		})
+	}
+
+	pub(crate) fn clear(&mut self) {
+		self.entries.clear();
+	}
+
+	pub(crate) fn push(&mut self, entry: CounterEntry) {
+		self.entries.push(entry);
+	}
+
+	pub(crate) fn retain(&mut self, keep: impl FnMut(&CounterEntry) -> bool) {
+		self.entries.retain(keep);
+	}
+
+	pub(crate) fn finish(&mut self, taken_at: Instant) {
+		self.entries.sort_unstable_by_key(|entry| entry.index);
+		self.taken_at = taken_at;
+	}
+}
+
+impl Default for CountersSnapshot {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+fn rate(bytes: u64, elapsed: Duration) -> ByteRate {
+	let seconds = elapsed.as_secs_f64();
+	if seconds <= 0.0 {
+		return ByteRate::ZERO;
+	}
+	ByteRate::from_bytes_per_sec(((bytes as f64) / seconds) as u64)
+}
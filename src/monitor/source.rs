@@ -0,0 +1,98 @@
+use super::breakdown::{ list_interface_counters, CastCounters, InterfaceCounters };
+use super::interface::{ interfaces_iter, CounterProvider, NetworkInterface };
+use super::stack::{ interface_stack, InterfaceStack };
+use crate::types::Result;
+
+/// Where an [`InterfaceManager`](super::InterfaceManager) reads interface counters from.
+///
+/// Without a source the manager reads the OS directly (`GetIfTable2`, `/proc/net/dev`, or
+/// `NET_RT_IFLIST2`). Implement this to feed counters from somewhere else, such as SNMP, a remote
+/// agent, or a test fixture, and pass it to [`NetworkMonitor::with_source`](super::NetworkMonitor::with_source).
+/// Filtering, tunnel and stack accounting, and rate calculation work the same for every source.
+pub trait CounterSource: Send {
+	/// Every interface with its cumulative byte counters, before any filtering. A row that could
+	/// not be converted is an `Err` entry and is handled per
+	/// [`RowErrorPolicy`](crate::RowErrorPolicy).
+	fn read_interfaces(&mut self) -> Result<Vec<Result<NetworkInterface>>>;
+
+	/// Reported by `provider_info` after each read.
+	fn provider(&self) -> CounterProvider;
+
+	/// Upper/lower layer links for [`StackAccounting`](crate::StackAccounting); none by default.
+	fn interface_stack(&mut self) -> Result<InterfaceStack> {
+		Ok(InterfaceStack::default())
+	}
+
+	/// Per-cast counters for `measure_breakdown`. By default every byte from
+	/// [`read_interfaces`](Self::read_interfaces) counts as unicast, without packet counts, and
+	/// rows that failed to convert are left out.
+	fn interface_counters(&mut self) -> Result<Vec<InterfaceCounters>> {
+		Ok(
+			self
+				.read_interfaces()?
+				.into_iter()
+				.flatten()
+				.map(|interface| InterfaceCounters {
+					sent: CastCounters {
+						unicast_bytes: interface.bytes_sent,
+						..Default::default()
+					},
+					received: CastCounters {
+						unicast_bytes: interface.bytes_received,
+						..Default::default()
+					},
+					outbound_discards: 0,
+					output_queue_length: 0,
+					interface,
+				})
+				.collect()
+		)
+	}
+}
+
+/// The OS counters a manager reads by default, as a [`CounterSource`], for sources that wrap or
+/// fall back to them. Reading through it skips the per-row fast path the manager uses for
+/// `GetIfTable2` snapshots, so prefer no source at all when nothing is added.
+#[derive(Debug, Clone, Copy)]
+pub struct SystemCounterSource {
+	provider: CounterProvider,
+}
+
+impl SystemCounterSource {
+	pub fn new() -> Self {
+		Self::default()
+	}
+}
+
+impl Default for SystemCounterSource {
+	fn default() -> Self {
+		Self {
+			#[cfg(windows)]
+			provider: CounterProvider::IfTable2,
+			#[cfg(target_os = "linux")]
+			provider: CounterProvider::ProcNetDev,
+			#[cfg(target_os = "macos")]
+			provider: CounterProvider::SysctlIfList2,
+		}
+	}
+}
+
+impl CounterSource for SystemCounterSource {
+	fn read_interfaces(&mut self) -> Result<Vec<Result<NetworkInterface>>> {
+		let iter = interfaces_iter()?;
+		self.provider = iter.provider();
+		Ok(iter.collect())
+	}
+
+	fn provider(&self) -> CounterProvider {
+		self.provider
+	}
+
+	fn interface_stack(&mut self) -> Result<InterfaceStack> {
+		interface_stack()
+	}
+
+	fn interface_counters(&mut self) -> Result<Vec<InterfaceCounters>> {
+		list_interface_counters()
+	}
+}
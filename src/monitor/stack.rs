@@ -0,0 +1,121 @@
+use std::collections::HashSet;
+
+#[cfg(windows)]
+use windows::Win32::NetworkManagement::IpHelper::{ FreeMibTable, GetIfStackTable, MIB_IFSTACK_TABLE };
+
+#[cfg(windows)]
+use crate::types::NetworkError;
+use crate::types::{ Result, StackAccounting };
+
+/// One edge of the interface stack: `upper` runs on top of `lower`, e.g. a VLAN sub-interface on
+/// its physical NIC, or a NIC team on one of its member adapters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StackLink {
+	pub upper: u32,
+	pub lower: u32,
+}
+
+/// Parent/child relationships between interfaces, from `GetIfStackTable` (the `lower_*` links in
+/// `/sys/class/net` on Linux; always empty on macOS). Parents are the upper
+/// layers (teams, VLANs, LBFO multiplexors) and children the adapters beneath them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InterfaceStack {
+	links: Vec<StackLink>,
+}
+
+impl InterfaceStack {
+	/// Builds a stack from `(upper, lower)` links. Entries with a zero index, which Windows uses
+	/// for the top and bottom of each stack, are dropped.
+	pub fn from_links(links: impl IntoIterator<Item = StackLink>) -> Self {
+		let mut links: Vec<StackLink> = links
+			.into_iter()
+			.filter(|link| link.upper != 0 && link.lower != 0 && link.upper != link.lower)
+			.collect();
+		links.sort_unstable_by_key(|link| (link.upper, link.lower));
+		links.dedup();
+		Self { links }
+	}
+
+	pub fn links(&self) -> &[StackLink] {
+		&self.links
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.links.is_empty()
+	}
+
+	/// Interfaces directly above `index` (the team or VLAN parent).
+	pub fn parents(&self, index: u32) -> impl Iterator<Item = u32> + '_ {
+		self.links
+			.iter()
+			.filter(move |link| link.lower == index)
+			.map(|link| link.upper)
+	}
+
+	/// Interfaces directly below `index` (team members, or the NIC under a VLAN).
+	pub fn children(&self, index: u32) -> impl Iterator<Item = u32> + '_ {
+		self.links
+			.iter()
+			.filter(move |link| link.upper == index)
+			.map(|link| link.lower)
+	}
+
+	/// Indices in `selected` that `accounting` leaves out because a selected interface above
+	/// ([`StackAccounting::PreferUpper`]) or below ([`StackAccounting::PreferLower`]) already
+	/// carries their traffic. Relationships are followed through intermediate layers.
+	pub fn shadowed(&self, selected: &[u32], accounting: StackAccounting) -> Vec<u32> {
+		if accounting == StackAccounting::CountAll || self.links.is_empty() {
+			return Vec::new();
+		}
+
+		let selected_set: HashSet<u32> = selected.iter().copied().collect();
+		selected
+			.iter()
+			.copied()
+			.filter(|&index| {
+				let mut visited = HashSet::new();
+				let mut pending = vec![index];
+				while let Some(current) = pending.pop() {
+					let next: Vec<u32> = match accounting {
+						StackAccounting::PreferUpper => self.parents(current).collect(),
+						_ => self.children(current).collect(),
+					};
+					for neighbor in next {
+						if selected_set.contains(&neighbor) {
+							return true;
+						}
+						if visited.insert(neighbor) {
+							pending.push(neighbor);
+						}
+					}
+				}
+				false
+			})
+			.collect()
+	}
+}
+
+/// Reads the interface stack table.
+pub fn interface_stack() -> Result<InterfaceStack> {
+	#[cfg(target_os = "linux")]
+	return super::linux::interface_stack();
+	#[cfg(target_os = "macos")]
+	return super::macos::interface_stack();
+	#[cfg(windows)]
+	unsafe {
+		let mut table_ptr: *mut MIB_IFSTACK_TABLE = std::ptr::null_mut();
+		GetIfStackTable(&mut table_ptr).map_err(NetworkError::WindowsApi)?;
+
+		let table = &*table_ptr;
+		let rows = std::slice::from_raw_parts(table.Table.as_ptr(), table.NumEntries as usize);
+		let stack = InterfaceStack::from_links(
+			rows.iter().map(|row| StackLink {
+				upper: row.HigherLayerInterfaceIndex,
+				lower: row.LowerLayerInterfaceIndex,
+			})
+		);
+
+		FreeMibTable(table_ptr as _).map_err(NetworkError::WindowsApi)?;
+		Ok(stack)
+	}
+}
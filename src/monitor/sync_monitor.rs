@@ -1,13 +1,68 @@
-use std::collections::VecDeque;
-use std::time::{ Duration, Instant };
-
-use crate::monitor::InterfaceManager;
-use crate::types::{ InterfaceStats, NetworkError, NetworkMonitorConfig, NetworkSpeed, PrecisionMode, Result };
+use std::collections::{ BTreeMap, VecDeque };
+#[cfg(any(feature = "arrow", feature = "persist"))]
+use std::path::Path;
+use std::time::{ Duration, Instant, SystemTime, UNIX_EPOCH };
+
+#[cfg(feature = "arrow")]
+use arrow_array::RecordBatch;
+
+#[cfg(feature = "arrow")]
+use crate::monitor::{ labeled_history_to_record_batch, write_labeled_history_parquet_file };
+#[cfg(feature = "charts")]
+use crate::monitor::{ render_chart, ChartOptions };
+#[cfg(feature = "persist")]
+use crate::monitor::{ append_labeled_history, HistoryCompression };
+use crate::monitor::interface::{ current_thread_cpu_time, system_uptime_ms };
+use crate::monitor::breakdown::{ BreakdownState, PacketState };
+use crate::monitor::utilization::UtilizationHistory;
+use crate::monitor::{
+	forecast_history,
+	traffic_by_category,
+	CachedInterface,
+	Composition,
+	CounterSource,
+	CountersSnapshot,
+	FilterDecision,
+	Forecast,
+	InterfaceBreakdown,
+	InterfaceCategory,
+	InterfaceManager,
+	NetworkInterface,
+	ProviderInfo,
+	SkippedRow,
+	UtilizationSample,
+};
+use crate::types::{
+	compute_precise_rate,
+	render_sparkline,
+	FirstSample,
+	InterfaceStats,
+	MonitorCheckpoint,
+	MonitorLabels,
+	MonitorStats,
+	NetworkError,
+	NetworkMonitorConfig,
+	NetworkSpeed,
+	NetworkStatsDelta,
+	OverheadStats,
+	PrecisionMode,
+	PreciseSpeed,
+	RateOptions,
+	Result,
+	SpeedSample,
+	SpeedSummary,
+	summarize,
+	time_weighted_average,
+};
 
 pub struct NetworkMonitor {
 	config: NetworkMonitorConfig,
 	interface_manager: InterfaceManager,
 	previous_stats: Option<InterfaceStats>,
+	overhead: OverheadStats,
+	stats: MonitorStats,
+	breakdown: Option<BreakdownState>,
+	packets: Option<PacketState>,
 }
 
 impl NetworkMonitor {
@@ -16,25 +71,171 @@ impl NetworkMonitor {
 	}
 
 	pub fn with_config(config: NetworkMonitorConfig) -> Self {
-		let interface_manager = InterfaceManager::new(config.clone());
+		Self::with_interface_manager(config.clone(), InterfaceManager::new(config))
+	}
 
+	/// Measures with counters from `source` instead of the OS, e.g. a remote agent or a test
+	/// fixture; see [`CounterSource`].
+	pub fn with_source(config: NetworkMonitorConfig, source: impl CounterSource + 'static) -> Self {
+		Self::with_interface_manager(config.clone(), InterfaceManager::with_source(config, source))
+	}
+
+	fn with_interface_manager(config: NetworkMonitorConfig, interface_manager: InterfaceManager) -> Self {
 		Self {
 			config,
 			interface_manager,
 			previous_stats: None,
+			overhead: OverheadStats::default(),
+			stats: MonitorStats::default(),
+			breakdown: None,
+			packets: None,
 		}
 	}
 
 	pub fn measure_speed(&mut self) -> Result<NetworkSpeed> {
-		match &self.config.precision {
+		self.measure_precise_speed().map(NetworkSpeed::from)
+	}
+
+	/// Same measurement as `measure_speed`, keeping fractional bytes/sec.
+	pub fn measure_precise_speed(&mut self) -> Result<PreciseSpeed> {
+		self.check_overhead_budget()?;
+		let before = self.overhead;
+
+		let result = match self.config.precision.clone() {
 			PrecisionMode::Instant => self.measure_instant(),
-			PrecisionMode::Windowed { duration } => self.measure_windowed(*duration),
-			PrecisionMode::Samples { samples, interval } => { self.measure_samples(samples.get(), *interval) }
-		}
+			PrecisionMode::Windowed { duration } => self.measure_windowed(duration),
+			PrecisionMode::Samples { samples, interval } => { self.measure_samples(samples.get(), interval) }
+		};
+
+		self.overhead.finish_call(&before);
+		self.stats.record_result(&result);
+		result
 	}
 
 	pub fn measure_speed_blocking(&mut self, measurement_duration: Duration) -> Result<NetworkSpeed> {
-		self.measure_windowed(measurement_duration)
+		self.check_overhead_budget()?;
+		let before = self.overhead;
+		let result = self.measure_windowed(measurement_duration);
+		self.overhead.finish_call(&before);
+		self.stats.record_result(&result);
+		result.map(NetworkSpeed::from)
+	}
+
+	/// Time and CPU the monitor has spent querying interface counters since creation.
+	pub fn overhead_stats(&self) -> OverheadStats {
+		self.overhead
+	}
+
+	pub fn reset_overhead_stats(&mut self) {
+		self.overhead = OverheadStats::default();
+	}
+
+	/// Samples taken, failures by kind, and enumeration latency since the monitor was created
+	/// or [`reset_stats`](Self::reset_stats); render with [`MonitorStats::to_prometheus`].
+	pub fn stats(&self) -> MonitorStats {
+		self.stats.clone()
+	}
+
+	pub fn reset_stats(&mut self) {
+		self.stats = MonitorStats::default();
+	}
+
+	/// The interface cache as of the latest enumeration, including interfaces that are missing but
+	/// still within `interface_retention`.
+	pub fn cached_interfaces(&self) -> Vec<CachedInterface> {
+		self.interface_manager.cache_entries()
+	}
+
+	/// Sorted indices of the interfaces that made up the latest measurement. Borrowed, so checking
+	/// it in a hot loop costs nothing; compare [`composition_version`](Self::composition_version)
+	/// to notice changes.
+	pub fn contributing_interfaces(&self) -> &[u32] {
+		self.interface_manager.contributing_interfaces()
+	}
+
+	/// Changes whenever the set of contributing interfaces does.
+	pub fn composition_version(&self) -> u64 {
+		self.interface_manager.composition().version
+	}
+
+	pub fn composition(&self) -> Composition {
+		self.interface_manager.composition().clone()
+	}
+
+	/// Every interface with the reason the filters keep or drop it.
+	pub fn explain_interfaces(&mut self) -> Result<Vec<FilterDecision>> {
+		self.interface_manager.explain_interfaces()
+	}
+
+	/// Which Windows API the counters come from. `None` until the first measurement. When
+	/// [`ProviderInfo::is_legacy`] is set, counters are 32-bit and the physical medium and GUID
+	/// are unavailable.
+	pub fn provider_info(&self) -> Option<ProviderInfo> {
+		self.interface_manager.provider_info()
+	}
+
+	/// [`measure_speed`](Self::measure_speed) together with the interfaces it was computed from.
+	pub fn measure_speed_with_composition(&mut self) -> Result<(NetworkSpeed, Composition)> {
+		let speed = self.measure_speed()?;
+		Ok((speed, self.composition()))
+	}
+
+	/// Like [`measure_speed`](Self::measure_speed), also returning the rows the read skipped under
+	/// [`RowErrorPolicy::Skip`](crate::RowErrorPolicy::Skip); the speed then covers only the
+	/// healthy interfaces.
+	pub fn measure_speed_with_warnings(&mut self) -> Result<(NetworkSpeed, Vec<SkippedRow>)> {
+		let speed = self.measure_speed()?;
+		Ok((speed, self.skipped_rows().to_vec()))
+	}
+
+	/// Rows skipped by the latest counter read; see [`measure_speed_with_warnings`](Self::measure_speed_with_warnings).
+	pub fn skipped_rows(&self) -> &[SkippedRow] {
+		self.interface_manager.skipped_rows()
+	}
+
+	/// Raw counters of the selected interfaces, for computing custom rates or groupings.
+	pub fn snapshot(&mut self) -> Result<CountersSnapshot> {
+		let mut snapshot = CountersSnapshot::new();
+		self.snapshot_into(&mut snapshot)?;
+		Ok(snapshot)
+	}
+
+	/// Like [`snapshot`](Self::snapshot), refilling `snapshot` in place to reuse its buffer.
+	pub fn snapshot_into(&mut self, snapshot: &mut CountersSnapshot) -> Result<()> {
+		let started = Instant::now();
+		let cpu_before = current_thread_cpu_time();
+		let result = self.interface_manager.fill_snapshot(snapshot);
+		let elapsed = started.elapsed();
+		self.overhead.record_query(elapsed, current_thread_cpu_time().saturating_sub(cpu_before));
+		self.stats.enumeration_latency.record(elapsed);
+		result
+	}
+
+	/// Suggested polling interval for this monitor, based on counter refresh granularity, the
+	/// precision mode, and the cost of the calls made so far. Re-check it after a few measurements.
+	pub fn recommended_interval(&self) -> Duration {
+		self.config.recommended_interval(&self.overhead)
+	}
+
+	/// Measures like `measure_speed`, reporting the first instant-mode reading according to
+	/// [`NetworkMonitorConfig::first_sample`].
+	pub fn sample(&mut self) -> Result<Option<SpeedSample>> {
+		let establishing_baseline =
+			self.previous_stats.is_none() && matches!(self.config.precision, PrecisionMode::Instant);
+
+		match self.measure_speed() {
+			Ok(speed) => Ok(Some(SpeedSample::Measured(speed))),
+			Err(err @ NetworkError::InsufficientTimeElapsed { .. }) if establishing_baseline => {
+				match (self.config.first_sample, self.previous_stats.as_ref()) {
+					(FirstSample::Baseline, Some(baseline)) => {
+						Ok(Some(SpeedSample::Baseline { timestamp: baseline.last_update }))
+					}
+					(FirstSample::Skip, Some(_)) => Ok(None),
+					_ => Err(err),
+				}
+			}
+			Err(e) => Err(e),
+		}
 	}
 
 	pub fn get_instantaneous_speed(&mut self) -> Result<Option<NetworkSpeed>> {
@@ -51,12 +252,101 @@ impl NetworkMonitor {
 
 	pub fn reset(&mut self) {
 		self.previous_stats = None;
+		self.breakdown = None;
+		self.packets = None;
+	}
+
+	/// Captures the current counter baseline, or `None` before the first measurement.
+	pub fn checkpoint(&self) -> Option<MonitorCheckpoint> {
+		let previous = self.previous_stats.as_ref()?;
+		let age_ms = previous.last_update.elapsed().as_millis() as u64;
+
+		Some(MonitorCheckpoint {
+			bytes_sent: previous.bytes_sent,
+			bytes_received: previous.bytes_received,
+			uptime_ms: system_uptime_ms().saturating_sub(age_ms),
+			unix_time_ms: unix_time_ms().saturating_sub(age_ms),
+		})
+	}
+
+	/// Restores a baseline saved by [`checkpoint`](Self::checkpoint) so the next measurement yields a
+	/// real rate. Returns `false` and leaves the monitor untouched if the system rebooted since.
+	pub fn restore_checkpoint(&mut self, checkpoint: &MonitorCheckpoint) -> bool {
+		let Some(age) = checkpoint.age_at(system_uptime_ms(), unix_time_ms()) else {
+			return false;
+		};
+		let now = Instant::now();
+		let Some(last_update) = now.checked_sub(age) else {
+			return false;
+		};
+
+		self.previous_stats = Some(InterfaceStats {
+			bytes_sent: checkpoint.bytes_sent,
+			bytes_received: checkpoint.bytes_received,
+			last_update,
+			captured_at: SystemTime::now().checked_sub(age).unwrap_or(UNIX_EPOCH),
+		});
+		true
 	}
 
 	pub fn refresh_interfaces(&mut self) -> Result<()> {
 		self.interface_manager.refresh_cache()
 	}
 
+	/// Cumulative `(sent, received)` bytes since boot across the interfaces selected by the config.
+	pub fn total_traffic(&mut self) -> Result<(u64, u64)> {
+		self.interface_manager.get_total_traffic()
+	}
+
+	/// The interfaces selected by the config, each carrying its own cumulative byte counters.
+	pub fn interface_traffic(&mut self) -> Result<Vec<NetworkInterface>> {
+		self.interface_manager.get_active_interfaces()
+	}
+
+	/// Cumulative `(sent, received)` bytes of the selected interfaces, grouped by category.
+	pub fn traffic_by_category(&mut self) -> Result<BTreeMap<InterfaceCategory, (u64, u64)>> {
+		Ok(traffic_by_category(&self.interface_traffic()?))
+	}
+
+	/// Unicast, multicast, and broadcast deltas per selected interface since the previous call.
+	///
+	/// This is an opt-in extended mode with its own baseline, independent of `measure_speed`; the
+	/// first call only records that baseline and returns `InsufficientTimeElapsed`.
+	pub fn measure_breakdown(&mut self) -> Result<Vec<InterfaceBreakdown>> {
+		let counters = self.interface_manager.get_active_interface_counters()?;
+
+		match self.breakdown.as_mut() {
+			Some(state) => Ok(state.advance(counters)),
+			None => {
+				self.breakdown = Some(BreakdownState::new(counters));
+				Err(NetworkError::InsufficientTimeElapsed {
+					min_ms: self.config.min_measurement_interval.as_millis() as u64,
+					actual_ms: 0,
+				})
+			}
+		}
+	}
+
+	/// Packets, errors, and discards across the selected interfaces since the previous call, for
+	/// packets-per-second and error rates next to `measure_speed`.
+	///
+	/// Like [`measure_breakdown`](Self::measure_breakdown) it keeps its own baseline; the first call
+	/// only records it and returns `InsufficientTimeElapsed`.
+	pub fn measure_stats_delta(&mut self) -> Result<NetworkStatsDelta> {
+		let interfaces = self.interface_manager.get_active_interfaces()?;
+
+		match self.packets.as_mut() {
+			Some(state) => Ok(state.advance(&interfaces)),
+			None => {
+				self.packets = Some(PacketState::new(&interfaces));
+				Err(NetworkError::InsufficientTimeElapsed {
+					min_ms: self.config.min_measurement_interval.as_millis() as u64,
+					actual_ms: 0,
+				})
+			}
+		}
+	}
+
 	pub fn get_config(&self) -> &NetworkMonitorConfig {
 		&self.config
 	}
@@ -64,103 +354,137 @@ impl NetworkMonitor {
 	pub fn update_config(&mut self, config: NetworkMonitorConfig) -> Result<()> {
 		config.validate()?;
 		self.config = config.clone();
-		self.interface_manager = InterfaceManager::new(config);
+		self.interface_manager.reconfigure(config);
 		self.reset();
 		Ok(())
 	}
 
+	/// Per-interface counters from the latest measurement and when they were read.
+	pub(crate) fn last_snapshot(&self) -> Option<(Vec<NetworkInterface>, Instant)> {
+		let previous = self.previous_stats.as_ref()?;
+		Some((self.interface_manager.cached_interfaces(), previous.last_update))
+	}
+
+	/// Refuses the call when the previous one was too recent for the configured overhead budget.
+	fn check_overhead_budget(&mut self) -> Result<()> {
+		let (Some(budget), Some(last_call_at)) = (self.config.overhead_budget, self.overhead.last_call_at) else {
+			return Ok(());
+		};
+
+		let required = self.overhead.required_interval(budget);
+		let elapsed = last_call_at.elapsed();
+		if elapsed < required {
+			self.overhead.throttled_calls += 1;
+			return Err(NetworkError::InsufficientTimeElapsed {
+				min_ms: required.as_millis().min(u64::MAX as u128) as u64,
+				actual_ms: elapsed.as_millis() as u64,
+			});
+		}
+
+		Ok(())
+	}
+
 	fn get_current_stats(&mut self) -> Result<InterfaceStats> {
-		let (total_sent, total_received) = self.interface_manager.get_total_traffic()?;
+		let started = Instant::now();
+		let cpu_before = current_thread_cpu_time();
+		let traffic = self.interface_manager.get_total_traffic();
+		let elapsed = started.elapsed();
+		self.overhead.record_query(elapsed, current_thread_cpu_time().saturating_sub(cpu_before));
+		self.stats.enumeration_latency.record(elapsed);
+
+		let (total_sent, total_received) = traffic?;
 
 		Ok(InterfaceStats {
 			bytes_sent: total_sent,
 			bytes_received: total_received,
 			last_update: Instant::now(),
+			captured_at: SystemTime::now(),
 		})
 	}
 
-	fn measure_instant(&mut self) -> Result<NetworkSpeed> {
+	fn measure_instant(&mut self) -> Result<PreciseSpeed> {
 		let current_stats = self.get_current_stats()?;
-		let timestamp = current_stats.last_update;
 
 		let speed = if let Some(ref previous) = self.previous_stats {
-			self.calculate_speed(&current_stats, previous, timestamp)?
+			self.calculate_speed(&current_stats, previous)?
 		} else {
-			NetworkSpeed::new(0, 0)
+			self.previous_stats = Some(current_stats);
+			return match self.config.first_sample {
+				FirstSample::Zero => Ok(PreciseSpeed::zero()),
+				FirstSample::Skip | FirstSample::Baseline =>
+					Err(NetworkError::InsufficientTimeElapsed {
+						min_ms: self.config.min_measurement_interval.as_millis() as u64,
+						actual_ms: 0,
+					}),
+				FirstSample::Block => {
+					std::thread::sleep(self.config.min_measurement_interval);
+					self.measure_instant()
+				}
+			};
 		};
 
 		self.previous_stats = Some(current_stats);
 		Ok(speed)
 	}
 
-	fn measure_windowed(&mut self, duration: Duration) -> Result<NetworkSpeed> {
+	fn measure_windowed(&mut self, duration: Duration) -> Result<PreciseSpeed> {
 		let initial_stats = self.get_current_stats()?;
 		std::thread::sleep(duration);
 		let final_stats = self.get_current_stats()?;
-		let timestamp = final_stats.last_update;
-		let speed = self.calculate_speed(&final_stats, &initial_stats, timestamp)?;
+		let speed = self.calculate_speed(&final_stats, &initial_stats)?;
 		self.previous_stats = Some(final_stats);
 		Ok(speed)
 	}
 
-	fn measure_samples(&mut self, samples: u8, interval: Duration) -> Result<NetworkSpeed> {
-		let mut total_upload: u128 = 0;
-		let mut total_download: u128 = 0;
+	fn measure_samples(&mut self, samples: u8, interval: Duration) -> Result<PreciseSpeed> {
+		let mut total_upload = 0.0;
+		let mut total_download = 0.0;
+		let mut total_interval = Duration::ZERO;
+		let mut timestamp = Instant::now();
 
 		for _ in 0..samples {
 			let speed = self.measure_windowed(interval)?;
-			total_upload += speed.upload_bytes_per_sec as u128;
-			total_download += speed.download_bytes_per_sec as u128;
+			total_upload += speed.upload_bytes_per_sec;
+			total_download += speed.download_bytes_per_sec;
+			total_interval += speed.interval;
+			timestamp = speed.timestamp;
 		}
 
-		let count = samples as u128;
-		let avg_upload = (total_upload / count) as u64;
-		let avg_download = (total_download / count) as u64;
-
-		Ok(NetworkSpeed::new(avg_upload, avg_download))
+		let count = samples as f64;
+		Ok(PreciseSpeed {
+			upload_bytes_per_sec: total_upload / count,
+			download_bytes_per_sec: total_download / count,
+			interval: total_interval,
+			timestamp,
+		})
 	}
 
-	fn calculate_speed(
-		&self,
-		current: &InterfaceStats,
-		previous: &InterfaceStats,
-		timestamp: Instant
-	) -> Result<NetworkSpeed> {
-		let duration = timestamp.duration_since(previous.last_update);
-
-		if duration < self.config.min_measurement_interval {
-			return Err(NetworkError::InsufficientTimeElapsed {
-				min_ms: self.config.min_measurement_interval.as_millis() as u64,
-				actual_ms: duration.as_millis() as u64,
-			});
-		}
-
-		let seconds = duration.as_secs_f64();
-		if seconds <= 0.0 {
-			return Err(NetworkError::InsufficientTimeElapsed {
-				min_ms: self.config.min_measurement_interval.as_millis() as u64,
-				actual_ms: 0,
-			});
-		}
-
-		let upload_diff = current.bytes_sent.wrapping_sub(previous.bytes_sent);
-		let download_diff = current.bytes_received.wrapping_sub(previous.bytes_received);
-
-		if upload_diff > self.config.max_counter_wrap_threshold || download_diff > self.config.max_counter_wrap_threshold {
-			return Err(NetworkError::CalculationOverflow);
+	fn calculate_speed(&self, current: &InterfaceStats, previous: &InterfaceStats) -> Result<PreciseSpeed> {
+		let result = compute_precise_rate(current, previous, &RateOptions::from(&self.config));
+		#[cfg(feature = "tracing")]
+		if let Err(NetworkError::CalculationOverflow) = result {
+			tracing::warn!(
+				previous_sent = previous.bytes_sent,
+				previous_received = previous.bytes_received,
+				current_sent = current.bytes_sent,
+				current_received = current.bytes_received,
+				elapsed_ms = current.last_update.saturating_duration_since(previous.last_update).as_millis() as u64,
+				wrap_strategy = ?self.config.wrap_strategy,
+				interfaces = ?self.interface_manager.contributing_interfaces(),
+				"counter difference exceeds the wrap strategy limit, treating it as a counter reset"
+			);
 		}
-
-		let upload_speed = ((upload_diff as f64) / seconds) as u64;
-		let download_speed = ((download_diff as f64) / seconds) as u64;
-
-		Ok(NetworkSpeed {
-			upload_bytes_per_sec: upload_speed,
-			download_bytes_per_sec: download_speed,
-			timestamp,
-		})
+		result
 	}
 }
 
+fn unix_time_ms() -> u64 {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|elapsed| elapsed.as_millis() as u64)
+		.unwrap_or(0)
+}
+
 impl Default for NetworkMonitor {
 	fn default() -> Self {
 		Self::new()
@@ -170,7 +494,9 @@ impl Default for NetworkMonitor {
 pub struct NetworkSpeedTracker {
 	monitor: NetworkMonitor,
 	history: VecDeque<NetworkSpeed>,
+	utilization: UtilizationHistory,
 	max_history_size: usize,
+	labels: MonitorLabels,
 }
 
 impl NetworkSpeedTracker {
@@ -178,62 +504,108 @@ impl NetworkSpeedTracker {
 		Self {
 			monitor: NetworkMonitor::new(),
 			history: VecDeque::with_capacity(max_history_size),
+			utilization: UtilizationHistory::new(max_history_size),
 			max_history_size,
+			labels: MonitorLabels::default(),
 		}
 	}
 
 	pub fn with_config(config: NetworkMonitorConfig, max_history_size: usize) -> Self {
+		Self::with_monitor(NetworkMonitor::with_config(config), max_history_size)
+	}
+
+	/// Tracks an existing monitor, e.g. one reading a custom [`CounterSource`].
+	pub fn with_monitor(monitor: NetworkMonitor, max_history_size: usize) -> Self {
 		Self {
-			monitor: NetworkMonitor::with_config(config),
+			monitor,
 			history: VecDeque::with_capacity(max_history_size),
+			utilization: UtilizationHistory::new(max_history_size),
 			max_history_size,
+			labels: MonitorLabels::default(),
 		}
 	}
 
+	/// Names the tracker; the name is written into history files and Parquet exports.
+	pub fn with_name(mut self, name: impl Into<String>) -> Self {
+		self.labels.name = Some(name.into());
+		self
+	}
+
+	/// Adds a key/value label that is written into history files and Parquet exports.
+	pub fn with_label(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+		self.labels.labels.insert(key.into(), value.into());
+		self
+	}
+
+	pub fn labels(&self) -> &MonitorLabels {
+		&self.labels
+	}
+
 	pub fn track_speed(&mut self) -> Result<NetworkSpeed> {
 		let speed = self.monitor.measure_speed()?;
+		if let Some((interfaces, timestamp)) = self.monitor.last_snapshot() {
+			self.utilization.update(interfaces, timestamp);
+		}
+		self.record(speed.clone());
+		Ok(speed)
+	}
 
-		self.history.push_back(speed.clone());
+	/// Utilization samples for the interface with this GUID (see [`NetworkInterface::guid`]),
+	/// one per `track_speed` call after the first.
+	pub fn utilization_history(&self, guid: &str) -> Vec<UtilizationSample> {
+		self.utilization.history(guid)
+	}
+
+	/// Highest utilization percentage (either direction) seen on the interface within `duration`.
+	pub fn peak_utilization(&self, guid: &str, duration: Duration) -> Option<f64> {
+		self.utilization.peak(guid, duration)
+	}
+
+	/// GUIDs of interfaces that have utilization history.
+	pub fn utilization_interfaces(&self) -> Vec<String> {
+		self.utilization.interfaces()
+	}
+
+	/// Appends a sample measured elsewhere (e.g. received from a remote agent) to the history.
+	pub fn record(&mut self, speed: NetworkSpeed) {
+		self.history.push_back(speed);
 
 		if self.history.len() > self.max_history_size {
 			self.history.pop_front();
 		}
-
-		Ok(speed)
 	}
 
-	pub fn get_history(&self) -> Vec<NetworkSpeed> {
-		self.history.iter().cloned().collect()
+	pub fn overhead_stats(&self) -> OverheadStats {
+		self.monitor.overhead_stats()
 	}
 
-	pub fn get_average_speed(&self, duration: Duration) -> Option<NetworkSpeed> {
-		if self.history.is_empty() {
-			return None;
-		}
+	/// See [`NetworkMonitor::stats`]; export with `stats().to_prometheus(tracker.labels())`.
+	pub fn stats(&self) -> MonitorStats {
+		self.monitor.stats()
+	}
 
-		let cutoff_time = Instant::now() - duration;
-		let recent_speeds: Vec<_> = self.history
-			.iter()
-			.filter(|speed| speed.timestamp >= cutoff_time)
-			.collect();
+	pub fn recommended_interval(&self) -> Duration {
+		self.monitor.recommended_interval()
+	}
 
-		if recent_speeds.is_empty() {
-			return None;
-		}
+	pub fn total_traffic(&mut self) -> Result<(u64, u64)> {
+		self.monitor.total_traffic()
+	}
 
-		let avg_upload =
-			recent_speeds
-				.iter()
-				.map(|s| s.upload_bytes_per_sec)
-				.sum::<u64>() / (recent_speeds.len() as u64);
+	pub fn latest(&self) -> Option<&NetworkSpeed> {
+		self.history.back()
+	}
 
-		let avg_download =
-			recent_speeds
-				.iter()
-				.map(|s| s.download_bytes_per_sec)
-				.sum::<u64>() / (recent_speeds.len() as u64);
+	pub fn get_history(&self) -> Vec<NetworkSpeed> {
+		self.history.iter().cloned().collect()
+	}
 
-		Some(NetworkSpeed::new(avg_upload, avg_download))
+	/// Time-weighted average of the samples within `duration` (see [`time_weighted_average`]).
+	pub fn get_average_speed(&self, duration: Duration) -> Option<NetworkSpeed> {
+		let cutoff_time = Instant::now().checked_sub(duration);
+		time_weighted_average(
+			self.history.iter().filter(|speed| cutoff_time.is_none_or(|cutoff| speed.timestamp >= cutoff))
+		)
 	}
 
 	pub fn get_peak_speed(&self, duration: Duration) -> Option<NetworkSpeed> {
@@ -241,16 +613,60 @@ impl NetworkSpeedTracker {
 			return None;
 		}
 
-		let cutoff_time = Instant::now() - duration;
+		let cutoff_time = Instant::now().checked_sub(duration);
 		self.history
 			.iter()
-			.filter(|speed| speed.timestamp >= cutoff_time)
+			.filter(|speed| cutoff_time.is_none_or(|cutoff| speed.timestamp >= cutoff))
 			.max_by_key(|speed| speed.total_bytes_per_sec())
 			.cloned()
 	}
 
+	/// Average, peak, p95, and transferred bytes over the whole history; see [`summarize`].
+	pub fn summary(&self) -> Option<SpeedSummary> {
+		summarize(&self.history)
+	}
+
+	/// Projects the trend of the whole history `horizon` into the future; see [`forecast_history`].
+	pub fn forecast(&self, horizon: Duration) -> Option<Forecast> {
+		forecast_history(&self.get_history(), horizon)
+	}
+
+	pub fn sparkline(&self, duration: Duration, width: usize) -> String {
+		let cutoff_time = Instant::now().checked_sub(duration);
+		let values: Vec<u64> = self.history
+			.iter()
+			.filter(|speed| cutoff_time.is_none_or(|cutoff| speed.timestamp >= cutoff))
+			.map(|speed| speed.total_bytes_per_sec().bytes_per_sec())
+			.collect();
+
+		render_sparkline(&values, width)
+	}
+
+	#[cfg(feature = "charts")]
+	pub fn render_chart(&self, options: &ChartOptions) -> Result<Vec<u8>> {
+		render_chart(&self.get_history(), options)
+	}
+
+	#[cfg(feature = "arrow")]
+	pub fn history_record_batch(&self) -> Result<RecordBatch> {
+		labeled_history_to_record_batch(&self.get_history(), &self.labels)
+	}
+
+	#[cfg(feature = "arrow")]
+	pub fn write_parquet(&self, path: impl AsRef<Path>) -> Result<()> {
+		write_labeled_history_parquet_file(&self.get_history(), &self.labels, path)
+	}
+
+	/// Appends the current history to a JSON Lines file; see [`HistoryWriter`](crate::monitor::HistoryWriter)
+	/// for continuous capture.
+	#[cfg(feature = "persist")]
+	pub fn append_history_to(&self, path: impl AsRef<Path>, compression: HistoryCompression) -> Result<()> {
+		append_labeled_history(path, &self.get_history(), compression, &self.labels)
+	}
+
 	pub fn clear_history(&mut self) {
 		self.history.clear();
+		self.utilization.clear();
 	}
 
 	pub fn reset(&mut self) {
@@ -0,0 +1,262 @@
+use std::collections::{ BTreeMap, VecDeque };
+use std::fs::{ File, OpenOptions };
+use std::io::{ BufRead, BufReader, BufWriter, Read, Write };
+use std::path::Path;
+use std::time::{ Duration, Instant };
+
+use serde::{ Deserialize, Serialize };
+
+use crate::monitor::{
+	CounterEntry,
+	CounterProvider,
+	CounterSource,
+	CountersSnapshot,
+	InterfaceStack,
+	NetworkInterface,
+	OperStatus,
+};
+use crate::types::{ NetworkError, PacketCounters, Result };
+
+/// One recorded enumeration (a JSON line): raw counters and the time since recording started.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TraceRecord {
+	pub offset_ms: u64,
+	pub entries: Vec<CounterEntry>,
+	/// Interface descriptions by index, written by [`RecordingCounterSource`] so name filters
+	/// match on replay.
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	pub names: BTreeMap<u32, String>,
+}
+
+/// Records counter snapshots to a JSON Lines trace file, so a measurement problem seen on one
+/// machine can be replayed elsewhere with [`read_trace`].
+///
+/// Each record is written through immediately; the file is flushed on [`flush`](Self::flush) and on drop.
+pub struct TraceRecorder {
+	out: BufWriter<File>,
+	started: Option<Instant>,
+}
+
+impl TraceRecorder {
+	/// Creates (or truncates) the trace file at `path`.
+	pub fn create(path: impl AsRef<Path>) -> Result<Self> {
+		let file = OpenOptions::new()
+			.create(true)
+			.write(true)
+			.truncate(true)
+			.open(path)
+			.map_err(trace_error)?;
+
+		Ok(Self {
+			out: BufWriter::new(file),
+			started: None,
+		})
+	}
+
+	/// Appends `snapshot`; offsets are measured from the first recorded snapshot.
+	pub fn record(&mut self, snapshot: &CountersSnapshot) -> Result<()> {
+		self.write(snapshot.taken_at(), snapshot.iter().copied().collect(), BTreeMap::new())
+	}
+
+	fn write(&mut self, taken_at: Instant, entries: Vec<CounterEntry>, names: BTreeMap<u32, String>) -> Result<()> {
+		let started = *self.started.get_or_insert(taken_at);
+		let record = TraceRecord {
+			offset_ms: taken_at.saturating_duration_since(started).as_millis() as u64,
+			entries,
+			names,
+		};
+
+		serde_json::to_writer(&mut self.out, &record).map_err(trace_error)?;
+		self.out.write_all(b"\n").map_err(trace_error)
+	}
+
+	pub fn flush(&mut self) -> Result<()> {
+		self.out.flush().map_err(trace_error)
+	}
+}
+
+impl Drop for TraceRecorder {
+	fn drop(&mut self) {
+		let _ = self.flush();
+	}
+}
+
+/// Reads a trace written by [`TraceRecorder`] back into snapshots whose timestamps keep the
+/// recorded spacing; diff consecutive snapshots to reproduce the measured rates.
+pub fn read_trace(path: impl AsRef<Path>) -> Result<Vec<CountersSnapshot>> {
+	read_trace_from(File::open(path).map_err(trace_error)?)
+}
+
+/// [`read_trace`] for an already open file or an in-memory buffer.
+pub fn read_trace_from(reader: impl Read) -> Result<Vec<CountersSnapshot>> {
+	let base = Instant::now();
+
+	let mut snapshots = Vec::new();
+	for record in read_records(reader)? {
+		let taken_at = base
+			.checked_add(Duration::from_millis(record.offset_ms))
+			.ok_or_else(|| trace_error(format!("offset_ms {} is out of range", record.offset_ms)))?;
+		snapshots.push(CountersSnapshot::from_entries(record.entries, taken_at));
+	}
+
+	Ok(snapshots)
+}
+
+fn read_records(reader: impl Read) -> Result<Vec<TraceRecord>> {
+	let mut records = Vec::new();
+	for line in BufReader::new(reader).lines() {
+		let line = line.map_err(trace_error)?;
+		if line.trim().is_empty() {
+			continue;
+		}
+		records.push(serde_json::from_str(&line).map_err(trace_error)?);
+	}
+	Ok(records)
+}
+
+/// A [`CounterSource`] that passes reads through from `inner` and records each one to a trace,
+/// for replaying later with [`TraceCounterSource`]. Rows that failed to convert are not recorded.
+pub struct RecordingCounterSource<S> {
+	inner: S,
+	recorder: TraceRecorder,
+}
+
+impl<S: CounterSource> RecordingCounterSource<S> {
+	pub fn new(inner: S, recorder: TraceRecorder) -> Self {
+		Self { inner, recorder }
+	}
+
+	/// Records to a new (or truncated) trace file at `path`.
+	pub fn create(inner: S, path: impl AsRef<Path>) -> Result<Self> {
+		Ok(Self::new(inner, TraceRecorder::create(path)?))
+	}
+
+	pub fn inner(&self) -> &S {
+		&self.inner
+	}
+
+	pub fn flush(&mut self) -> Result<()> {
+		self.recorder.flush()
+	}
+}
+
+impl<S: CounterSource> CounterSource for RecordingCounterSource<S> {
+	fn read_interfaces(&mut self) -> Result<Vec<Result<NetworkInterface>>> {
+		let taken_at = Instant::now();
+		let interfaces = self.inner.read_interfaces()?;
+
+		let mut entries = Vec::new();
+		let mut names = BTreeMap::new();
+		for interface in interfaces.iter().flatten() {
+			entries.push(CounterEntry {
+				index: interface.index,
+				interface_type: interface.interface_type,
+				bytes_sent: interface.bytes_sent,
+				bytes_received: interface.bytes_received,
+			});
+			names.insert(interface.index, interface.description.clone());
+		}
+		self.recorder.write(taken_at, entries, names)?;
+
+		Ok(interfaces)
+	}
+
+	fn provider(&self) -> CounterProvider {
+		self.inner.provider()
+	}
+
+	fn interface_stack(&mut self) -> Result<InterfaceStack> {
+		self.inner.interface_stack()
+	}
+}
+
+/// A [`CounterSource`] that replays a trace, one record per read, so
+/// [`NetworkMonitor::with_source`](crate::NetworkMonitor::with_source) reproduces the captured rates.
+///
+/// Reads wait until the record's offset has passed since the first read, so polling at or faster
+/// than the capture's pace sees its original spacing. Once the trace runs out, the last counters
+/// repeat, as on an idle link. Interfaces are replayed as up, with the recorded descriptions (or
+/// `trace<index>` for traces written by [`TraceRecorder::record`]) and no link speed.
+pub struct TraceCounterSource {
+	records: VecDeque<TraceRecord>,
+	last: Option<TraceRecord>,
+	started: Option<Instant>,
+}
+
+impl TraceCounterSource {
+	pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+		Self::from_reader(File::open(path).map_err(trace_error)?)
+	}
+
+	/// [`open`](Self::open) for an already open file or an in-memory buffer.
+	pub fn from_reader(reader: impl Read) -> Result<Self> {
+		let records = read_records(reader)?;
+		if records.is_empty() {
+			return Err(trace_error("trace holds no records"));
+		}
+
+		Ok(Self {
+			records: records.into(),
+			last: None,
+			started: None,
+		})
+	}
+
+	/// Records not replayed yet.
+	pub fn remaining(&self) -> usize {
+		self.records.len()
+	}
+}
+
+impl CounterSource for TraceCounterSource {
+	fn read_interfaces(&mut self) -> Result<Vec<Result<NetworkInterface>>> {
+		if let Some(record) = self.records.pop_front() {
+			let started = *self.started.get_or_insert_with(Instant::now);
+			let due = started + Duration::from_millis(record.offset_ms);
+			let now = Instant::now();
+			if due > now {
+				std::thread::sleep(due - now);
+			}
+			self.last = Some(record);
+		}
+
+		let Some(record) = &self.last else {
+			return Ok(Vec::new());
+		};
+		Ok(
+			record.entries
+				.iter()
+				.map(|entry| {
+					Ok(NetworkInterface {
+						index: entry.index,
+						guid: String::new(),
+						interface_type: entry.interface_type,
+						physical_medium: 0,
+						description: record.names
+							.get(&entry.index)
+							.cloned()
+							.unwrap_or_else(|| format!("trace{}", entry.index)),
+						driver_description: String::new(),
+						is_operational: true,
+						oper_status: OperStatus::Up,
+						bytes_sent: entry.bytes_sent,
+						bytes_received: entry.bytes_received,
+						packets: PacketCounters::default(),
+						speed: 0,
+					})
+				})
+				.collect()
+		)
+	}
+
+	fn provider(&self) -> CounterProvider {
+		CounterProvider::Custom("trace")
+	}
+}
+
+fn trace_error(err: impl std::fmt::Display) -> NetworkError {
+	NetworkError::Persistence {
+		target: "Counter trace",
+		reason: err.to_string(),
+	}
+}
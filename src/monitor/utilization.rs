@@ -0,0 +1,110 @@
+use std::collections::{ HashMap, VecDeque };
+use std::time::{ Duration, Instant };
+
+use super::interface::NetworkInterface;
+
+/// Link utilization of one interface over one sampling interval, as a percentage of its link speed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UtilizationSample {
+	pub timestamp: Instant,
+	pub upload_percent: f64,
+	pub download_percent: f64,
+	/// Link speed (bits/s) reported by the interface at the end of the interval.
+	pub link_speed_bps: u64,
+}
+
+impl UtilizationSample {
+	/// Computes utilization from two counter readings of the same interface taken `elapsed` apart.
+	///
+	/// Returns `None` when the link speed is unknown, no time passed, or the counters went backwards
+	/// (adapter reset).
+	pub fn between(previous: &NetworkInterface, current: &NetworkInterface, elapsed: Duration, timestamp: Instant) -> Option<Self> {
+		let seconds = elapsed.as_secs_f64();
+		if current.speed == 0 || seconds <= 0.0 {
+			return None;
+		}
+
+		let sent = current.bytes_sent.checked_sub(previous.bytes_sent)?;
+		let received = current.bytes_received.checked_sub(previous.bytes_received)?;
+		let percent = |bytes: u64| ((bytes as f64) * 8.0 / seconds / (current.speed as f64)) * 100.0;
+
+		Some(Self {
+			timestamp,
+			upload_percent: percent(sent),
+			download_percent: percent(received),
+			link_speed_bps: current.speed,
+		})
+	}
+
+	/// The busier direction, which is what saturates first on a full-duplex link.
+	pub fn peak_percent(&self) -> f64 {
+		self.upload_percent.max(self.download_percent)
+	}
+}
+
+/// Per-interface utilization series keyed by interface GUID.
+pub(crate) struct UtilizationHistory {
+	previous: HashMap<String, (NetworkInterface, Instant)>,
+	samples: HashMap<String, VecDeque<UtilizationSample>>,
+	max_history_size: usize,
+}
+
+impl UtilizationHistory {
+	pub(crate) fn new(max_history_size: usize) -> Self {
+		Self {
+			previous: HashMap::new(),
+			samples: HashMap::new(),
+			max_history_size,
+		}
+	}
+
+	/// Interfaces without a GUID are skipped: their indices are not stable enough to key a history.
+	pub(crate) fn update(&mut self, interfaces: Vec<NetworkInterface>, timestamp: Instant) {
+		for interface in interfaces {
+			if interface.guid.is_empty() {
+				continue;
+			}
+
+			if let Some((previous, previous_at)) = self.previous.get(&interface.guid) {
+				let elapsed = timestamp.saturating_duration_since(*previous_at);
+				if let Some(sample) = UtilizationSample::between(previous, &interface, elapsed, timestamp) {
+					let series = self.samples.entry(interface.guid.clone()).or_default();
+					series.push_back(sample);
+					if series.len() > self.max_history_size {
+						series.pop_front();
+					}
+				}
+			}
+
+			self.previous.insert(interface.guid.clone(), (interface, timestamp));
+		}
+	}
+
+	pub(crate) fn history(&self, guid: &str) -> Vec<UtilizationSample> {
+		self.samples
+			.get(guid)
+			.map(|series| series.iter().copied().collect())
+			.unwrap_or_default()
+	}
+
+	pub(crate) fn peak(&self, guid: &str, duration: Duration) -> Option<f64> {
+		let cutoff_time = Instant::now().checked_sub(duration);
+		self.samples
+			.get(guid)?
+			.iter()
+			.filter(|sample| cutoff_time.is_none_or(|cutoff| sample.timestamp >= cutoff))
+			.map(UtilizationSample::peak_percent)
+			.fold(None, |peak: Option<f64>, percent| Some(peak.map_or(percent, |peak| peak.max(percent))))
+	}
+
+	pub(crate) fn interfaces(&self) -> Vec<String> {
+		let mut guids: Vec<String> = self.samples.keys().cloned().collect();
+		guids.sort();
+		guids
+	}
+
+	pub(crate) fn clear(&mut self) {
+		self.previous.clear();
+		self.samples.clear();
+	}
+}
@@ -0,0 +1,249 @@
+use std::net::{ Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket };
+use std::time::{ Duration, Instant, SystemTime, UNIX_EPOCH };
+
+#[cfg(windows)]
+use windows::{
+	core::HRESULT,
+	Win32::Foundation::ERROR_BUFFER_OVERFLOW,
+	Win32::NetworkManagement::IpHelper::{ GetNetworkParams, FIXED_INFO_W2KSP1, IP_ADDR_STRING },
+};
+
+use crate::types::{ NetworkError, Result };
+
+pub const DEFAULT_DNS_TIMEOUT: Duration = Duration::from_secs(2);
+
+const DNS_PORT: u16 = 53;
+const MAX_DNS_RESPONSE: usize = 512;
+
+/// Outcome of resolving one hostname against one resolver.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DnsLookup {
+	pub hostname: String,
+	/// Round-trip time until the matching response arrived; `None` on timeout or socket error.
+	pub latency: Option<Duration>,
+	/// RCODE from the response header (0 = no error, 3 = NXDOMAIN).
+	pub response_code: Option<u8>,
+}
+
+impl DnsLookup {
+	pub fn is_answered(&self) -> bool {
+		self.latency.is_some()
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolverTiming {
+	pub resolver: SocketAddr,
+	pub lookups: Vec<DnsLookup>,
+}
+
+impl ResolverTiming {
+	/// Mean latency over answered lookups, or `None` if the resolver never answered.
+	pub fn average_latency(&self) -> Option<Duration> {
+		let answered: Vec<Duration> = self.lookups
+			.iter()
+			.filter_map(|lookup| lookup.latency)
+			.collect();
+		if answered.is_empty() {
+			return None;
+		}
+		Some(answered.iter().sum::<Duration>() / (answered.len() as u32))
+	}
+
+	pub fn max_latency(&self) -> Option<Duration> {
+		self.lookups
+			.iter()
+			.filter_map(|lookup| lookup.latency)
+			.max()
+	}
+
+	pub fn failures(&self) -> usize {
+		self.lookups
+			.iter()
+			.filter(|lookup| !lookup.is_answered())
+			.count()
+	}
+}
+
+/// Times an `A` query for each hostname against every resolver configured on this machine.
+pub fn dns_probe<S: AsRef<str>>(hostnames: &[S]) -> Result<Vec<ResolverTiming>> {
+	let resolvers = system_resolvers()?;
+	if resolvers.is_empty() {
+		return Err(NetworkError::ProbeFailed {
+			target: "dns".to_string(),
+			reason: "no DNS resolvers configured".to_string(),
+		});
+	}
+	dns_probe_with(hostnames, &resolvers, DEFAULT_DNS_TIMEOUT)
+}
+
+/// Like [`dns_probe`], but against explicit resolvers. Queries go straight to each resolver over UDP,
+/// bypassing the OS cache, so the numbers reflect the resolver rather than a local hit.
+pub fn dns_probe_with<S: AsRef<str>>(
+	hostnames: &[S],
+	resolvers: &[SocketAddr],
+	timeout: Duration
+) -> Result<Vec<ResolverTiming>> {
+	let queries = hostnames
+		.iter()
+		.map(|hostname| encode_query(hostname.as_ref()).map(|query| (hostname.as_ref(), query)))
+		.collect::<Result<Vec<_>>>()?;
+
+	Ok(
+		resolvers
+			.iter()
+			.map(|&resolver| ResolverTiming {
+				resolver,
+				lookups: queries
+					.iter()
+					.map(|(hostname, query)| time_query(resolver, hostname, query, timeout))
+					.collect(),
+			})
+			.collect()
+	)
+}
+
+/// DNS servers from the system network parameters, in preference order.
+#[cfg(windows)]
+pub fn system_resolvers() -> Result<Vec<SocketAddr>> {
+	unsafe {
+		let mut size = 0u32;
+		match GetNetworkParams(None, &mut size) {
+			Err(err) if err.code() == HRESULT::from_win32(ERROR_BUFFER_OVERFLOW.0) => {}
+			Err(err) => {
+				return Err(NetworkError::WindowsApi(err));
+			}
+			Ok(()) => {
+				return Ok(Vec::new());
+			}
+		}
+
+		// u64 backing keeps the buffer aligned for the pointer fields in FIXED_INFO.
+		let mut buffer = vec![0u64; (size as usize).div_ceil(8)];
+		let info_ptr = buffer.as_mut_ptr() as *mut FIXED_INFO_W2KSP1;
+		GetNetworkParams(Some(info_ptr), &mut size).map_err(NetworkError::WindowsApi)?;
+
+		let mut resolvers = Vec::new();
+		let mut entry: *const IP_ADDR_STRING = &(*info_ptr).DnsServerList;
+		while !entry.is_null() {
+			let raw = &(*entry).IpAddress.String;
+			let len = raw
+				.iter()
+				.position(|&b| b == 0)
+				.unwrap_or(raw.len());
+			if let Ok(ip) = std::str::from_utf8(&raw[..len]).unwrap_or("").trim().parse() {
+				resolvers.push(SocketAddr::new(ip, DNS_PORT));
+			}
+			entry = (*entry).Next;
+		}
+
+		Ok(resolvers)
+	}
+}
+
+/// DNS servers from the `nameserver` lines of `/etc/resolv.conf`, in file order. On macOS the file
+/// mirrors the primary resolver configuration only, not per-domain resolvers.
+#[cfg(unix)]
+pub fn system_resolvers() -> Result<Vec<SocketAddr>> {
+	let contents = std::fs::read_to_string("/etc/resolv.conf")?;
+	Ok(
+		contents
+			.lines()
+			.filter_map(|line| line.trim().strip_prefix("nameserver"))
+			// Link-local IPv6 servers carry a `%zone` suffix, which `IpAddr` does not parse.
+			.filter_map(|server| server.trim().split('%').next()?.parse().ok())
+			.map(|ip| SocketAddr::new(ip, DNS_PORT))
+			.collect()
+	)
+}
+
+fn time_query(resolver: SocketAddr, hostname: &str, query: &[u8], timeout: Duration) -> DnsLookup {
+	let unanswered = DnsLookup {
+		hostname: hostname.to_string(),
+		latency: None,
+		response_code: None,
+	};
+
+	let bind_addr: SocketAddr = if resolver.is_ipv4() {
+		(Ipv4Addr::UNSPECIFIED, 0).into()
+	} else {
+		(Ipv6Addr::UNSPECIFIED, 0).into()
+	};
+	let socket = match UdpSocket::bind(bind_addr) {
+		Ok(socket) => socket,
+		Err(_) => {
+			return unanswered;
+		}
+	};
+
+	let id = [query[0], query[1]];
+	let started = Instant::now();
+	if socket.send_to(query, resolver).is_err() {
+		return unanswered;
+	}
+
+	let mut response = [0u8; MAX_DNS_RESPONSE];
+	loop {
+		let remaining = match timeout.checked_sub(started.elapsed()) {
+			Some(remaining) if !remaining.is_zero() => remaining,
+			_ => {
+				return unanswered;
+			}
+		};
+		if socket.set_read_timeout(Some(remaining)).is_err() {
+			return unanswered;
+		}
+
+		match socket.recv_from(&mut response) {
+			// Ignore stray datagrams: only a response from the resolver with our ID counts.
+			Ok((len, from)) if from == resolver && len >= 12 && response[..2] == id && response[2] & 0x80 != 0 => {
+				return DnsLookup {
+					latency: Some(started.elapsed()),
+					response_code: Some(response[3] & 0x0f),
+					..unanswered
+				};
+			}
+			Ok(_) => {
+				continue;
+			}
+			Err(_) => {
+				return unanswered;
+			}
+		}
+	}
+}
+
+/// Builds a recursive `A`/`IN` query for `hostname`.
+fn encode_query(hostname: &str) -> Result<Vec<u8>> {
+	let invalid = |reason: &str| NetworkError::InvalidConfiguration {
+		field: format!("hostname '{hostname}': {reason}"),
+	};
+
+	let name = hostname.trim_end_matches('.');
+	if name.is_empty() || name.len() > 253 {
+		return Err(invalid("length must be 1-253 characters"));
+	}
+
+	let nanos = SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|elapsed| elapsed.subsec_nanos())
+		.unwrap_or(0);
+	let id = ((nanos >> 8) as u16) ^ (name.len() as u16);
+
+	let mut query = Vec::with_capacity(name.len() + 18);
+	query.extend_from_slice(&id.to_be_bytes());
+	query.extend_from_slice(&[0x01, 0x00]); // RD
+	query.extend_from_slice(&[0, 1, 0, 0, 0, 0, 0, 0]); // QDCOUNT=1
+
+	for label in name.split('.') {
+		if label.is_empty() || label.len() > 63 {
+			return Err(invalid("labels must be 1-63 characters"));
+		}
+		query.push(label.len() as u8);
+		query.extend_from_slice(label.as_bytes());
+	}
+	query.push(0);
+	query.extend_from_slice(&[0, 1, 0, 1]); // QTYPE=A, QCLASS=IN
+
+	Ok(query)
+}
@@ -0,0 +1,201 @@
+use std::io::{ Read, Write };
+use std::net::{ SocketAddr, TcpStream };
+use std::time::{ Duration, Instant };
+
+use super::tcp::{ connect_timed, probe_error, DEFAULT_CONNECT_TIMEOUT };
+#[cfg(feature = "tls")]
+use crate::agent::ClientTls;
+use crate::types::{ NetworkError, Result };
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HttpTiming {
+	pub connect_time: Duration,
+	/// From the start of the TCP connect until the first response byte, as browsers report it.
+	pub time_to_first_byte: Duration,
+	pub status: u16,
+}
+
+impl HttpTiming {
+	/// Server think time: TTFB minus the TCP handshake.
+	pub fn server_time(&self) -> Duration {
+		self.time_to_first_byte.saturating_sub(self.connect_time)
+	}
+}
+
+/// Issues a `GET` for a plain `http://` URL and measures time to first byte.
+pub fn http_ttfb(url: &str) -> Result<HttpTiming> {
+	http_ttfb_with(url, DEFAULT_CONNECT_TIMEOUT)
+}
+
+/// `timeout` bounds the connect and each read separately.
+pub fn http_ttfb_with(url: &str, timeout: Duration) -> Result<HttpTiming> {
+	let target = HttpTarget::parse(url)?;
+
+	let started = Instant::now();
+	let (mut stream, connect_time) = connect_timed((target.host.as_str(), target.port), timeout)?;
+	stream.set_read_timeout(Some(timeout)).map_err(|err| probe_error(url, err))?;
+	stream.set_nodelay(true).map_err(|err| probe_error(url, err))?;
+
+	stream.write_all(target.request().as_bytes()).map_err(|err| probe_error(url, err))?;
+
+	let mut head = Vec::with_capacity(64);
+	let mut buffer = [0u8; 1024];
+	let mut time_to_first_byte = None;
+	while !head.contains(&b'\n') {
+		let read = stream.read(&mut buffer).map_err(|err| probe_error(url, err))?;
+		if read == 0 {
+			return Err(probe_error(url, "connection closed before a status line"));
+		}
+		time_to_first_byte.get_or_insert_with(|| started.elapsed());
+		head.extend_from_slice(&buffer[..read]);
+	}
+
+	Ok(HttpTiming {
+		connect_time,
+		time_to_first_byte: time_to_first_byte.unwrap_or_default(),
+		status: parse_status(&head).ok_or_else(|| probe_error(url, "malformed status line"))?,
+	})
+}
+
+/// Largest response body [`http_get_text`] reads.
+const MAX_TEXT_RESPONSE: usize = 16 * 1024;
+
+/// Issues a `GET` and returns the response body together with the local address of the connection.
+/// Meant for small plain-text answers; fails on a non-2xx status.
+pub(crate) fn http_get_text(url: &str, timeout: Duration) -> Result<(String, SocketAddr)> {
+	let target = HttpTarget::parse(url)?;
+	let (stream, local) = connect_text(url, &target, timeout)?;
+	Ok((get_text(url, &target, stream)?, local))
+}
+
+/// [`http_get_text`] for an `https://` URL, verified against `tls`'s trust roots with the URL's
+/// host as the server name.
+#[cfg(feature = "tls")]
+pub(crate) fn https_get_text(url: &str, timeout: Duration, tls: &ClientTls) -> Result<(String, SocketAddr)> {
+	let target = HttpTarget::parse_https(url)?;
+	let (stream, local) = connect_text(url, &target, timeout)?;
+	let stream = tls.with_server_name(&target.host)?.connect(stream)?;
+	Ok((get_text(url, &target, stream)?, local))
+}
+
+fn connect_text(url: &str, target: &HttpTarget, timeout: Duration) -> Result<(TcpStream, SocketAddr)> {
+	let (stream, _) = connect_timed((target.host.as_str(), target.port), timeout)?;
+	stream.set_read_timeout(Some(timeout)).map_err(|err| probe_error(url, err))?;
+	let local = stream.local_addr().map_err(|err| probe_error(url, err))?;
+	Ok((stream, local))
+}
+
+fn get_text(url: &str, target: &HttpTarget, mut stream: impl Read + Write) -> Result<String> {
+	stream.write_all(target.request().as_bytes()).map_err(|err| probe_error(url, err))?;
+
+	let mut response = Vec::new();
+	stream
+		.take(MAX_TEXT_RESPONSE as u64)
+		.read_to_end(&mut response)
+		.map_err(|err| probe_error(url, err))?;
+
+	let status = parse_status(&response).ok_or_else(|| probe_error(url, "malformed status line"))?;
+	if !(200..300).contains(&status) {
+		return Err(probe_error(url, format!("HTTP status {status}")));
+	}
+	let body = response
+		.windows(4)
+		.position(|window| window == b"\r\n\r\n")
+		.map(|end| &response[end + 4..])
+		.ok_or_else(|| probe_error(url, "response has no body"))?;
+
+	Ok(String::from_utf8_lossy(body).into_owned())
+}
+
+#[cfg(feature = "async")]
+pub async fn http_ttfb_async(url: &str) -> Result<HttpTiming> {
+	let url = url.to_string();
+	tokio::task
+		::spawn_blocking(move || http_ttfb(&url)).await
+		.map_err(NetworkError::from)?
+}
+
+pub(crate) struct HttpTarget {
+	pub(crate) host: String,
+	pub(crate) port: u16,
+	pub(crate) path: String,
+	/// `https://`: the caller wraps the connection in TLS.
+	pub(crate) secure: bool,
+}
+
+impl HttpTarget {
+	pub(crate) fn parse(url: &str) -> Result<Self> {
+		Self::parse_scheme(url, false)
+	}
+
+	/// Like [`parse`](Self::parse), for an `https://` URL (default port 443).
+	#[cfg(feature = "tls")]
+	pub(crate) fn parse_https(url: &str) -> Result<Self> {
+		Self::parse_scheme(url, true)
+	}
+
+	fn parse_scheme(url: &str, secure: bool) -> Result<Self> {
+		let invalid = |reason: &str| NetworkError::InvalidConfiguration {
+			field: format!("url '{url}': {reason}"),
+		};
+
+		let (scheme, default_port) = if secure { ("https://", 443) } else { ("http://", 80) };
+		let rest = url.strip_prefix(scheme).ok_or_else(|| invalid(&format!("only {scheme} URLs are supported")))?;
+		let (authority, path) = match rest.find(['/', '?']) {
+			Some(index) if rest[index..].starts_with('?') => (&rest[..index], format!("/{}", &rest[index..])),
+			Some(index) => (&rest[..index], rest[index..].to_string()),
+			None => (rest, "/".to_string()),
+		};
+
+		let (host, port) = match authority.strip_prefix('[') {
+			Some(bracketed) => {
+				let (host, after) = bracketed.split_once(']').ok_or_else(|| invalid("unterminated IPv6 literal"))?;
+				(host, after.strip_prefix(':'))
+			}
+			None =>
+				match authority.rsplit_once(':') {
+					Some((host, port)) => (host, Some(port)),
+					None => (authority, None),
+				}
+		};
+		if host.is_empty() {
+			return Err(invalid("missing host"));
+		}
+		let port = match port {
+			Some(port) => port.parse().map_err(|_| invalid("invalid port"))?,
+			None => default_port,
+		};
+
+		Ok(Self {
+			host: host.to_string(),
+			port,
+			path,
+			secure,
+		})
+	}
+
+	fn request(&self) -> String {
+		format!(
+			"GET {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: network-speed/{}\r\nConnection: close\r\n\r\n",
+			self.path,
+			self.host_header(),
+			env!("CARGO_PKG_VERSION")
+		)
+	}
+
+	pub(crate) fn host_header(&self) -> String {
+		let host = if self.host.contains(':') { format!("[{}]", self.host) } else { self.host.clone() };
+		let default_port = if self.secure { 443 } else { 80 };
+		if self.port == default_port { host } else { format!("{host}:{}", self.port) }
+	}
+}
+
+pub(crate) fn parse_status(head: &[u8]) -> Option<u16> {
+	let line = head.split(|&b| b == b'\n').next()?;
+	let line = std::str::from_utf8(line).ok()?;
+	let mut parts = line.split_whitespace();
+	if !parts.next()?.starts_with("HTTP/") {
+		return None;
+	}
+	parts.next()?.parse().ok()
+}
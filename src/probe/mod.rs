@@ -0,0 +1,13 @@
+pub mod dns;
+pub mod http;
+pub mod mtu;
+pub mod public_ip;
+pub mod quality;
+pub mod tcp;
+
+pub use dns::*;
+pub use http::*;
+pub use mtu::*;
+pub use public_ip::*;
+pub use quality::*;
+pub use tcp::*;
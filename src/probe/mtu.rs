@@ -0,0 +1,192 @@
+#[cfg(windows)]
+use std::net::{ IpAddr, Ipv4Addr };
+use std::time::Duration;
+
+#[cfg(windows)]
+use windows::Win32::{
+	Foundation::HANDLE,
+	NetworkManagement::IpHelper::{
+		IcmpCloseHandle,
+		IcmpCreateFile,
+		IcmpSendEcho,
+		ICMP_ECHO_REPLY,
+		IP_FLAG_DF,
+		IP_OPTION_INFORMATION,
+		IP_PACKET_TOO_BIG,
+		IP_SUCCESS,
+	},
+};
+
+#[cfg(windows)]
+use super::tcp::probe_error;
+#[cfg(windows)]
+use crate::monitor::{ interface_details, route_for };
+#[cfg(windows)]
+use crate::types::NetworkError;
+use crate::types::Result;
+
+pub const DEFAULT_MTU_PROBE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// IPv4 header plus ICMP echo header, added to the echo payload to get the packet size.
+#[cfg(windows)]
+const IPV4_ICMP_OVERHEAD: u32 = 28;
+/// Every IPv4 link must carry this much without fragmentation (RFC 791).
+#[cfg(windows)]
+const MIN_IPV4_MTU: u32 = 576;
+/// Assumed local MTU when the outgoing interface reports none.
+#[cfg(windows)]
+const FALLBACK_LOCAL_MTU: u32 = 1500;
+
+/// What happened to one don't-fragment echo request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MtuProbeOutcome {
+	Delivered,
+	/// A router (or the local stack) answered "fragmentation needed".
+	TooBig,
+	/// No echo reply within the timeout, or an ICMP error other than "fragmentation needed".
+	Dropped,
+}
+
+/// Result of `path_mtu` (Windows).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PathMtu {
+	/// Largest packet, IP header included, that reached the destination unfragmented.
+	pub mtu: u32,
+	/// MTU of the outgoing interface, the upper bound of the search.
+	pub local_mtu: u32,
+	/// Oversized probes that vanished without a "fragmentation needed" reply. Non-zero together with
+	/// `mtu < local_mtu` suggests a PMTU black hole: a hop that drops large packets silently, which
+	/// stalls TCP transfers instead of making them fragment.
+	pub silent_drops: u32,
+	pub probes: u32,
+}
+
+impl PathMtu {
+	/// Whether something on the path carries less than the local link, e.g. a VPN or PPPoE hop.
+	pub fn is_reduced(&self) -> bool {
+		self.mtu < self.local_mtu
+	}
+}
+
+/// Finds the largest packet that reaches `destination` with the don't-fragment bit set, by binary
+/// search over ICMP echo sizes between 576 bytes and the outgoing interface's MTU. IPv4 only; the
+/// destination must answer ping. Windows only.
+#[cfg(windows)]
+pub fn path_mtu(destination: IpAddr) -> Result<PathMtu> {
+	path_mtu_with(destination, DEFAULT_MTU_PROBE_TIMEOUT)
+}
+
+/// Like [`path_mtu`]; `timeout` bounds each echo request.
+#[cfg(windows)]
+pub fn path_mtu_with(destination: IpAddr, timeout: Duration) -> Result<PathMtu> {
+	let IpAddr::V4(target) = destination else {
+		return Err(NetworkError::InvalidConfiguration {
+			field: format!("destination '{destination}': path MTU probing supports IPv4 only"),
+		});
+	};
+
+	let local_mtu = route_for(destination)
+		.ok()
+		.and_then(|lookup| interface_details(lookup.route.interface_index).ok())
+		.and_then(|details| details.mtu)
+		.unwrap_or(FALLBACK_LOCAL_MTU)
+		.max(MIN_IPV4_MTU);
+
+	let pinger = Pinger::open()?;
+	let timeout_ms = timeout.as_millis().clamp(1, u32::MAX as u128) as u32;
+	let probe = |size: u32| Ok(pinger.echo(target, size - IPV4_ICMP_OVERHEAD, timeout_ms));
+
+	if probe(MIN_IPV4_MTU)? != MtuProbeOutcome::Delivered {
+		return Err(probe_error(&destination.to_string(), "no echo reply at the minimum IPv4 MTU"));
+	}
+	search_path_mtu(MIN_IPV4_MTU, local_mtu, probe)
+}
+
+/// The binary search behind `path_mtu`: `known_good` must already have been delivered, and
+/// `probe(size)` sends one packet of `size` bytes. Counts `known_good`'s probe in `probes`.
+pub fn search_path_mtu(
+	known_good: u32,
+	upper_bound: u32,
+	mut probe: impl FnMut(u32) -> Result<MtuProbeOutcome>
+) -> Result<PathMtu> {
+	let mut result = PathMtu {
+		mtu: known_good,
+		local_mtu: upper_bound,
+		silent_drops: 0,
+		probes: 1,
+	};
+	let (mut good, mut bad) = (known_good, upper_bound.saturating_add(1));
+	while bad - good > 1 {
+		let size = good + (bad - good) / 2;
+		result.probes += 1;
+		match probe(size)? {
+			MtuProbeOutcome::Delivered => {
+				good = size;
+			}
+			MtuProbeOutcome::TooBig => {
+				bad = size;
+			}
+			MtuProbeOutcome::Dropped => {
+				result.silent_drops += 1;
+				bad = size;
+			}
+		}
+	}
+	result.mtu = good;
+	Ok(result)
+}
+
+#[cfg(windows)]
+struct Pinger(HANDLE);
+
+#[cfg(windows)]
+impl Pinger {
+	fn open() -> Result<Self> {
+		Ok(Self(unsafe { IcmpCreateFile()? }))
+	}
+
+	fn echo(&self, target: Ipv4Addr, payload: u32, timeout_ms: u32) -> MtuProbeOutcome {
+		let request = vec![0xa5u8; payload as usize];
+		let options = IP_OPTION_INFORMATION {
+			Ttl: 128,
+			Flags: IP_FLAG_DF as u8,
+			..Default::default()
+		};
+		// Room for the reply header, the echoed payload, and an ICMP error's quoted header.
+		let reply_size = std::mem::size_of::<ICMP_ECHO_REPLY>() + (payload as usize) + 8 + 64;
+		let mut reply = vec![0u64; reply_size.div_ceil(8)];
+
+		let replies = unsafe {
+			IcmpSendEcho(
+				self.0,
+				u32::from_ne_bytes(target.octets()),
+				request.as_ptr().cast(),
+				payload as u16,
+				Some(&options),
+				reply.as_mut_ptr().cast(),
+				(reply.len() * 8) as u32,
+				timeout_ms
+			)
+		};
+		let status = if replies > 0 {
+			unsafe { (*(reply.as_ptr() as *const ICMP_ECHO_REPLY)).Status }
+		} else {
+			std::io::Error::last_os_error().raw_os_error().map_or(u32::MAX, |code| code as u32)
+		};
+
+		match status {
+			IP_SUCCESS => MtuProbeOutcome::Delivered,
+			IP_PACKET_TOO_BIG => MtuProbeOutcome::TooBig,
+			_ => MtuProbeOutcome::Dropped,
+		}
+	}
+}
+
+#[cfg(windows)]
+impl Drop for Pinger {
+	fn drop(&mut self) {
+		unsafe {
+			let _ = IcmpCloseHandle(self.0);
+		}
+	}
+}
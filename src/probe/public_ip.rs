@@ -0,0 +1,346 @@
+use std::net::{ IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs, UdpSocket };
+use std::time::{ Duration, Instant, SystemTime, UNIX_EPOCH };
+
+use super::http::http_get_text;
+#[cfg(feature = "tls")]
+use super::http::https_get_text;
+use super::tcp::{ probe_error, DEFAULT_CONNECT_TIMEOUT };
+#[cfg(feature = "tls")]
+use crate::agent::ClientTls;
+use crate::types::{ NetworkError, Result };
+
+/// Public STUN servers tried by [`public_ip_probe`] when no endpoints are given.
+pub const DEFAULT_STUN_SERVERS: [&str; 2] = ["stun.l.google.com:19302", "stun.cloudflare.com:3478"];
+
+/// Bounds each request of [`public_ip_probe`].
+pub const DEFAULT_PUBLIC_IP_TIMEOUT: Duration = DEFAULT_CONNECT_TIMEOUT;
+
+const STUN_MAGIC_COOKIE: u32 = 0x2112_a442;
+const STUN_BINDING_REQUEST: u16 = 0x0001;
+const STUN_BINDING_SUCCESS: u16 = 0x0101;
+const STUN_MAPPED_ADDRESS: u16 = 0x0001;
+const STUN_XOR_MAPPED_ADDRESS: u16 = 0x0020;
+
+/// Where [`public_ip_probe`] asks for this host's public address.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PublicIpEndpoint {
+	/// A STUN server (`host:port`), asked with a Binding request over UDP.
+	Stun(String),
+	/// A URL whose response body is the caller's IP address, e.g. `http://api.ipify.org/`. An
+	/// `https://` URL needs the `tls` feature and `public_ip_probe_with_tls`.
+	Http(String),
+}
+
+impl PublicIpEndpoint {
+	pub fn address(&self) -> &str {
+		match self {
+			PublicIpEndpoint::Stun(address) | PublicIpEndpoint::Http(address) => address,
+		}
+	}
+}
+
+/// Whether traffic leaves through address translation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NatStatus {
+	/// The public address is assigned to this host.
+	NotBehindNat,
+	/// The host sits behind NAT that keeps one mapping per local socket.
+	BehindNat,
+	/// The NAT picks a new public port per destination (symmetric NAT); peer-to-peer traffic usually
+	/// needs a relay. Only detected when two STUN servers answered.
+	SymmetricNat,
+	/// The addresses could not be compared, e.g. an IPv4 socket answered with an IPv6 address.
+	Unknown,
+}
+
+/// The public address as seen by one endpoint, from [`public_ip_probe`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublicIpInfo {
+	pub public_ip: IpAddr,
+	/// Local address of the socket that asked.
+	pub local_ip: Option<IpAddr>,
+	pub endpoint: PublicIpEndpoint,
+	/// Round-trip time of the request that answered.
+	pub latency: Duration,
+	pub nat: NatStatus,
+}
+
+/// One STUN Binding exchange, from [`stun_binding`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StunMapping {
+	pub local: SocketAddr,
+	/// Address and port the server saw the request come from.
+	pub mapped: SocketAddr,
+	pub latency: Duration,
+}
+
+/// Asks each endpoint in turn for this host's public IP and returns the first answer. With no
+/// endpoints, [`DEFAULT_STUN_SERVERS`] are used. When a STUN server answers, the next STUN endpoint
+/// is asked from the same socket to tell symmetric NAT from the ordinary kind.
+pub fn public_ip_probe(endpoints: &[PublicIpEndpoint], timeout: Duration) -> Result<PublicIpInfo> {
+	probe_endpoints(endpoints, timeout, &(|url, timeout| {
+		if url.starts_with("https://") {
+			return Err(NetworkError::InvalidConfiguration {
+				field: format!("https:// endpoint '{url}' needs the tls feature and public_ip_probe_with_tls"),
+			});
+		}
+		http_get_text(url, timeout)
+	}))
+}
+
+/// Like [`public_ip_probe`], also asking `https://` endpoints. Their certificates are verified
+/// against `tls`'s trust roots, with each URL's host as the server name. A plain-HTTP answer can
+/// be forged by anyone on the path, so prefer HTTPS endpoints when the address matters.
+#[cfg(feature = "tls")]
+pub fn public_ip_probe_with_tls(endpoints: &[PublicIpEndpoint], timeout: Duration, tls: &ClientTls) -> Result<PublicIpInfo> {
+	probe_endpoints(endpoints, timeout, &(|url, timeout| {
+		if url.starts_with("https://") { https_get_text(url, timeout, tls) } else { http_get_text(url, timeout) }
+	}))
+}
+
+type GetText<'a> = dyn Fn(&str, Duration) -> Result<(String, SocketAddr)> + 'a;
+
+fn probe_endpoints(endpoints: &[PublicIpEndpoint], timeout: Duration, get_text: &GetText) -> Result<PublicIpInfo> {
+	let defaults: Vec<PublicIpEndpoint> = DEFAULT_STUN_SERVERS.iter()
+		.map(|server| PublicIpEndpoint::Stun(server.to_string()))
+		.collect();
+	let endpoints = if endpoints.is_empty() { &defaults[..] } else { endpoints };
+
+	let mut last_error = None;
+	for (position, endpoint) in endpoints.iter().enumerate() {
+		let answer = match endpoint {
+			PublicIpEndpoint::Stun(server) => {
+				let others: Vec<&str> = endpoints[position + 1..]
+					.iter()
+					.filter_map(|other| match other {
+						PublicIpEndpoint::Stun(server) => Some(server.as_str()),
+						PublicIpEndpoint::Http(_) => None,
+					})
+					.collect();
+				stun_probe(endpoint, server, &others, timeout)
+			}
+			PublicIpEndpoint::Http(url) => http_probe(endpoint, url, timeout, get_text),
+		};
+		match answer {
+			Ok(info) => {
+				return Ok(info);
+			}
+			Err(err) => {
+				last_error = Some(err);
+			}
+		}
+	}
+
+	Err(last_error.unwrap_or_else(|| probe_error("public ip", "no endpoints")))
+}
+
+/// Sends one STUN Binding request to `server` and returns the mapping it reports.
+pub fn stun_binding(server: impl ToSocketAddrs, timeout: Duration) -> Result<StunMapping> {
+	let server = resolve(server)?;
+	let socket = bind_for(server)?;
+	binding_on(&socket, server, timeout)
+}
+
+fn stun_probe(endpoint: &PublicIpEndpoint, server: &str, others: &[&str], timeout: Duration) -> Result<PublicIpInfo> {
+	let address = resolve(server)?;
+	let socket = bind_for(address)?;
+	let mapping = binding_on(&socket, address, timeout)?;
+
+	let mut nat = nat_status(mapping.local.ip(), mapping.mapped.ip());
+	if nat == NatStatus::BehindNat {
+		// Any second server works; one that fails to answer just leaves the NAT kind undecided.
+		let second = others
+			.iter()
+			.filter_map(|other| resolve(*other).ok())
+			.filter(|other| other.is_ipv4() == address.is_ipv4() && *other != address)
+			.find_map(|other| binding_on(&socket, other, timeout).ok());
+		if second.is_some_and(|second| second.mapped != mapping.mapped) {
+			nat = NatStatus::SymmetricNat;
+		}
+	}
+
+	Ok(PublicIpInfo {
+		public_ip: mapping.mapped.ip(),
+		local_ip: Some(mapping.local.ip()),
+		endpoint: endpoint.clone(),
+		latency: mapping.latency,
+		nat,
+	})
+}
+
+fn http_probe(endpoint: &PublicIpEndpoint, url: &str, timeout: Duration, get_text: &GetText) -> Result<PublicIpInfo> {
+	let started = Instant::now();
+	let (body, local) = get_text(url, timeout)?;
+	let latency = started.elapsed();
+	// Tolerates chunked framing: the address is the one line that parses.
+	let public_ip = body
+		.lines()
+		.find_map(|line| line.trim().parse::<IpAddr>().ok())
+		.ok_or_else(|| probe_error(url, "response body is not an IP address"))?;
+
+	Ok(PublicIpInfo {
+		public_ip,
+		local_ip: Some(local.ip()),
+		endpoint: endpoint.clone(),
+		latency,
+		nat: nat_status(local.ip(), public_ip),
+	})
+}
+
+/// Compares the address a socket was bound to with the one a server saw.
+pub fn nat_status(local: IpAddr, public: IpAddr) -> NatStatus {
+	if local.is_unspecified() || local.is_ipv4() != public.is_ipv4() {
+		NatStatus::Unknown
+	} else if local == public {
+		NatStatus::NotBehindNat
+	} else {
+		NatStatus::BehindNat
+	}
+}
+
+fn resolve(server: impl ToSocketAddrs) -> Result<SocketAddr> {
+	server
+		.to_socket_addrs()
+		.map_err(|err| probe_error("stun", err))?
+		.next()
+		.ok_or_else(|| probe_error("stun", "address resolved to nothing"))
+}
+
+/// A UDP socket on an ephemeral port in `server`'s address family.
+fn bind_for(server: SocketAddr) -> Result<UdpSocket> {
+	let bind_addr: SocketAddr = if server.is_ipv4() {
+		(Ipv4Addr::UNSPECIFIED, 0).into()
+	} else {
+		(Ipv6Addr::UNSPECIFIED, 0).into()
+	};
+	UdpSocket::bind(bind_addr).map_err(|err| probe_error(&server.to_string(), err))
+}
+
+fn binding_on(socket: &UdpSocket, server: SocketAddr, timeout: Duration) -> Result<StunMapping> {
+	let target = server.to_string();
+	let transaction = transaction_id();
+	let request = encode_binding_request(&transaction);
+	// Connecting picks the local address the route would use; reconnecting for a second server keeps
+	// the port, and with it the NAT mapping being compared.
+	socket.connect(server).map_err(|err| probe_error(&target, err))?;
+	let local = socket.local_addr().map_err(|err| probe_error(&target, err))?;
+
+	let started = Instant::now();
+	socket.send(&request).map_err(|err| probe_error(&target, err))?;
+
+	let mut response = [0u8; 512];
+	loop {
+		let remaining = match timeout.checked_sub(started.elapsed()) {
+			Some(remaining) if !remaining.is_zero() => remaining,
+			_ => {
+				return Err(NetworkError::Timeout {
+					operation: format!("STUN binding to {target}"),
+					timeout_ms: timeout.as_millis() as u64,
+				});
+			}
+		};
+		socket.set_read_timeout(Some(remaining)).map_err(|err| probe_error(&target, err))?;
+
+		match socket.recv(&mut response) {
+			Ok(len) => {
+				// Ignore stray datagrams and answers to earlier transactions.
+				if let Some(mapped) = parse_binding_response(&response[..len], &transaction) {
+					return Ok(StunMapping {
+						local,
+						mapped,
+						latency: started.elapsed(),
+					});
+				}
+			}
+			Err(err) if matches!(err.kind(), std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut) => {
+				continue;
+			}
+			Err(err) => {
+				return Err(probe_error(&target, err));
+			}
+		}
+	}
+}
+
+fn transaction_id() -> [u8; 12] {
+	let nanos = SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|elapsed| elapsed.as_nanos())
+		.unwrap_or(0);
+	let mixed = nanos ^ ((std::process::id() as u128) << 64);
+	let mut id = [0u8; 12];
+	id.copy_from_slice(&mixed.to_le_bytes()[..12]);
+	id
+}
+
+/// RFC 5389 Binding request without attributes.
+fn encode_binding_request(transaction: &[u8; 12]) -> [u8; 20] {
+	let mut request = [0u8; 20];
+	request[0..2].copy_from_slice(&STUN_BINDING_REQUEST.to_be_bytes());
+	request[4..8].copy_from_slice(&STUN_MAGIC_COOKIE.to_be_bytes());
+	request[8..20].copy_from_slice(transaction);
+	request
+}
+
+/// The (XOR-)MAPPED-ADDRESS of a Binding success response for `transaction`; XOR-MAPPED-ADDRESS
+/// wins when both are present.
+pub fn parse_binding_response(response: &[u8], transaction: &[u8; 12]) -> Option<SocketAddr> {
+	if response.len() < 20 || response[0..2] != STUN_BINDING_SUCCESS.to_be_bytes() {
+		return None;
+	}
+	if response[4..8] != STUN_MAGIC_COOKIE.to_be_bytes() || response[8..20] != transaction[..] {
+		return None;
+	}
+	let length = u16::from_be_bytes([response[2], response[3]]) as usize;
+	let attributes = response.get(20..20 + length)?;
+
+	let mut mapped = None;
+	let mut offset = 0;
+	while offset + 4 <= attributes.len() {
+		let kind = u16::from_be_bytes([attributes[offset], attributes[offset + 1]]);
+		let len = u16::from_be_bytes([attributes[offset + 2], attributes[offset + 3]]) as usize;
+		let value = attributes.get(offset + 4..offset + 4 + len)?;
+		match kind {
+			STUN_XOR_MAPPED_ADDRESS => {
+				return decode_address(value, Some(transaction));
+			}
+			STUN_MAPPED_ADDRESS => {
+				mapped = decode_address(value, None);
+			}
+			_ => {}
+		}
+		// Attributes are padded to four bytes.
+		offset += 4 + len.div_ceil(4) * 4;
+	}
+	mapped
+}
+
+fn decode_address(value: &[u8], xor_transaction: Option<&[u8; 12]>) -> Option<SocketAddr> {
+	if value.len() < 4 {
+		return None;
+	}
+	let cookie = STUN_MAGIC_COOKIE.to_be_bytes();
+	let mut port = u16::from_be_bytes([value[2], value[3]]);
+	if xor_transaction.is_some() {
+		port ^= (STUN_MAGIC_COOKIE >> 16) as u16;
+	}
+
+	match value[1] {
+		0x01 => {
+			let mut octets: [u8; 4] = value.get(4..8)?.try_into().ok()?;
+			if xor_transaction.is_some() {
+				octets.iter_mut().zip(cookie).for_each(|(byte, key)| *byte ^= key);
+			}
+			Some(SocketAddr::new(IpAddr::V4(Ipv4Addr::from(octets)), port))
+		}
+		0x02 => {
+			let mut octets: [u8; 16] = value.get(4..20)?.try_into().ok()?;
+			if let Some(transaction) = xor_transaction {
+				let key = cookie.iter().chain(transaction.iter());
+				octets.iter_mut().zip(key).for_each(|(byte, key)| *byte ^= key);
+			}
+			Some(SocketAddr::new(IpAddr::V6(Ipv6Addr::from(octets)), port))
+		}
+		_ => None,
+	}
+}
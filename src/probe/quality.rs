@@ -0,0 +1,70 @@
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use super::dns::{ dns_probe, dns_probe_with, ResolverTiming };
+use super::public_ip::{ public_ip_probe, NatStatus, PublicIpEndpoint, PublicIpInfo };
+use crate::types::Result;
+
+/// Responsiveness report that complements throughput numbers.
+///
+/// Slow name resolution feels like a slow link to users, so it is reported separately from bandwidth.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LinkQuality {
+	pub dns: Vec<ResolverTiming>,
+	/// Public address and NAT status, when requested with [`with_public_ip`](Self::with_public_ip).
+	pub public_ip: Option<PublicIpInfo>,
+}
+
+impl LinkQuality {
+	/// Probes the system resolvers with `hostnames`.
+	pub fn measure<S: AsRef<str>>(hostnames: &[S]) -> Result<Self> {
+		Ok(Self {
+			dns: dns_probe(hostnames)?,
+			public_ip: None,
+		})
+	}
+
+	pub fn measure_with<S: AsRef<str>>(hostnames: &[S], resolvers: &[SocketAddr], timeout: Duration) -> Result<Self> {
+		Ok(Self {
+			dns: dns_probe_with(hostnames, resolvers, timeout)?,
+			public_ip: None,
+		})
+	}
+
+	/// Adds the [`public_ip_probe`] result. This contacts third-party endpoints, so it is opt-in;
+	/// pass an empty slice for the default STUN servers.
+	pub fn with_public_ip(mut self, endpoints: &[PublicIpEndpoint], timeout: Duration) -> Result<Self> {
+		self.public_ip = Some(public_ip_probe(endpoints, timeout)?);
+		Ok(self)
+	}
+
+	/// NAT status from the public IP probe, `None` when it was not run.
+	pub fn nat(&self) -> Option<NatStatus> {
+		self.public_ip.as_ref().map(|info| info.nat)
+	}
+
+	/// Mean latency across all answered lookups on every resolver.
+	pub fn dns_latency(&self) -> Option<Duration> {
+		let answered: Vec<Duration> = self.dns
+			.iter()
+			.flat_map(|timing| timing.lookups.iter().filter_map(|lookup| lookup.latency))
+			.collect();
+		if answered.is_empty() {
+			return None;
+		}
+		Some(answered.iter().sum::<Duration>() / (answered.len() as u32))
+	}
+
+	/// The resolver with the highest average latency; resolvers that never answered rank slowest.
+	pub fn slowest_resolver(&self) -> Option<&ResolverTiming> {
+		self.dns.iter().max_by_key(|timing| timing.average_latency().unwrap_or(Duration::MAX))
+	}
+
+	pub fn fastest_resolver(&self) -> Option<&ResolverTiming> {
+		self.dns
+			.iter()
+			.filter_map(|timing| timing.average_latency().map(|latency| (latency, timing)))
+			.min_by_key(|(latency, _)| *latency)
+			.map(|(_, timing)| timing)
+	}
+}
@@ -0,0 +1,52 @@
+use std::net::{ SocketAddr, TcpStream, ToSocketAddrs };
+use std::time::{ Duration, Instant };
+
+use crate::types::{ NetworkError, Result };
+
+pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Time to complete a TCP handshake with `addr`. Name resolution happens before the clock starts.
+pub fn tcp_connect_time(addr: impl ToSocketAddrs) -> Result<Duration> {
+	tcp_connect_time_with(addr, DEFAULT_CONNECT_TIMEOUT)
+}
+
+/// Tries each resolved address in turn and reports the first successful handshake.
+pub fn tcp_connect_time_with(addr: impl ToSocketAddrs, timeout: Duration) -> Result<Duration> {
+	connect_timed(addr, timeout).map(|(_, elapsed)| elapsed)
+}
+
+#[cfg(feature = "async")]
+pub async fn tcp_connect_time_async(addr: impl ToSocketAddrs + Send + 'static) -> Result<Duration> {
+	tokio::task
+		::spawn_blocking(move || tcp_connect_time(addr)).await
+		.map_err(NetworkError::from)?
+}
+
+pub(crate) fn connect_timed(addr: impl ToSocketAddrs, timeout: Duration) -> Result<(TcpStream, Duration)> {
+	let addrs: Vec<SocketAddr> = addr
+		.to_socket_addrs()
+		.map_err(|err| probe_error("tcp", err))?
+		.collect();
+
+	let mut last_error = None;
+	for addr in addrs {
+		let started = Instant::now();
+		match TcpStream::connect_timeout(&addr, timeout) {
+			Ok(stream) => {
+				return Ok((stream, started.elapsed()));
+			}
+			Err(err) => {
+				last_error = Some(probe_error(&addr.to_string(), err));
+			}
+		}
+	}
+
+	Err(last_error.unwrap_or_else(|| probe_error("tcp", "address resolved to nothing")))
+}
+
+pub(crate) fn probe_error(target: &str, reason: impl std::fmt::Display) -> NetworkError {
+	NetworkError::ProbeFailed {
+		target: target.to_string(),
+		reason: reason.to_string(),
+	}
+}
@@ -0,0 +1,108 @@
+#[cfg(feature = "serde")]
+use serde::{ Deserialize, Serialize };
+
+use super::error::{ NetworkError, Result };
+use super::rate::ByteRate;
+use super::speed::{ Direction, NetworkSpeed };
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ActivityLevel {
+	Idle,
+	Light,
+	Moderate,
+	Heavy,
+	Saturated,
+}
+
+impl ActivityLevel {
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			ActivityLevel::Idle => "idle",
+			ActivityLevel::Light => "light",
+			ActivityLevel::Moderate => "moderate",
+			ActivityLevel::Heavy => "heavy",
+			ActivityLevel::Saturated => "saturated",
+		}
+	}
+}
+
+/// Lower bounds (inclusive) of each activity level, compared against the total rate, or one
+/// direction's rate with [`NetworkSpeed::classify_in`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Thresholds {
+	pub light: ByteRate,
+	pub moderate: ByteRate,
+	pub heavy: ByteRate,
+	pub saturated: ByteRate,
+}
+
+impl Thresholds {
+	pub fn new(light: ByteRate, moderate: ByteRate, heavy: ByteRate, saturated: ByteRate) -> Self {
+		Self {
+			light,
+			moderate,
+			heavy,
+			saturated,
+		}
+	}
+
+	/// Derives thresholds as 1% / 10% / 50% / 90% of a link's capacity.
+	pub fn for_link_speed(link: ByteRate) -> Self {
+		let percent = |p: u64| ByteRate::from_bytes_per_sec(((link.bytes_per_sec() as u128) * (p as u128) / 100) as u64);
+		Self::new(percent(1), percent(10), percent(50), percent(90))
+	}
+
+	pub fn validate(&self) -> Result<()> {
+		if self.light.is_zero() {
+			return Err(NetworkError::InvalidConfiguration {
+				field: "thresholds.light must be > 0".to_string(),
+			});
+		}
+
+		if !(self.light <= self.moderate && self.moderate <= self.heavy && self.heavy <= self.saturated) {
+			return Err(NetworkError::InvalidConfiguration {
+				field: "thresholds must be ordered light <= moderate <= heavy <= saturated".to_string(),
+			});
+		}
+
+		Ok(())
+	}
+
+	pub fn classify(&self, rate: ByteRate) -> ActivityLevel {
+		if rate >= self.saturated {
+			ActivityLevel::Saturated
+		} else if rate >= self.heavy {
+			ActivityLevel::Heavy
+		} else if rate >= self.moderate {
+			ActivityLevel::Moderate
+		} else if rate >= self.light {
+			ActivityLevel::Light
+		} else {
+			ActivityLevel::Idle
+		}
+	}
+}
+
+impl Default for Thresholds {
+	fn default() -> Self {
+		Self::new(
+			ByteRate::from_bytes_per_sec(1024),
+			ByteRate::from_bytes_per_sec(128 * 1024),
+			ByteRate::from_bytes_per_sec(1024 * 1024),
+			ByteRate::from_bytes_per_sec(10 * 1024 * 1024)
+		)
+	}
+}
+
+impl NetworkSpeed {
+	pub fn classify(&self, thresholds: &Thresholds) -> ActivityLevel {
+		self.classify_in(Direction::Total, thresholds)
+	}
+
+	/// Classifies one direction's rate, e.g. with upload thresholds scaled to a slower uplink.
+	pub fn classify_in(&self, direction: Direction, thresholds: &Thresholds) -> ActivityLevel {
+		thresholds.classify(self.rate(direction))
+	}
+}
@@ -0,0 +1,42 @@
+use std::time::Duration;
+
+#[cfg(feature = "serde")]
+use serde::{ Deserialize, Serialize };
+
+/// How far the derived boot time may drift between two readings and still count as the same boot.
+pub const BOOT_TIME_TOLERANCE: Duration = Duration::from_secs(5);
+
+/// Snapshot of a monitor's counter baseline that survives a process restart.
+///
+/// Timing is anchored to system uptime rather than wall-clock time, so clock adjustments between
+/// save and restore do not distort the first rate after a restart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MonitorCheckpoint {
+	pub bytes_sent: u64,
+	pub bytes_received: u64,
+	/// System uptime when the baseline counters were read.
+	pub uptime_ms: u64,
+	/// Wall-clock time (Unix epoch milliseconds) when the baseline counters were read.
+	pub unix_time_ms: u64,
+}
+
+impl MonitorCheckpoint {
+	pub fn boot_time_ms(&self) -> u64 {
+		self.unix_time_ms.saturating_sub(self.uptime_ms)
+	}
+
+	/// Age of the baseline at the given clock readings, or `None` if the system rebooted since.
+	pub fn age_at(&self, uptime_ms: u64, unix_time_ms: u64) -> Option<Duration> {
+		if uptime_ms < self.uptime_ms {
+			return None;
+		}
+
+		let boot_time_ms = unix_time_ms.saturating_sub(uptime_ms);
+		if boot_time_ms.abs_diff(self.boot_time_ms()) > (BOOT_TIME_TOLERANCE.as_millis() as u64) {
+			return None;
+		}
+
+		Some(Duration::from_millis(uptime_ms - self.uptime_ms))
+	}
+}
@@ -5,6 +5,20 @@ use std::time::Duration;
 use serde::{ Deserialize, Serialize };
 
 use super::error::{ NetworkError, Result };
+use super::format::parse_duration;
+use super::interface_kind::InterfaceKind;
+use super::overhead::OverheadStats;
+use super::rate::ByteRate;
+use super::schedule::Schedule;
+use super::speed::Direction;
+
+/// How often typical Windows NIC drivers refresh their byte counters. Polling faster mostly
+/// re-reads unchanged values and produces alternating zero and doubled rates.
+pub const COUNTER_UPDATE_GRANULARITY: Duration = Duration::from_millis(500);
+/// Fraction of wall time `recommended_interval` allows for queries when no `overhead_budget` is set.
+pub const DEFAULT_RECOMMENDED_OVERHEAD: f64 = 0.01;
+/// Default [`WrapStrategy::Error`] limit: larger deltas are treated as counter resets, not traffic.
+pub const DEFAULT_COUNTER_WRAP_THRESHOLD: u64 = 1 << 62;
 
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -12,13 +26,62 @@ pub struct NetworkMonitorConfig {
 	pub exclude_virtual: bool,
 	pub exclude_loopback: bool,
 	pub exclude_bluetooth: bool,
+	/// Keep Hyper-V vEthernet, WSL, and container adapters even when `exclude_virtual` is set.
+	#[cfg_attr(feature = "serde", serde(default))]
+	pub include_container_interfaces: bool,
+	/// Measure interfaces whose operational status is Dormant (e.g. waiting for an external event
+	/// such as a dial-up or Wi-Fi association). Excluded by default.
+	#[cfg_attr(feature = "serde", serde(default))]
+	pub include_dormant_interfaces: bool,
 	pub min_measurement_interval: Duration,
-	pub max_counter_wrap_threshold: u64,
+	/// How long an interface that vanished from enumeration keeps its cache entry (and its last
+	/// counters in the total). Zero evicts it immediately.
+	#[cfg_attr(feature = "serde", serde(default))]
+	pub interface_retention: Duration,
+	/// How a counter that went backwards between two reads (a wrap or a reset) is turned into a delta.
+	#[cfg_attr(feature = "serde", serde(default))]
+	pub wrap_strategy: WrapStrategy,
+	/// Noise floor: an upload or download rate below this is reported as zero, so keep-alives and
+	/// other background chatter do not count as activity. Zero keeps every rate.
+	#[cfg_attr(feature = "serde", serde(default))]
+	pub ignore_below_bytes_per_sec: ByteRate,
+	/// Overrides `ignore_below_bytes_per_sec` for upload rates.
+	#[cfg_attr(feature = "serde", serde(default))]
+	pub ignore_below_upload_bytes_per_sec: Option<ByteRate>,
+	/// Overrides `ignore_below_bytes_per_sec` for download rates.
+	#[cfg_attr(feature = "serde", serde(default))]
+	pub ignore_below_download_bytes_per_sec: Option<ByteRate>,
 	pub interface_name_filters: Vec<String>,
-	pub interface_type_filters: Vec<u32>,
+	pub interface_type_filters: Vec<InterfaceKind>,
 	pub include_interface_indices: Vec<u32>,
 	pub include_interface_name_patterns: Vec<String>,
+	/// How the include lists and the exclusion rules combine.
+	#[cfg_attr(feature = "serde", serde(default))]
+	pub filter_priority: FilterPriority,
+	/// How traffic seen on both a VPN/tunnel adapter and the physical NIC carrying it is counted.
+	#[cfg_attr(feature = "serde", serde(default))]
+	pub tunnel_accounting: TunnelAccounting,
+	/// How traffic seen on both a team/VLAN interface and the adapters beneath it is counted.
+	#[cfg_attr(feature = "serde", serde(default))]
+	pub stack_accounting: StackAccounting,
 	pub precision: PrecisionMode,
+	/// What the first instant-mode measurement returns, before a previous reading exists.
+	#[cfg_attr(feature = "serde", serde(default))]
+	pub first_sample: FirstSample,
+	/// Whether an interface row that fails to convert fails the whole read or is skipped.
+	#[cfg_attr(feature = "serde", serde(default))]
+	pub row_errors: RowErrorPolicy,
+	/// How many failed sub-samples `collect_samples` / `measure_average_speed` absorb.
+	#[cfg_attr(feature = "serde", serde(default))]
+	pub sample_tolerance: SampleTolerance,
+	/// Maximum fraction of wall time (0.0..=1.0) the monitor may spend querying counters.
+	/// Calls arriving sooner than the budget allows fail with `InsufficientTimeElapsed`.
+	#[cfg_attr(feature = "serde", serde(default))]
+	pub overhead_budget: Option<f64>,
+	/// Local-time windows that mute alerts or stretch the sampling interval, applied by
+	/// [`EventMonitor`](crate::EventMonitor) and the agent's sampling loop.
+	#[cfg_attr(feature = "serde", serde(default))]
+	pub schedule: Schedule,
 }
 
 impl NetworkMonitorConfig {
@@ -37,25 +100,44 @@ impl NetworkMonitorConfig {
 			});
 		}
 
-		if self.max_counter_wrap_threshold == 0 {
+		if self.wrap_strategy == (WrapStrategy::Error { max_delta: 0 }) {
 			return Err(NetworkError::InvalidConfiguration {
-				field: "max_counter_wrap_threshold cannot be zero".to_string(),
+				field: "wrap_strategy max_delta cannot be zero".to_string(),
 			});
 		}
 
-		if let PrecisionMode::Samples { samples, .. } = &self.precision {
-			if samples.get() < 2 {
+		self.precision.validate()?;
+		self.sample_tolerance.validate()?;
+		self.schedule.validate()?;
+
+		if let Some(budget) = self.overhead_budget {
+			if !(budget > 0.0 && budget <= 1.0) {
 				return Err(NetworkError::InvalidConfiguration {
-					field: "precision.samples.samples must be >= 2".to_string(),
+					field: "overhead_budget must be in (0.0, 1.0]".to_string(),
 				});
 			}
 		}
 
-		self.precision.validate()?;
-
 		Ok(())
 	}
 
+	/// Suggested polling interval: no faster than counters refresh or `min_measurement_interval`
+	/// allows, no shorter than one precision-mode measurement window, and slow enough that the
+	/// observed per-call cost stays within `overhead_budget` (1% when unset).
+	pub fn recommended_interval(&self, overhead: &OverheadStats) -> Duration {
+		let mut interval = self.min_measurement_interval
+			.max(COUNTER_UPDATE_GRANULARITY)
+			.max(self.precision.measurement_window());
+
+		if overhead.calls > 0 {
+			let budget = self.overhead_budget.unwrap_or(DEFAULT_RECOMMENDED_OVERHEAD);
+			let per_call = overhead.average_wall_time().as_secs_f64();
+			interval = interval.max(Duration::try_from_secs_f64(per_call / budget).unwrap_or(Duration::MAX));
+		}
+
+		interval
+	}
+
 	pub fn with_exclude_virtual(mut self, exclude: bool) -> Self {
 		self.exclude_virtual = exclude;
 		self
@@ -71,18 +153,33 @@ impl NetworkMonitorConfig {
 		self
 	}
 
+	pub fn with_include_container_interfaces(mut self, include: bool) -> Self {
+		self.include_container_interfaces = include;
+		self
+	}
+
+	pub fn with_include_dormant_interfaces(mut self, include: bool) -> Self {
+		self.include_dormant_interfaces = include;
+		self
+	}
+
 	pub fn with_min_interval(mut self, interval: Duration) -> Self {
 		self.min_measurement_interval = interval;
 		self
 	}
 
+	pub fn with_interface_retention(mut self, retention: Duration) -> Self {
+		self.interface_retention = retention;
+		self
+	}
+
 	pub fn add_interface_filter(mut self, filter: String) -> Self {
 		self.interface_name_filters.push(filter);
 		self
 	}
 
-	pub fn add_type_filter(mut self, interface_type: u32) -> Self {
-		self.interface_type_filters.push(interface_type);
+	pub fn add_type_filter(mut self, interface_type: impl Into<InterfaceKind>) -> Self {
+		self.interface_type_filters.push(interface_type.into());
 		self
 	}
 
@@ -96,10 +193,87 @@ impl NetworkMonitorConfig {
 		self
 	}
 
+	pub fn with_filter_priority(mut self, priority: FilterPriority) -> Self {
+		self.filter_priority = priority;
+		self
+	}
+
+	pub fn with_tunnel_accounting(mut self, accounting: TunnelAccounting) -> Self {
+		self.tunnel_accounting = accounting;
+		self
+	}
+
+	pub fn with_wrap_strategy(mut self, strategy: WrapStrategy) -> Self {
+		self.wrap_strategy = strategy;
+		self
+	}
+
+	pub fn with_ignore_below_bytes_per_sec(mut self, floor: impl Into<ByteRate>) -> Self {
+		self.ignore_below_bytes_per_sec = floor.into();
+		self
+	}
+
+	/// Sets the noise floor for one direction; `Direction::Total` sets the shared one.
+	pub fn with_noise_floor(mut self, direction: Direction, floor: impl Into<ByteRate>) -> Self {
+		let floor = floor.into();
+		match direction {
+			Direction::Upload => {
+				self.ignore_below_upload_bytes_per_sec = Some(floor);
+			}
+			Direction::Download => {
+				self.ignore_below_download_bytes_per_sec = Some(floor);
+			}
+			Direction::Total => {
+				self.ignore_below_bytes_per_sec = floor;
+			}
+		}
+		self
+	}
+
+	/// The noise floor in effect for `direction`: its override, else `ignore_below_bytes_per_sec`.
+	pub fn noise_floor(&self, direction: Direction) -> ByteRate {
+		let floor = match direction {
+			Direction::Upload => self.ignore_below_upload_bytes_per_sec,
+			Direction::Download => self.ignore_below_download_bytes_per_sec,
+			Direction::Total => None,
+		};
+		floor.unwrap_or(self.ignore_below_bytes_per_sec)
+	}
+
+	pub fn with_stack_accounting(mut self, accounting: StackAccounting) -> Self {
+		self.stack_accounting = accounting;
+		self
+	}
+
 	pub fn with_precision(mut self, precision: PrecisionMode) -> Self {
 		self.precision = precision;
 		self
 	}
+
+	pub fn with_overhead_budget(mut self, budget: f64) -> Self {
+		self.overhead_budget = Some(budget);
+		self
+	}
+
+	pub fn with_first_sample(mut self, first_sample: FirstSample) -> Self {
+		self.first_sample = first_sample;
+		self
+	}
+
+	pub fn with_row_errors(mut self, policy: RowErrorPolicy) -> Self {
+		self.row_errors = policy;
+		self
+	}
+
+	pub fn with_schedule(mut self, schedule: Schedule) -> Self {
+		self.schedule = schedule;
+		self
+	}
+
+	pub fn with_sample_tolerance(mut self, tolerance: SampleTolerance) -> Self {
+		self.sample_tolerance = tolerance;
+		self
+	}
 }
 
 impl Default for NetworkMonitorConfig {
@@ -108,13 +282,27 @@ impl Default for NetworkMonitorConfig {
 			exclude_virtual: true,
 			exclude_loopback: true,
 			exclude_bluetooth: true,
+			include_container_interfaces: false,
+			include_dormant_interfaces: false,
 			min_measurement_interval: Duration::from_millis(100),
-			max_counter_wrap_threshold: 1u64 << 62,
+			interface_retention: Duration::ZERO,
+			wrap_strategy: WrapStrategy::default(),
+			ignore_below_bytes_per_sec: ByteRate::ZERO,
+			ignore_below_upload_bytes_per_sec: None,
+			ignore_below_download_bytes_per_sec: None,
 			interface_name_filters: Vec::new(),
-			interface_type_filters: vec![24],
+			interface_type_filters: vec![InterfaceKind::Loopback],
 			include_interface_indices: Vec::new(),
 			include_interface_name_patterns: Vec::new(),
+			filter_priority: FilterPriority::ExcludeWins,
+			tunnel_accounting: TunnelAccounting::CountBoth,
+			stack_accounting: StackAccounting::CountAll,
 			precision: PrecisionMode::Instant,
+			first_sample: FirstSample::Zero,
+			row_errors: RowErrorPolicy::Fail,
+			sample_tolerance: SampleTolerance::default(),
+			overhead_budget: None,
+			schedule: Schedule::default(),
 		}
 	}
 }
@@ -145,13 +333,43 @@ impl NetworkMonitorConfigBuilder {
 		self
 	}
 
+	pub fn include_container_interfaces(mut self, include: bool) -> Self {
+		self.config.include_container_interfaces = include;
+		self
+	}
+
+	pub fn include_dormant_interfaces(mut self, include: bool) -> Self {
+		self.config.include_dormant_interfaces = include;
+		self
+	}
+
 	pub fn min_measurement_interval(mut self, interval: Duration) -> Self {
 		self.config.min_measurement_interval = interval;
 		self
 	}
 
-	pub fn max_counter_wrap_threshold(mut self, threshold: u64) -> Self {
-		self.config.max_counter_wrap_threshold = threshold;
+	pub fn interface_retention(mut self, retention: Duration) -> Self {
+		self.config.interface_retention = retention;
+		self
+	}
+
+	#[deprecated(note = "use wrap_strategy(WrapStrategy::Error { max_delta })")]
+	pub fn max_counter_wrap_threshold(self, threshold: u64) -> Self {
+		self.wrap_strategy(WrapStrategy::Error { max_delta: threshold })
+	}
+
+	pub fn wrap_strategy(mut self, strategy: WrapStrategy) -> Self {
+		self.config.wrap_strategy = strategy;
+		self
+	}
+
+	pub fn ignore_below_bytes_per_sec(mut self, floor: impl Into<ByteRate>) -> Self {
+		self.config.ignore_below_bytes_per_sec = floor.into();
+		self
+	}
+
+	pub fn noise_floor(mut self, direction: Direction, floor: impl Into<ByteRate>) -> Self {
+		self.config = self.config.with_noise_floor(direction, floor);
 		self
 	}
 
@@ -160,8 +378,8 @@ impl NetworkMonitorConfigBuilder {
 		self
 	}
 
-	pub fn add_interface_type_filter(mut self, interface_type: u32) -> Self {
-		self.config.interface_type_filters.push(interface_type);
+	pub fn add_interface_type_filter(mut self, interface_type: impl Into<InterfaceKind>) -> Self {
+		self.config.interface_type_filters.push(interface_type.into());
 		self
 	}
 
@@ -170,7 +388,7 @@ impl NetworkMonitorConfigBuilder {
 		self
 	}
 
-	pub fn interface_type_filters(mut self, filters: Vec<u32>) -> Self {
+	pub fn interface_type_filters(mut self, filters: Vec<InterfaceKind>) -> Self {
 		self.config.interface_type_filters = filters;
 		self
 	}
@@ -185,11 +403,51 @@ impl NetworkMonitorConfigBuilder {
 		self
 	}
 
+	pub fn filter_priority(mut self, priority: FilterPriority) -> Self {
+		self.config.filter_priority = priority;
+		self
+	}
+
+	pub fn tunnel_accounting(mut self, accounting: TunnelAccounting) -> Self {
+		self.config.tunnel_accounting = accounting;
+		self
+	}
+
+	pub fn stack_accounting(mut self, accounting: StackAccounting) -> Self {
+		self.config.stack_accounting = accounting;
+		self
+	}
+
 	pub fn precision(mut self, precision: PrecisionMode) -> Self {
 		self.config.precision = precision;
 		self
 	}
 
+	pub fn overhead_budget(mut self, budget: f64) -> Self {
+		self.config.overhead_budget = Some(budget);
+		self
+	}
+
+	pub fn first_sample(mut self, first_sample: FirstSample) -> Self {
+		self.config.first_sample = first_sample;
+		self
+	}
+
+	pub fn sample_tolerance(mut self, tolerance: SampleTolerance) -> Self {
+		self.config.sample_tolerance = tolerance;
+		self
+	}
+
+	pub fn row_errors(mut self, policy: RowErrorPolicy) -> Self {
+		self.config.row_errors = policy;
+		self
+	}
+
+	pub fn schedule(mut self, schedule: Schedule) -> Self {
+		self.config.schedule = schedule;
+		self
+	}
+
 	pub fn build(self) -> Result<NetworkMonitorConfig> {
 		self.config.validate()?;
 		Ok(self.config)
@@ -206,20 +464,118 @@ impl Default for NetworkMonitorConfigBuilder {
 	}
 }
 
+/// Precedence between `include_interface_indices` / `include_interface_name_patterns` and the
+/// exclusion rules (`exclude_*`, `interface_type_filters`, `interface_name_filters`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum FilterPriority {
+	/// Non-empty include lists restrict the candidates, and exclusions still apply to what they let
+	/// through.
+	#[default]
+	ExcludeWins,
+	/// An interface matching an include list is always kept; every other interface is judged by
+	/// the exclusions alone. Expresses "exclude virtual except the WireGuard tunnel".
+	IncludeWins,
+}
+
+/// VPN traffic is visible twice: once on the tunnel adapter and once, encapsulated, on the
+/// physical NIC. Only matters when tunnel adapters pass the filters (`exclude_virtual` is off or an
+/// include list selects them).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TunnelAccounting {
+	/// Count every selected interface, so tunneled traffic is counted twice.
+	#[default]
+	CountBoth,
+	/// Drop tunnel adapters; totals reflect actual WAN usage on the physical NICs.
+	PreferPhysical,
+	/// While a tunnel adapter is up, drop the physical adapters; totals reflect traffic through the
+	/// VPN. Assumes a full tunnel: split-tunneled traffic is not counted while the VPN is up.
+	PreferTunnel,
+	/// Keep tunnel adapters in per-interface results (`interface_traffic`, `traffic_by_category`,
+	/// snapshots) but leave them out of aggregate speeds and totals.
+	Separate,
+}
+
+/// How the delta between two counter reads is computed when the later read is smaller, or the
+/// difference is implausibly large. Windows counters are 64-bit except under the legacy
+/// `GetIfTable` fallback, where they are 32-bit and wrap after 4 GiB.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum WrapStrategy {
+	/// Take the 64-bit modular difference, and fail with `CalculationOverflow` when it exceeds
+	/// `max_delta`, which is what a reset (adapter restart, driver reload) looks like. Accurate, but
+	/// drops the sample that spans a reset.
+	Error {
+		max_delta: u64,
+	},
+	/// Count a counter that went backwards as zero traffic for that interval. Never fails, at the
+	/// cost of undercounting one interval around a reset or wrap.
+	ClampToZero,
+	/// Take the difference modulo 2^32, for 32-bit counters that wrap regularly. Never fails, but a
+	/// reset reads as up to 4 GiB of traffic.
+	Modular32,
+	/// Take the difference modulo 2^64. Never fails, but a reset reads as a huge spike.
+	Modular64,
+}
+
+impl WrapStrategy {
+	/// Bytes moved from `previous` to `current`, or `None` when [`WrapStrategy::Error`] treats the
+	/// change as a reset.
+	pub fn delta(&self, previous: u64, current: u64) -> Option<u64> {
+		let modular = current.wrapping_sub(previous);
+		match *self {
+			WrapStrategy::Error { max_delta } => (modular <= max_delta).then_some(modular),
+			WrapStrategy::ClampToZero => Some(current.saturating_sub(previous)),
+			WrapStrategy::Modular32 => Some(modular & u64::from(u32::MAX)),
+			WrapStrategy::Modular64 => Some(modular),
+		}
+	}
+}
+
+impl Default for WrapStrategy {
+	fn default() -> Self {
+		WrapStrategy::Error {
+			max_delta: DEFAULT_COUNTER_WRAP_THRESHOLD,
+		}
+	}
+}
+
+/// Traffic of stacked interfaces is visible on every layer: a NIC team reports the sum of its
+/// members, and a physical NIC includes the traffic of its VLAN sub-interfaces. Only matters when
+/// more than one layer of a stack passes the filters.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum StackAccounting {
+	/// Count every selected interface, so stacked traffic is counted once per selected layer.
+	#[default]
+	CountAll,
+	/// Roll members up into their parent: drop interfaces below another selected interface, so a
+	/// team is counted as one adapter. Undercounts VLANs, since the physical NIC is dropped along
+	/// with its untagged traffic.
+	PreferUpper,
+	/// Drop interfaces above another selected interface, counting the physical adapters. Correct for
+	/// both teams and VLANs, but per-interface results show the members rather than the team.
+	PreferLower,
+}
+
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum InterfaceFilter {
 	ByName(String),
-	ByType(u32),
+	ByType(InterfaceKind),
 	ByDescription(String),
+	#[cfg(all(feature = "platform", windows))]
+	#[cfg_attr(feature = "serde", serde(skip))]
 	Custom(fn(&windows::Win32::NetworkManagement::IpHelper::MIB_IFROW) -> bool),
 }
 
+#[cfg(all(feature = "platform", windows))]
 impl InterfaceFilter {
 	pub fn matches(&self, interface: &windows::Win32::NetworkManagement::IpHelper::MIB_IFROW) -> bool {
 		match self {
 			InterfaceFilter::ByName(_name) => false,
-			InterfaceFilter::ByType(interface_type) => interface.dwType == *interface_type,
+			InterfaceFilter::ByType(kind) => InterfaceKind::from(interface.dwType) == *kind,
 			InterfaceFilter::ByDescription(desc) => unsafe {
 				let desc_slice = std::slice::from_raw_parts(interface.bDescr.as_ptr(), interface.dwDescrLen as usize);
 				if let Ok(description) = std::str::from_utf8(desc_slice) {
@@ -233,10 +589,11 @@ impl InterfaceFilter {
 	}
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum PrecisionMode {
 	/// Use differential sampling based on previous measurements (default).
+	#[default]
 	Instant,
 	/// Measure over a specific blocking window to improve accuracy.
 	Windowed {
@@ -250,6 +607,34 @@ pub enum PrecisionMode {
 }
 
 impl PrecisionMode {
+	/// A validated [`PrecisionMode::Windowed`].
+	pub fn windowed(duration: Duration) -> Result<Self> {
+		let mode = PrecisionMode::Windowed { duration };
+		mode.validate()?;
+		Ok(mode)
+	}
+
+	/// A validated [`PrecisionMode::Samples`]; needs at least two samples and a non-zero interval.
+	pub fn samples(samples: u8, interval: Duration) -> Result<Self> {
+		let samples = NonZeroU8::new(samples).ok_or_else(|| NetworkError::InvalidConfiguration {
+			field: "precision.samples.samples must be >= 2".to_string(),
+		})?;
+		let mode = PrecisionMode::Samples { samples, interval };
+		mode.validate()?;
+		Ok(mode)
+	}
+
+	/// Wall time one measurement spends inside its window; zero for `Instant`.
+	pub fn measurement_window(&self) -> Duration {
+		match self {
+			PrecisionMode::Instant => Duration::ZERO,
+			PrecisionMode::Windowed { duration } => *duration,
+			PrecisionMode::Samples { samples, interval } => {
+				interval.checked_mul(samples.get().into()).unwrap_or(Duration::MAX)
+			}
+		}
+	}
+
 	pub fn validate(&self) -> Result<()> {
 		match self {
 			PrecisionMode::Instant => Ok(()),
@@ -261,7 +646,12 @@ impl PrecisionMode {
 				}
 				Ok(())
 			}
-			PrecisionMode::Samples { samples: _, interval } => {
+			PrecisionMode::Samples { samples, interval } => {
+				if samples.get() < 2 {
+					return Err(NetworkError::InvalidConfiguration {
+						field: "precision.samples.samples must be >= 2".to_string(),
+					});
+				}
 				if interval.is_zero() {
 					return Err(NetworkError::InvalidConfiguration {
 						field: "precision.samples.interval must be > 0".to_string(),
@@ -272,3 +662,120 @@ impl PrecisionMode {
 		}
 	}
 }
+
+/// Parses `instant`, `windowed:<duration>`, or `samples:<count>x<duration>`, where a duration is
+/// accepted by [`parse_duration`] (e.g. `windowed:500ms`, `samples:5x200ms`). The result is
+/// validated like the constructors.
+impl std::str::FromStr for PrecisionMode {
+	type Err = NetworkError;
+
+	fn from_str(value: &str) -> Result<Self> {
+		let value = value.trim();
+		let invalid = || NetworkError::InvalidConfiguration {
+			field: format!("precision \"{value}\": expected instant, windowed:<duration>, or samples:<count>x<duration>"),
+		};
+
+		let (kind, argument) = value.split_once(':').unwrap_or((value, ""));
+		match (kind.to_ascii_lowercase().as_str(), argument) {
+			("instant", "") => Ok(PrecisionMode::Instant),
+			("windowed", duration) => PrecisionMode::windowed(parse_duration(duration).ok_or_else(invalid)?),
+			("samples", argument) => {
+				let (count, interval) = argument.split_once(['x', 'X']).ok_or_else(invalid)?;
+				let count = count.trim().parse::<u8>().map_err(|_| invalid())?;
+				PrecisionMode::samples(count, parse_duration(interval).ok_or_else(invalid)?)
+			}
+			_ => Err(invalid()),
+		}
+	}
+}
+
+impl TryFrom<&str> for PrecisionMode {
+	type Error = NetworkError;
+
+	fn try_from(value: &str) -> Result<Self> {
+		value.parse()
+	}
+}
+
+/// Formats in the syntax [`FromStr`](std::str::FromStr) accepts, with durations in milliseconds.
+impl std::fmt::Display for PrecisionMode {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			PrecisionMode::Instant => f.write_str("instant"),
+			PrecisionMode::Windowed { duration } => write!(f, "windowed:{}ms", duration.as_millis()),
+			PrecisionMode::Samples { samples, interval } => write!(f, "samples:{}x{}ms", samples, interval.as_millis()),
+		}
+	}
+}
+
+/// Behavior of the first measurement in [`PrecisionMode::Instant`], which has no earlier reading
+/// to diff against. Windowed and sampled modes always measure a real interval.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum FirstSample {
+	/// Record the baseline and report a zero speed (historical behavior).
+	#[default]
+	Zero,
+	/// Record the baseline; `measure_speed` fails with `InsufficientTimeElapsed` and
+	/// `NetworkMonitor::sample` returns `Ok(None)`.
+	Skip,
+	/// Like `Skip`, but `NetworkMonitor::sample` returns `SpeedSample::Baseline`.
+	Baseline,
+	/// Record the baseline, wait `min_measurement_interval`, and report the real delta.
+	Block,
+}
+
+/// What a read does when one row of the interface table cannot be converted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum RowErrorPolicy {
+	/// Fail the whole measurement with the row's error.
+	#[default]
+	Fail,
+	/// Skip the row, record it (see `NetworkMonitor::skipped_rows`), and measure the rest. The
+	/// skipped interface counts as missing, so `interface_retention` keeps its last counters in the
+	/// total instead of letting it dip.
+	Skip,
+}
+
+/// Failure policy for multi-sample collection. The default aborts on the first failed sample.
+///
+/// `InsufficientTimeElapsed` results are always skipped and never count as failures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SampleTolerance {
+	/// Failed samples to skip before the whole collection fails with the last error.
+	pub max_failed_samples: usize,
+	/// Successful samples required for the collection to succeed.
+	pub min_successful_samples: usize,
+}
+
+impl SampleTolerance {
+	/// Aborts on the first failure (historical behavior).
+	pub const STRICT: SampleTolerance = SampleTolerance {
+		max_failed_samples: 0,
+		min_successful_samples: 1,
+	};
+
+	pub fn new(max_failed_samples: usize, min_successful_samples: usize) -> Self {
+		Self {
+			max_failed_samples,
+			min_successful_samples,
+		}
+	}
+
+	pub fn validate(&self) -> Result<()> {
+		if self.min_successful_samples == 0 {
+			return Err(NetworkError::InvalidConfiguration {
+				field: "sample_tolerance.min_successful_samples must be >= 1".to_string(),
+			});
+		}
+		Ok(())
+	}
+}
+
+impl Default for SampleTolerance {
+	fn default() -> Self {
+		Self::STRICT
+	}
+}
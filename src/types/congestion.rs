@@ -0,0 +1,59 @@
+use std::fmt;
+
+#[cfg(feature = "serde")]
+use serde::{ Deserialize, Serialize };
+
+/// Share of outbound packets discarded at or above which a link counts as congested.
+pub const CONGESTED_DISCARD_RATIO: f64 = 0.01;
+/// Share of outbound packets discarded at or above which a link counts as elevated.
+pub const ELEVATED_DISCARD_RATIO: f64 = 0.001;
+/// Output queue length (packets) at or above which a link counts as congested.
+pub const CONGESTED_QUEUE_LENGTH: u64 = 10;
+
+/// Coarse congestion signal derived from outbound discards and the output queue.
+///
+/// Discards rise when the adapter cannot drain its send queue, which can happen well below the
+/// nominal link speed (shared Wi-Fi airtime, a saturated upstream hop behind a fast LAN port).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum CongestionLevel {
+	#[default]
+	Clear,
+	Elevated,
+	Congested,
+}
+
+impl CongestionLevel {
+	/// Classifies an interval in which `packets_sent` packets left the interface, `discarded` were
+	/// dropped on the way out, and `queue_length` packets were waiting at the end.
+	pub fn classify(packets_sent: u64, discarded: u64, queue_length: u64) -> Self {
+		let attempted = packets_sent.saturating_add(discarded);
+		let ratio = if attempted == 0 { 0.0 } else { (discarded as f64) / (attempted as f64) };
+
+		if ratio >= CONGESTED_DISCARD_RATIO || queue_length >= CONGESTED_QUEUE_LENGTH {
+			CongestionLevel::Congested
+		} else if ratio >= ELEVATED_DISCARD_RATIO || queue_length > 0 {
+			CongestionLevel::Elevated
+		} else {
+			CongestionLevel::Clear
+		}
+	}
+
+	pub fn is_congested(&self) -> bool {
+		matches!(self, CongestionLevel::Congested)
+	}
+
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			CongestionLevel::Clear => "clear",
+			CongestionLevel::Elevated => "elevated",
+			CongestionLevel::Congested => "congested",
+		}
+	}
+}
+
+impl fmt::Display for CongestionLevel {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(self.as_str())
+	}
+}
@@ -2,6 +2,7 @@ use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum NetworkError {
+	#[cfg(all(feature = "platform", windows))]
 	#[error("Windows API error: {0}")] WindowsApi(#[from] windows::core::Error),
 
 	#[error("Memory allocation failed")]
@@ -20,6 +21,8 @@ pub enum NetworkError {
 	#[error("No network interfaces found")]
 	NoInterfacesFound,
 
+	/// Catch-all kept for compatibility; the crate itself reports the specific variants below.
+	#[deprecated(note = "match on the specific variants (LockPoisoned, TaskJoin, Persistence, Export, Io, ...)")]
 	#[error("Interface operation failed: {reason}")] InterfaceOperationFailed {
 		reason: String,
 	},
@@ -30,25 +33,249 @@ pub enum NetworkError {
 	#[error("Invalid configuration: {field}")] InvalidConfiguration {
 		field: String,
 	},
+
+	#[error("Protocol error: {reason}")] Protocol {
+		reason: String,
+	},
+
+	#[error("Probe of {target} failed: {reason}")] ProbeFailed {
+		target: String,
+		reason: String,
+	},
+
+	#[error("{operation} timed out after {timeout_ms}ms")] Timeout {
+		operation: String,
+		timeout_ms: u64,
+	},
+
+	#[error("Operation cancelled")]
+	Cancelled,
+
+	/// A thread panicked while holding the named lock (`"Monitor"`, `"History"`, `"Collector"`).
+	#[error("{resource} mutex poisoned")] LockPoisoned {
+		resource: &'static str,
+	},
+
+	#[error("Background task {}", if *panicked { "panicked" } else { "was cancelled" })] TaskJoin {
+		panicked: bool,
+	},
+
+	/// Reading or writing a history file or counter trace failed.
+	#[error("{target} persistence failed: {reason}")] Persistence {
+		target: &'static str,
+		reason: String,
+	},
+
+	/// Rendering a chart or converting history to Arrow/Parquet failed.
+	#[error("{format} export failed: {reason}")] Export {
+		format: &'static str,
+		reason: String,
+	},
+
+	#[error("I/O error: {0}")] Io(#[from] std::io::Error),
+
+	/// Fewer measurements succeeded than `SampleTolerance::min_successful_samples` requires.
+	#[error("only {succeeded} of {required} required samples succeeded ({failed} failed)")] InsufficientSamples {
+		succeeded: usize,
+		required: usize,
+		failed: usize,
+	},
+
+	/// The provider (e.g. `"ETW"`) only works from an elevated process; prompt for elevation
+	/// rather than retrying.
+	#[error("{provider} requires administrator privileges")] InsufficientPrivileges {
+		provider: &'static str,
+	},
+
+	/// Sending a scheduled report (`"Webhook"`, `"SMTP"`) failed.
+	#[error("{channel} delivery failed: {reason}")] Delivery {
+		channel: &'static str,
+		reason: String,
+	},
+
+	/// Reading the counters of a provider (`"/proc/net/dev"`, `"NET_RT_IFLIST2"`, or a custom
+	/// [`CounterSource`](crate::CounterSource)'s name) failed.
+	#[error("{provider} failed: {source}")] ProviderFailed {
+		provider: &'static str,
+		source: Box<dyn std::error::Error + Send + Sync>,
+	},
+}
+
+/// Common IP Helper failures grouped by what the user can do about them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Win32ErrorCategory {
+	/// `ERROR_ACCESS_DENIED`: the call needs an elevated process.
+	AccessDenied,
+	/// `ERROR_NOT_SUPPORTED`, `ERROR_INVALID_FUNCTION`, `ERROR_CALL_NOT_IMPLEMENTED`: the API is
+	/// unavailable on this Windows version or adapter.
+	NotSupported,
+	/// `ERROR_INSUFFICIENT_BUFFER`, `ERROR_BUFFER_OVERFLOW`, `ERROR_MORE_DATA`: the interface table
+	/// changed size between calls; retrying normally succeeds.
+	Buffer,
+	/// `ERROR_NOT_ENOUGH_MEMORY`, `ERROR_OUTOFMEMORY`.
+	OutOfMemory,
+	/// `ERROR_NOT_FOUND`, `ERROR_FILE_NOT_FOUND`, `ERROR_NO_DATA`: the interface went away.
+	NotFound,
+	/// `ERROR_GEN_FAILURE`, `ERROR_NOT_READY`, `ERROR_DEVICE_NOT_AVAILABLE`: the network driver
+	/// or adapter misbehaved.
+	Driver,
+	Other,
+}
+
+impl Win32ErrorCategory {
+	pub fn from_win32_code(code: u32) -> Self {
+		match code {
+			5 => Win32ErrorCategory::AccessDenied,
+			1 | 50 | 120 => Win32ErrorCategory::NotSupported,
+			111 | 122 | 234 => Win32ErrorCategory::Buffer,
+			8 | 14 => Win32ErrorCategory::OutOfMemory,
+			2 | 232 | 1168 => Win32ErrorCategory::NotFound,
+			21 | 31 | 4319 => Win32ErrorCategory::Driver,
+			_ => Win32ErrorCategory::Other,
+		}
+	}
+
+	/// Short advice suitable for showing to end users.
+	pub fn hint(&self) -> &'static str {
+		match self {
+			Win32ErrorCategory::AccessDenied => "Run the application as administrator",
+			Win32ErrorCategory::NotSupported => "Not supported on this Windows version or adapter",
+			Win32ErrorCategory::Buffer => "Interface list changed during the query; try again",
+			Win32ErrorCategory::OutOfMemory => "The system is out of memory",
+			Win32ErrorCategory::NotFound => "The network interface is no longer available",
+			Win32ErrorCategory::Driver => "The network adapter or its driver reported a failure",
+			Win32ErrorCategory::Other => "Unexpected Windows error",
+		}
+	}
+
+	/// Whether repeating the call may succeed without user action.
+	pub fn is_transient(&self) -> bool {
+		matches!(self, Win32ErrorCategory::Buffer | Win32ErrorCategory::NotFound)
+	}
+}
+
+impl std::fmt::Display for Win32ErrorCategory {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(self.hint())
+	}
+}
+
+#[cfg(feature = "async")]
+impl From<tokio::task::JoinError> for NetworkError {
+	fn from(err: tokio::task::JoinError) -> Self {
+		NetworkError::TaskJoin {
+			panicked: err.is_panic(),
+		}
+	}
 }
 
 pub type Result<T> = std::result::Result<T, NetworkError>;
 
 impl NetworkError {
+	/// Wraps an I/O failure of `provider` in [`NetworkError::ProviderFailed`]; every other variant
+	/// already says what went wrong and is returned as is.
+	pub fn in_provider(self, provider: &'static str) -> Self {
+		match self {
+			NetworkError::Io(err) => NetworkError::ProviderFailed {
+				provider,
+				source: Box::new(err),
+			},
+			other => other,
+		}
+	}
+
 	pub fn is_recoverable(&self) -> bool {
-		matches!(self, NetworkError::InsufficientTimeElapsed { .. } | NetworkError::CalculationOverflow)
+		matches!(
+			self,
+			NetworkError::InsufficientTimeElapsed { .. } |
+				NetworkError::CalculationOverflow |
+				NetworkError::Timeout { .. } |
+				NetworkError::Cancelled
+		)
+	}
+
+	/// Whether running elevated would likely fix the failure: [`NetworkError::InsufficientPrivileges`]
+	/// or a Windows `ERROR_ACCESS_DENIED`.
+	pub fn needs_elevation(&self) -> bool {
+		matches!(self, NetworkError::InsufficientPrivileges { .. }) ||
+			self.win32_category() == Some(Win32ErrorCategory::AccessDenied)
+	}
+
+	/// HRESULT of a `NetworkError::WindowsApi` failure (Windows).
+	pub fn hresult(&self) -> Option<i32> {
+		match self {
+			#[cfg(all(feature = "platform", windows))]
+			NetworkError::WindowsApi(err) => Some(err.code().0),
+			_ => None,
+		}
+	}
+
+	/// Win32 error code (e.g. 5 for `ERROR_ACCESS_DENIED`) when the HRESULT wraps one.
+	pub fn win32_code(&self) -> Option<u32> {
+		let hresult = self.hresult()? as u32;
+		if hresult & 0xffff_0000 == 0x8007_0000 { Some(hresult & 0xffff) } else { None }
+	}
+
+	/// Broad cause of a Windows API failure, for choosing what to tell the user.
+	pub fn win32_category(&self) -> Option<Win32ErrorCategory> {
+		self.win32_code().map(Win32ErrorCategory::from_win32_code)
+	}
+
+	/// Stable snake_case name of the variant, e.g. `"no_interfaces_found"`; used as the failure
+	/// label in [`MonitorStats`](crate::MonitorStats).
+	pub fn kind(&self) -> &'static str {
+		match self {
+			#[cfg(all(feature = "platform", windows))]
+			NetworkError::WindowsApi(_) => "windows_api",
+			NetworkError::MemoryAllocation => "memory_allocation",
+			NetworkError::InvalidInterface => "invalid_interface",
+			NetworkError::InsufficientTimeElapsed { .. } => "insufficient_time_elapsed",
+			NetworkError::NoInterfacesFound => "no_interfaces_found",
+			#[allow(deprecated)]
+			NetworkError::InterfaceOperationFailed { .. } => "interface_operation_failed",
+			NetworkError::CalculationOverflow => "calculation_overflow",
+			NetworkError::InvalidConfiguration { .. } => "invalid_configuration",
+			NetworkError::Protocol { .. } => "protocol",
+			NetworkError::ProbeFailed { .. } => "probe_failed",
+			NetworkError::Timeout { .. } => "timeout",
+			NetworkError::Cancelled => "cancelled",
+			NetworkError::LockPoisoned { .. } => "lock_poisoned",
+			NetworkError::TaskJoin { .. } => "task_join",
+			NetworkError::Persistence { .. } => "persistence",
+			NetworkError::Export { .. } => "export",
+			NetworkError::Io(_) => "io",
+			NetworkError::InsufficientSamples { .. } => "insufficient_samples",
+			NetworkError::InsufficientPrivileges { .. } => "insufficient_privileges",
+			NetworkError::Delivery { .. } => "delivery",
+			NetworkError::ProviderFailed { .. } => "provider_failed",
+		}
 	}
 
 	pub fn error_code(&self) -> u32 {
 		match self {
+			#[cfg(all(feature = "platform", windows))]
 			NetworkError::WindowsApi(_) => 1001,
 			NetworkError::MemoryAllocation => 1002,
 			NetworkError::InvalidInterface => 1003,
 			NetworkError::InsufficientTimeElapsed { .. } => 1004,
 			NetworkError::NoInterfacesFound => 1005,
+			#[allow(deprecated)]
 			NetworkError::InterfaceOperationFailed { .. } => 1006,
 			NetworkError::CalculationOverflow => 1007,
 			NetworkError::InvalidConfiguration { .. } => 1008,
+			NetworkError::Protocol { .. } => 1009,
+			NetworkError::ProbeFailed { .. } => 1010,
+			NetworkError::Timeout { .. } => 1011,
+			NetworkError::Cancelled => 1012,
+			NetworkError::LockPoisoned { .. } => 1013,
+			NetworkError::TaskJoin { .. } => 1014,
+			NetworkError::Persistence { .. } => 1015,
+			NetworkError::Export { .. } => 1016,
+			NetworkError::Io(_) => 1017,
+			NetworkError::InsufficientSamples { .. } => 1018,
+			NetworkError::InsufficientPrivileges { .. } => 1019,
+			NetworkError::Delivery { .. } => 1020,
+			NetworkError::ProviderFailed { .. } => 1021,
 		}
 	}
 }
@@ -0,0 +1,318 @@
+#[cfg(feature = "serde")]
+use serde::{ Deserialize, Serialize };
+
+use std::time::Duration;
+
+use super::rate::ByteRate;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum UnitSystem {
+	/// Decimal prefixes (1 kB = 1000 B).
+	Si,
+	/// Binary prefixes (1 KiB = 1024 B).
+	Iec,
+}
+
+impl UnitSystem {
+	pub fn base(&self) -> f64 {
+		match self {
+			UnitSystem::Si => 1000.0,
+			UnitSystem::Iec => 1024.0,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DataUnit {
+	Bytes,
+	Bits,
+}
+
+/// Number and unit conventions for formatted rates. The unit labels are built from the English
+/// ones (`MB/s`, `Mbps`) by swapping in [`byte_symbol`](Self::byte_symbol),
+/// [`bit_symbol`](Self::bit_symbol), and [`per_second`](Self::per_second), so a French locale
+/// shows `1,50 Mo/s` and a German one `1,50 MB/s`. The `B` in `kB/s` and `bps` is only replaced at
+/// the end of the label, so the prefixes stay as they are.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Locale {
+	pub decimal_separator: char,
+	/// Placed between the number and the unit.
+	pub unit_separator: String,
+	/// Replaces `B` in byte units, e.g. `o` (octet) in French.
+	pub byte_symbol: String,
+	/// Replaces `bps` in bit units, e.g. `bit/s`.
+	pub bit_symbol: String,
+	/// Replaces `/s` in byte units.
+	pub per_second: String,
+}
+
+impl Locale {
+	/// The default: `1.50 MB/s`, `8.00 Mbps`.
+	pub fn english() -> Self {
+		Self {
+			decimal_separator: '.',
+			unit_separator: " ".to_string(),
+			byte_symbol: "B".to_string(),
+			bit_symbol: "bps".to_string(),
+			per_second: "/s".to_string(),
+		}
+	}
+
+	/// `1,50 Mo/s`, `8,00 Mbit/s`, with a narrow no-break space before the unit.
+	pub fn french() -> Self {
+		Self {
+			decimal_separator: ',',
+			unit_separator: "\u{202F}".to_string(),
+			byte_symbol: "o".to_string(),
+			bit_symbol: "bit/s".to_string(),
+			..Self::english()
+		}
+	}
+
+	/// `1,50 MB/s`, `8,00 Mbit/s`, as written in German, Spanish, Italian, and most of Europe.
+	pub fn decimal_comma() -> Self {
+		Self {
+			decimal_separator: ',',
+			bit_symbol: "bit/s".to_string(),
+			..Self::english()
+		}
+	}
+
+	/// Picks a preset from a BCP 47 tag or Windows locale name such as `de-DE` or `fr_CA`.
+	/// Returns `None` for languages without a preset; fill in the fields yourself for those.
+	pub fn for_language_tag(tag: &str) -> Option<Self> {
+		let language = tag
+			.split(['-', '_'])
+			.next()
+			.unwrap_or_default()
+			.to_ascii_lowercase();
+		match language.as_str() {
+			"en" => Some(Self::english()),
+			"fr" => Some(Self::french()),
+			"de" | "es" | "it" | "nl" | "pt" | "pl" | "cs" | "sv" | "da" | "fi" | "nb" | "tr" => Some(Self::decimal_comma()),
+			_ => None,
+		}
+	}
+
+	pub fn with_decimal_separator(mut self, decimal_separator: char) -> Self {
+		self.decimal_separator = decimal_separator;
+		self
+	}
+
+	pub fn with_unit_separator(mut self, unit_separator: impl Into<String>) -> Self {
+		self.unit_separator = unit_separator.into();
+		self
+	}
+
+	pub fn with_byte_symbol(mut self, byte_symbol: impl Into<String>) -> Self {
+		self.byte_symbol = byte_symbol.into();
+		self
+	}
+
+	pub fn with_bit_symbol(mut self, bit_symbol: impl Into<String>) -> Self {
+		self.bit_symbol = bit_symbol.into();
+		self
+	}
+
+	pub fn with_per_second(mut self, per_second: impl Into<String>) -> Self {
+		self.per_second = per_second.into();
+		self
+	}
+
+	fn localize_unit(&self, unit: &str) -> String {
+		if let Some(prefix) = unit.strip_suffix("bps") {
+			format!("{prefix}{}", self.bit_symbol)
+		} else if let Some(prefix) = unit.strip_suffix("B/s") {
+			format!("{prefix}{}{}", self.byte_symbol, self.per_second)
+		} else {
+			unit.to_string()
+		}
+	}
+
+	fn localize_number(&self, number: String) -> String {
+		if self.decimal_separator == '.' {
+			number
+		} else {
+			number.replace('.', self.decimal_separator.encode_utf8(&mut [0; 4]))
+		}
+	}
+}
+
+impl Default for Locale {
+	fn default() -> Self {
+		Self::english()
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FormatOptions {
+	pub unit_system: UnitSystem,
+	pub decimal_places: usize,
+	pub bits_or_bytes: DataUnit,
+	#[cfg_attr(feature = "serde", serde(default))]
+	pub locale: Locale,
+}
+
+impl FormatOptions {
+	pub fn new(unit_system: UnitSystem, decimal_places: usize, bits_or_bytes: DataUnit) -> Self {
+		Self {
+			unit_system,
+			decimal_places,
+			bits_or_bytes,
+			locale: Locale::default(),
+		}
+	}
+
+	pub fn bytes() -> Self {
+		Self::new(UnitSystem::Iec, 2, DataUnit::Bytes)
+	}
+
+	pub fn bits() -> Self {
+		Self::new(UnitSystem::Si, 2, DataUnit::Bits)
+	}
+
+	pub fn with_unit_system(mut self, unit_system: UnitSystem) -> Self {
+		self.unit_system = unit_system;
+		self
+	}
+
+	pub fn with_decimal_places(mut self, decimal_places: usize) -> Self {
+		self.decimal_places = decimal_places;
+		self
+	}
+
+	pub fn with_bits_or_bytes(mut self, bits_or_bytes: DataUnit) -> Self {
+		self.bits_or_bytes = bits_or_bytes;
+		self
+	}
+
+	pub fn with_locale(mut self, locale: Locale) -> Self {
+		self.locale = locale;
+		self
+	}
+
+	fn units(&self) -> &'static [&'static str] {
+		match (self.unit_system, self.bits_or_bytes) {
+			(UnitSystem::Si, DataUnit::Bytes) => &["B/s", "kB/s", "MB/s", "GB/s", "TB/s"],
+			(UnitSystem::Iec, DataUnit::Bytes) => &["B/s", "KiB/s", "MiB/s", "GiB/s", "TiB/s"],
+			(UnitSystem::Si, DataUnit::Bits) => &["bps", "Kbps", "Mbps", "Gbps", "Tbps"],
+			(UnitSystem::Iec, DataUnit::Bits) => &["bps", "Kibps", "Mibps", "Gibps", "Tibps"],
+		}
+	}
+}
+
+impl Default for FormatOptions {
+	fn default() -> Self {
+		Self::bytes()
+	}
+}
+
+pub fn format_rate(rate: impl Into<ByteRate>, options: &FormatOptions) -> String {
+	let units = options.units();
+	let bytes_per_sec = rate.into().bytes_per_sec();
+	let value = match options.bits_or_bytes {
+		DataUnit::Bytes => bytes_per_sec as f64,
+		DataUnit::Bits => (bytes_per_sec as f64) * 8.0,
+	};
+	let base = options.unit_system.base();
+	let mut size = value;
+	let mut unit_index = 0;
+
+	while size >= base && unit_index < units.len() - 1 {
+		size /= base;
+		unit_index += 1;
+	}
+
+	let number = if unit_index == 0 {
+		(value as u64).to_string()
+	} else {
+		format!("{:.*}", options.decimal_places, size)
+	};
+	let locale = &options.locale;
+	format!("{}{}{}", locale.localize_number(number), locale.unit_separator, locale.localize_unit(units[unit_index]))
+}
+
+/// Parses a number with a `ms`, `s`, `m`, or `h` suffix, e.g. `500ms`, `1.5s`, or `10m`.
+pub fn parse_duration(value: &str) -> Option<Duration> {
+	let value = value.trim();
+	if let Some(millis) = value.strip_suffix("ms") {
+		return millis.trim().parse().ok().map(Duration::from_millis);
+	}
+
+	let (number, unit_secs) = if let Some(seconds) = value.strip_suffix('s') {
+		(seconds, 1.0)
+	} else if let Some(minutes) = value.strip_suffix('m') {
+		(minutes, 60.0)
+	} else if let Some(hours) = value.strip_suffix('h') {
+		(hours, 3600.0)
+	} else {
+		return None;
+	};
+
+	let number = number.trim().parse::<f64>().ok()?;
+	Duration::try_from_secs_f64(number * unit_secs).ok()
+}
+
+/// Short human-readable form: `850ms`, `42.0s`, `12m 05s`, or `3h 20m`.
+pub fn format_duration(duration: Duration) -> String {
+	let secs = duration.as_secs();
+	if secs == 0 {
+		format!("{}ms", duration.as_millis())
+	} else if secs < 60 {
+		format!("{:.1}s", duration.as_secs_f64())
+	} else if secs < 3600 {
+		format!("{}m {:02}s", secs / 60, secs % 60)
+	} else {
+		format!("{}h {:02}m", secs / 3600, (secs % 3600) / 60)
+	}
+}
+
+/// [`format_duration`] for an estimate such as [`NetworkSpeed::eta_for`](super::speed::NetworkSpeed::eta_for),
+/// with `—` when there is none because the rate is zero.
+pub fn format_eta(eta: Option<Duration>) -> String {
+	eta.map(format_duration).unwrap_or_else(|| "—".to_string())
+}
+
+/// Renders `values` as Unicode block characters, averaging into at most `width` columns.
+///
+/// Fewer values than `width` produce a shorter line rather than padding.
+pub fn render_sparkline(values: &[u64], width: usize) -> String {
+	const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+	if values.is_empty() || width == 0 {
+		return String::new();
+	}
+
+	let columns: Vec<u64> = if values.len() <= width {
+		values.to_vec()
+	} else {
+		(0..width)
+			.map(|column| {
+				let start = (column * values.len()) / width;
+				let end = (((column + 1) * values.len()) / width).max(start + 1);
+				let bucket = &values[start..end];
+				(bucket
+					.iter()
+					.map(|&v| v as u128)
+					.sum::<u128>() / (bucket.len() as u128)) as u64
+			})
+			.collect()
+	};
+
+	let max = columns.iter().copied().max().unwrap_or(0);
+	columns
+		.iter()
+		.map(|&value| {
+			if max == 0 {
+				BLOCKS[0]
+			} else {
+				let level = ((value as u128) * ((BLOCKS.len() - 1) as u128)) / (max as u128);
+				BLOCKS[level as usize]
+			}
+		})
+		.collect()
+}
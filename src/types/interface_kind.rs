@@ -0,0 +1,85 @@
+use std::fmt;
+
+#[cfg(feature = "serde")]
+use serde::{ Deserialize, Serialize };
+
+/// Interface type reported by Windows (`IFTYPE`, e.g. `IF_TYPE_ETHERNET_CSMACD`).
+///
+/// Types without a named variant are kept as `Other` with their raw value. Serialized as the raw
+/// number, so configs written with plain type IDs keep loading.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(from = "u32", into = "u32"))]
+pub enum InterfaceKind {
+	Ethernet,
+	Wifi,
+	Loopback,
+	Tunnel,
+	Wwan,
+	Other(u32),
+}
+
+impl InterfaceKind {
+	pub const ETHERNET_ID: u32 = 6;
+	pub const LOOPBACK_ID: u32 = 24;
+	pub const WIFI_ID: u32 = 71;
+	pub const TUNNEL_ID: u32 = 131;
+	pub const WWAN_ID: u32 = 243;
+
+	pub fn as_u32(&self) -> u32 {
+		match self {
+			InterfaceKind::Ethernet => Self::ETHERNET_ID,
+			InterfaceKind::Wifi => Self::WIFI_ID,
+			InterfaceKind::Loopback => Self::LOOPBACK_ID,
+			InterfaceKind::Tunnel => Self::TUNNEL_ID,
+			InterfaceKind::Wwan => Self::WWAN_ID,
+			InterfaceKind::Other(id) => *id,
+		}
+	}
+
+	pub fn name(&self) -> &'static str {
+		match self.as_u32() {
+			1 => "Other",
+			Self::ETHERNET_ID => "Ethernet",
+			9 => "Token Ring",
+			23 => "PPP",
+			Self::LOOPBACK_ID => "Loopback",
+			37 => "Serial",
+			Self::WIFI_ID => "Wi-Fi",
+			Self::TUNNEL_ID => "Tunnel",
+			144 => "IEEE 1394",
+			237 => "WiMAX",
+			// 243 is 3GPP mobile broadband, 244 its 3GPP2 (CDMA) counterpart.
+			Self::WWAN_ID | 244 => "WWAN",
+			_ => "Unknown",
+		}
+	}
+}
+
+impl From<u32> for InterfaceKind {
+	fn from(id: u32) -> Self {
+		match id {
+			Self::ETHERNET_ID => InterfaceKind::Ethernet,
+			Self::WIFI_ID => InterfaceKind::Wifi,
+			Self::LOOPBACK_ID => InterfaceKind::Loopback,
+			Self::TUNNEL_ID => InterfaceKind::Tunnel,
+			Self::WWAN_ID => InterfaceKind::Wwan,
+			other => InterfaceKind::Other(other),
+		}
+	}
+}
+
+impl From<InterfaceKind> for u32 {
+	fn from(kind: InterfaceKind) -> Self {
+		kind.as_u32()
+	}
+}
+
+impl fmt::Display for InterfaceKind {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			InterfaceKind::Other(id) => write!(f, "{} ({id})", self.name()),
+			_ => f.write_str(self.name()),
+		}
+	}
+}
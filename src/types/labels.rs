@@ -0,0 +1,43 @@
+use std::collections::BTreeMap;
+
+#[cfg(feature = "serde")]
+use serde::{ Deserialize, Serialize };
+
+/// Identifies a tracker in exported data: an optional name plus free-form key/value labels
+/// (e.g. `group=office`, `interface=wifi`), so several monitors stay distinguishable downstream.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MonitorLabels {
+	#[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+	pub name: Option<String>,
+	#[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "BTreeMap::is_empty"))]
+	pub labels: BTreeMap<String, String>,
+}
+
+impl MonitorLabels {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn named(name: impl Into<String>) -> Self {
+		Self::new().with_name(name)
+	}
+
+	pub fn with_name(mut self, name: impl Into<String>) -> Self {
+		self.name = Some(name.into());
+		self
+	}
+
+	pub fn with_label(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+		self.labels.insert(key.into(), value.into());
+		self
+	}
+
+	pub fn get(&self, key: &str) -> Option<&str> {
+		self.labels.get(key).map(String::as_str)
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.name.is_none() && self.labels.is_empty()
+	}
+}
@@ -1,7 +1,33 @@
+pub mod activity;
+pub mod checkpoint;
 pub mod config;
+pub mod congestion;
 pub mod error;
+pub mod format;
+pub mod interface_kind;
+pub mod labels;
+pub mod monitor_stats;
+pub mod overhead;
+pub mod packets;
+pub mod rate;
+pub mod schedule;
 pub mod speed;
+pub mod template;
+pub mod traffic_class;
 
+pub use activity::*;
+pub use checkpoint::*;
 pub use config::*;
+pub use congestion::*;
 pub use error::*;
+pub use format::*;
+pub use interface_kind::InterfaceKind;
+pub use labels::*;
+pub use monitor_stats::*;
+pub use overhead::*;
+pub use packets::*;
+pub use rate::*;
+pub use schedule::*;
 pub use speed::*;
+pub use template::*;
+pub use traffic_class::*;
@@ -0,0 +1,160 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::time::Duration;
+
+use super::labels::MonitorLabels;
+
+/// Upper bounds of the [`LatencyHistogram`] buckets; slower reads land in the `+Inf` bucket.
+pub const LATENCY_BUCKETS: [Duration; 10] = [
+	Duration::from_micros(100),
+	Duration::from_micros(250),
+	Duration::from_micros(500),
+	Duration::from_millis(1),
+	Duration::from_millis(2),
+	Duration::from_millis(5),
+	Duration::from_millis(10),
+	Duration::from_millis(25),
+	Duration::from_millis(100),
+	Duration::from_millis(500),
+];
+
+/// Distribution of counter enumeration times, bucketed by [`LATENCY_BUCKETS`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LatencyHistogram {
+	/// Observations per bucket (not cumulative); the last entry is the `+Inf` bucket.
+	pub counts: [u64; LATENCY_BUCKETS.len() + 1],
+	pub sum: Duration,
+}
+
+impl LatencyHistogram {
+	pub fn record(&mut self, latency: Duration) {
+		let bucket = LATENCY_BUCKETS.iter()
+			.position(|&bound| latency <= bound)
+			.unwrap_or(LATENCY_BUCKETS.len());
+		self.counts[bucket] += 1;
+		self.sum += latency;
+	}
+
+	pub fn count(&self) -> u64 {
+		self.counts.iter().sum()
+	}
+
+	/// Upper bound of the bucket holding the `quantile` (0.0–1.0) observation, or `None` when
+	/// empty or the observation is in the `+Inf` bucket.
+	pub fn quantile_upper_bound(&self, quantile: f64) -> Option<Duration> {
+		let count = self.count();
+		if count == 0 {
+			return None;
+		}
+		let rank = ((count as f64) * quantile.clamp(0.0, 1.0)).ceil().max(1.0) as u64;
+		let mut seen = 0;
+		for (bucket, &observations) in self.counts.iter().enumerate() {
+			seen += observations;
+			if seen >= rank {
+				return LATENCY_BUCKETS.get(bucket).copied();
+			}
+		}
+		None
+	}
+}
+
+/// Operational metrics of a monitor itself, for alerting on the monitor degrading rather than on
+/// the network. See [`NetworkMonitor::stats`](crate::NetworkMonitor::stats).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MonitorStats {
+	/// Measurements that produced a speed.
+	pub samples: u64,
+	/// Failed measurements by [`NetworkError::kind`](crate::NetworkError::kind).
+	pub failures: BTreeMap<&'static str, u64>,
+	/// Time spent in each counter enumeration.
+	pub enumeration_latency: LatencyHistogram,
+	/// Events that could not be delivered because their subscriber had gone away; only counted by
+	/// an [`EventMonitor`](crate::EventMonitor).
+	pub dropped_events: u64,
+}
+
+impl MonitorStats {
+	pub fn failure_count(&self) -> u64 {
+		self.failures.values().sum()
+	}
+
+	#[cfg(feature = "platform")]
+	pub(crate) fn record_result<T>(&mut self, result: &super::error::Result<T>) {
+		match result {
+			Ok(_) => {
+				self.samples += 1;
+			}
+			Err(err) => {
+				*self.failures.entry(err.kind()).or_insert(0) += 1;
+			}
+		}
+	}
+
+	/// Renders the stats in the Prometheus text exposition format, with metric names starting
+	/// `network_speed_monitor_`. The labels' name becomes a `monitor` label, and every label is
+	/// attached to every series.
+	pub fn to_prometheus(&self, labels: &MonitorLabels) -> String {
+		let labels: Vec<String> = labels.name
+			.iter()
+			.map(|name| ("monitor", name.as_str()))
+			.chain(labels.labels.iter().map(|(key, value)| (key.as_str(), value.as_str())))
+			.map(|(key, value)| format!("{}=\"{}\"", prometheus_name(key), escape_label_value(value)))
+			.collect();
+		let series = |extra: Option<String>| {
+			let all: Vec<&str> = labels.iter().map(String::as_str).chain(extra.as_deref()).collect();
+			if all.is_empty() { String::new() } else { format!("{{{}}}", all.join(",")) }
+		};
+
+		let mut out = String::new();
+		let _ = writeln!(out, "# HELP network_speed_monitor_samples_total Measurements that produced a speed.");
+		let _ = writeln!(out, "# TYPE network_speed_monitor_samples_total counter");
+		let _ = writeln!(out, "network_speed_monitor_samples_total{} {}", series(None), self.samples);
+
+		let _ = writeln!(out, "# HELP network_speed_monitor_failures_total Failed measurements by error kind.");
+		let _ = writeln!(out, "# TYPE network_speed_monitor_failures_total counter");
+		for (kind, count) in &self.failures {
+			let _ = writeln!(out, "network_speed_monitor_failures_total{} {count}", series(Some(format!("kind=\"{kind}\""))));
+		}
+
+		let _ = writeln!(out, "# HELP network_speed_monitor_enumeration_seconds Time spent reading interface counters.");
+		let _ = writeln!(out, "# TYPE network_speed_monitor_enumeration_seconds histogram");
+		let mut cumulative = 0;
+		for (bucket, &count) in self.enumeration_latency.counts.iter().enumerate() {
+			cumulative += count;
+			let bound = LATENCY_BUCKETS.get(bucket)
+				.map(|bound| bound.as_secs_f64().to_string())
+				.unwrap_or_else(|| "+Inf".to_string());
+			let _ = writeln!(
+				out,
+				"network_speed_monitor_enumeration_seconds_bucket{} {cumulative}",
+				series(Some(format!("le=\"{bound}\"")))
+			);
+		}
+		let _ = writeln!(
+			out,
+			"network_speed_monitor_enumeration_seconds_sum{} {}",
+			series(None),
+			self.enumeration_latency.sum.as_secs_f64()
+		);
+		let _ = writeln!(out, "network_speed_monitor_enumeration_seconds_count{} {cumulative}", series(None));
+
+		let _ = writeln!(out, "# HELP network_speed_monitor_dropped_events_total Events not delivered to a subscriber.");
+		let _ = writeln!(out, "# TYPE network_speed_monitor_dropped_events_total counter");
+		let _ = writeln!(out, "network_speed_monitor_dropped_events_total{} {}", series(None), self.dropped_events);
+		out
+	}
+}
+
+/// Replaces characters Prometheus does not allow in label names with `_`.
+fn prometheus_name(name: &str) -> String {
+	name.chars()
+		.enumerate()
+		.map(|(position, c)| {
+			if c.is_ascii_alphabetic() || c == '_' || (position > 0 && c.is_ascii_digit()) { c } else { '_' }
+		})
+		.collect()
+}
+
+fn escape_label_value(value: &str) -> String {
+	value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
@@ -0,0 +1,59 @@
+use std::time::{ Duration, Instant };
+
+/// Cost of the monitor itself: time spent inside the OS counter queries.
+///
+/// Deliberate sleeps in windowed/sampled precision modes are not counted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OverheadStats {
+	/// Completed `measure_speed` calls (successful or not, excluding throttled ones).
+	pub calls: u64,
+	/// Calls refused because the overhead budget required a longer gap.
+	pub throttled_calls: u64,
+	pub wall_time: Duration,
+	/// Thread CPU time (user + kernel). Windows accounts this at scheduler-tick granularity,
+	/// so short calls often register as zero; compare totals over many calls.
+	pub cpu_time: Duration,
+	pub max_call_wall_time: Duration,
+	pub last_call_wall_time: Duration,
+	pub last_call_at: Option<Instant>,
+}
+
+impl OverheadStats {
+	pub fn average_wall_time(&self) -> Duration {
+		if self.calls == 0 {
+			return Duration::ZERO;
+		}
+		self.wall_time / (self.calls.min(u32::MAX as u64) as u32)
+	}
+
+	pub fn average_cpu_time(&self) -> Duration {
+		if self.calls == 0 {
+			return Duration::ZERO;
+		}
+		self.cpu_time / (self.calls.min(u32::MAX as u64) as u32)
+	}
+
+	/// Minimum gap after the last call that keeps query time within `budget` (fraction of wall time).
+	pub fn required_interval(&self, budget: f64) -> Duration {
+		if budget <= 0.0 {
+			return Duration::MAX;
+		}
+		Duration::try_from_secs_f64(self.last_call_wall_time.as_secs_f64() / budget).unwrap_or(Duration::MAX)
+	}
+
+	#[cfg(feature = "platform")]
+	pub(crate) fn record_query(&mut self, wall_time: Duration, cpu_time: Duration) {
+		self.wall_time += wall_time;
+		self.cpu_time += cpu_time;
+	}
+
+	/// Closes a `measure_speed` call; `before` is the snapshot taken when the call started.
+	#[cfg(feature = "platform")]
+	pub(crate) fn finish_call(&mut self, before: &OverheadStats) {
+		let call_wall_time = self.wall_time.saturating_sub(before.wall_time);
+		self.calls += 1;
+		self.last_call_wall_time = call_wall_time;
+		self.max_call_wall_time = self.max_call_wall_time.max(call_wall_time);
+		self.last_call_at = Some(Instant::now());
+	}
+}
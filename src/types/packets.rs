@@ -0,0 +1,127 @@
+use std::time::Duration;
+
+#[cfg(feature = "serde")]
+use serde::{ Deserialize, Serialize };
+
+/// Cumulative packet, error, and discard counters of an interface.
+///
+/// Errors are packets that could not be sent or delivered because of a fault (bad checksum,
+/// framing, carrier loss); discards were dropped without one, usually for lack of buffer space.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PacketCounters {
+	pub packets_sent: u64,
+	pub packets_received: u64,
+	pub send_errors: u64,
+	pub receive_errors: u64,
+	pub send_discards: u64,
+	pub receive_discards: u64,
+}
+
+impl PacketCounters {
+	/// Per-field difference; counters that went backwards (adapter reset) yield zero.
+	pub fn delta_since(&self, earlier: &PacketCounters) -> PacketCounters {
+		PacketCounters {
+			packets_sent: self.packets_sent.saturating_sub(earlier.packets_sent),
+			packets_received: self.packets_received.saturating_sub(earlier.packets_received),
+			send_errors: self.send_errors.saturating_sub(earlier.send_errors),
+			receive_errors: self.receive_errors.saturating_sub(earlier.receive_errors),
+			send_discards: self.send_discards.saturating_sub(earlier.send_discards),
+			receive_discards: self.receive_discards.saturating_sub(earlier.receive_discards),
+		}
+	}
+
+	/// Per-field sum, e.g. to total several interfaces.
+	pub fn saturating_add(&self, other: &PacketCounters) -> PacketCounters {
+		PacketCounters {
+			packets_sent: self.packets_sent.saturating_add(other.packets_sent),
+			packets_received: self.packets_received.saturating_add(other.packets_received),
+			send_errors: self.send_errors.saturating_add(other.send_errors),
+			receive_errors: self.receive_errors.saturating_add(other.receive_errors),
+			send_discards: self.send_discards.saturating_add(other.send_discards),
+			receive_discards: self.receive_discards.saturating_add(other.receive_discards),
+		}
+	}
+
+	pub fn total_packets(&self) -> u64 {
+		self.packets_sent.saturating_add(self.packets_received)
+	}
+
+	pub fn total_errors(&self) -> u64 {
+		self.send_errors.saturating_add(self.receive_errors)
+	}
+
+	pub fn total_discards(&self) -> u64 {
+		self.send_discards.saturating_add(self.receive_discards)
+	}
+}
+
+/// Packet, error, and discard counts over an interval, from
+/// [`NetworkMonitor::measure_stats_delta`](crate::NetworkMonitor::measure_stats_delta).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NetworkStatsDelta {
+	pub counters: PacketCounters,
+	pub elapsed: Duration,
+}
+
+impl NetworkStatsDelta {
+	pub fn between(earlier: &PacketCounters, later: &PacketCounters, elapsed: Duration) -> Self {
+		Self {
+			counters: later.delta_since(earlier),
+			elapsed,
+		}
+	}
+
+	pub fn packets_sent_per_sec(&self) -> f64 {
+		self.per_sec(self.counters.packets_sent)
+	}
+
+	pub fn packets_received_per_sec(&self) -> f64 {
+		self.per_sec(self.counters.packets_received)
+	}
+
+	pub fn packets_per_sec(&self) -> f64 {
+		self.per_sec(self.counters.total_packets())
+	}
+
+	pub fn errors_per_sec(&self) -> f64 {
+		self.per_sec(self.counters.total_errors())
+	}
+
+	pub fn discards_per_sec(&self) -> f64 {
+		self.per_sec(self.counters.total_discards())
+	}
+
+	/// Share of outbound packets that failed with an error, or `None` when nothing was sent.
+	pub fn send_error_ratio(&self) -> Option<f64> {
+		ratio(self.counters.send_errors, self.counters.packets_sent)
+	}
+
+	/// Share of inbound packets that arrived with an error, or `None` when nothing was received.
+	pub fn receive_error_ratio(&self) -> Option<f64> {
+		ratio(self.counters.receive_errors, self.counters.packets_received)
+	}
+
+	/// Share of packets in either direction that were discarded, or `None` when there were none.
+	pub fn discard_ratio(&self) -> Option<f64> {
+		ratio(self.counters.total_discards(), self.counters.total_packets())
+	}
+
+	fn per_sec(&self, count: u64) -> f64 {
+		let seconds = self.elapsed.as_secs_f64();
+		if seconds <= 0.0 {
+			return 0.0;
+		}
+		(count as f64) / seconds
+	}
+}
+
+/// `failed` out of `failed + succeeded`: failed packets are not counted as sent or received.
+fn ratio(failed: u64, succeeded: u64) -> Option<f64> {
+	let attempted = failed.saturating_add(succeeded);
+	if attempted == 0 {
+		return None;
+	}
+	Some((failed as f64) / (attempted as f64))
+}
@@ -0,0 +1,130 @@
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{ Add, Sub };
+
+#[cfg(feature = "serde")]
+use serde::{ Deserialize, Serialize };
+
+use super::speed::format_bytes_per_second;
+
+/// A transfer rate stored as whole bytes per second.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
+pub struct ByteRate(u64);
+
+impl ByteRate {
+	pub const ZERO: ByteRate = ByteRate(0);
+	pub const MAX: ByteRate = ByteRate(u64::MAX);
+
+	pub const fn from_bytes_per_sec(bytes_per_sec: u64) -> Self {
+		Self(bytes_per_sec)
+	}
+
+	/// Builds a rate from bits per second (the unit link speeds are quoted in).
+	pub const fn from_bps(bits_per_sec: u64) -> Self {
+		Self(bits_per_sec / 8)
+	}
+
+	pub fn from_kbps(kbps: f64) -> Self {
+		Self::from_bits_f64(kbps * 1_000.0)
+	}
+
+	pub fn from_mbps(mbps: f64) -> Self {
+		Self::from_bits_f64(mbps * 1_000_000.0)
+	}
+
+	pub fn from_gbps(gbps: f64) -> Self {
+		Self::from_bits_f64(gbps * 1_000_000_000.0)
+	}
+
+	pub const fn bytes_per_sec(&self) -> u64 {
+		self.0
+	}
+
+	pub const fn bits_per_sec(&self) -> u64 {
+		self.0.saturating_mul(8)
+	}
+
+	pub fn as_kbps(&self) -> f64 {
+		((self.0 as f64) * 8.0) / 1_000.0
+	}
+
+	pub fn as_mbps(&self) -> f64 {
+		((self.0 as f64) * 8.0) / 1_000_000.0
+	}
+
+	pub fn as_gbps(&self) -> f64 {
+		((self.0 as f64) * 8.0) / 1_000_000_000.0
+	}
+
+	pub const fn is_zero(&self) -> bool {
+		self.0 == 0
+	}
+
+	pub const fn saturating_add(self, other: ByteRate) -> ByteRate {
+		ByteRate(self.0.saturating_add(other.0))
+	}
+
+	pub const fn saturating_sub(self, other: ByteRate) -> ByteRate {
+		ByteRate(self.0.saturating_sub(other.0))
+	}
+
+	fn from_bits_f64(bits_per_sec: f64) -> Self {
+		if bits_per_sec.is_nan() || bits_per_sec <= 0.0 {
+			return Self::ZERO;
+		}
+		Self((bits_per_sec / 8.0) as u64)
+	}
+}
+
+impl From<u64> for ByteRate {
+	fn from(bytes_per_sec: u64) -> Self {
+		Self(bytes_per_sec)
+	}
+}
+
+impl From<ByteRate> for u64 {
+	fn from(rate: ByteRate) -> Self {
+		rate.0
+	}
+}
+
+impl PartialEq<u64> for ByteRate {
+	fn eq(&self, other: &u64) -> bool {
+		self.0 == *other
+	}
+}
+
+impl PartialOrd<u64> for ByteRate {
+	fn partial_cmp(&self, other: &u64) -> Option<std::cmp::Ordering> {
+		self.0.partial_cmp(other)
+	}
+}
+
+impl Add for ByteRate {
+	type Output = ByteRate;
+
+	fn add(self, other: ByteRate) -> ByteRate {
+		self.saturating_add(other)
+	}
+}
+
+impl Sub for ByteRate {
+	type Output = ByteRate;
+
+	fn sub(self, other: ByteRate) -> ByteRate {
+		self.saturating_sub(other)
+	}
+}
+
+impl Sum for ByteRate {
+	fn sum<I: Iterator<Item = ByteRate>>(iter: I) -> Self {
+		iter.fold(ByteRate::ZERO, |acc, rate| acc.saturating_add(rate))
+	}
+}
+
+impl fmt::Display for ByteRate {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(&format_bytes_per_second(*self))
+	}
+}
@@ -0,0 +1,242 @@
+use std::str::FromStr;
+use std::time::Duration;
+
+#[cfg(feature = "serde")]
+use serde::{ Deserialize, Serialize };
+
+use super::error::{ NetworkError, Result };
+
+const MINUTES_PER_DAY: u16 = 24 * 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Weekday {
+	Monday,
+	Tuesday,
+	Wednesday,
+	Thursday,
+	Friday,
+	Saturday,
+	Sunday,
+}
+
+impl Weekday {
+	pub const ALL: [Weekday; 7] = [
+		Weekday::Monday,
+		Weekday::Tuesday,
+		Weekday::Wednesday,
+		Weekday::Thursday,
+		Weekday::Friday,
+		Weekday::Saturday,
+		Weekday::Sunday,
+	];
+	pub const WORKDAYS: [Weekday; 5] = [
+		Weekday::Monday,
+		Weekday::Tuesday,
+		Weekday::Wednesday,
+		Weekday::Thursday,
+		Weekday::Friday,
+	];
+	pub const WEEKEND: [Weekday; 2] = [Weekday::Saturday, Weekday::Sunday];
+
+	/// Days since Sunday, as C's `tm_wday` and Windows' `wDayOfWeek` count them.
+	pub fn from_days_since_sunday(days: u32) -> Self {
+		match days % 7 {
+			0 => Weekday::Sunday,
+			days => Weekday::ALL[(days - 1) as usize],
+		}
+	}
+
+	pub fn previous(self) -> Self {
+		Weekday::ALL[((self as usize) + 6) % 7]
+	}
+
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			Weekday::Monday => "mon",
+			Weekday::Tuesday => "tue",
+			Weekday::Wednesday => "wed",
+			Weekday::Thursday => "thu",
+			Weekday::Friday => "fri",
+			Weekday::Saturday => "sat",
+			Weekday::Sunday => "sun",
+		}
+	}
+}
+
+/// A local wall-clock time within the week, which [`Schedule`]s are evaluated against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LocalTime {
+	pub weekday: Weekday,
+	/// Minutes since local midnight, `0..1440`.
+	pub minute: u16,
+}
+
+impl LocalTime {
+	pub fn new(weekday: Weekday, hour: u8, minute: u8) -> Self {
+		Self {
+			weekday,
+			minute: ((hour as u16) * 60 + (minute as u16)) % MINUTES_PER_DAY,
+		}
+	}
+}
+
+/// A recurring stretch of the day, e.g. 22:00–07:00, and what changes while it is active.
+///
+/// A window whose end is not after its start runs past midnight into the next day; equal start and
+/// end cover a full 24 hours. `days` are the days the window starts on, so a Friday 22:00–07:00
+/// window also covers early Saturday.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ScheduleWindow {
+	/// Days the window starts on; empty means every day.
+	#[cfg_attr(feature = "serde", serde(default))]
+	pub days: Vec<Weekday>,
+	/// Start, in minutes since local midnight.
+	pub start: u16,
+	/// End (exclusive), in minutes since local midnight.
+	pub end: u16,
+	/// Drop alert events while the window is active.
+	#[cfg_attr(feature = "serde", serde(default))]
+	pub mute_alerts: bool,
+	/// Multiplies the sampling interval while the window is active; 1 leaves it unchanged.
+	#[cfg_attr(feature = "serde", serde(default = "default_interval_factor"))]
+	pub interval_factor: u32,
+}
+
+#[cfg(feature = "serde")]
+fn default_interval_factor() -> u32 {
+	1
+}
+
+impl ScheduleWindow {
+	/// A window from `start` to `end`, both `HH:MM`, on every day and without effects yet.
+	pub fn new(start: &str, end: &str) -> Result<Self> {
+		Ok(Self {
+			days: Vec::new(),
+			start: parse_time_of_day(start)?,
+			end: parse_time_of_day(end)?,
+			mute_alerts: false,
+			interval_factor: 1,
+		})
+	}
+
+	/// Alerts muted from `start` to `end`, e.g. `quiet_hours("22:00", "07:00")`.
+	pub fn quiet_hours(start: &str, end: &str) -> Result<Self> {
+		Ok(Self::new(start, end)?.with_muted_alerts())
+	}
+
+	pub fn on(mut self, days: &[Weekday]) -> Self {
+		self.days = days.to_vec();
+		self
+	}
+
+	pub fn with_muted_alerts(mut self) -> Self {
+		self.mute_alerts = true;
+		self
+	}
+
+	pub fn with_interval_factor(mut self, factor: u32) -> Self {
+		self.interval_factor = factor;
+		self
+	}
+
+	pub fn validate(&self) -> Result<()> {
+		if self.start >= MINUTES_PER_DAY || self.end >= MINUTES_PER_DAY {
+			return Err(NetworkError::InvalidConfiguration {
+				field: "schedule window start and end must be before 24:00".to_string(),
+			});
+		}
+		if self.interval_factor == 0 {
+			return Err(NetworkError::InvalidConfiguration {
+				field: "schedule window interval_factor must be > 0".to_string(),
+			});
+		}
+		Ok(())
+	}
+
+	pub fn contains(&self, at: LocalTime) -> bool {
+		let starts_on = |day: Weekday| self.days.is_empty() || self.days.contains(&day);
+		if self.start < self.end {
+			starts_on(at.weekday) && at.minute >= self.start && at.minute < self.end
+		} else {
+			(starts_on(at.weekday) && at.minute >= self.start) || (starts_on(at.weekday.previous()) && at.minute < self.end)
+		}
+	}
+}
+
+/// Parses `HH:MM` (24-hour) into minutes since midnight.
+pub(crate) fn parse_time_of_day(value: &str) -> Result<u16> {
+	let invalid = || NetworkError::InvalidConfiguration {
+		field: format!("schedule time must be HH:MM, got {value:?}"),
+	};
+	let (hours, minutes) = value.trim().split_once(':').ok_or_else(invalid)?;
+	let hours: u16 = hours.parse().map_err(|_| invalid())?;
+	let minutes: u16 = minutes.parse().map_err(|_| invalid())?;
+	if hours >= 24 || minutes >= 60 {
+		return Err(invalid());
+	}
+	Ok(hours * 60 + minutes)
+}
+
+impl FromStr for ScheduleWindow {
+	type Err = NetworkError;
+
+	/// Parses `HH:MM-HH:MM`, e.g. `22:00-07:00`, into a window on every day.
+	fn from_str(value: &str) -> Result<Self> {
+		let (start, end) = value.split_once('-').ok_or_else(|| NetworkError::InvalidConfiguration {
+			field: format!("schedule window must be HH:MM-HH:MM, got {value:?}"),
+		})?;
+		Self::new(start, end)
+	}
+}
+
+/// Recurring windows that mute alerts or slow down sampling, so always-on deployments stay quiet
+/// overnight or sample less on weekends. Times are local; see
+/// [`local_time`](crate::local_time).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Schedule {
+	pub windows: Vec<ScheduleWindow>,
+}
+
+impl Schedule {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn with_window(mut self, window: ScheduleWindow) -> Self {
+		self.windows.push(window);
+		self
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.windows.is_empty()
+	}
+
+	pub fn validate(&self) -> Result<()> {
+		self.windows.iter().try_for_each(ScheduleWindow::validate)
+	}
+
+	pub fn active_windows(&self, at: LocalTime) -> impl Iterator<Item = &ScheduleWindow> {
+		self.windows.iter().filter(move |window| window.contains(at))
+	}
+
+	/// Whether any active window mutes alerts.
+	pub fn alerts_muted(&self, at: LocalTime) -> bool {
+		self.active_windows(at).any(|window| window.mute_alerts)
+	}
+
+	/// The largest interval factor among the active windows, 1 when none is active.
+	pub fn interval_factor(&self, at: LocalTime) -> u32 {
+		self.active_windows(at)
+			.map(|window| window.interval_factor)
+			.max()
+			.unwrap_or(1)
+	}
+
+	/// `interval` scaled by [`interval_factor`](Self::interval_factor).
+	pub fn sampling_interval(&self, interval: Duration, at: LocalTime) -> Duration {
+		interval.saturating_mul(self.interval_factor(at))
+	}
+}
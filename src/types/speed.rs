@@ -1,51 +1,81 @@
-use std::time::Instant;
+use std::fmt;
+use std::time::{ Duration, Instant, SystemTime };
 
 #[cfg(feature = "serde")]
 use serde::{ Deserialize, Serialize };
 
+use super::config::{ NetworkMonitorConfig, WrapStrategy, DEFAULT_COUNTER_WRAP_THRESHOLD };
+use super::error::{ NetworkError, Result };
+use super::format::{ format_rate, FormatOptions };
+use super::rate::ByteRate;
+use super::template::FormatTemplate;
+
+/// Which rate of a sample a threshold applies to, so upload and download can be judged
+/// separately, e.g. alerting only on sustained upload.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Direction {
+	Upload,
+	Download,
+	/// Upload plus download.
+	#[default]
+	Total,
+}
+
+impl Direction {
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			Direction::Upload => "upload",
+			Direction::Download => "download",
+			Direction::Total => "total",
+		}
+	}
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct NetworkSpeed {
-	pub upload_bytes_per_sec: u64,
-	pub download_bytes_per_sec: u64,
+	pub upload_bytes_per_sec: ByteRate,
+	pub download_bytes_per_sec: ByteRate,
+	#[cfg_attr(feature = "serde", serde(skip, default = "Instant::now"))]
 	pub timestamp: Instant,
 }
 
 impl NetworkSpeed {
-	pub fn new(upload: u64, download: u64) -> Self {
+	pub fn new(upload: impl Into<ByteRate>, download: impl Into<ByteRate>) -> Self {
 		Self {
-			upload_bytes_per_sec: upload,
-			download_bytes_per_sec: download,
+			upload_bytes_per_sec: upload.into(),
+			download_bytes_per_sec: download.into(),
 			timestamp: Instant::now(),
 		}
 	}
 
 	pub fn zero() -> Self {
-		Self::new(0, 0)
+		Self::new(ByteRate::ZERO, ByteRate::ZERO)
 	}
 
 	pub fn upload_kbps(&self) -> f64 {
-		((self.upload_bytes_per_sec as f64) * 8.0) / 1_000.0
+		self.upload_bytes_per_sec.as_kbps()
 	}
 
 	pub fn download_kbps(&self) -> f64 {
-		((self.download_bytes_per_sec as f64) * 8.0) / 1_000.0
+		self.download_bytes_per_sec.as_kbps()
 	}
 
 	pub fn upload_mbps(&self) -> f64 {
-		((self.upload_bytes_per_sec as f64) * 8.0) / 1_000_000.0
+		self.upload_bytes_per_sec.as_mbps()
 	}
 
 	pub fn download_mbps(&self) -> f64 {
-		((self.download_bytes_per_sec as f64) * 8.0) / 1_000_000.0
+		self.download_bytes_per_sec.as_mbps()
 	}
 
 	pub fn upload_gbps(&self) -> f64 {
-		((self.upload_bytes_per_sec as f64) * 8.0) / 1_000_000_000.0
+		self.upload_bytes_per_sec.as_gbps()
 	}
 
 	pub fn download_gbps(&self) -> f64 {
-		((self.download_bytes_per_sec as f64) * 8.0) / 1_000_000_000.0
+		self.download_bytes_per_sec.as_gbps()
 	}
 
 	pub fn upload_formatted(&self) -> String {
@@ -57,19 +87,91 @@ impl NetworkSpeed {
 	}
 
 	pub fn upload_bits_formatted(&self) -> String {
-		format_bits_per_second(self.upload_bytes_per_sec * 8)
+		format_bits_per_second(self.upload_bytes_per_sec.bits_per_sec())
 	}
 
 	pub fn download_bits_formatted(&self) -> String {
-		format_bits_per_second(self.download_bytes_per_sec * 8)
+		format_bits_per_second(self.download_bytes_per_sec.bits_per_sec())
 	}
 
-	pub fn total_bytes_per_sec(&self) -> u64 {
+	pub fn upload_formatted_with(&self, options: &FormatOptions) -> String {
+		format_rate(self.upload_bytes_per_sec, options)
+	}
+
+	pub fn download_formatted_with(&self, options: &FormatOptions) -> String {
+		format_rate(self.download_bytes_per_sec, options)
+	}
+
+	pub fn format_with(&self, options: &FormatOptions) -> FormattedSpeed {
+		FormattedSpeed {
+			upload: self.upload_formatted_with(options),
+			download: self.download_formatted_with(options),
+			total: format_rate(self.total_bytes_per_sec(), options),
+		}
+	}
+
+	/// Renders the speed through a [`FormatTemplate`] such as `"↑{up:mbps:.1} ↓{down:mbps:.1}"`.
+	/// Parse the template once with [`FormatTemplate::parse`] when rendering many samples.
+	pub fn format_template(&self, template: &str) -> Result<String> {
+		Ok(FormatTemplate::parse(template)?.render(self))
+	}
+
+	pub fn total_bytes_per_sec(&self) -> ByteRate {
 		self.upload_bytes_per_sec.saturating_add(self.download_bytes_per_sec)
 	}
 
-	pub fn is_active(&self, threshold_bytes_per_sec: u64) -> bool {
-		self.total_bytes_per_sec() > threshold_bytes_per_sec
+	pub fn rate(&self, direction: Direction) -> ByteRate {
+		match direction {
+			Direction::Upload => self.upload_bytes_per_sec,
+			Direction::Download => self.download_bytes_per_sec,
+			Direction::Total => self.total_bytes_per_sec(),
+		}
+	}
+
+	pub fn is_active(&self, threshold: impl Into<ByteRate>) -> bool {
+		self.is_active_in(Direction::Total, threshold)
+	}
+
+	/// Like [`is_active`](Self::is_active), against one direction's rate only.
+	pub fn is_active_in(&self, direction: Direction, threshold: impl Into<ByteRate>) -> bool {
+		self.rate(direction) > threshold.into()
+	}
+
+	/// Change of the total rate relative to `earlier`, in percent. `None` when `earlier` was zero.
+	pub fn percent_change(&self, earlier: &NetworkSpeed) -> Option<f64> {
+		percent_change(earlier.total_bytes_per_sec(), self.total_bytes_per_sec())
+	}
+
+	pub fn upload_percent_change(&self, earlier: &NetworkSpeed) -> Option<f64> {
+		percent_change(earlier.upload_bytes_per_sec, self.upload_bytes_per_sec)
+	}
+
+	pub fn download_percent_change(&self, earlier: &NetworkSpeed) -> Option<f64> {
+		percent_change(earlier.download_bytes_per_sec, self.download_bytes_per_sec)
+	}
+
+	/// Estimates `(sent, received)` bytes over `elapsed`, averaging this rate with `earlier`.
+	pub fn bytes_transferred_since(&self, earlier: &NetworkSpeed, elapsed: Duration) -> (u64, u64) {
+		let seconds = elapsed.as_secs_f64();
+		let estimate = |before: ByteRate, after: ByteRate| {
+			let average = ((before.bytes_per_sec() as f64) + (after.bytes_per_sec() as f64)) / 2.0;
+			(average * seconds) as u64
+		};
+
+		(
+			estimate(earlier.upload_bytes_per_sec, self.upload_bytes_per_sec),
+			estimate(earlier.download_bytes_per_sec, self.download_bytes_per_sec),
+		)
+	}
+
+	/// How long downloading `bytes` takes at the current download rate; `None` while idle.
+	/// Pair with [`format_eta`](super::format::format_eta) for display.
+	pub fn eta_for(&self, bytes: u64) -> Option<Duration> {
+		estimate_transfer_time(bytes, self.download_bytes_per_sec)
+	}
+
+	pub fn upload_eta_for(&self, bytes: u64) -> Option<Duration> {
+		estimate_transfer_time(bytes, self.upload_bytes_per_sec)
 	}
 }
 
@@ -79,11 +181,153 @@ impl Default for NetworkSpeed {
 	}
 }
 
+/// Speed sample with fractional rates, for short windows where whole bytes/sec would round
+/// small trickles down to zero. [`to_speed`](Self::to_speed) truncates to whole bytes per second,
+/// matching what `measure_speed` reports.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PreciseSpeed {
+	pub upload_bytes_per_sec: f64,
+	pub download_bytes_per_sec: f64,
+	/// Length of the window the rates were averaged over; zero for the first (baseline) sample.
+	pub interval: Duration,
+	#[cfg_attr(feature = "serde", serde(skip, default = "Instant::now"))]
+	pub timestamp: Instant,
+}
+
+impl PreciseSpeed {
+	pub fn zero() -> Self {
+		Self {
+			upload_bytes_per_sec: 0.0,
+			download_bytes_per_sec: 0.0,
+			interval: Duration::ZERO,
+			timestamp: Instant::now(),
+		}
+	}
+
+	/// Rates from raw byte deltas over `interval`; zero when `interval` is zero.
+	pub fn from_deltas(uploaded: u64, downloaded: u64, interval: Duration, timestamp: Instant) -> Self {
+		let seconds = interval.as_secs_f64();
+		let rate = |bytes: u64| if seconds > 0.0 { (bytes as f64) / seconds } else { 0.0 };
+
+		Self {
+			upload_bytes_per_sec: rate(uploaded),
+			download_bytes_per_sec: rate(downloaded),
+			interval,
+			timestamp,
+		}
+	}
+
+	pub fn total_bytes_per_sec(&self) -> f64 {
+		self.upload_bytes_per_sec + self.download_bytes_per_sec
+	}
+
+	pub fn upload_kbps(&self) -> f64 {
+		(self.upload_bytes_per_sec * 8.0) / 1_000.0
+	}
+
+	pub fn download_kbps(&self) -> f64 {
+		(self.download_bytes_per_sec * 8.0) / 1_000.0
+	}
+
+	pub fn upload_mbps(&self) -> f64 {
+		(self.upload_bytes_per_sec * 8.0) / 1_000_000.0
+	}
+
+	pub fn download_mbps(&self) -> f64 {
+		(self.download_bytes_per_sec * 8.0) / 1_000_000.0
+	}
+
+	/// Zeroes the upload and download rates that are below `floor`, each on its own, so background
+	/// chatter in one direction does not hide real traffic in the other.
+	pub fn ignore_below(self, floor: ByteRate) -> Self {
+		self.ignore_below_each(floor, floor)
+	}
+
+	/// Like [`ignore_below`](Self::ignore_below), with a separate floor per direction.
+	pub fn ignore_below_each(mut self, upload_floor: ByteRate, download_floor: ByteRate) -> Self {
+		if self.upload_bytes_per_sec < (upload_floor.bytes_per_sec() as f64) {
+			self.upload_bytes_per_sec = 0.0;
+		}
+		if self.download_bytes_per_sec < (download_floor.bytes_per_sec() as f64) {
+			self.download_bytes_per_sec = 0.0;
+		}
+		self
+	}
+
+	pub fn to_speed(&self) -> NetworkSpeed {
+		NetworkSpeed {
+			upload_bytes_per_sec: ByteRate::from_bytes_per_sec(self.upload_bytes_per_sec as u64),
+			download_bytes_per_sec: ByteRate::from_bytes_per_sec(self.download_bytes_per_sec as u64),
+			timestamp: self.timestamp,
+		}
+	}
+}
+
+impl Default for PreciseSpeed {
+	fn default() -> Self {
+		Self::zero()
+	}
+}
+
+impl From<PreciseSpeed> for NetworkSpeed {
+	fn from(speed: PreciseSpeed) -> Self {
+		speed.to_speed()
+	}
+}
+
+/// Result of `NetworkMonitor::sample`: either a measured speed or the marker for the reading that
+/// only established a baseline.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpeedSample {
+	Measured(NetworkSpeed),
+	Baseline {
+		timestamp: Instant,
+	},
+}
+
+impl SpeedSample {
+	pub fn speed(&self) -> Option<&NetworkSpeed> {
+		match self {
+			SpeedSample::Measured(speed) => Some(speed),
+			SpeedSample::Baseline { .. } => None,
+		}
+	}
+
+	pub fn is_baseline(&self) -> bool {
+		matches!(self, SpeedSample::Baseline { .. })
+	}
+
+	pub fn timestamp(&self) -> Instant {
+		match self {
+			SpeedSample::Measured(speed) => speed.timestamp,
+			SpeedSample::Baseline { timestamp } => *timestamp,
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormattedSpeed {
+	pub upload: String,
+	pub download: String,
+	pub total: String,
+}
+
+impl fmt::Display for FormattedSpeed {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "↑ {} ↓ {}", self.upload, self.download)
+	}
+}
+
+/// A snapshot of cumulative byte counters.
 #[derive(Debug, Clone)]
 pub struct InterfaceStats {
 	pub bytes_sent: u64,
 	pub bytes_received: u64,
+	/// Monotonic read time, used for rates.
 	pub last_update: Instant,
+	/// Wall-clock read time, for logging and correlating snapshots across processes.
+	pub captured_at: SystemTime,
 }
 
 impl InterfaceStats {
@@ -92,12 +336,122 @@ impl InterfaceStats {
 			bytes_sent: sent,
 			bytes_received: received,
 			last_update: Instant::now(),
+			captured_at: SystemTime::now(),
 		}
 	}
 
 	pub fn total_bytes(&self) -> u64 {
 		self.bytes_sent.saturating_add(self.bytes_received)
 	}
+
+	/// Traffic since `earlier`, with the default wrap threshold. See [`delta_within`](Self::delta_within).
+	pub fn delta(&self, earlier: &InterfaceStats) -> Result<TrafficDelta> {
+		self.delta_within(earlier, DEFAULT_COUNTER_WRAP_THRESHOLD)
+	}
+
+	/// Traffic since `earlier`. Counters that wrapped past `u64::MAX` are handled; a difference
+	/// above `max_counter_wrap` means the counters were reset (adapter restart, driver reload) and
+	/// fails with [`NetworkError::CalculationOverflow`] instead of reporting a huge spike.
+	pub fn delta_within(&self, earlier: &InterfaceStats, max_counter_wrap: u64) -> Result<TrafficDelta> {
+		self.delta_with(earlier, WrapStrategy::Error { max_delta: max_counter_wrap })
+	}
+
+	/// Traffic since `earlier`, with counters that went backwards handled per `strategy`. Only
+	/// [`WrapStrategy::Error`] can fail, with [`NetworkError::CalculationOverflow`].
+	pub fn delta_with(&self, earlier: &InterfaceStats, strategy: WrapStrategy) -> Result<TrafficDelta> {
+		let (Some(bytes_sent), Some(bytes_received)) = (
+			strategy.delta(earlier.bytes_sent, self.bytes_sent),
+			strategy.delta(earlier.bytes_received, self.bytes_received),
+		) else {
+			return Err(NetworkError::CalculationOverflow);
+		};
+
+		Ok(TrafficDelta {
+			bytes_sent,
+			bytes_received,
+			elapsed: self.last_update.saturating_duration_since(earlier.last_update),
+		})
+	}
+}
+
+/// The limits [`compute_rate`] applies; [`NetworkMonitor`](crate::NetworkMonitor) takes them from its
+/// config.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateOptions {
+	/// Snapshots closer together than this are rejected (and zero-length windows always are).
+	pub min_interval: Duration,
+	/// How counters that went backwards are handled; see [`InterfaceStats::delta_with`].
+	pub wrap_strategy: WrapStrategy,
+	/// Upload rates below this are zeroed; see [`PreciseSpeed::ignore_below_each`].
+	pub ignore_below_upload: ByteRate,
+	/// Download rates below this are zeroed.
+	pub ignore_below_download: ByteRate,
+}
+
+impl From<&NetworkMonitorConfig> for RateOptions {
+	fn from(config: &NetworkMonitorConfig) -> Self {
+		Self {
+			min_interval: config.min_measurement_interval,
+			wrap_strategy: config.wrap_strategy,
+			ignore_below_upload: config.noise_floor(Direction::Upload),
+			ignore_below_download: config.noise_floor(Direction::Download),
+		}
+	}
+}
+
+impl Default for RateOptions {
+	fn default() -> Self {
+		Self::from(&NetworkMonitorConfig::default())
+	}
+}
+
+/// The rate between two counter snapshots, as `NetworkMonitor` computes it, truncated to whole
+/// bytes per second. Useful for custom counter sources and for replaying recorded snapshots.
+pub fn compute_rate(current: &InterfaceStats, previous: &InterfaceStats, opts: &RateOptions) -> Result<NetworkSpeed> {
+	compute_precise_rate(current, previous, opts).map(NetworkSpeed::from)
+}
+
+/// Like [`compute_rate`], without truncating. Fails with
+/// [`InsufficientTimeElapsed`](NetworkError::InsufficientTimeElapsed) when the snapshots are less
+/// than `opts.min_interval` apart (or out of order), and with
+/// [`CalculationOverflow`](NetworkError::CalculationOverflow) when `opts.wrap_strategy` treats the
+/// change as a counter reset. Rates below `opts.ignore_below_upload` / `opts.ignore_below_download`
+/// come back as zero.
+pub fn compute_precise_rate(current: &InterfaceStats, previous: &InterfaceStats, opts: &RateOptions) -> Result<PreciseSpeed> {
+	let elapsed = current.last_update.saturating_duration_since(previous.last_update);
+	if elapsed.is_zero() || elapsed < opts.min_interval {
+		return Err(NetworkError::InsufficientTimeElapsed {
+			min_ms: opts.min_interval.as_millis() as u64,
+			actual_ms: elapsed.as_millis() as u64,
+		});
+	}
+
+	Ok(
+		current
+			.delta_with(previous, opts.wrap_strategy)?
+			.to_speed(current.last_update)
+			.ignore_below_each(opts.ignore_below_upload, opts.ignore_below_download)
+	)
+}
+
+/// Bytes moved between two [`InterfaceStats`] snapshots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrafficDelta {
+	pub bytes_sent: u64,
+	pub bytes_received: u64,
+	/// Monotonic time between the snapshots; zero if they were taken out of order.
+	pub elapsed: Duration,
+}
+
+impl TrafficDelta {
+	pub fn total_bytes(&self) -> u64 {
+		self.bytes_sent.saturating_add(self.bytes_received)
+	}
+
+	/// Rates over [`elapsed`](Self::elapsed); zero when no time passed.
+	pub fn to_speed(&self, timestamp: Instant) -> PreciseSpeed {
+		PreciseSpeed::from_deltas(self.bytes_sent, self.bytes_received, self.elapsed, timestamp)
+	}
 }
 
 impl Default for InterfaceStats {
@@ -106,8 +460,134 @@ impl Default for InterfaceStats {
 	}
 }
 
-pub fn format_bytes_per_second(bytes_per_sec: u64) -> String {
+/// Averages samples (oldest first), weighting each by the time it covers: the gap since the
+/// previous sample. The first sample has no predecessor and borrows the weight of the second.
+/// Falls back to an equal-weight mean when all samples share one timestamp.
+///
+/// With evenly spaced samples this equals the plain mean; with skipped ticks or jitter it gives
+/// the true bytes-over-time rate.
+pub fn time_weighted_average<'a>(samples: impl IntoIterator<Item = &'a NetworkSpeed>) -> Option<NetworkSpeed> {
+	let samples: Vec<&NetworkSpeed> = samples.into_iter().collect();
+	if samples.is_empty() {
+		return None;
+	}
+
+	let mut weights = sample_durations(&samples);
+	if weights.iter().sum::<f64>() <= 0.0 {
+		weights.fill(1.0);
+	}
+	let total_weight: f64 = weights.iter().sum();
+
+	let average = |rate: fn(&NetworkSpeed) -> ByteRate| {
+		let weighted: f64 = samples
+			.iter()
+			.zip(&weights)
+			.map(|(sample, weight)| (rate(sample).bytes_per_sec() as f64) * weight)
+			.sum();
+		ByteRate::from_bytes_per_sec((weighted / total_weight) as u64)
+	};
+
+	Some(NetworkSpeed::new(average(|s| s.upload_bytes_per_sec), average(|s| s.download_bytes_per_sec)))
+}
+
+/// Statistics over a run of samples, see [`summarize`].
+#[derive(Debug, Clone)]
+pub struct SpeedSummary {
+	pub samples: usize,
+	/// Wall time the samples cover.
+	pub duration: Duration,
+	/// Time-weighted average, as [`time_weighted_average`].
+	pub average: NetworkSpeed,
+	/// The sample with the highest combined rate.
+	pub peak: NetworkSpeed,
+	/// 95th percentile of the upload and download rates, each taken on its own.
+	pub p95: NetworkSpeed,
+	pub bytes_sent: u64,
+	pub bytes_received: u64,
+}
+
+impl SpeedSummary {
+	pub fn total_bytes(&self) -> u64 {
+		self.bytes_sent.saturating_add(self.bytes_received)
+	}
+}
+
+/// Summarizes `samples` (oldest first); `None` when empty. Transferred bytes are each rate times
+/// the time its sample covers, so they match the counters when no ticks were skipped.
+pub fn summarize<'a>(samples: impl IntoIterator<Item = &'a NetworkSpeed>) -> Option<SpeedSummary> {
+	let samples: Vec<&NetworkSpeed> = samples.into_iter().collect();
+	let average = time_weighted_average(samples.iter().copied())?;
+	let durations = sample_durations(&samples);
+
+	let transferred = |rate: fn(&NetworkSpeed) -> ByteRate| {
+		samples
+			.iter()
+			.zip(&durations)
+			.map(|(sample, secs)| (rate(sample).bytes_per_sec() as f64) * secs)
+			.sum::<f64>() as u64
+	};
+	let p95 = |rate: fn(&NetworkSpeed) -> ByteRate| {
+		let mut values: Vec<u64> = samples
+			.iter()
+			.map(|sample| rate(sample).bytes_per_sec())
+			.collect();
+		values.sort_unstable();
+		// Nearest-rank percentile.
+		let rank = ((values.len() as f64) * 0.95).ceil() as usize;
+		values[rank.clamp(1, values.len()) - 1]
+	};
+
+	Some(SpeedSummary {
+		samples: samples.len(),
+		duration: Duration::from_secs_f64(durations.iter().sum()),
+		average,
+		peak: samples
+			.iter()
+			.max_by_key(|sample| sample.total_bytes_per_sec())
+			.map(|sample| (*sample).clone())?,
+		p95: NetworkSpeed::new(p95(|s| s.upload_bytes_per_sec), p95(|s| s.download_bytes_per_sec)),
+		bytes_sent: transferred(|s| s.upload_bytes_per_sec),
+		bytes_received: transferred(|s| s.download_bytes_per_sec),
+	})
+}
+
+/// Seconds each sample covers (oldest first): the gap since its predecessor, with the first sample
+/// borrowing the gap of the second.
+pub(crate) fn sample_durations(samples: &[&NetworkSpeed]) -> Vec<f64> {
+	let mut durations: Vec<f64> = samples
+		.windows(2)
+		.map(|pair| pair[1].timestamp.saturating_duration_since(pair[0].timestamp).as_secs_f64())
+		.collect();
+	if !samples.is_empty() {
+		durations.insert(0, durations.first().copied().unwrap_or(0.0));
+	}
+	durations
+}
+
+fn percent_change(before: ByteRate, after: ByteRate) -> Option<f64> {
+	if before.is_zero() {
+		return None;
+	}
+
+	let before = before.bytes_per_sec() as f64;
+	let after = after.bytes_per_sec() as f64;
+	Some(((after - before) / before) * 100.0)
+}
+
+/// Time to transfer `bytes` at a constant `rate`; `None` when the rate is zero.
+pub fn estimate_transfer_time(bytes: u64, rate: impl Into<ByteRate>) -> Option<Duration> {
+	let bytes_per_sec = rate.into().bytes_per_sec();
+	if bytes_per_sec == 0 {
+		return None;
+	}
+
+	let remainder_nanos = ((bytes % bytes_per_sec) as u128) * 1_000_000_000 / (bytes_per_sec as u128);
+	Some(Duration::from_secs(bytes / bytes_per_sec) + Duration::from_nanos(remainder_nanos as u64))
+}
+
+pub fn format_bytes_per_second(rate: impl Into<ByteRate>) -> String {
 	const UNITS: &[&str] = &["B/s", "KB/s", "MB/s", "GB/s", "TB/s"];
+	let bytes_per_sec = rate.into().bytes_per_sec();
 	let mut size = bytes_per_sec as f64;
 	let mut unit_index = 0;
 
@@ -139,3 +619,20 @@ pub fn format_bits_per_second(bits_per_sec: u64) -> String {
 		format!("{:.2} {}", size, UNITS[unit_index])
 	}
 }
+
+pub fn format_bytes_total(bytes: u64) -> String {
+	const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB", "PB"];
+	let mut size = bytes as f64;
+	let mut unit_index = 0;
+
+	while size >= 1024.0 && unit_index < UNITS.len() - 1 {
+		size /= 1024.0;
+		unit_index += 1;
+	}
+
+	if unit_index == 0 {
+		format!("{} {}", bytes, UNITS[unit_index])
+	} else {
+		format!("{:.2} {}", size, UNITS[unit_index])
+	}
+}
@@ -0,0 +1,280 @@
+use std::fmt::Write;
+use std::time::{ Instant, SystemTime, UNIX_EPOCH };
+
+use super::error::{ NetworkError, Result };
+use super::format::{ format_rate, FormatOptions };
+use super::rate::ByteRate;
+use super::speed::NetworkSpeed;
+
+/// Timestamp layout used by a bare `{ts}`.
+pub const DEFAULT_TIMESTAMP_FORMAT: &str = "%H:%M:%S";
+
+/// A parsed output template such as `"{ts:%H:%M:%S} ↑{up:mbps:.1} ↓{down:mbps:.1}"`.
+///
+/// Placeholders are `{up}`, `{down}`, and `{total}`, optionally followed by a unit and a
+/// precision (`{up:mbps}`, `{down:MiB:.1}`, `{total:.0}`), and `{ts}` with an optional
+/// strftime-style layout. `auto` (the default unit) and `autobits` include the unit label;
+/// fixed units (`bps`, `kbps`, `mbps`, `gbps`, `B`, `kB`, `MB`, `GB`, `KiB`, `MiB`, `GiB`) print
+/// only the number. `{{` and `}}` are literal braces.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatTemplate {
+	segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+	Literal(String),
+	Rate {
+		field: RateField,
+		unit: TemplateUnit,
+		precision: Option<usize>,
+	},
+	Timestamp(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RateField {
+	Upload,
+	Download,
+	Total,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TemplateUnit {
+	Auto,
+	AutoBits,
+	/// Bits per second divided by the factor.
+	Bits(u64),
+	/// Bytes per second divided by the factor.
+	Bytes(u64),
+}
+
+impl TemplateUnit {
+	fn parse(name: &str) -> Option<Self> {
+		Some(match name {
+			"auto" => TemplateUnit::Auto,
+			"autobits" => TemplateUnit::AutoBits,
+			"bps" => TemplateUnit::Bits(1),
+			"kbps" => TemplateUnit::Bits(1_000),
+			"mbps" => TemplateUnit::Bits(1_000_000),
+			"gbps" => TemplateUnit::Bits(1_000_000_000),
+			"B" => TemplateUnit::Bytes(1),
+			"kB" => TemplateUnit::Bytes(1_000),
+			"MB" => TemplateUnit::Bytes(1_000_000),
+			"GB" => TemplateUnit::Bytes(1_000_000_000),
+			"KiB" => TemplateUnit::Bytes(1 << 10),
+			"MiB" => TemplateUnit::Bytes(1 << 20),
+			"GiB" => TemplateUnit::Bytes(1 << 30),
+			_ => {
+				return None;
+			}
+		})
+	}
+
+	fn render(&self, rate: ByteRate, precision: Option<usize>) -> String {
+		match *self {
+			TemplateUnit::Auto => format_rate(rate, &FormatOptions::bytes().with_decimal_places(precision.unwrap_or(2))),
+			TemplateUnit::AutoBits => format_rate(rate, &FormatOptions::bits().with_decimal_places(precision.unwrap_or(2))),
+			TemplateUnit::Bits(factor) => format_scaled(rate.bits_per_sec(), factor, precision),
+			TemplateUnit::Bytes(factor) => format_scaled(rate.bytes_per_sec(), factor, precision),
+		}
+	}
+}
+
+/// Whole units print as integers unless a precision is given; scaled ones default to two decimals.
+fn format_scaled(value: u64, factor: u64, precision: Option<usize>) -> String {
+	match precision {
+		None if factor == 1 => value.to_string(),
+		precision => format!("{:.*}", precision.unwrap_or(2), (value as f64) / (factor as f64)),
+	}
+}
+
+impl FormatTemplate {
+	pub fn parse(template: &str) -> Result<Self> {
+		let invalid = |reason: String| NetworkError::InvalidConfiguration {
+			field: format!("format template \"{template}\": {reason}"),
+		};
+
+		let mut segments = Vec::new();
+		let mut literal = String::new();
+		let mut chars = template.chars().peekable();
+		while let Some(c) = chars.next() {
+			match c {
+				'{' if chars.peek() == Some(&'{') => {
+					chars.next();
+					literal.push('{');
+				}
+				'}' if chars.peek() == Some(&'}') => {
+					chars.next();
+					literal.push('}');
+				}
+				'}' => {
+					return Err(invalid("unmatched '}', write '}}' for a literal brace".to_string()));
+				}
+				'{' => {
+					let mut placeholder = String::new();
+					loop {
+						match chars.next() {
+							Some('}') => break,
+							Some(c) => placeholder.push(c),
+							None => {
+								return Err(invalid("unclosed '{'".to_string()));
+							}
+						}
+					}
+					if !literal.is_empty() {
+						segments.push(Segment::Literal(std::mem::take(&mut literal)));
+					}
+					segments.push(Self::parse_placeholder(&placeholder).map_err(invalid)?);
+				}
+				c => literal.push(c),
+			}
+		}
+		if !literal.is_empty() {
+			segments.push(Segment::Literal(literal));
+		}
+
+		Ok(Self { segments })
+	}
+
+	fn parse_placeholder(placeholder: &str) -> std::result::Result<Segment, String> {
+		let (name, spec) = placeholder.split_once(':').unwrap_or((placeholder, ""));
+		let field = match name.trim() {
+			"ts" => {
+				let layout = if spec.is_empty() { DEFAULT_TIMESTAMP_FORMAT } else { spec };
+				check_timestamp_format(layout)?;
+				return Ok(Segment::Timestamp(layout.to_string()));
+			}
+			"up" => RateField::Upload,
+			"down" => RateField::Download,
+			"total" => RateField::Total,
+			other => {
+				return Err(format!("unknown placeholder {{{other}}}, expected up, down, total, or ts"));
+			}
+		};
+
+		let mut unit = TemplateUnit::Auto;
+		let mut precision = None;
+		for part in spec.split(':').filter(|part| !part.is_empty()) {
+			if let Some(digits) = part.strip_prefix('.') {
+				precision = Some(digits.parse().map_err(|_| format!("invalid precision \"{part}\""))?);
+			} else {
+				unit = TemplateUnit::parse(part).ok_or_else(|| format!("unknown unit \"{part}\""))?;
+			}
+		}
+
+		Ok(Segment::Rate { field, unit, precision })
+	}
+
+	/// Renders `speed`, with `{ts}` showing the sample's wall-clock time in UTC.
+	pub fn render(&self, speed: &NetworkSpeed) -> String {
+		let taken_at = SystemTime::now()
+			.checked_sub(Instant::now().saturating_duration_since(speed.timestamp))
+			.unwrap_or_else(SystemTime::now);
+		self.render_with(speed, |layout| format_utc(taken_at, layout))
+	}
+
+	/// Like [`render`](Self::render), but `{ts}` is produced by `format_timestamp`, which receives
+	/// the placeholder's layout. Use this for local time, e.g. with chrono's `format`.
+	pub fn render_with(&self, speed: &NetworkSpeed, format_timestamp: impl Fn(&str) -> String) -> String {
+		let mut output = String::new();
+		for segment in &self.segments {
+			match segment {
+				Segment::Literal(text) => output.push_str(text),
+				Segment::Timestamp(layout) => output.push_str(&format_timestamp(layout)),
+				Segment::Rate { field, unit, precision } => {
+					let rate = match field {
+						RateField::Upload => speed.upload_bytes_per_sec,
+						RateField::Download => speed.download_bytes_per_sec,
+						RateField::Total => speed.total_bytes_per_sec(),
+					};
+					output.push_str(&unit.render(rate, *precision));
+				}
+			}
+		}
+		output
+	}
+}
+
+impl std::str::FromStr for FormatTemplate {
+	type Err = NetworkError;
+
+	fn from_str(value: &str) -> Result<Self> {
+		Self::parse(value)
+	}
+}
+
+impl TryFrom<&str> for FormatTemplate {
+	type Error = NetworkError;
+
+	fn try_from(value: &str) -> Result<Self> {
+		Self::parse(value)
+	}
+}
+
+/// Timestamp directives [`FormatTemplate::render`] understands; all of them are valid strftime
+/// (and chrono) directives too.
+const TIMESTAMP_DIRECTIVES: &[char] = &['Y', 'm', 'd', 'H', 'M', 'S', 's', '%'];
+
+fn check_timestamp_format(layout: &str) -> std::result::Result<(), String> {
+	let mut chars = layout.chars();
+	while let Some(c) = chars.next() {
+		if c == '%' {
+			match chars.next() {
+				Some(directive) if TIMESTAMP_DIRECTIVES.contains(&directive) => {}
+				Some(directive) => {
+					return Err(format!("unsupported timestamp directive %{directive}, use %Y %m %d %H %M %S %s or %%"));
+				}
+				None => {
+					return Err("timestamp layout ends with '%'".to_string());
+				}
+			}
+		}
+	}
+	Ok(())
+}
+
+pub(crate) fn format_utc(time: SystemTime, layout: &str) -> String {
+	let seconds = time
+		.duration_since(UNIX_EPOCH)
+		.map(|elapsed| elapsed.as_secs())
+		.unwrap_or(0);
+	let (year, month, day) = civil_from_days((seconds / 86_400) as i64);
+	let second_of_day = seconds % 86_400;
+
+	let mut output = String::new();
+	let mut chars = layout.chars();
+	while let Some(c) = chars.next() {
+		if c != '%' {
+			output.push(c);
+			continue;
+		}
+		let _ = match chars.next() {
+			Some('Y') => write!(output, "{year:04}"),
+			Some('m') => write!(output, "{month:02}"),
+			Some('d') => write!(output, "{day:02}"),
+			Some('H') => write!(output, "{:02}", second_of_day / 3600),
+			Some('M') => write!(output, "{:02}", (second_of_day % 3600) / 60),
+			Some('S') => write!(output, "{:02}", second_of_day % 60),
+			Some('s') => write!(output, "{seconds}"),
+			Some('%') => write!(output, "%"),
+			Some(other) => write!(output, "%{other}"),
+			None => write!(output, "%"),
+		};
+	}
+	output
+}
+
+/// Days since 1970-01-01 to a proleptic Gregorian date (Howard Hinnant's algorithm).
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+	let z = days + 719_468;
+	let era = z.div_euclid(146_097);
+	let day_of_era = z.rem_euclid(146_097);
+	let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+	let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+	let month_index = (5 * day_of_year + 2) / 153;
+	let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+	let month = (if month_index < 10 { month_index + 3 } else { month_index - 9 }) as u32;
+	let year = year_of_era + era * 400 + i64::from(month <= 2);
+	(year, month, day)
+}
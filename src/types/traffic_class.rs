@@ -0,0 +1,71 @@
+use std::time::Duration;
+
+/// Cumulative counters of one packet-scheduler traffic class (a QoS flow).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TrafficClassCounters {
+	/// The scheduler's instance name, typically the adapter followed by the flow or policy name.
+	pub class: String,
+	pub bytes_transmitted: u64,
+	pub packets_transmitted: u64,
+}
+
+/// Bytes and packets one traffic class sent over an interval.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TrafficClassUsage {
+	pub class: String,
+	pub bytes: u64,
+	pub packets: u64,
+}
+
+/// Per-class traffic over `elapsed`, built by [`TrafficClassUsage::between`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct QosBreakdown {
+	pub elapsed: Duration,
+	/// Classes sorted by bytes, largest first.
+	pub classes: Vec<TrafficClassUsage>,
+}
+
+impl TrafficClassUsage {
+	/// Diffs two counter readings. Classes are matched by name; a class that only appears in
+	/// `current` (a flow created during the interval) counts from zero, and counters that went
+	/// backwards (flow recreated) yield zero.
+	pub fn between(earlier: &[TrafficClassCounters], current: &[TrafficClassCounters], elapsed: Duration) -> QosBreakdown {
+		let mut classes: Vec<TrafficClassUsage> = current
+			.iter()
+			.map(|counters| {
+				let before = earlier.iter().find(|before| before.class == counters.class);
+				TrafficClassUsage {
+					class: counters.class.clone(),
+					bytes: counters.bytes_transmitted.saturating_sub(before.map_or(0, |b| b.bytes_transmitted)),
+					packets: counters.packets_transmitted.saturating_sub(before.map_or(0, |b| b.packets_transmitted)),
+				}
+			})
+			.collect();
+		classes.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.class.cmp(&b.class)));
+
+		QosBreakdown { elapsed, classes }
+	}
+}
+
+impl QosBreakdown {
+	/// Bytes sent through any scheduler flow, i.e. traffic that matched a QoS policy.
+	pub fn prioritized_bytes(&self) -> u64 {
+		self.classes.iter().fold(0u64, |sum, class| sum.saturating_add(class.bytes))
+	}
+
+	/// Bytes of `total_sent` (the interfaces' sent bytes over the same interval) that matched no
+	/// QoS policy and went out best-effort.
+	pub fn best_effort_bytes(&self, total_sent: u64) -> u64 {
+		total_sent.saturating_sub(self.prioritized_bytes())
+	}
+
+	/// Fraction of `total_sent` that was prioritized, or `None` when nothing was sent.
+	pub fn prioritized_share(&self, total_sent: u64) -> Option<f64> {
+		if total_sent == 0 {
+			return None;
+		}
+		Some((self.prioritized_bytes().min(total_sent) as f64) / (total_sent as f64))
+	}
+}
@@ -0,0 +1,206 @@
+#![cfg(feature = "agent")]
+
+use network_speed::agent::{ read_message, write_message, Message, RemoteSample, PROTOCOL_VERSION };
+use network_speed::{ Agent, AgentOptions, Collector, NetworkSpeed };
+use std::thread;
+use std::time::{ Duration, Instant };
+
+#[test]
+fn test_protocol_round_trip() {
+	let messages = vec![
+		Message::Hello {
+			hostname: "host-a".to_string(),
+			token: Some("secret".to_string()),
+		},
+		Message::Hello {
+			hostname: "host-b".to_string(),
+			token: None,
+		},
+		Message::Sample(RemoteSample {
+			timestamp_ms: 1_700_000_000_000,
+			upload_bytes_per_sec: 1_024,
+			download_bytes_per_sec: 4_096,
+		}),
+		Message::Goodbye,
+		Message::Accepted,
+		Message::Rejected {
+			reason: "nope".to_string(),
+		}
+	];
+
+	let mut buffer = Vec::new();
+	for message in &messages {
+		write_message(&mut buffer, message).unwrap();
+	}
+
+	let mut reader = buffer.as_slice();
+	for message in &messages {
+		assert_eq!(read_message(&mut reader).unwrap().as_ref(), Some(message));
+	}
+	assert!(read_message(&mut reader).unwrap().is_none());
+}
+
+#[test]
+fn test_protocol_rejects_bad_frames() {
+	assert!(Message::decode(&[]).is_err());
+	assert!(Message::decode(&[PROTOCOL_VERSION + 1, 3]).is_err());
+	assert!(Message::decode(&[PROTOCOL_VERSION, 99]).is_err());
+	assert!(Message::decode(&[PROTOCOL_VERSION, 2, 0, 0]).is_err());
+
+	let oversized = (1u32 << 20).to_be_bytes();
+	assert!(read_message(&mut oversized.as_slice()).is_err());
+
+	// Each string fits its u16 length, but together they exceed the frame limit.
+	let hello = Message::Hello {
+		hostname: "h".repeat(40_000),
+		token: Some("t".repeat(40_000)),
+	};
+	assert!(hello.encode().is_err());
+	let mut buffer = Vec::new();
+	assert!(write_message(&mut buffer, &hello).is_err());
+	assert!(buffer.is_empty());
+}
+
+#[test]
+fn test_agent_streams_to_collector() {
+	let collector = Collector::bind("127.0.0.1:0", 10).unwrap();
+	let addr = collector.local_addr().unwrap();
+	let handle = collector.spawn().unwrap();
+
+	let mut agent = Agent::connect(addr, "host-a").unwrap();
+	agent.send(&NetworkSpeed::new(1_000, 2_000)).unwrap();
+	agent.send(&NetworkSpeed::new(3_000, 4_000)).unwrap();
+	agent.close().unwrap();
+
+	let deadline = Instant::now() + Duration::from_secs(5);
+	while handle.history("host-a").len() < 2 && Instant::now() < deadline {
+		thread::sleep(Duration::from_millis(10));
+	}
+
+	assert_eq!(handle.hosts(), vec!["host-a".to_string()]);
+	assert_eq!(handle.history("host-a").len(), 2);
+	let latest = handle.latest("host-a").unwrap();
+	assert_eq!(latest.upload_bytes_per_sec, 3_000);
+	assert_eq!(latest.download_bytes_per_sec, 4_000);
+	assert!(handle.latest("host-b").is_none());
+}
+
+#[test]
+fn test_collector_token_auth() {
+	let collector = Collector::bind("127.0.0.1:0", 10).unwrap().with_token("s3cret");
+	let addr = collector.local_addr().unwrap();
+	collector.spawn().unwrap();
+
+	assert!(Agent::connect(addr, "anonymous").is_err());
+	assert!(Agent::connect_with(addr, "intruder", &AgentOptions::new().with_token("guess")).is_err());
+	assert!(Agent::connect_with(addr, "trusted", &AgentOptions::new().with_token("s3cret")).is_ok());
+}
+
+#[test]
+fn test_collector_requires_token_when_exposed() {
+	let collector = Collector::bind("0.0.0.0:0", 10).unwrap();
+	assert!(collector.spawn().is_err());
+
+	let collector = Collector::bind("0.0.0.0:0", 10).unwrap().allow_unauthenticated(true);
+	assert!(collector.spawn().is_ok());
+}
+
+#[test]
+fn test_collector_refuses_plaintext_token_when_exposed() {
+	let collector = Collector::bind("0.0.0.0:0", 10).unwrap().with_token("s3cret");
+	assert!(collector.check_exposure().is_err());
+
+	let collector = Collector::bind("0.0.0.0:0", 10).unwrap().with_token("s3cret").allow_plaintext_token(true);
+	assert!(collector.check_exposure().is_ok());
+
+	let collector = Collector::bind("127.0.0.1:0", 10).unwrap().with_token("s3cret");
+	assert!(collector.check_exposure().is_ok());
+}
+
+#[test]
+fn test_collector_limits_idle_connections() {
+	use std::io::Read;
+	use std::net::TcpStream;
+
+	let collector = Collector::bind("127.0.0.1:0", 10)
+		.unwrap()
+		.with_handshake_timeout(Duration::from_millis(200))
+		.with_max_connections(1);
+	let addr = collector.local_addr().unwrap();
+	collector.spawn().unwrap();
+
+	let mut idle = TcpStream::connect(addr).unwrap();
+	idle.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+	thread::sleep(Duration::from_millis(50));
+
+	// Over the cap: closed without being served.
+	let mut extra = TcpStream::connect(addr).unwrap();
+	extra.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+	let closed = |stream: &mut TcpStream| {
+		match stream.read(&mut [0u8; 1]) {
+			Ok(read) => read == 0,
+			Err(err) => !matches!(err.kind(), std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut),
+		}
+	};
+	assert!(closed(&mut extra));
+
+	// The idle connection never sends a hello and is dropped after the handshake timeout.
+	let started = Instant::now();
+	assert!(closed(&mut idle));
+	assert!(started.elapsed() < Duration::from_secs(5));
+
+	// Its slot is free again.
+	thread::sleep(Duration::from_millis(50));
+	let mut agent = Agent::connect(addr, "after-timeout").unwrap();
+	agent.send(&NetworkSpeed::new(1, 2)).unwrap();
+}
+
+#[cfg(feature = "tls")]
+#[test]
+fn test_agent_over_tls() {
+	use network_speed::agent::{ ClientTls, ServerTls };
+
+	let server_tls = ServerTls::from_pem(
+		include_bytes!("fixtures/test_server.pem"),
+		include_bytes!("fixtures/test_server.key")
+	).unwrap();
+	let client_tls = ClientTls::from_ca_pem(include_bytes!("fixtures/test_ca.pem"), "localhost").unwrap();
+
+	let collector = Collector::bind("127.0.0.1:0", 10).unwrap().with_token("s3cret").with_tls(server_tls);
+	let addr = collector.local_addr().unwrap();
+	let handle = collector.spawn().unwrap();
+
+	let options = AgentOptions::new().with_token("s3cret").with_tls(client_tls);
+	let mut agent = Agent::connect_with(addr, "tls-host", &options).unwrap();
+	agent.send(&NetworkSpeed::new(5_000, 6_000)).unwrap();
+	agent.close().unwrap();
+
+	let deadline = Instant::now() + Duration::from_secs(5);
+	while handle.latest("tls-host").is_none() && Instant::now() < deadline {
+		thread::sleep(Duration::from_millis(10));
+	}
+	assert_eq!(handle.latest("tls-host").unwrap().download_bytes_per_sec, 6_000);
+
+	assert!(Agent::connect_with(addr, "plain", &AgentOptions::new().with_token("s3cret")).is_err());
+}
+
+#[test]
+fn test_collector_stops_on_shutdown() {
+	use network_speed::Shutdown;
+
+	let shutdown = Shutdown::new();
+	let collector = Collector::bind("127.0.0.1:0", 10).unwrap();
+	let addr = collector.local_addr().unwrap();
+	let worker = {
+		let shutdown = shutdown.clone();
+		thread::spawn(move || collector.run_until(&shutdown))
+	};
+
+	let mut agent = Agent::connect(addr, "before-shutdown").unwrap();
+	agent.send(&NetworkSpeed::new(1, 2)).unwrap();
+	agent.close().unwrap();
+
+	shutdown.trigger();
+	worker.join().unwrap().unwrap();
+	assert!(Agent::connect(addr, "after-shutdown").is_err());
+}
@@ -1,3 +1,5 @@
+#![cfg(feature = "platform")]
+
 use network_speed::{
 	list_interfaces,
 	NetworkMonitor,
@@ -124,7 +126,7 @@ async fn test_readme_async_example() {
 		let monitor = AsyncNetworkMonitor::new();
 
 		let speed = monitor.measure_speed().await.unwrap();
-		assert!(speed.upload_bytes_per_sec == 0 || speed.upload_bytes_per_sec > 0);
-		assert!(speed.download_bytes_per_sec == 0 || speed.download_bytes_per_sec > 0);
+		assert!(speed.total_bytes_per_sec() >= speed.upload_bytes_per_sec);
+		assert!(speed.total_bytes_per_sec() >= speed.download_bytes_per_sec);
 	}
 }
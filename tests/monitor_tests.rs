@@ -1,6 +1,9 @@
+#![cfg(feature = "platform")]
+
 use network_speed::{
 	get_interface_count,
 	list_interfaces,
+	InterfaceKind,
 	NetworkMonitor,
 	NetworkMonitorConfig,
 	NetworkSpeedTracker,
@@ -108,6 +111,21 @@ fn test_list_interfaces() {
 	}
 }
 
+#[test]
+fn test_interfaces_iter() {
+	if cfg!(windows) {
+		let iter = network_speed::interfaces_iter().unwrap();
+		let count = iter.len();
+		assert_eq!(count, get_interface_count().unwrap());
+
+		let interfaces: Vec<_> = iter.collect::<Result<_, _>>().unwrap();
+		assert_eq!(interfaces.len(), count);
+
+		let first = network_speed::interfaces_iter().unwrap().next().unwrap().unwrap();
+		assert_eq!(first.index, interfaces[0].index);
+	}
+}
+
 #[test]
 fn test_interface_count() {
 	if cfg!(windows) {
@@ -117,18 +135,21 @@ fn test_interface_count() {
 	}
 }
 
+#[cfg(windows)]
 #[test]
 fn test_virtual_interface_detection() {
 	use network_speed::NetworkInterface;
 	use windows::Win32::NetworkManagement::IpHelper::MIB_IF_ROW2;
 
-	let mut row = MIB_IF_ROW2::default();
-	row.InterfaceIndex = 42;
-	row.Type = 6; // Ethernet
-	row.TransmitLinkSpeed = 1_000_000;
-	row.ReceiveLinkSpeed = 1_000_000;
-	row.OutOctets = 1_000;
-	row.InOctets = 2_000;
+	let mut row = MIB_IF_ROW2 {
+		InterfaceIndex: 42,
+		Type: 6, // Ethernet
+		TransmitLinkSpeed: 1_000_000,
+		ReceiveLinkSpeed: 1_000_000,
+		OutOctets: 1_000,
+		InOctets: 2_000,
+		..Default::default()
+	};
 	row.OperStatus.0 = 1; // Up
 
 	let description = "VMware Virtual Ethernet Adapter";
@@ -157,6 +178,37 @@ fn test_tracker_history_management() {
 	}
 }
 
+#[test]
+fn test_tracker_registry() {
+	use network_speed::TrackerRegistry;
+
+	let mut registry = TrackerRegistry::new();
+	registry.register("all", NetworkMonitorConfig::default(), 5).unwrap();
+	let loopback = NetworkMonitorConfig::builder().exclude_loopback(false).interface_type_filters(Vec::new()).build().unwrap();
+	registry.register("loopback", loopback, 2).unwrap();
+	assert!(registry.register("all", NetworkMonitorConfig::default(), 5).is_err());
+	assert!(registry.insert(NetworkSpeedTracker::new(5)).is_err());
+	assert_eq!(registry.names().collect::<Vec<_>>(), ["all", "loopback"]);
+	assert!(registry.recommended_interval().is_some());
+	let slow = NetworkMonitorConfig::builder().min_measurement_interval(Duration::from_secs(5)).build().unwrap();
+	registry.register("slow", slow, 2).unwrap();
+	assert_eq!(registry.recommended_interval(), Some(Duration::from_secs(5)));
+	registry.remove("slow");
+
+	for _ in 0..3 {
+		let results = registry.track_all();
+		assert_eq!(results.len(), 2);
+		thread::sleep(Duration::from_millis(120));
+	}
+	let loopback = registry.get("loopback").unwrap();
+	assert_eq!(loopback.labels().name.as_deref(), Some("loopback"));
+	assert!(loopback.get_history().len() <= 2);
+
+	assert!(registry.remove("all").is_some());
+	assert!(!registry.contains("all"));
+	assert_eq!(registry.len(), 1);
+}
+
 #[test]
 fn test_tracker_statistics() {
 	if cfg!(windows) {
@@ -170,12 +222,26 @@ fn test_tracker_statistics() {
 		let avg_speed = tracker.get_average_speed(Duration::from_secs(1));
 		let peak_speed = tracker.get_peak_speed(Duration::from_secs(1));
 
-		if tracker.get_history().len() > 0 {
+		if !tracker.get_history().is_empty() {
 			assert!(avg_speed.is_some() || peak_speed.is_some());
 		}
 	}
 }
 
+#[test]
+fn test_tracker_window_longer_than_uptime() {
+	use network_speed::NetworkSpeed;
+
+	let mut tracker = NetworkSpeedTracker::new(5);
+	tracker.record(NetworkSpeed::new(1_000, 2_000));
+	tracker.record(NetworkSpeed::new(3_000, 4_000));
+
+	// Reaches past the earliest representable `Instant`: every sample counts.
+	assert_eq!(tracker.get_peak_speed(Duration::MAX).map(|speed| speed.download_bytes_per_sec.bytes_per_sec()), Some(4_000));
+	assert!(tracker.get_average_speed(Duration::MAX).is_some());
+	assert_eq!(tracker.sparkline(Duration::MAX, 10).chars().count(), 2);
+}
+
 #[cfg(feature = "async")]
 #[tokio::test]
 async fn test_async_monitor_creation() {
@@ -240,6 +306,62 @@ async fn test_collect_samples() {
 	}
 }
 
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn test_collect_samples_for_duration() {
+	if cfg!(windows) {
+		let monitor = AsyncNetworkMonitor::new();
+		let started = std::time::Instant::now();
+
+		let result = monitor.collect_samples_for(Duration::from_millis(650), Duration::from_millis(200)).await;
+
+		assert!(started.elapsed() < Duration::from_secs(2), "Deadline not honored");
+		if let Ok(samples) = result {
+			assert!(!samples.is_empty(), "No samples collected");
+			assert!(samples.len() <= 4, "Too many samples collected");
+		}
+	}
+}
+
+#[cfg(all(feature = "async", feature = "test-util"))]
+#[tokio::test]
+async fn test_collect_samples_until_keeps_partial_samples() {
+	use network_speed::{ MockCounterSource, NetworkError, PartialSamples };
+
+	let mock = MockCounterSource::new();
+	mock.push_counters(&[(1_000, 10_000)]);
+	mock.push_deltas(&[(500, 20_000)]);
+	mock.push_deltas(&[(500, 20_000)]);
+	mock.push_error(NetworkError::InvalidInterface);
+
+	let config = NetworkMonitorConfig::builder().min_measurement_interval(Duration::from_millis(20)).build().unwrap();
+	let monitor = AsyncNetworkMonitor::with_source(config, mock);
+	let deadline = std::time::Instant::now() + Duration::from_secs(5);
+
+	// The default tolerance gives up on the first failure, after the baseline and two deltas.
+	match monitor.try_collect_samples_until(deadline, Duration::from_millis(50)).await {
+		Err(PartialSamples { samples, error }) => {
+			assert_eq!(samples.len(), 3);
+			assert!(samples[1..].iter().all(|speed| speed.download_bytes_per_sec > 0));
+			assert!(matches!(error, NetworkError::InvalidInterface));
+		}
+		Ok(samples) => panic!("expected a failed run, got {} samples", samples.len()),
+	}
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn test_coalesced_measurements_share_sample() {
+	if cfg!(windows) {
+		let monitor = std::sync::Arc::new(AsyncNetworkMonitor::new().with_coalescing(Duration::from_secs(1)));
+
+		let (first, second) = tokio::join!(monitor.measure_speed(), monitor.measure_speed());
+		if let (Ok(first), Ok(second)) = (first, second) {
+			assert_eq!(first.timestamp, second.timestamp, "Concurrent callers measured separately");
+		}
+	}
+}
+
 #[cfg(feature = "async")]
 #[tokio::test]
 async fn test_monitor_with_channel() {
@@ -277,3 +399,1487 @@ async fn test_async_config_update() {
 	assert!(!updated_config.exclude_bluetooth);
 	assert_eq!(updated_config.min_measurement_interval, Duration::from_millis(50));
 }
+
+#[cfg(feature = "charts")]
+#[test]
+fn test_render_chart_formats() {
+	use network_speed::{ render_chart, ChartFormat, ChartOptions, NetworkSpeed };
+
+	let history = vec![NetworkSpeed::new(1_000, 2_000), NetworkSpeed::new(3_000, 4_000)];
+
+	let svg = render_chart(&history, &ChartOptions::default().with_title("Throughput")).unwrap();
+	let svg = String::from_utf8(svg).unwrap();
+	assert!(svg.starts_with("<svg"));
+	assert!(svg.contains("Throughput"));
+
+	let png = render_chart(&history, &ChartOptions::new(ChartFormat::Png).with_size(200, 100)).unwrap();
+	assert_eq!(&png[..4], b"\x89PNG");
+
+	assert!(render_chart(&[], &ChartOptions::default()).is_ok());
+	assert!(render_chart(&history, &ChartOptions::default().with_size(0, 100)).is_err());
+	assert!(render_chart(&history, &ChartOptions::default().with_window(Duration::MAX)).is_ok());
+}
+
+#[cfg(feature = "arrow")]
+#[test]
+fn test_history_arrow_export() {
+	use network_speed::{ history_to_record_batch, write_history_parquet, NetworkSpeed };
+
+	let history = vec![NetworkSpeed::new(1_000, 2_000), NetworkSpeed::new(3_000, 4_000)];
+
+	let batch = history_to_record_batch(&history).unwrap();
+	assert_eq!(batch.num_rows(), 2);
+	assert_eq!(batch.num_columns(), 3);
+	assert_eq!(batch.schema().field(1).name(), "upload_bytes_per_sec");
+
+	let mut parquet = Vec::new();
+	write_history_parquet(&history, &mut parquet).unwrap();
+	assert_eq!(&parquet[..4], b"PAR1");
+}
+
+#[cfg(feature = "arrow")]
+#[test]
+fn test_labeled_arrow_export() {
+	use network_speed::{ labeled_history_to_record_batch, MonitorLabels, NetworkSpeed };
+
+	let labels = MonitorLabels::named("office-uplink").with_label("site", "berlin");
+	let batch = labeled_history_to_record_batch(&[NetworkSpeed::new(1, 2)], &labels).unwrap();
+	let schema = batch.schema();
+	assert_eq!(schema.metadata().get("monitor.name").map(String::as_str), Some("office-uplink"));
+	assert_eq!(schema.metadata().get("label.site").map(String::as_str), Some("berlin"));
+}
+
+#[cfg(all(feature = "arrow", feature = "persist"))]
+#[test]
+fn test_usage_arrow_export() {
+	use arrow_array::{ Array, StringArray, UInt64Array };
+	use network_speed::{
+		usage_report_to_record_batch,
+		usage_to_record_batch,
+		write_usage_report_parquet,
+		UsageReport,
+		UsageStats,
+		UsageTotals,
+	};
+	use std::collections::BTreeMap;
+
+	let usage = BTreeMap::from([
+		("home".to_string(), UsageTotals { bytes_sent: 10, bytes_received: 200 }),
+		("office".to_string(), UsageTotals { bytes_sent: 30, bytes_received: 400 }),
+	]);
+	let batch = usage_to_record_batch(&usage).unwrap();
+	assert_eq!(batch.num_rows(), 2);
+	let received = batch.column(2).as_any().downcast_ref::<UInt64Array>().unwrap();
+	assert_eq!(received.values(), &[200, 400]);
+
+	let stats = UsageStats { bytes_sent: 800, bytes_received: 320_000, samples: 6, ..Default::default() };
+	let report = UsageReport {
+		start_ms: 0,
+		end_ms: 3_600_000,
+		total: stats,
+		busiest_hour: None,
+		interfaces: BTreeMap::from([("ethernet".to_string(), stats)]),
+	};
+	let batch = usage_report_to_record_batch(&report).unwrap();
+	assert_eq!((batch.num_rows(), batch.num_columns()), (2, 11));
+	let interfaces = batch.column(2).as_any().downcast_ref::<StringArray>().unwrap();
+	assert_eq!((interfaces.value(0), interfaces.value(1)), ("ethernet", "total"));
+	assert_eq!(batch.column(10).len(), 2);
+
+	let mut parquet = Vec::new();
+	write_usage_report_parquet(&report, &mut parquet).unwrap();
+	assert_eq!(&parquet[..4], b"PAR1");
+}
+
+#[cfg(feature = "egui")]
+#[test]
+fn test_speed_graph_widget() {
+	use network_speed::{ NetworkSpeed, NetworkSpeedTracker, SpeedGraph };
+	use std::time::Duration;
+
+	let history = vec![NetworkSpeed::new(1_000, 2_000), NetworkSpeed::new(3_000, 4_000)];
+	let mut tracker = NetworkSpeedTracker::new(10);
+	for speed in &history {
+		tracker.record(speed.clone());
+	}
+
+	let ctx = egui::Context::default();
+	let output = ctx.run(egui::RawInput::default(), |ctx| {
+		egui::CentralPanel::default().show(ctx, |ui| {
+			let response = ui.add(SpeedGraph::new(&history).window(Duration::from_secs(30)).size(200.0, 60.0));
+			assert!(response.rect.height() >= 60.0);
+			ui.add(SpeedGraph::from_tracker(&tracker).show_labels(false));
+		});
+	});
+	assert!(!output.shapes.is_empty());
+}
+
+#[test]
+fn test_utilization_sample_between_readings() {
+	use network_speed::{ NetworkInterface, UtilizationSample };
+	use std::time::Instant;
+
+	let reading = |sent: u64, received: u64| NetworkInterface {
+		index: 7,
+		guid: "{4D36E972-E325-11CE-BFC1-08002BE10318}".to_string(),
+		interface_type: InterfaceKind::Ethernet,
+		physical_medium: 0,
+		description: "Ethernet".to_string(),
+		driver_description: String::new(),
+		is_operational: true,
+		oper_status: network_speed::OperStatus::Up,
+		bytes_sent: sent,
+		bytes_received: received,
+		packets: Default::default(),
+		speed: 100_000_000,
+	};
+
+	// 100 Mbps link: 1.25 MB in one second is 10%, 6.25 MB is 50%.
+	let sample = UtilizationSample::between(
+		&reading(0, 0),
+		&reading(1_250_000, 6_250_000),
+		Duration::from_secs(1),
+		Instant::now()
+	).unwrap();
+	assert!((sample.upload_percent - 10.0).abs() < 1e-9);
+	assert!((sample.download_percent - 50.0).abs() < 1e-9);
+	assert!((sample.peak_percent() - 50.0).abs() < 1e-9);
+	assert_eq!(sample.link_speed_bps, 100_000_000);
+
+	// Counters went backwards (adapter reset) or no time passed: no sample.
+	assert!(UtilizationSample::between(&reading(10, 10), &reading(5, 20), Duration::from_secs(1), Instant::now()).is_none());
+	assert!(UtilizationSample::between(&reading(0, 0), &reading(5, 5), Duration::ZERO, Instant::now()).is_none());
+}
+
+#[test]
+fn test_adaptive_alert_hourly_baseline() {
+	use network_speed::{ AdaptiveAlert, Direction, Event, EventMonitor, LocalTime, NetworkSpeed, Weekday };
+	use std::time::Instant;
+
+	let base = Instant::now();
+	let sample = |seconds: u64, download: u64| NetworkSpeed {
+		timestamp: base + Duration::from_secs(seconds),
+		..NetworkSpeed::new(0, download)
+	};
+	let morning = LocalTime::new(Weekday::Monday, 9, 15);
+	let night = LocalTime::new(Weekday::Monday, 3, 0);
+	let mut alert = AdaptiveAlert::new("throughput")
+		.with_training_window(Duration::from_secs(60))
+		.with_min_hour_samples(10)
+		.with_consecutive(2);
+
+	// Training: 1.0–1.4 MB/s at 09:00, never alerting.
+	for second in 0..60 {
+		assert!(alert.observe(&sample(second, 1_000_000 + (second % 5) * 100_000), morning).is_none());
+	}
+	assert!(alert.is_trained(base + Duration::from_secs(60)));
+	assert_eq!(alert.baseline().hour(9).count(), 60);
+	assert_eq!(alert.baseline().hour(9).mean().round(), 1_200_000.0);
+	assert!(alert.observe(&sample(61, 1_300_000), morning).is_none());
+
+	// Two deviating samples in a row fire; they are not learned.
+	assert!(alert.observe(&sample(62, 5_000_000), morning).is_none());
+	let Some(Event::AlertFired { name, message }) = alert.observe(&sample(63, 5_000_000), morning) else {
+		panic!("expected the alert to fire");
+	};
+	assert_eq!(name, "throughput");
+	assert!(message.contains("above the 09:00 baseline"), "{message}");
+	assert!(alert.is_firing());
+	assert_eq!(alert.baseline().hour(9).count(), 61);
+
+	// An untrained hour is never evaluated, so it counts towards clearing.
+	assert!(alert.observe(&sample(64, 5_000_000), night).is_none());
+	assert!(matches!(alert.observe(&sample(65, 1_200_000), morning), Some(Event::AlertCleared { .. })));
+
+	assert!(alert.observe(&sample(66, 0), morning).is_none());
+	let Some(Event::AlertFired { message, .. }) = alert.observe(&sample(67, 0), morning) else {
+		panic!("a drop in traffic fires as well");
+	};
+	assert!(message.contains("below"), "{message}");
+
+	// An upload alert learns upload only, so a download surge leaves it quiet and an upload one fires.
+	let mut upload = AdaptiveAlert::new("upload")
+		.with_direction(Direction::Upload)
+		.with_training_window(Duration::from_secs(60))
+		.with_min_hour_samples(10);
+	for second in 0..60 {
+		upload.observe(&NetworkSpeed { timestamp: base + Duration::from_secs(second), ..NetworkSpeed::new(50_000, 1_000_000) }, morning);
+	}
+	assert_eq!(upload.baseline().hour(9).mean().round(), 50_000.0);
+	assert!(upload.observe(&sample(61, 9_000_000), morning).is_none());
+	let surge = NetworkSpeed { timestamp: base + Duration::from_secs(62), ..NetworkSpeed::new(2_000_000, 0) };
+	let Some(Event::AlertFired { message, .. }) = upload.observe(&surge, morning) else {
+		panic!("expected the upload alert to fire");
+	};
+	assert!(message.starts_with("upload throughput"), "{message}");
+
+	assert!(EventMonitor::new(NetworkMonitor::new()).with_adaptive_alert(AdaptiveAlert::new("x").with_sigma(0.0)).is_err());
+	let events = EventMonitor::new(NetworkMonitor::new()).with_adaptive_alert(alert).unwrap();
+	assert_eq!(events.adaptive_alerts()[0].name(), "throughput");
+}
+
+#[test]
+fn test_event_bus_fan_out_and_pruning() {
+	use network_speed::{ Event, EventBus, NetworkSpeed };
+
+	let bus = EventBus::new();
+	let first = bus.subscribe();
+	let second = bus.clone().subscribe();
+	assert_eq!(bus.subscriber_count(), 2);
+
+	bus.publish(Event::Sample(NetworkSpeed::new(1, 2)));
+	assert!(matches!(first.try_recv(), Ok(Event::Sample(_))));
+	assert!(matches!(second.try_recv(), Ok(Event::Sample(_))));
+
+	drop(second);
+	bus.publish(Event::AlertCleared { name: "quota".to_string() });
+	assert_eq!(bus.subscriber_count(), 1);
+	assert!(matches!(first.try_recv(), Ok(Event::AlertCleared { .. })));
+}
+
+#[test]
+fn test_interface_events_diff() {
+	use network_speed::{ interface_events, Event, NetworkInterface };
+
+	let iface = |index: u32, guid: &str, speed: u64| NetworkInterface {
+		index,
+		guid: guid.to_string(),
+		interface_type: InterfaceKind::Ethernet,
+		physical_medium: 0,
+		description: format!("NIC {index}"),
+		driver_description: String::new(),
+		is_operational: true,
+		oper_status: network_speed::OperStatus::Up,
+		bytes_sent: 0,
+		bytes_received: 0,
+		packets: Default::default(),
+		speed,
+	};
+
+	let before = vec![iface(1, "{A}", 1_000), iface(2, "{B}", 1_000), iface(3, "", 1_000)];
+	// {A} renumbered but same GUID with a new link speed, {B} gone, {C} new, index 3 unchanged.
+	let after = vec![iface(9, "{A}", 100), iface(4, "{C}", 1_000), iface(3, "", 1_000)];
+
+	let events = interface_events(&before, &after);
+	assert_eq!(events.len(), 3);
+	assert!(events.iter().any(|event| matches!(event, Event::LinkSpeedChanged { interface, previous_bps: 1_000 } if interface.guid == "{A}")));
+	assert!(events.iter().any(|event| matches!(event, Event::InterfaceAdded(interface) if interface.guid == "{C}")));
+	assert!(events.iter().any(|event| matches!(event, Event::InterfaceRemoved(interface) if interface.guid == "{B}")));
+}
+
+#[test]
+fn test_network_profile_events_diff() {
+	use network_speed::{ network_profile_events, Event, NetworkCategory, NetworkProfile };
+
+	let network = |name: &str, category: NetworkCategory| NetworkProfile {
+		name: name.to_string(),
+		category,
+	};
+
+	let before = vec![network("Office", NetworkCategory::Public), network("Home", NetworkCategory::Private)];
+	let after = vec![network("Office", NetworkCategory::Domain), network("Cafe", NetworkCategory::Public)];
+
+	let events = network_profile_events(&before, &after);
+	assert_eq!(events.len(), 3);
+	assert!(events.iter().any(|event| matches!(event, Event::NetworkCategoryChanged { network, previous: NetworkCategory::Public } if network.name == "Office" && network.category == NetworkCategory::Domain)));
+	assert!(events.iter().any(|event| matches!(event, Event::NetworkConnected(network) if network.name == "Cafe")));
+	assert!(events.iter().any(|event| matches!(event, Event::NetworkDisconnected(network) if network.name == "Home")));
+	assert!(network_profile_events(&after, &after).is_empty());
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn test_event_bus_async_subscriber() {
+	use network_speed::{ Event, EventBus, NetworkSpeed };
+
+	let bus = EventBus::new();
+	let mut receiver = bus.subscribe_async();
+	bus.publish(Event::Sample(NetworkSpeed::new(5, 6)));
+	assert!(matches!(receiver.recv().await, Some(Event::Sample(speed)) if speed.download_bytes_per_sec == 6));
+}
+
+#[test]
+fn test_forecast_linear_trend() {
+	use network_speed::{ forecast_history, NetworkSpeed };
+	use std::time::Instant;
+
+	let now = Instant::now();
+	// Download grows 100 B/s every second: 1000, 1100, ..., 1400 over the last 4 seconds.
+	let history: Vec<NetworkSpeed> = (0..5u64)
+		.map(|i| NetworkSpeed {
+			timestamp: now - Duration::from_secs(4 - i),
+			..NetworkSpeed::new(500, 1_000 + i * 100)
+		})
+		.collect();
+
+	let forecast = forecast_history(&history, Duration::from_secs(10)).unwrap();
+	assert_eq!(forecast.upload_bytes_per_sec, 500);
+	assert_eq!(forecast.download_bytes_per_sec, 2_400);
+	assert!((forecast.download_trend - 100.0).abs() < 1e-6);
+	assert_eq!(forecast.projected_upload_bytes, 5_000);
+	// Trapezoid from 1400 to 2400 B/s over 10 s.
+	assert_eq!(forecast.projected_download_bytes, 19_000);
+	assert_eq!(forecast.projected_total_bytes(), 24_000);
+}
+
+#[test]
+fn test_forecast_cycle_end() {
+	use network_speed::{ forecast_cycle_end, UsageTotals };
+
+	const DAY: Duration = Duration::from_secs(86_400);
+	// 100 GB received in the first 10 days of a 30-day cycle.
+	let used = UsageTotals { bytes_sent: 5_000_000_000, bytes_received: 100_000_000_000 };
+	let forecast = forecast_cycle_end(used, 10 * DAY, 20 * DAY).unwrap();
+	assert_eq!(forecast.projected, UsageTotals { bytes_sent: 15_000_000_000, bytes_received: 300_000_000_000 });
+	assert_eq!(forecast.projected_total_bytes(), 315_000_000_000);
+	assert!(forecast.exceeds(300_000_000_000));
+	assert!(!forecast.exceeds(400_000_000_000));
+
+	assert_eq!(forecast_cycle_end(used, 30 * DAY, Duration::ZERO).unwrap().projected, used);
+	assert!(forecast_cycle_end(used, Duration::ZERO, 30 * DAY).is_none());
+}
+
+#[test]
+fn test_forecast_clamps_declining_traffic() {
+	use network_speed::{ forecast_history, NetworkSpeed };
+	use std::time::Instant;
+
+	let now = Instant::now();
+	let history = vec![
+		NetworkSpeed { timestamp: now - Duration::from_secs(1), ..NetworkSpeed::new(200, 0) },
+		NetworkSpeed { timestamp: now, ..NetworkSpeed::new(100, 0) }
+	];
+
+	let forecast = forecast_history(&history, Duration::from_secs(10)).unwrap();
+	assert_eq!(forecast.upload_bytes_per_sec, 0);
+	// Falls from 100 B/s to zero after 1 s, then stays at zero.
+	assert_eq!(forecast.projected_upload_bytes, 50);
+
+	assert!(forecast_history(&history[..1], Duration::from_secs(1)).is_none());
+}
+
+#[cfg(feature = "persist")]
+#[test]
+fn test_history_persistence_round_trip() {
+	use network_speed::{ append_history, read_history, HistoryCompression, HistoryWriter, NetworkSpeed };
+
+	for compression in [HistoryCompression::Gzip, HistoryCompression::None] {
+		let path = std::env::temp_dir().join(format!("network-speed-history-{:?}-{}.jsonl", compression, std::process::id()));
+		let _ = std::fs::remove_file(&path);
+
+		{
+			let mut writer = HistoryWriter::open(&path, compression).unwrap().with_chunk_size(2);
+			for i in 0..5u64 {
+				writer.push(&NetworkSpeed::new(i, i * 10)).unwrap();
+			}
+			// The fifth sample is flushed on drop.
+		}
+		append_history(&path, &[NetworkSpeed::new(99, 990)], compression).unwrap();
+
+		let bytes = std::fs::read(&path).unwrap();
+		assert_eq!(bytes.starts_with(&[0x1f, 0x8b]), compression == HistoryCompression::Gzip);
+
+		let records = read_history(&path).unwrap();
+		let uploads: Vec<u64> = records
+			.iter()
+			.map(|record| record.upload_bytes_per_sec)
+			.collect();
+		assert_eq!(uploads, vec![0, 1, 2, 3, 4, 99]);
+		assert_eq!(records[5].to_speed().download_bytes_per_sec, 990);
+
+		std::fs::remove_file(&path).unwrap();
+	}
+}
+
+#[cfg(feature = "persist")]
+#[test]
+fn test_records_to_speeds_keeps_spacing() {
+	use network_speed::{ records_to_speeds, summarize, HistoryRecord, MonitorLabels };
+	use std::time::Instant;
+
+	// A session recorded long ago, one sample per second.
+	let record = |timestamp_ms: u64, download: u64| HistoryRecord {
+		timestamp_ms,
+		upload_bytes_per_sec: 0,
+		download_bytes_per_sec: download,
+		labels: MonitorLabels::default(),
+	};
+	let records = [record(1_000_000, 1_000), record(1_001_000, 3_000), record(1_002_000, 2_000)];
+
+	let origin = Instant::now();
+	let speeds = records_to_speeds(&records, origin);
+	assert_eq!(speeds[0].timestamp, origin);
+	assert_eq!(speeds[2].timestamp, origin + Duration::from_secs(2));
+
+	let summary = summarize(&speeds).unwrap();
+	assert_eq!(summary.duration, Duration::from_secs(3));
+	assert_eq!(summary.bytes_received, 6_000);
+	assert!(records_to_speeds(&[], origin).is_empty());
+}
+
+#[cfg(feature = "persist")]
+#[test]
+fn test_usage_by_label() {
+	use network_speed::{ usage_by_label, HistoryRecord, MonitorLabels };
+
+	let record = |timestamp_ms: u64, ssid: Option<&str>| HistoryRecord {
+		timestamp_ms,
+		upload_bytes_per_sec: 100,
+		download_bytes_per_sec: 1_000,
+		labels: match ssid {
+			Some(ssid) => MonitorLabels::new().with_label("ssid", ssid),
+			None => MonitorLabels::new(),
+		},
+	};
+	let records = [
+		record(0, Some("Home")),
+		record(1_000, Some("Home")),
+		record(2_000, Some("Home")),
+		// Roamed to a hotspot, then a gap that starts a new session.
+		record(2_500, Some("Hotspot")),
+		record(600_000, Some("Hotspot")),
+		record(601_000, None),
+	];
+
+	let usage = usage_by_label(&records, "ssid");
+	assert_eq!(usage.len(), 2);
+	assert_eq!(usage["Home"].bytes_sent, 200);
+	assert_eq!(usage["Home"].bytes_received, 2_000);
+	assert_eq!(usage["Hotspot"].bytes_received, 500);
+}
+
+#[cfg(feature = "persist")]
+#[test]
+fn test_usage_report_between() {
+	use network_speed::{ HistoryRecord, MonitorLabels, UsageReport };
+	use std::time::UNIX_EPOCH;
+
+	const HOUR: u64 = 3_600_000;
+	let record = |timestamp_ms: u64, interface: &str, download: u64| HistoryRecord {
+		timestamp_ms,
+		upload_bytes_per_sec: 10,
+		download_bytes_per_sec: download,
+		labels: MonitorLabels::named("office").with_label("interface", interface),
+	};
+	let records = [
+		// Before the report starts; only the second half of the next interval counts.
+		record(9 * HOUR - 10_000, "wifi", 1_000),
+		record(9 * HOUR + 10_000, "wifi", 1_000),
+		record(9 * HOUR + 20_000, "wifi", 1_000),
+		record(10 * HOUR, "ethernet", 0),
+		record(10 * HOUR + 30_000, "ethernet", 5_000),
+		record(10 * HOUR + 60_000, "ethernet", 5_000),
+		// A new session after a gap, and a sample past the end.
+		record(11 * HOUR, "ethernet", 5_000),
+		record(12 * HOUR, "ethernet", 9_000),
+	];
+	let at = |ms: u64| UNIX_EPOCH + Duration::from_millis(ms);
+
+	let report = UsageReport::between(&records, at(9 * HOUR), at(12 * HOUR));
+	assert_eq!(report.interfaces.keys().collect::<Vec<_>>(), vec!["ethernet", "wifi"]);
+
+	let wifi = &report.interfaces["wifi"];
+	assert_eq!((wifi.bytes_received, wifi.covered_ms, wifi.samples), (20_000, 20_000, 2));
+	assert_eq!(wifi.average_download_bytes_per_sec, 1_000);
+
+	let ethernet = &report.interfaces["ethernet"];
+	assert_eq!((ethernet.bytes_received, ethernet.samples), (300_000, 4));
+	assert_eq!(ethernet.peak_download_bytes_per_sec, 5_000);
+	assert_eq!(ethernet.average_download_bytes_per_sec, 5_000);
+
+	assert_eq!(report.total.bytes_received, 320_000);
+	assert_eq!(report.total.bytes_sent, 800);
+	let busiest = report.busiest_hour.unwrap();
+	assert_eq!((busiest.start_ms, busiest.bytes_received), (10 * HOUR, 300_000));
+
+	let json = report.to_json().unwrap();
+	assert_eq!(serde_json::from_str::<UsageReport>(&json).unwrap(), report);
+	let csv = report.to_csv();
+	let mut lines = csv.lines();
+	assert!(lines.next().unwrap().starts_with("interface,bytes_sent,bytes_received,"));
+	assert_eq!(lines.next().unwrap(), "ethernet,600,300000,60,10,5000,10,5000,4");
+	assert!(csv.ends_with("total,800,320000,60,13,5333,10,5000,6\n"));
+
+	let empty = UsageReport::between(&records, at(0), at(HOUR));
+	assert!(empty.interfaces.is_empty() && empty.busiest_hour.is_none());
+	assert!(UsageReport::last(&records, Duration::from_secs(60)).interfaces.is_empty());
+
+	// Halfway through the 9:00-12:00 "cycle", the 320 kB so far double by its end.
+	let forecast = report.forecast_cycle_end_at(at(10 * HOUR + HOUR / 2)).unwrap();
+	assert_eq!((forecast.used.bytes_received, forecast.projected.bytes_received), (320_000, 640_000));
+	assert_eq!(forecast.remaining, Duration::from_millis(HOUR + HOUR / 2));
+	assert_eq!(report.forecast_cycle_end_at(at(13 * HOUR)).unwrap().projected_total_bytes(), 320_800);
+	assert!(report.forecast_cycle_end_at(at(8 * HOUR)).is_none());
+}
+
+#[cfg(feature = "persist")]
+#[test]
+fn test_report_scheduler() {
+	use network_speed::{
+		append_labeled_history,
+		HistoryCompression,
+		LocalTime,
+		MonitorLabels,
+		NetworkSpeed,
+		RenderedReport,
+		ReportCadence,
+		ReportFormat,
+		ReportScheduler,
+		Weekday,
+	};
+	use std::sync::{ Arc, Mutex };
+	use std::time::SystemTime;
+
+	let path = std::env::temp_dir().join(format!("network-speed-report-{}.jsonl", std::process::id()));
+	let _ = std::fs::remove_file(&path);
+	let mut history = vec![NetworkSpeed::new(100, 1_000), NetworkSpeed::new(100, 1_000)];
+	// Both samples before the report end, 10 s apart.
+	history[0].timestamp -= Duration::from_secs(20);
+	history[1].timestamp -= Duration::from_secs(10);
+	let labels = MonitorLabels::named("uplink").with_label("interface", "<wan>");
+	append_labeled_history(&path, &history, HistoryCompression::None, &labels).unwrap();
+
+	let sent: Arc<Mutex<Vec<RenderedReport>>> = Arc::default();
+	let mut scheduler = ReportScheduler::new(&path, ReportCadence::Weekly(Weekday::Monday), "08:00")
+		.unwrap()
+		.with_format(ReportFormat::Html)
+		.with_delivery({
+			let sent = Arc::clone(&sent);
+			move |report: &RenderedReport| {
+				sent.lock().unwrap().push(report.clone());
+				Ok(())
+			}
+		});
+
+	let now = SystemTime::now();
+	assert!(scheduler.is_due(LocalTime::new(Weekday::Monday, 8, 30), now));
+	assert!(!scheduler.is_due(LocalTime::new(Weekday::Monday, 7, 59), now));
+	assert!(!scheduler.is_due(LocalTime::new(Weekday::Monday, 9, 0), now));
+	assert!(!scheduler.is_due(LocalTime::new(Weekday::Tuesday, 8, 30), now));
+
+	let report = scheduler.send_now().unwrap();
+	assert_eq!(report.interfaces["<wan>"].bytes_received, 10_000);
+	assert!(!scheduler.is_due(LocalTime::new(Weekday::Monday, 8, 30), SystemTime::now()));
+
+	let sent = sent.lock().unwrap();
+	assert_eq!(sent.len(), 1);
+	assert!(sent[0].subject.starts_with("Network usage "));
+	assert_eq!(sent[0].format.content_type(), "text/html; charset=utf-8");
+	assert!(sent[0].body.contains("<td>&lt;wan&gt;</td><td>1000 B</td><td>9.77 KB</td>"));
+	assert!(report.to_text().contains("Total:        1000 B sent, 9.77 KB received"));
+
+	assert!(ReportScheduler::new(&path, ReportCadence::Daily, "25:00").is_err());
+	let mut missing = ReportScheduler::new(path.with_extension("missing"), ReportCadence::Daily, "08:00").unwrap();
+	assert!(missing.send_now().is_err());
+	assert_eq!(missing.failed_attempts(), 1);
+
+	std::fs::remove_file(&path).unwrap();
+}
+
+#[cfg(all(feature = "webhook", feature = "smtp"))]
+#[test]
+fn test_report_webhook_and_smtp_delivery() {
+	use network_speed::{ NetworkError, ReportDelivery, ReportFormat, RenderedReport, SmtpDelivery, WebhookDelivery };
+	use std::io::{ BufRead, BufReader, Read, Write };
+	use std::net::TcpListener;
+
+	let report = RenderedReport {
+		subject: "Network usage".to_string(),
+		body: "{\"total\": 1}\n.hidden\n".to_string(),
+		format: ReportFormat::Json,
+	};
+
+	let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+	let url = format!("http://{}/hooks/report", listener.local_addr().unwrap());
+	let server = std::thread::spawn(move || {
+		let (mut stream, _) = listener.accept().unwrap();
+		let mut request = Vec::new();
+		let mut buffer = [0u8; 1024];
+		while !request.ends_with(b".hidden\n") {
+			let read = stream.read(&mut buffer).unwrap();
+			request.extend_from_slice(&buffer[..read]);
+		}
+		stream.write_all(b"HTTP/1.1 204 No Content\r\n\r\n").unwrap();
+		String::from_utf8(request).unwrap()
+	});
+	let mut webhook = WebhookDelivery::new(&url).unwrap().with_header("Authorization", "Bearer secret");
+	webhook.deliver(&report).unwrap();
+	let request = server.join().unwrap();
+	assert!(request.starts_with("POST /hooks/report HTTP/1.1\r\n"));
+	assert!(request.contains("Content-Type: application/json\r\n"));
+	assert!(request.contains("Authorization: Bearer secret\r\n"));
+	#[cfg(not(feature = "tls"))]
+	assert!(WebhookDelivery::new("https://example.com").is_err());
+
+	// Off the loopback interface the token would cross the network in the clear.
+	let exposed = std::net::UdpSocket::bind("0.0.0.0:0")
+		.and_then(|socket| socket.connect("192.0.2.1:9").and_then(|_| socket.local_addr()))
+		.ok()
+		.map(|addr| addr.ip())
+		.filter(|ip| !ip.is_loopback() && !ip.is_unspecified());
+	if let Some(ip) = exposed {
+		let listener = TcpListener::bind((ip, 0)).unwrap();
+		let url = format!("http://{}/hooks/report", listener.local_addr().unwrap());
+		let mut webhook = WebhookDelivery::new(&url).unwrap().with_header("authorization", "Bearer secret");
+		assert!(matches!(webhook.deliver(&report), Err(NetworkError::InvalidConfiguration { .. })));
+	}
+
+	let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+	let server_addr = listener.local_addr().unwrap().to_string();
+	let server = std::thread::spawn(move || {
+		let (stream, _) = listener.accept().unwrap();
+		let mut reader = BufReader::new(stream.try_clone().unwrap());
+		let mut writer = stream;
+		let mut transcript = Vec::new();
+		writer.write_all(b"220 relay ready\r\n").unwrap();
+		let mut in_data = false;
+		loop {
+			let mut line = String::new();
+			if reader.read_line(&mut line).unwrap() == 0 {
+				break;
+			}
+			let reply: &[u8] = if in_data {
+				if line == ".\r\n" {
+					in_data = false;
+					b"250 queued\r\n"
+				} else {
+					transcript.push(line);
+					continue;
+				}
+			} else if line.starts_with("EHLO") {
+				b"250-relay\r\n250 AUTH PLAIN\r\n"
+			} else if line.starts_with("DATA") {
+				in_data = true;
+				b"354 go ahead\r\n"
+			} else if line.starts_with("QUIT") {
+				writer.write_all(b"221 bye\r\n").unwrap();
+				transcript.push(line);
+				break;
+			} else if line.starts_with("AUTH") {
+				b"235 ok\r\n"
+			} else {
+				b"250 ok\r\n"
+			};
+			transcript.push(line);
+			writer.write_all(reply).unwrap();
+		}
+		transcript
+	});
+	let mut smtp = SmtpDelivery::new(server_addr, "monitor@example.com", &["ops@example.com", "noc@example.com"])
+		.unwrap()
+		.with_credentials("monitor", "hunter2");
+	smtp.deliver(&report).unwrap();
+	let transcript = server.join().unwrap();
+	assert!(transcript.contains(&"AUTH PLAIN AG1vbml0b3IAaHVudGVyMg==\r\n".to_string()));
+	assert!(transcript.contains(&"RCPT TO:<noc@example.com>\r\n".to_string()));
+	assert!(transcript.contains(&"Subject: Network usage\r\n".to_string()));
+	assert!(transcript.contains(&"..hidden\r\n".to_string()));
+	assert_eq!(transcript.last().unwrap(), "QUIT\r\n");
+
+	assert!(SmtpDelivery::new("localhost:25", "monitor", &["ops@example.com"]).is_err());
+	assert!(SmtpDelivery::new("localhost:25", "monitor@example.com", &[]).is_err());
+}
+
+#[cfg(all(feature = "webhook", feature = "tls"))]
+#[test]
+fn test_report_webhook_over_https() {
+	use network_speed::agent::ClientTls;
+	use network_speed::{ NetworkError, ReportDelivery, ReportFormat, RenderedReport, WebhookDelivery };
+	use std::io::{ Read, Write };
+	use std::net::TcpListener;
+	use std::sync::Arc;
+
+	let report = RenderedReport {
+		subject: "Network usage".to_string(),
+		body: "{\"total\": 1}\n".to_string(),
+		format: ReportFormat::Json,
+	};
+
+	let certs = rustls_pemfile
+		::certs(&mut &include_bytes!("fixtures/test_server.pem")[..])
+		.collect::<Result<Vec<_>, _>>()
+		.unwrap();
+	let key = rustls_pemfile::private_key(&mut &include_bytes!("fixtures/test_server.key")[..]).unwrap().unwrap();
+	let config = rustls::ServerConfig
+		::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+		.with_safe_default_protocol_versions()
+		.unwrap()
+		.with_no_client_auth()
+		.with_single_cert(certs, key)
+		.unwrap();
+
+	let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+	let port = listener.local_addr().unwrap().port();
+	let url = format!("https://localhost:{port}/hooks/report");
+	let server = std::thread::spawn(move || {
+		let (stream, _) = listener.accept().unwrap();
+		let mut stream = rustls::StreamOwned::new(rustls::ServerConnection::new(Arc::new(config)).unwrap(), stream);
+		let mut request = Vec::new();
+		let mut buffer = [0u8; 1024];
+		while !request.ends_with(b"}\n") {
+			let read = stream.read(&mut buffer).unwrap();
+			request.extend_from_slice(&buffer[..read]);
+		}
+		stream.write_all(b"HTTP/1.1 200 OK\r\n\r\n").unwrap();
+		stream.flush().unwrap();
+		String::from_utf8(request).unwrap()
+	});
+
+	let tls = ClientTls::from_ca_pem(include_bytes!("fixtures/test_ca.pem"), "localhost").unwrap();
+	let mut webhook = WebhookDelivery::new(&url).unwrap().with_header("Authorization", "Bearer secret").with_tls(tls);
+	webhook.deliver(&report).unwrap();
+	let request = server.join().unwrap();
+	assert!(request.starts_with("POST /hooks/report HTTP/1.1\r\n"));
+	assert!(request.contains(&format!("Host: localhost:{port}\r\n")));
+	assert!(request.contains("Authorization: Bearer secret\r\n"));
+
+	// Without trust roots there is nothing to verify the server against.
+	let mut webhook = WebhookDelivery::new("https://127.0.0.1:9/hooks").unwrap();
+	assert!(matches!(webhook.deliver(&report), Err(NetworkError::InvalidConfiguration { .. })));
+}
+
+#[cfg(feature = "persist")]
+#[test]
+fn test_history_records_carry_labels() {
+	use network_speed::{ read_history, HistoryCompression, HistoryWriter, MonitorLabels, NetworkSpeed };
+
+	let path = std::env::temp_dir().join(format!("network-speed-labeled-{}.jsonl", std::process::id()));
+	let _ = std::fs::remove_file(&path);
+
+	let labels = MonitorLabels::named("wifi").with_label("group", "lab");
+	{
+		let mut writer = HistoryWriter::open(&path, HistoryCompression::None).unwrap().with_labels(labels.clone());
+		writer.push(&NetworkSpeed::new(1, 2)).unwrap();
+	}
+	{
+		let mut writer = HistoryWriter::open(&path, HistoryCompression::None).unwrap();
+		writer.push(&NetworkSpeed::new(3, 4)).unwrap();
+	}
+
+	let text = std::fs::read_to_string(&path).unwrap();
+	let mut lines = text.lines();
+	assert!(lines.next().unwrap().contains(r#""name":"wifi","labels":{"group":"lab"}"#));
+	assert!(!lines.next().unwrap().contains("labels"), "empty labels are omitted");
+
+	let records = read_history(&path).unwrap();
+	assert_eq!(records[0].labels, labels);
+	assert!(records[1].labels.is_empty());
+
+	std::fs::remove_file(&path).unwrap();
+}
+
+#[cfg(feature = "persist")]
+#[test]
+fn test_counter_trace_round_trip() {
+	use network_speed::{ read_trace, CounterEntry, CountersSnapshot, TraceRecorder };
+	use std::time::Instant;
+
+	let path = std::env::temp_dir().join(format!("network-speed-trace-{}.jsonl", std::process::id()));
+	let entry = |sent: u64, received: u64| CounterEntry {
+		index: 7,
+		interface_type: InterfaceKind::Wifi,
+		bytes_sent: sent,
+		bytes_received: received,
+	};
+
+	let start = Instant::now();
+	{
+		let mut recorder = TraceRecorder::create(&path).unwrap();
+		recorder.record(&CountersSnapshot::from_entries([entry(0, 0)], start)).unwrap();
+		recorder.record(&CountersSnapshot::from_entries([entry(500, 2_000)], start + Duration::from_millis(500))).unwrap();
+	}
+
+	let snapshots = read_trace(&path).unwrap();
+	assert_eq!(snapshots.len(), 2);
+	assert_eq!(snapshots[1].taken_at() - snapshots[0].taken_at(), Duration::from_millis(500));
+
+	let delta = snapshots[1].diff(&snapshots[0]).next().unwrap();
+	assert_eq!(delta.upload_rate().bytes_per_sec(), 1_000);
+	assert_eq!(delta.download_rate().bytes_per_sec(), 4_000);
+
+	std::fs::remove_file(&path).unwrap();
+}
+
+#[cfg(all(feature = "persist", feature = "test-util"))]
+#[test]
+fn test_counter_trace_replay() {
+	use network_speed::{ CounterSource, MockCounterSource, RecordingCounterSource, TraceCounterSource };
+
+	let path = std::env::temp_dir().join(format!("network-speed-replay-{}.jsonl", std::process::id()));
+	let mock = MockCounterSource::new();
+	mock.push_counters(&[(1_000, 10_000)]);
+	mock.push_deltas(&[(500, 20_000)]);
+	mock.push_deltas(&[(500, 20_000)]);
+
+	let config = NetworkMonitorConfig::builder().min_measurement_interval(Duration::from_millis(20)).build().unwrap();
+	let mut recorded = Vec::new();
+	{
+		let source = RecordingCounterSource::create(mock, &path).unwrap();
+		let mut monitor = NetworkMonitor::with_source(config.clone(), source);
+		for _ in 0..3 {
+			recorded.push(monitor.total_traffic().unwrap());
+			std::thread::sleep(Duration::from_millis(200));
+		}
+	}
+
+	let mut replay = TraceCounterSource::open(&path).unwrap();
+	assert_eq!(replay.remaining(), 3);
+	let interfaces = replay.read_interfaces().unwrap();
+	assert_eq!(interfaces[0].as_ref().unwrap().description, "mock0");
+
+	// Replayed without pauses, reads keep the recorded 200 ms spacing.
+	let mut monitor = NetworkMonitor::with_source(config, TraceCounterSource::open(&path).unwrap());
+	monitor.measure_speed().unwrap();
+	let speed = monitor.measure_speed().unwrap();
+	assert!(speed.download_bytes_per_sec.bytes_per_sec().abs_diff(100_000) < 15_000, "{speed:?}");
+	assert_eq!(recorded[2], (2_000, 50_000));
+	assert_eq!(monitor.total_traffic().unwrap(), recorded[2]);
+
+	std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_shutdown_wakes_waiters_and_runs_hooks_once() {
+	use network_speed::Shutdown;
+	use std::sync::atomic::{ AtomicUsize, Ordering };
+	use std::sync::Arc;
+	use std::time::Instant;
+
+	let shutdown = Shutdown::new();
+	let flushed = Arc::new(AtomicUsize::new(0));
+	let counter = Arc::clone(&flushed);
+	shutdown.on_shutdown(move || {
+		counter.fetch_add(1, Ordering::SeqCst);
+	});
+
+	assert!(!shutdown.wait_timeout(Duration::from_millis(10)));
+
+	let waiter = shutdown.clone();
+	let started = Instant::now();
+	let handle = thread::spawn(move || waiter.wait_timeout(Duration::from_secs(30)));
+	thread::sleep(Duration::from_millis(20));
+	shutdown.trigger();
+	shutdown.trigger();
+
+	assert!(handle.join().unwrap());
+	assert!(started.elapsed() < Duration::from_secs(5));
+	assert!(shutdown.is_triggered());
+	assert_eq!(flushed.load(Ordering::SeqCst), 1);
+
+	// Hooks registered after the fact run immediately.
+	let counter = Arc::clone(&flushed);
+	shutdown.on_shutdown(move || {
+		counter.fetch_add(1, Ordering::SeqCst);
+	});
+	assert_eq!(flushed.load(Ordering::SeqCst), 2);
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn test_shutdown_async_triggered() {
+	use network_speed::Shutdown;
+
+	let shutdown = Shutdown::new();
+	let trigger = shutdown.clone();
+	tokio::spawn(async move {
+		tokio::time::sleep(Duration::from_millis(10)).await;
+		trigger.trigger();
+	});
+
+	tokio::time::timeout(Duration::from_secs(5), shutdown.triggered()).await.unwrap();
+}
+
+#[test]
+fn test_cast_breakdown_deltas() {
+	use network_speed::{ CastCounters, InterfaceBreakdown, InterfaceCounters, NetworkInterface };
+
+	let interface = NetworkInterface {
+		index: 3,
+		guid: "{4D36E972-E325-11CE-BFC1-08002BE10318}".to_string(),
+		interface_type: InterfaceKind::Wifi,
+		physical_medium: 0,
+		description: "Wi-Fi".to_string(),
+		driver_description: String::new(),
+		is_operational: true,
+		oper_status: network_speed::OperStatus::Up,
+		bytes_sent: 0,
+		bytes_received: 0,
+		packets: Default::default(),
+		speed: 0,
+	};
+	let counters = |unicast: u64, multicast: u64, broadcast: u64| InterfaceCounters {
+		interface: interface.clone(),
+		sent: CastCounters::default(),
+		received: CastCounters {
+			unicast_bytes: unicast,
+			multicast_bytes: multicast,
+			broadcast_bytes: broadcast,
+			unicast_packets: unicast / 1_000,
+			non_unicast_packets: (multicast + broadcast) / 100,
+		},
+		outbound_discards: 0,
+		output_queue_length: 0,
+	};
+
+	let breakdown = InterfaceBreakdown::between(
+		&counters(10_000, 1_000, 500),
+		&counters(16_000, 4_000, 2_500),
+		Duration::from_secs(2)
+	);
+	assert_eq!(breakdown.received.unicast_bytes, 6_000);
+	assert_eq!(breakdown.received.multicast_bytes, 3_000);
+	assert_eq!(breakdown.received.broadcast_bytes, 2_000);
+	assert_eq!(breakdown.received.total_bytes(), 11_000);
+	assert!((breakdown.received.non_unicast_share().unwrap() - 5.0 / 11.0).abs() < 1e-9);
+	assert!((breakdown.non_unicast_receive_rate() - 2_500.0).abs() < 1e-9);
+	assert_eq!(breakdown.sent.non_unicast_share(), None);
+
+	// A counter reset yields zero rather than wrapping.
+	let reset = counters(0, 0, 0).received.delta_since(&counters(5, 5, 5).received);
+	assert_eq!(reset, CastCounters::default());
+}
+
+#[test]
+fn test_congestion_from_discards() {
+	use network_speed::{ overall_congestion, CastCounters, CongestionLevel, InterfaceBreakdown, InterfaceCounters, NetworkInterface };
+
+	assert_eq!(CongestionLevel::classify(0, 0, 0), CongestionLevel::Clear);
+	assert_eq!(CongestionLevel::classify(10_000, 5, 0), CongestionLevel::Clear);
+	assert_eq!(CongestionLevel::classify(10_000, 20, 0), CongestionLevel::Elevated);
+	assert_eq!(CongestionLevel::classify(10_000, 0, 1), CongestionLevel::Elevated);
+	assert_eq!(CongestionLevel::classify(1_000, 50, 0), CongestionLevel::Congested);
+	assert_eq!(CongestionLevel::classify(0, 0, 12), CongestionLevel::Congested);
+
+	let counters = |packets: u64, discards: u64| InterfaceCounters {
+		interface: NetworkInterface {
+			index: 9,
+			guid: String::new(),
+			interface_type: InterfaceKind::Ethernet,
+			physical_medium: 0,
+			description: "Ethernet".to_string(),
+			driver_description: String::new(),
+			is_operational: true,
+			oper_status: network_speed::OperStatus::Up,
+			bytes_sent: 0,
+			bytes_received: 0,
+			packets: Default::default(),
+			speed: 0,
+		},
+		sent: CastCounters { unicast_packets: packets, ..CastCounters::default() },
+		received: CastCounters::default(),
+		outbound_discards: discards,
+		output_queue_length: 0,
+	};
+
+	let quiet = InterfaceBreakdown::between(&counters(0, 100), &counters(5_000, 100), Duration::from_secs(1));
+	let dropping = InterfaceBreakdown::between(&counters(0, 100), &counters(1_000, 150), Duration::from_secs(1));
+	assert_eq!(dropping.outbound_discards, 50);
+	assert_eq!(quiet.congestion(), CongestionLevel::Clear);
+	assert!(dropping.congestion().is_congested());
+	assert_eq!(overall_congestion(&[quiet, dropping]), CongestionLevel::Congested);
+	assert_eq!(overall_congestion(&[]), CongestionLevel::Clear);
+}
+
+#[test]
+fn test_time_weighted_average() {
+	use network_speed::{ time_weighted_average, NetworkSpeed };
+	use std::time::Instant;
+
+	let start = Instant::now();
+	let at = |offset_ms: u64, download: u64| NetworkSpeed {
+		timestamp: start + Duration::from_millis(offset_ms),
+		..NetworkSpeed::new(0, download)
+	};
+
+	// Two ticks were skipped before the last reading, so it covers 3 s and weighs three times as much:
+	// (1000 + 1000 + 3 * 3000) / 5 = 2200 B/s, where the plain mean would say 1666 B/s.
+	let samples = [at(0, 1_000), at(1_000, 1_000), at(4_000, 3_000)];
+	let average = time_weighted_average(&samples).unwrap();
+	assert_eq!(average.download_bytes_per_sec.bytes_per_sec(), 2_200);
+
+	// Evenly spaced samples reduce to the plain mean.
+	let even = [at(0, 1_000), at(1_000, 2_000), at(2_000, 6_000)];
+	assert_eq!(time_weighted_average(&even).unwrap().download_bytes_per_sec.bytes_per_sec(), 3_000);
+
+	// Identical timestamps fall back to equal weights.
+	let same = [at(0, 1_000), at(0, 3_000)];
+	assert_eq!(time_weighted_average(&same).unwrap().download_bytes_per_sec.bytes_per_sec(), 2_000);
+
+	assert!(time_weighted_average(&[]).is_none());
+}
+
+#[test]
+fn test_summarize() {
+	use network_speed::{ format_duration, parse_duration, summarize, NetworkSpeed };
+	use std::time::Instant;
+
+	let start = Instant::now();
+	let samples: Vec<NetworkSpeed> = (0..20u64)
+		.map(|i| NetworkSpeed {
+			timestamp: start + Duration::from_secs(i),
+			..NetworkSpeed::new(100, (i + 1) * 1_000)
+		})
+		.collect();
+
+	let summary = summarize(&samples).unwrap();
+	assert_eq!(summary.samples, 20);
+	assert_eq!(summary.duration, Duration::from_secs(20));
+	assert_eq!(summary.average.download_bytes_per_sec.bytes_per_sec(), 10_500);
+	assert_eq!(summary.peak.download_bytes_per_sec.bytes_per_sec(), 20_000);
+	assert_eq!(summary.p95.download_bytes_per_sec.bytes_per_sec(), 19_000);
+	assert_eq!(summary.p95.upload_bytes_per_sec.bytes_per_sec(), 100);
+	assert_eq!(summary.bytes_received, 210_000);
+	assert_eq!(summary.bytes_sent, 2_000);
+	assert!(summarize(&[]).is_none());
+
+	assert_eq!(parse_duration("250ms"), Some(Duration::from_millis(250)));
+	assert_eq!(parse_duration("1.5s"), Some(Duration::from_millis(1500)));
+	assert_eq!(parse_duration("5m"), Some(Duration::from_secs(300)));
+	assert_eq!(parse_duration("2h"), Some(Duration::from_secs(7200)));
+	assert_eq!(parse_duration("30"), None);
+	assert_eq!(parse_duration("-1s"), None);
+	assert_eq!(format_duration(Duration::from_millis(850)), "850ms");
+	assert_eq!(format_duration(Duration::from_secs(725)), "12m 05s");
+}
+
+#[test]
+fn test_counter_provider() {
+	use network_speed::CounterProvider;
+
+	assert_eq!(CounterProvider::IfTable2.counter_bits(), 64);
+	assert_eq!(CounterProvider::LegacyIfTable.counter_bits(), 32);
+	assert!(CounterProvider::LegacyIfTable.is_legacy());
+
+	let mut monitor = NetworkMonitor::new();
+	assert!(monitor.provider_info().is_none());
+	if cfg!(windows) && monitor.measure_speed().is_ok() {
+		let info = monitor.provider_info().unwrap();
+		assert_eq!(info.is_legacy(), info.legacy_reads > 0);
+	}
+}
+
+#[test]
+fn test_custom_counter_source() {
+	use network_speed::{
+		CounterProvider,
+		CounterSource,
+		InterfaceKind,
+		NetworkError,
+		NetworkInterface,
+		OperStatus,
+		RowErrorPolicy,
+		SystemCounterSource,
+	};
+	use std::sync::atomic::{ AtomicU64, Ordering };
+	use std::sync::Arc;
+
+	/// One Ethernet adapter gaining 1 MB per read, plus a row that always fails to convert.
+	struct Fixture(Arc<AtomicU64>);
+
+	impl CounterSource for Fixture {
+		fn read_interfaces(&mut self) -> network_speed::Result<Vec<network_speed::Result<NetworkInterface>>> {
+			let reads = self.0.fetch_add(1, Ordering::SeqCst) + 1;
+			let interface = NetworkInterface {
+				index: 7,
+				guid: String::new(),
+				interface_type: InterfaceKind::Ethernet,
+				physical_medium: 0,
+				description: "fixture0".to_string(),
+				driver_description: String::new(),
+				is_operational: true,
+				oper_status: OperStatus::Up,
+				bytes_sent: reads * 1_000_000,
+				bytes_received: reads * 2_000_000,
+				packets: Default::default(),
+				speed: 1_000_000_000,
+			};
+			Ok(vec![Ok(interface), Err(NetworkError::InvalidInterface)])
+		}
+
+		fn provider(&self) -> CounterProvider {
+			CounterProvider::Custom("fixture")
+		}
+	}
+
+	let reads = Arc::new(AtomicU64::new(0));
+	let config = NetworkMonitorConfig::builder().row_errors(RowErrorPolicy::Skip).build().unwrap();
+	let mut monitor = NetworkMonitor::with_source(config.clone(), Fixture(Arc::clone(&reads)));
+	assert_eq!(monitor.total_traffic().unwrap(), (1_000_000, 2_000_000));
+	assert_eq!(monitor.provider_info().unwrap().provider, CounterProvider::Custom("fixture"));
+	assert_eq!(monitor.provider_info().unwrap().provider.as_str(), "fixture");
+	assert_eq!(monitor.skipped_rows().len(), 1);
+
+	monitor.update_config(config).unwrap();
+	assert_eq!(monitor.interface_traffic().unwrap()[0].description, "fixture0");
+	// Explaining reports every row, so the broken one fails it regardless of the policy.
+	assert!(monitor.explain_interfaces().is_err());
+
+	let _ = monitor.measure_breakdown();
+	assert!(reads.load(Ordering::SeqCst) >= 4);
+
+	let mut system = SystemCounterSource::new();
+	assert!(!system.read_interfaces().unwrap().is_empty());
+	assert_eq!(system.provider().counter_bits(), 64);
+}
+
+#[test]
+fn test_measure_stats_delta() {
+	use network_speed::{ CounterProvider, CounterSource, NetworkError, NetworkInterface, OperStatus, PacketCounters };
+
+	/// One adapter sending 100 packets per read, 1 of them failing, and receiving 400 with 4 discarded.
+	struct Fixture(u64);
+
+	impl CounterSource for Fixture {
+		fn read_interfaces(&mut self) -> network_speed::Result<Vec<network_speed::Result<NetworkInterface>>> {
+			self.0 += 1;
+			Ok(
+				vec![
+					Ok(NetworkInterface {
+						index: 3,
+						guid: String::new(),
+						interface_type: InterfaceKind::Ethernet,
+						physical_medium: 0,
+						description: "fixture0".to_string(),
+						driver_description: String::new(),
+						is_operational: true,
+						oper_status: OperStatus::Up,
+						bytes_sent: self.0 * 150_000,
+						bytes_received: self.0 * 600_000,
+						packets: PacketCounters {
+							packets_sent: self.0 * 99,
+							packets_received: self.0 * 396,
+							send_errors: self.0,
+							receive_discards: self.0 * 4,
+							..Default::default()
+						},
+						speed: 1_000_000_000,
+					})
+				]
+			)
+		}
+
+		fn provider(&self) -> CounterProvider {
+			CounterProvider::Custom("fixture")
+		}
+	}
+
+	let mut monitor = NetworkMonitor::with_source(NetworkMonitorConfig::default(), Fixture(0));
+	assert_eq!(monitor.interface_traffic().unwrap()[0].packets.packets_sent, 99);
+	assert!(matches!(monitor.measure_stats_delta(), Err(NetworkError::InsufficientTimeElapsed { .. })));
+
+	std::thread::sleep(Duration::from_millis(20));
+	let delta = monitor.measure_stats_delta().unwrap();
+	assert_eq!(delta.counters.packets_sent, 99);
+	assert_eq!(delta.counters.packets_received, 396);
+	assert_eq!(delta.counters.total_errors(), 1);
+	assert_eq!(delta.counters.total_discards(), 4);
+	assert!(delta.elapsed >= Duration::from_millis(20));
+	assert!(delta.packets_per_sec() > 0.0);
+	assert_eq!(delta.send_error_ratio(), Some(0.01));
+
+	monitor.reset();
+	assert!(monitor.measure_stats_delta().is_err());
+}
+
+#[cfg(feature = "test-util")]
+#[test]
+fn test_mock_counter_source() {
+	use network_speed::{ MockCounterSource, NetworkError, NetworkSpeedTracker };
+
+	let mock = MockCounterSource::with_interfaces(
+		vec![
+			MockCounterSource::interface(1, InterfaceKind::Ethernet, "mock0"),
+			MockCounterSource::interface(2, InterfaceKind::Loopback, "lo")
+		]
+	);
+	mock.push_counters(&[(1_000, 10_000), (500, 500)]);
+	mock.push_deltas(&[(1_000, 50_000)]);
+	mock.push_deltas(&[(0, 125_000)]);
+	mock.push_error(NetworkError::InvalidInterface);
+	mock.push_error(std::io::Error::new(std::io::ErrorKind::TimedOut, "agent unreachable").into());
+
+	let config = NetworkMonitorConfig::builder().min_measurement_interval(Duration::from_millis(20)).build().unwrap();
+	let monitor = NetworkMonitor::with_source(config, mock.clone());
+	let mut tracker = NetworkSpeedTracker::with_monitor(monitor, 10);
+
+	// Loopback is excluded by default, so only mock0 counts.
+	assert_eq!(tracker.total_traffic().unwrap(), (1_000, 10_000));
+	tracker.track_speed().unwrap();
+	std::thread::sleep(Duration::from_millis(50));
+	let speed = tracker.track_speed().unwrap();
+	assert_eq!(speed.upload_bytes_per_sec, 0);
+	assert!(speed.download_bytes_per_sec > 0 && speed.download_bytes_per_sec <= 125_000 * 20);
+	assert!(matches!(tracker.total_traffic(), Err(NetworkError::InvalidInterface)));
+	// I/O failures of a source are tagged with its name.
+	assert!(matches!(tracker.total_traffic(), Err(NetworkError::ProviderFailed { provider: "mock", .. })));
+
+	// An exhausted script repeats the last counters: an idle link.
+	assert_eq!(tracker.total_traffic().unwrap(), (2_000, 185_000));
+	assert_eq!((mock.reads(), mock.remaining()), (6, 0));
+}
+
+#[cfg(feature = "test-util")]
+#[test]
+fn test_vanished_interface_retention() {
+	use network_speed::{ CounterProvider, CounterSource, InterfaceManager, MockCounterSource, NetworkInterface, Result };
+	use std::collections::VecDeque;
+
+	struct Reads(VecDeque<Vec<NetworkInterface>>);
+
+	impl CounterSource for Reads {
+		fn read_interfaces(&mut self) -> Result<Vec<Result<NetworkInterface>>> {
+			Ok(self.0.pop_front().unwrap_or_default().into_iter().map(Ok).collect())
+		}
+
+		fn provider(&self) -> CounterProvider {
+			CounterProvider::ProcNetDev
+		}
+	}
+
+	let interface = |index, name| NetworkInterface {
+		bytes_sent: 3_000,
+		bytes_received: 3_000,
+		..MockCounterSource::interface(index, InterfaceKind::Ethernet, name)
+	};
+	let reads = || Reads(VecDeque::from([vec![interface(1, "eth0"), interface(2, "eth1")], vec![interface(1, "eth0")]]));
+
+	// The default zero retention drops eth1 from the total on the read it goes missing.
+	let mut manager = InterfaceManager::with_source(NetworkMonitorConfig::default(), reads());
+	assert_eq!(manager.get_total_traffic().unwrap(), (6_000, 6_000));
+	assert_eq!(manager.get_total_traffic().unwrap(), (3_000, 3_000));
+
+	let config = NetworkMonitorConfig::builder().interface_retention(Duration::from_secs(30)).build().unwrap();
+	let mut manager = InterfaceManager::with_source(config, reads());
+	assert_eq!(manager.get_total_traffic().unwrap(), (6_000, 6_000));
+	assert_eq!(manager.get_total_traffic().unwrap(), (6_000, 6_000));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_linux_backend() {
+	use network_speed::{ CounterProvider, InterfaceManager };
+
+	let interfaces = list_interfaces().unwrap();
+	let loopback = interfaces
+		.iter()
+		.find(|interface| interface.is_loopback())
+		.expect("lo is always present");
+	assert_eq!(loopback.description, "lo");
+	assert!(loopback.oper_status.is_up());
+	assert!(loopback.guid.is_empty());
+	assert_eq!(get_interface_count().unwrap(), interfaces.len());
+
+	let config = NetworkMonitorConfig::builder().exclude_loopback(false).exclude_virtual(false).build().unwrap();
+	let mut manager = InterfaceManager::new(config.clone());
+	assert!(!manager.get_active_interfaces().unwrap().is_empty());
+	assert_eq!(manager.provider_info().unwrap().provider, CounterProvider::ProcNetDev);
+
+	let mut monitor = NetworkMonitor::with_config(config);
+	monitor.measure_speed().unwrap();
+	thread::sleep(Duration::from_millis(100));
+	monitor.measure_speed().unwrap();
+	assert_eq!(monitor.provider_info().unwrap().provider, CounterProvider::ProcNetDev);
+	assert!(!monitor.provider_info().unwrap().is_legacy());
+}
+
+#[cfg(target_os = "macos")]
+#[test]
+fn test_macos_backend() {
+	use network_speed::{ read_interface_list, CounterProvider, InterfaceManager };
+
+	let links = read_interface_list().unwrap();
+	assert!(links.iter().any(|link| link.name == "lo0"));
+
+	let interfaces = list_interfaces().unwrap();
+	assert_eq!(get_interface_count().unwrap(), interfaces.len());
+	let loopback = interfaces
+		.iter()
+		.find(|interface| interface.is_loopback())
+		.expect("lo0 is always present");
+	assert_eq!(loopback.description, "lo0");
+	assert!(loopback.oper_status.is_up());
+
+	let config = NetworkMonitorConfig::builder().exclude_loopback(false).exclude_virtual(false).build().unwrap();
+	let mut manager = InterfaceManager::new(config.clone());
+	assert!(!manager.get_active_interfaces().unwrap().is_empty());
+	assert_eq!(manager.provider_info().unwrap().provider, CounterProvider::SysctlIfList2);
+
+	let mut monitor = NetworkMonitor::with_config(config);
+	monitor.measure_speed().unwrap();
+	thread::sleep(Duration::from_millis(100));
+	monitor.measure_speed().unwrap();
+	assert_eq!(monitor.provider_info().unwrap().provider, CounterProvider::SysctlIfList2);
+}
+
+#[cfg(windows)]
+#[test]
+fn test_capabilities() {
+	use network_speed::{ Capabilities, CounterProvider, ProviderKind };
+
+	let capabilities = Capabilities {
+		legacy_if_table: true,
+		wlan: true,
+		..Capabilities::default()
+	};
+	assert!(capabilities.can_monitor());
+	assert!(!capabilities.is_available(ProviderKind::IfTable2));
+	assert_eq!(capabilities.available().collect::<Vec<_>>(), vec![ProviderKind::LegacyIfTable, ProviderKind::Wlan]);
+	assert_eq!(ProviderKind::from(CounterProvider::IfTable2), ProviderKind::IfTable2);
+	assert!(ProviderKind::Etw.requires_elevation());
+	assert!(!ProviderKind::IfTable2.requires_elevation());
+	assert!(ProviderKind::Etw.privilege_error().needs_elevation());
+
+	let capabilities = network_speed::probe_capabilities();
+	assert!(capabilities.can_monitor());
+	assert!(capabilities.pdh);
+}
+
+#[test]
+fn test_composition_changes() {
+	use network_speed::Composition;
+
+	let before = Composition { version: 3, indices: vec![1, 4, 9] };
+	let after = Composition { version: 4, indices: vec![1, 9, 12] };
+
+	assert!(after.changed_since(&before));
+	assert!(!before.changed_since(&before.clone()));
+	assert_eq!(after.added(&before).collect::<Vec<_>>(), vec![12]);
+	assert_eq!(after.removed(&before).collect::<Vec<_>>(), vec![4]);
+
+	if cfg!(windows) {
+		let mut monitor = NetworkMonitor::new();
+		if let Ok((_, composition)) = monitor.measure_speed_with_composition() {
+			assert_eq!(composition.indices, monitor.contributing_interfaces());
+			assert!(composition.version >= 1);
+		}
+	}
+}
+
+#[test]
+fn test_rate_limited_sink() {
+	use network_speed::{ Aggregation, NetworkSpeed, RateLimitedSink, SpeedSink };
+	use std::time::Instant;
+
+	let start = Instant::now();
+	let at = |offset_ms: u64, download: u64| NetworkSpeed {
+		timestamp: start + Duration::from_millis(offset_ms),
+		..NetworkSpeed::new(0, download)
+	};
+
+	let mut emitted = Vec::new();
+	{
+		let collect = |speed: &NetworkSpeed| {
+			emitted.push(speed.clone());
+			Ok(())
+		};
+		let mut sink = RateLimitedSink::new(collect, Duration::from_secs(1), Aggregation::Max);
+		for (offset, download) in [(0, 100), (250, 400), (500, 200), (750, 300), (1_000, 50), (1_250, 700)] {
+			sink.accept(&at(offset, download)).unwrap();
+		}
+		assert_eq!(sink.pending(), 1);
+		sink.flush().unwrap();
+	}
+
+	// The first window closes with the 1 s sample; the flush emits the leftover one.
+	assert_eq!(emitted.len(), 2);
+	assert_eq!(emitted[0].download_bytes_per_sec.bytes_per_sec(), 400);
+	assert_eq!(emitted[0].timestamp, start + Duration::from_millis(1_000));
+	assert_eq!(emitted[1].download_bytes_per_sec.bytes_per_sec(), 700);
+
+	// 1000 B/s for 1 s plus 3000 B/s for 2 s (first sample borrows the 1 s gap).
+	let window = [at(0, 1_000), at(1_000, 1_000), at(3_000, 3_000)];
+	let sum = Aggregation::Sum.apply(&window).unwrap();
+	assert_eq!(sum.download_bytes_per_sec.bytes_per_sec(), 8_000);
+	let average = Aggregation::Average.apply(&window).unwrap();
+	assert_eq!(average.download_bytes_per_sec.bytes_per_sec(), 2_000);
+	assert!(Aggregation::Max.apply(&[]).is_none());
+
+	// Twelve seconds at 1000 B/s: the flushed lone sample still covers the second since the window.
+	let mut totals = Vec::new();
+	{
+		let collect = |speed: &NetworkSpeed| {
+			totals.push(speed.download_bytes_per_sec.bytes_per_sec());
+			Ok(())
+		};
+		let mut sink = RateLimitedSink::new(collect, Duration::from_secs(10), Aggregation::Sum);
+		for second in 0..12 {
+			sink.accept(&at(second * 1_000, 1_000)).unwrap();
+		}
+		sink.flush().unwrap();
+	}
+	assert_eq!(totals, [11_000, 1_000]);
+
+	let mut totals = Vec::new();
+	{
+		let collect = |speed: &NetworkSpeed| {
+			totals.push(speed.download_bytes_per_sec.bytes_per_sec());
+			Ok(())
+		};
+		let mut sink = RateLimitedSink::new(collect, Duration::ZERO, Aggregation::Sum);
+		for (offset, download) in [(0, 1_000), (2_000, 1_000), (2_500, 4_000)] {
+			sink.accept(&at(offset, download)).unwrap();
+		}
+	}
+	assert_eq!(totals, [0, 2_000, 2_000]);
+}
+
+#[test]
+fn test_counters_snapshot_diff() {
+	use network_speed::{ ByteRate, CounterEntry, CountersSnapshot };
+	use std::time::Instant;
+
+	let entry = |index: u32, sent: u64, received: u64| CounterEntry {
+		index,
+		interface_type: InterfaceKind::Ethernet,
+		bytes_sent: sent,
+		bytes_received: received,
+	};
+	let start = Instant::now();
+	let earlier = CountersSnapshot::from_entries([entry(12, 1_000, 5_000), entry(3, 0, 0)], start);
+	let later = CountersSnapshot::from_entries(
+		[entry(3, 500, 2_000), entry(12, 900, 9_000), entry(20, 1, 1)],
+		start + Duration::from_secs(2)
+	);
+
+	assert_eq!(later.len(), 3);
+	assert_eq!(later.iter().map(|e| e.index).collect::<Vec<_>>(), vec![3, 12, 20]);
+	assert_eq!(later.get(12).unwrap().bytes_received, 9_000);
+	assert_eq!(later.total(), (1_401, 11_001));
+
+	let deltas: Vec<_> = later.diff(&earlier).collect();
+	assert_eq!(deltas.len(), 2, "interface 20 has no baseline");
+	assert_eq!(deltas[0].index, 3);
+	assert_eq!(deltas[0].upload_rate(), ByteRate::from_bytes_per_sec(250));
+	assert_eq!(deltas[0].download_rate(), ByteRate::from_bytes_per_sec(1_000));
+	// Counter went backwards on interface 12: zero rather than a wrapped value.
+	assert_eq!(deltas[1].bytes_sent, 0);
+	assert_eq!(deltas[1].bytes_received, 4_000);
+	assert_eq!(deltas[1].elapsed, Duration::from_secs(2));
+}
+
+#[test]
+fn test_import_data_usage() {
+	use network_speed::DataUsageRecord;
+	use std::time::SystemTime;
+
+	let record = DataUsageRecord {
+		profile_name: "Home".to_string(),
+		interface_guid: None,
+		start: SystemTime::now(),
+		bytes_sent: 1_000,
+		bytes_received: 9_000,
+		connected: Duration::from_secs(3_600),
+	};
+	assert_eq!(record.total_bytes(), 10_000);
+
+	let hotspot = DataUsageRecord { profile_name: "Hotspot".to_string(), bytes_received: 500, ..record.clone() };
+	let usage = network_speed::data_usage_by_network(&[record.clone(), hotspot, record]);
+	assert_eq!(usage["Home"].total_bytes(), 20_000);
+	assert_eq!(usage["Hotspot"].bytes_received, 500);
+
+	#[cfg(windows)]
+	{
+		let records = network_speed::import_data_usage(7).unwrap();
+		assert!(records.iter().all(|record| record.start <= SystemTime::now()));
+	}
+}
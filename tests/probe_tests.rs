@@ -0,0 +1,292 @@
+#![cfg(feature = "platform")]
+
+use network_speed::probe::{ dns_probe_with, http_ttfb, tcp_connect_time, LinkQuality };
+use std::io::{ Read, Write };
+use std::net::{ SocketAddr, TcpListener, UdpSocket };
+use std::thread;
+use std::time::Duration;
+
+/// Answers every query with an empty NOERROR response after `delay`.
+fn spawn_fake_resolver(delay: Duration) -> SocketAddr {
+	let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+	let addr = socket.local_addr().unwrap();
+
+	thread::spawn(move || {
+		let mut buffer = [0u8; 512];
+		while let Ok((len, from)) = socket.recv_from(&mut buffer) {
+			thread::sleep(delay);
+			let mut response = buffer[..len].to_vec();
+			response[2] |= 0x80;
+			let _ = socket.send_to(&response, from);
+		}
+	});
+
+	addr
+}
+
+#[test]
+fn test_dns_probe_times_each_resolver() {
+	let fast = spawn_fake_resolver(Duration::ZERO);
+	let slow = spawn_fake_resolver(Duration::from_millis(50));
+
+	let timings = dns_probe_with(&["example.com", "rust-lang.org"], &[fast, slow], Duration::from_secs(2)).unwrap();
+	assert_eq!(timings.len(), 2);
+	assert_eq!(timings[0].resolver, fast);
+
+	for timing in &timings {
+		assert_eq!(timing.lookups.len(), 2);
+		assert_eq!(timing.failures(), 0);
+		assert!(timing.lookups.iter().all(|lookup| lookup.response_code == Some(0)));
+	}
+	assert!(timings[1].average_latency().unwrap() >= Duration::from_millis(50));
+}
+
+#[test]
+fn test_dns_probe_reports_timeouts() {
+	// Bound but never answers.
+	let silent = UdpSocket::bind("127.0.0.1:0").unwrap();
+	let resolver = silent.local_addr().unwrap();
+
+	let timings = dns_probe_with(&["example.com"], &[resolver], Duration::from_millis(100)).unwrap();
+	assert_eq!(timings[0].failures(), 1);
+	assert!(timings[0].average_latency().is_none());
+}
+
+#[test]
+fn test_dns_probe_rejects_invalid_hostnames() {
+	let resolver = spawn_fake_resolver(Duration::ZERO);
+	assert!(dns_probe_with(&[""], &[resolver], Duration::from_millis(100)).is_err());
+	assert!(dns_probe_with(&["a..b"], &[resolver], Duration::from_millis(100)).is_err());
+	assert!(dns_probe_with(&["x".repeat(64)], &[resolver], Duration::from_millis(100)).is_err());
+}
+
+#[test]
+fn test_link_quality_ranks_resolvers() {
+	let fast = spawn_fake_resolver(Duration::ZERO);
+	let slow = spawn_fake_resolver(Duration::from_millis(40));
+	let silent = UdpSocket::bind("127.0.0.1:0").unwrap();
+	let dead = silent.local_addr().unwrap();
+
+	let quality = LinkQuality::measure_with(&["example.com"], &[fast, slow, dead], Duration::from_millis(300)).unwrap();
+	assert_eq!(quality.fastest_resolver().unwrap().resolver, fast);
+	assert_eq!(quality.slowest_resolver().unwrap().resolver, dead);
+	assert!(quality.dns_latency().is_some());
+}
+
+/// Answers every connection with a canned 204 after `delay`.
+fn spawn_http_server(delay: Duration) -> SocketAddr {
+	let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+	let addr = listener.local_addr().unwrap();
+
+	thread::spawn(move || {
+		for stream in listener.incoming() {
+			let Ok(mut stream) = stream else {
+				continue;
+			};
+			let mut buffer = [0u8; 1024];
+			let _ = stream.read(&mut buffer);
+			thread::sleep(delay);
+			let _ = stream.write_all(b"HTTP/1.1 204 No Content\r\nConnection: close\r\n\r\n");
+		}
+	});
+
+	addr
+}
+
+#[test]
+fn test_tcp_connect_time() {
+	let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+	let elapsed = tcp_connect_time(listener.local_addr().unwrap()).unwrap();
+	assert!(elapsed < Duration::from_secs(5));
+
+	let closed = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+	assert!(tcp_connect_time(closed).is_err());
+}
+
+#[test]
+fn test_http_ttfb() {
+	let addr = spawn_http_server(Duration::from_millis(50));
+
+	let timing = http_ttfb(&format!("http://{addr}/health?full=1")).unwrap();
+	assert_eq!(timing.status, 204);
+	assert!(timing.time_to_first_byte >= timing.connect_time);
+	assert!(timing.server_time() >= Duration::from_millis(50));
+}
+
+#[test]
+fn test_http_ttfb_rejects_unsupported_urls() {
+	assert!(http_ttfb("https://example.com/").is_err());
+	assert!(http_ttfb("ftp://example.com/").is_err());
+	assert!(http_ttfb("http://:80/").is_err());
+	assert!(http_ttfb("http://example.com:notaport/").is_err());
+}
+
+/// Answers STUN Binding requests with the sender's address as XOR-MAPPED-ADDRESS, optionally
+/// reporting `mapped` instead.
+fn spawn_stun_server(mapped: Option<SocketAddr>) -> SocketAddr {
+	let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+	let addr = socket.local_addr().unwrap();
+
+	thread::spawn(move || {
+		let mut buffer = [0u8; 512];
+		while let Ok((len, from)) = socket.recv_from(&mut buffer) {
+			if len < 20 {
+				continue;
+			}
+			let reported = mapped.unwrap_or(from);
+			let SocketAddr::V4(reported) = reported else {
+				continue;
+			};
+			let mut response = vec![0x01, 0x01, 0x00, 0x0c];
+			response.extend_from_slice(&buffer[4..20]);
+			response.extend_from_slice(&[0x00, 0x20, 0x00, 0x08, 0x00, 0x01]);
+			response.extend_from_slice(&(reported.port() ^ 0x2112).to_be_bytes());
+			let cookie = [0x21, 0x12, 0xa4, 0x42];
+			response.extend(reported.ip().octets().iter().zip(cookie).map(|(byte, key)| byte ^ key));
+			let _ = socket.send_to(&response, from);
+		}
+	});
+
+	addr
+}
+
+#[test]
+fn test_public_ip_probe_stun() {
+	use network_speed::probe::{ public_ip_probe, stun_binding, NatStatus, PublicIpEndpoint };
+
+	let direct = spawn_stun_server(None);
+	let mapping = stun_binding(direct, Duration::from_secs(2)).unwrap();
+	assert_eq!(mapping.mapped, mapping.local);
+
+	let info = public_ip_probe(&[PublicIpEndpoint::Stun(direct.to_string())], Duration::from_secs(2)).unwrap();
+	assert_eq!(info.public_ip, "127.0.0.1".parse::<std::net::IpAddr>().unwrap());
+	assert_eq!(info.nat, NatStatus::NotBehindNat);
+
+	// Two servers reporting different public ports look like symmetric NAT.
+	let first = spawn_stun_server(Some("203.0.113.7:40000".parse().unwrap()));
+	let second = spawn_stun_server(Some("203.0.113.7:40001".parse().unwrap()));
+	let endpoints = [PublicIpEndpoint::Stun(first.to_string()), PublicIpEndpoint::Stun(second.to_string())];
+	let info = public_ip_probe(&endpoints, Duration::from_secs(2)).unwrap();
+	assert_eq!(info.public_ip.to_string(), "203.0.113.7");
+	assert_eq!(info.nat, NatStatus::SymmetricNat);
+
+	// A dead server falls through to the next endpoint.
+	let silent = UdpSocket::bind("127.0.0.1:0").unwrap();
+	let endpoints = [PublicIpEndpoint::Stun(silent.local_addr().unwrap().to_string()), PublicIpEndpoint::Stun(first.to_string())];
+	let info = public_ip_probe(&endpoints, Duration::from_millis(300)).unwrap();
+	assert_eq!(info.endpoint, endpoints[1]);
+	assert_eq!(info.nat, NatStatus::BehindNat);
+}
+
+#[test]
+fn test_public_ip_probe_http() {
+	use network_speed::probe::{ public_ip_probe, NatStatus, PublicIpEndpoint };
+
+	let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+	let addr = listener.local_addr().unwrap();
+	thread::spawn(move || {
+		for stream in listener.incoming() {
+			let Ok(mut stream) = stream else {
+				continue;
+			};
+			let mut buffer = [0u8; 1024];
+			let _ = stream.read(&mut buffer);
+			let _ = stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 12\r\nConnection: close\r\n\r\n198.51.100.9");
+		}
+	});
+
+	let info = public_ip_probe(&[PublicIpEndpoint::Http(format!("http://{addr}/"))], Duration::from_secs(2)).unwrap();
+	assert_eq!(info.public_ip.to_string(), "198.51.100.9");
+	assert_eq!(info.local_ip, Some("127.0.0.1".parse().unwrap()));
+	assert_eq!(info.nat, NatStatus::BehindNat);
+}
+
+#[cfg(feature = "tls")]
+#[test]
+fn test_public_ip_probe_https() {
+	use network_speed::agent::ClientTls;
+	use network_speed::probe::{ public_ip_probe, public_ip_probe_with_tls, PublicIpEndpoint };
+	use network_speed::NetworkError;
+	use std::sync::Arc;
+
+	let certs = rustls_pemfile
+		::certs(&mut &include_bytes!("fixtures/test_server.pem")[..])
+		.collect::<Result<Vec<_>, _>>()
+		.unwrap();
+	let key = rustls_pemfile::private_key(&mut &include_bytes!("fixtures/test_server.key")[..]).unwrap().unwrap();
+	let config = rustls::ServerConfig
+		::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+		.with_safe_default_protocol_versions()
+		.unwrap()
+		.with_no_client_auth()
+		.with_single_cert(certs, key)
+		.unwrap();
+	let config = Arc::new(config);
+
+	let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+	let addr = listener.local_addr().unwrap();
+	thread::spawn(move || {
+		for stream in listener.incoming() {
+			let Ok(stream) = stream else {
+				continue;
+			};
+			let connection = rustls::ServerConnection::new(Arc::clone(&config)).unwrap();
+			let mut stream = rustls::StreamOwned::new(connection, stream);
+			let mut buffer = [0u8; 1024];
+			if stream.read(&mut buffer).is_err() {
+				continue;
+			}
+			let _ = stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 11\r\nConnection: close\r\n\r\n203.0.113.7");
+			stream.conn.send_close_notify();
+			let _ = stream.flush();
+		}
+	});
+
+	let endpoints = [PublicIpEndpoint::Http(format!("https://{addr}/"))];
+	let tls = ClientTls::from_ca_pem(include_bytes!("fixtures/test_ca.pem"), "localhost").unwrap();
+	let info = public_ip_probe_with_tls(&endpoints, Duration::from_secs(2), &tls).unwrap();
+	assert_eq!(info.public_ip.to_string(), "203.0.113.7");
+	assert!(
+		matches!(public_ip_probe(&endpoints, Duration::from_secs(2)), Err(NetworkError::InvalidConfiguration { .. }))
+	);
+}
+
+#[test]
+fn test_path_mtu_search() {
+	use network_speed::probe::{ search_path_mtu, MtuProbeOutcome };
+
+	// A PPPoE hop that reports "fragmentation needed" above 1492 bytes.
+	let mut sizes = Vec::new();
+	let result = search_path_mtu(576, 1500, |size| {
+		sizes.push(size);
+		Ok(if size <= 1492 { MtuProbeOutcome::Delivered } else { MtuProbeOutcome::TooBig })
+	}).unwrap();
+	assert_eq!(result.mtu, 1492);
+	assert_eq!(result.local_mtu, 1500);
+	assert!(result.is_reduced());
+	assert_eq!(result.silent_drops, 0);
+	assert_eq!(result.probes as usize, sizes.len() + 1);
+	assert!(sizes.len() <= 11);
+
+	// A black hole drops oversized packets without telling anyone.
+	let result = search_path_mtu(576, 1500, |size| {
+		Ok(if size <= 1400 { MtuProbeOutcome::Delivered } else { MtuProbeOutcome::Dropped })
+	}).unwrap();
+	assert_eq!(result.mtu, 1400);
+	assert!(result.silent_drops > 0);
+
+	// Nothing in the way.
+	let result = search_path_mtu(576, 1500, |_| Ok(MtuProbeOutcome::Delivered)).unwrap();
+	assert_eq!(result.mtu, 1500);
+	assert!(!result.is_reduced());
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn test_async_probes() {
+	use network_speed::probe::{ http_ttfb_async, tcp_connect_time_async };
+
+	let addr = spawn_http_server(Duration::ZERO);
+	assert!(tcp_connect_time_async(addr).await.is_ok());
+	assert_eq!(http_ttfb_async(&format!("http://{addr}")).await.unwrap().status, 204);
+}
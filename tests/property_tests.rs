@@ -0,0 +1,150 @@
+//! Property-based checks for the rate math and formatting on generated counter sequences.
+
+use network_speed::{
+	compute_precise_rate,
+	format_rate,
+	ByteRate,
+	DataUnit,
+	FormatOptions,
+	InterfaceStats,
+	Locale,
+	NetworkError,
+	RateOptions,
+	UnitSystem,
+	WrapStrategy,
+};
+use proptest::prelude::*;
+use std::time::Duration;
+
+/// How a counter moves between two snapshots.
+#[derive(Debug, Clone, Copy)]
+enum Step {
+	Increment(u64),
+	/// Wraps through `u64::MAX` after moving the given amount.
+	Wrap(u64),
+	/// Restarts from a small value, as after an adapter restart.
+	Reset(u64),
+}
+
+impl Step {
+	fn apply(self, counter: u64) -> u64 {
+		match self {
+			Step::Increment(bytes) => counter.saturating_add(bytes),
+			Step::Wrap(bytes) => (u64::MAX - bytes / 2).wrapping_add(bytes),
+			Step::Reset(value) => value,
+		}
+	}
+}
+
+fn step() -> impl Strategy<Value = Step> {
+	prop_oneof![
+		8 => (0u64..10_000_000_000).prop_map(Step::Increment),
+		1 => (0u64..10_000_000).prop_map(Step::Wrap),
+		1 => (0u64..1_000_000).prop_map(Step::Reset),
+	]
+}
+
+/// Elapsed time between snapshots, including zero and sub-minimum jitter.
+fn elapsed() -> impl Strategy<Value = Duration> {
+	prop_oneof![
+		1 => Just(Duration::ZERO),
+		2 => (0u64..100_000).prop_map(Duration::from_micros),
+		7 => (100u64..10_000).prop_map(Duration::from_millis),
+	]
+}
+
+fn snapshots(start: (u64, u64), steps: &[(Duration, Step, Step)]) -> Vec<InterfaceStats> {
+	let mut stats = vec![InterfaceStats::new(start.0, start.1)];
+	for &(elapsed, sent, received) in steps {
+		let previous = stats.last().unwrap();
+		stats.push(InterfaceStats {
+			bytes_sent: sent.apply(previous.bytes_sent),
+			bytes_received: received.apply(previous.bytes_received),
+			last_update: previous.last_update + elapsed,
+			captured_at: previous.captured_at + elapsed,
+		});
+	}
+	stats
+}
+
+proptest! {
+	#[test]
+	fn rates_are_finite_and_bounded(
+		start in (any::<u64>(), any::<u64>()),
+		steps in prop::collection::vec((elapsed(), step(), step()), 1..32),
+		max_counter_wrap in prop_oneof![Just(1u64 << 62), 1u64..u64::MAX],
+	) {
+		let opts = RateOptions { wrap_strategy: WrapStrategy::Error { max_delta: max_counter_wrap }, ..RateOptions::default() };
+		for pair in snapshots(start, &steps).windows(2) {
+			let (previous, current) = (&pair[0], &pair[1]);
+			let elapsed = current.last_update - previous.last_update;
+			let sent = current.bytes_sent.wrapping_sub(previous.bytes_sent);
+			let received = current.bytes_received.wrapping_sub(previous.bytes_received);
+
+			match compute_precise_rate(current, previous, &opts) {
+				Ok(speed) => {
+					prop_assert!(elapsed >= opts.min_interval && !elapsed.is_zero());
+					prop_assert!(sent <= max_counter_wrap && received <= max_counter_wrap);
+					prop_assert_eq!(speed.interval, elapsed);
+					for (rate, bytes) in [(speed.upload_bytes_per_sec, sent), (speed.download_bytes_per_sec, received)] {
+						prop_assert!(rate.is_finite() && rate >= 0.0);
+						prop_assert!(rate <= (max_counter_wrap as f64) / opts.min_interval.as_secs_f64());
+						let expected = (bytes as f64) / elapsed.as_secs_f64();
+						prop_assert!((rate - expected).abs() <= expected * 1e-9);
+					}
+				}
+				Err(NetworkError::InsufficientTimeElapsed { .. }) => {
+					prop_assert!(elapsed < opts.min_interval || elapsed.is_zero());
+				}
+				Err(NetworkError::CalculationOverflow) => {
+					prop_assert!(elapsed >= opts.min_interval);
+					prop_assert!(sent > max_counter_wrap || received > max_counter_wrap);
+				}
+				Err(other) => prop_assert!(false, "unexpected error {other:?}"),
+			}
+		}
+	}
+
+	#[test]
+	fn formatted_rates_parse_back(
+		bytes_per_sec in any::<u64>(),
+		unit_system in prop_oneof![Just(UnitSystem::Si), Just(UnitSystem::Iec)],
+		bits_or_bytes in prop_oneof![Just(DataUnit::Bytes), Just(DataUnit::Bits)],
+		decimal_places in 0usize..6,
+		french in any::<bool>(),
+	) {
+		let locale = if french { Locale::french() } else { Locale::english() };
+		let options = FormatOptions::new(unit_system, decimal_places, bits_or_bytes).with_locale(locale.clone());
+		let text = format_rate(ByteRate::from_bytes_per_sec(bytes_per_sec), &options);
+
+		let (number, unit) = text.split_once(locale.unit_separator.as_str()).unwrap();
+		let number: f64 = number.replace(locale.decimal_separator, ".").parse().unwrap();
+
+		let multiplier = unit_multiplier(unit, unit_system);
+		let value = match bits_or_bytes {
+			DataUnit::Bytes => bytes_per_sec as f64,
+			DataUnit::Bits => (bytes_per_sec as f64) * 8.0,
+		};
+		prop_assert!(number >= 0.0);
+		if multiplier == 1.0 {
+			prop_assert_eq!(number, value.trunc());
+		} else {
+			// Rounded to `decimal_places` in the chosen unit.
+			let tolerance = 0.5 * 10f64.powi(-(decimal_places as i32)) * multiplier + value * 1e-12;
+			prop_assert!((number * multiplier - value).abs() <= tolerance, "{text} vs {value}");
+		}
+	}
+}
+
+/// The scale a formatted unit stands for, from its prefix.
+fn unit_multiplier(unit: &str, unit_system: UnitSystem) -> f64 {
+	let base = unit_system.base();
+	let power = match unit.chars().next() {
+		Some('k' | 'K') => 1,
+		Some('M') => 2,
+		Some('G') => 3,
+		Some('T') => 4,
+		_ => 0,
+	};
+	base.powi(power)
+}
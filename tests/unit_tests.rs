@@ -1,10 +1,39 @@
 use network_speed::{
+	ActivityLevel,
+	estimate_transfer_time,
 	format_bits_per_second,
 	format_bytes_per_second,
+	format_bytes_total,
+	format_eta,
+	format_rate,
+	ByteRate,
+	COUNTER_UPDATE_GRANULARITY,
+	DataUnit,
+	FirstSample,
+	FormatOptions,
+	FormatTemplate,
+	InterfaceKind,
 	InterfaceStats,
+	Locale,
+	LatencyHistogram,
+	MonitorCheckpoint,
+	MonitorLabels,
+	MonitorStats,
+	NetworkError,
 	NetworkMonitorConfig,
 	NetworkSpeed,
+	OverheadStats,
 	PrecisionMode,
+	PreciseSpeed,
+	render_sparkline,
+	RowErrorPolicy,
+	SampleTolerance,
+	Schedule,
+	ScheduleWindow,
+	SpeedSample,
+	Thresholds,
+	UnitSystem,
+	Weekday,
 };
 use std::num::NonZeroU8;
 use std::time::Duration;
@@ -35,9 +64,16 @@ fn test_formatting() {
 
 #[test]
 fn test_is_active() {
+	use network_speed::Direction;
+
 	let speed = NetworkSpeed::new(100, 200);
 	assert!(speed.is_active(250));
 	assert!(!speed.is_active(350));
+
+	assert_eq!(speed.rate(Direction::Total), 300);
+	assert!(speed.is_active_in(Direction::Download, 150));
+	assert!(!speed.is_active_in(Direction::Upload, 150));
+	assert!(speed.is_active_in(Direction::Total, 250));
 }
 
 #[test]
@@ -48,6 +84,7 @@ fn test_config_default() {
 	assert!(config.exclude_bluetooth);
 	assert_eq!(config.min_measurement_interval, Duration::from_millis(100));
 	assert!(matches!(config.precision, PrecisionMode::Instant));
+	assert_eq!(config.first_sample, FirstSample::Zero);
 }
 
 #[test]
@@ -87,6 +124,107 @@ fn test_config_validation() {
 	assert!(result.is_err());
 }
 
+#[test]
+fn test_overhead_budget_validation() {
+	assert!(NetworkMonitorConfig::builder().overhead_budget(0.01).build().is_ok());
+	assert!(NetworkMonitorConfig::builder().overhead_budget(0.0).build().is_err());
+	assert!(NetworkMonitorConfig::builder().overhead_budget(1.5).build().is_err());
+	assert!(NetworkMonitorConfig::new().with_overhead_budget(f64::NAN).validate().is_err());
+}
+
+#[test]
+fn test_sample_tolerance_validation() {
+	assert_eq!(NetworkMonitorConfig::default().sample_tolerance, SampleTolerance::STRICT);
+
+	let config = NetworkMonitorConfig::builder().sample_tolerance(SampleTolerance::new(3, 5)).build().unwrap();
+	assert_eq!(config.sample_tolerance.max_failed_samples, 3);
+	assert_eq!(config.sample_tolerance.min_successful_samples, 5);
+
+	assert!(NetworkMonitorConfig::new().with_sample_tolerance(SampleTolerance::new(1, 0)).validate().is_err());
+}
+
+#[test]
+fn test_overhead_stats_math() {
+	let stats = OverheadStats {
+		calls: 4,
+		wall_time: Duration::from_millis(8),
+		cpu_time: Duration::from_millis(4),
+		last_call_wall_time: Duration::from_millis(2),
+		..OverheadStats::default()
+	};
+
+	assert_eq!(stats.average_wall_time(), Duration::from_millis(2));
+	assert_eq!(stats.average_cpu_time(), Duration::from_millis(1));
+	assert_eq!(stats.required_interval(0.01), Duration::from_millis(200));
+	assert_eq!(OverheadStats::default().average_wall_time(), Duration::ZERO);
+}
+
+#[test]
+fn test_latency_histogram_buckets() {
+	let mut histogram = LatencyHistogram::default();
+	assert_eq!(histogram.quantile_upper_bound(0.5), None);
+
+	histogram.record(Duration::from_micros(80));
+	histogram.record(Duration::from_micros(100));
+	histogram.record(Duration::from_millis(3));
+	histogram.record(Duration::from_secs(2));
+
+	assert_eq!(histogram.counts[0], 2);
+	assert_eq!(histogram.count(), 4);
+	assert_eq!(histogram.sum, Duration::from_micros(2_003_180));
+	assert_eq!(histogram.quantile_upper_bound(0.5), Some(Duration::from_micros(100)));
+	assert_eq!(histogram.quantile_upper_bound(0.75), Some(Duration::from_millis(5)));
+	assert_eq!(histogram.quantile_upper_bound(1.0), None);
+}
+
+#[test]
+fn test_monitor_stats_prometheus() {
+	let mut stats = MonitorStats {
+		samples: 7,
+		dropped_events: 2,
+		..MonitorStats::default()
+	};
+	stats.failures.insert(NetworkError::NoInterfacesFound.kind(), 3);
+	stats.enumeration_latency.record(Duration::from_millis(1));
+	assert_eq!(stats.failure_count(), 3);
+
+	let text = stats.to_prometheus(&MonitorLabels::named("office").with_label("site-id", "a\"b"));
+	assert!(text.contains("network_speed_monitor_samples_total{monitor=\"office\",site_id=\"a\\\"b\"} 7\n"));
+	assert!(text.contains("kind=\"no_interfaces_found\"} 3\n"));
+	assert!(text.contains("le=\"0.001\"} 1\n"));
+	assert!(text.contains("le=\"+Inf\"} 1\n"));
+	assert!(text.contains("network_speed_monitor_dropped_events_total{monitor=\"office\",site_id=\"a\\\"b\"} 2\n"));
+
+	let unlabeled = MonitorStats::default().to_prometheus(&MonitorLabels::new());
+	assert!(unlabeled.contains("network_speed_monitor_samples_total 0\n"));
+	assert!(unlabeled.contains("network_speed_monitor_enumeration_seconds_count 0\n"));
+}
+
+#[test]
+fn test_recommended_interval() {
+	let config = NetworkMonitorConfig::default();
+	let idle = OverheadStats::default();
+	assert_eq!(config.recommended_interval(&idle), COUNTER_UPDATE_GRANULARITY);
+
+	let slow_config = NetworkMonitorConfig::new().with_min_interval(Duration::from_secs(2));
+	assert_eq!(slow_config.recommended_interval(&idle), Duration::from_secs(2));
+
+	let sampled = NetworkMonitorConfig::new().with_precision(PrecisionMode::Samples {
+		samples: NonZeroU8::new(4).unwrap(),
+		interval: Duration::from_millis(250),
+	});
+	assert_eq!(sampled.recommended_interval(&idle), Duration::from_secs(1));
+
+	// 20 ms per call at the default 1% budget needs 2 s between calls; a 10% budget needs 200 ms.
+	let expensive = OverheadStats {
+		calls: 5,
+		wall_time: Duration::from_millis(100),
+		..OverheadStats::default()
+	};
+	assert_eq!(config.recommended_interval(&expensive), Duration::from_secs(2));
+	assert_eq!(config.clone().with_overhead_budget(0.1).recommended_interval(&expensive), COUNTER_UPDATE_GRANULARITY);
+}
+
 #[test]
 fn test_precision_samples_validation() {
 	let result = NetworkMonitorConfig::builder()
@@ -110,6 +248,37 @@ fn test_precision_windowed_validation() {
 	assert!(result.is_err());
 }
 
+#[test]
+fn test_precision_mode_constructors_and_parsing() {
+	assert!(matches!(PrecisionMode::windowed(Duration::from_millis(500)), Ok(PrecisionMode::Windowed { .. })));
+	assert!(PrecisionMode::windowed(Duration::ZERO).is_err());
+	assert!(PrecisionMode::samples(1, Duration::from_millis(200)).is_err());
+	assert!(PrecisionMode::samples(0, Duration::from_millis(200)).is_err());
+	assert!(PrecisionMode::samples(5, Duration::ZERO).is_err());
+
+	assert!(matches!(PrecisionMode::try_from("instant"), Ok(PrecisionMode::Instant)));
+	match PrecisionMode::try_from("windowed:1.5s").unwrap() {
+		PrecisionMode::Windowed { duration } => assert_eq!(duration, Duration::from_millis(1500)),
+		other => panic!("unexpected {other:?}"),
+	}
+	match "samples:5x200ms".parse::<PrecisionMode>().unwrap() {
+		PrecisionMode::Samples { samples, interval } => {
+			assert_eq!(samples.get(), 5);
+			assert_eq!(interval, Duration::from_millis(200));
+		}
+		other => panic!("unexpected {other:?}"),
+	}
+
+	for invalid in ["", "windowed", "windowed:0ms", "windowed:500", "samples:1x200ms", "samples:5", "turbo"] {
+		let err = PrecisionMode::try_from(invalid).unwrap_err();
+		assert!(matches!(err, network_speed::NetworkError::InvalidConfiguration { .. }), "{invalid}");
+	}
+
+	let mode = PrecisionMode::samples(4, Duration::from_millis(250)).unwrap();
+	assert_eq!(mode.to_string(), "samples:4x250ms");
+	assert_eq!(mode.to_string().parse::<PrecisionMode>().unwrap().to_string(), "samples:4x250ms");
+}
+
 #[test]
 fn test_config_fluent_precision() {
 	let config = NetworkMonitorConfig::new().with_precision(PrecisionMode::Samples {
@@ -126,6 +295,61 @@ fn test_config_fluent_precision() {
 	}
 }
 
+#[test]
+fn test_config_first_sample() {
+	let config = NetworkMonitorConfig::builder().first_sample(FirstSample::Baseline).build().unwrap();
+	assert_eq!(config.first_sample, FirstSample::Baseline);
+	assert_eq!(NetworkMonitorConfig::new().with_first_sample(FirstSample::Block).first_sample, FirstSample::Block);
+
+	let speed = NetworkSpeed::new(10, 20);
+	assert_eq!(SpeedSample::Measured(speed.clone()).speed(), Some(&speed));
+	let baseline = SpeedSample::Baseline { timestamp: speed.timestamp };
+	assert!(baseline.is_baseline());
+	assert!(baseline.speed().is_none());
+	assert_eq!(baseline.timestamp(), speed.timestamp);
+}
+
+#[test]
+fn test_config_row_errors() {
+	assert_eq!(NetworkMonitorConfig::default().row_errors, RowErrorPolicy::Fail);
+	let config = NetworkMonitorConfig::builder().row_errors(RowErrorPolicy::Skip).build().unwrap();
+	assert_eq!(config.row_errors, RowErrorPolicy::Skip);
+	assert_eq!(NetworkMonitorConfig::new().with_row_errors(RowErrorPolicy::Skip).row_errors, RowErrorPolicy::Skip);
+}
+
+#[test]
+fn test_schedule_windows() {
+	use network_speed::LocalTime;
+
+	let quiet = ScheduleWindow::quiet_hours("22:00", "07:00").unwrap().on(&Weekday::WORKDAYS);
+	assert!(quiet.contains(LocalTime::new(Weekday::Monday, 23, 30)));
+	assert!(quiet.contains(LocalTime::new(Weekday::Saturday, 6, 59)));
+	assert!(!quiet.contains(LocalTime::new(Weekday::Saturday, 7, 0)));
+	assert!(!quiet.contains(LocalTime::new(Weekday::Monday, 6, 0)));
+	assert!(!quiet.contains(LocalTime::new(Weekday::Sunday, 22, 0)));
+
+	let weekend = "00:00-00:00".parse::<ScheduleWindow>().unwrap().on(&Weekday::WEEKEND).with_interval_factor(10);
+	assert!(weekend.contains(LocalTime::new(Weekday::Sunday, 23, 59)));
+	assert!(!weekend.contains(LocalTime::new(Weekday::Monday, 0, 0)));
+
+	let schedule = Schedule::new().with_window(quiet).with_window(weekend);
+	let saturday_night = LocalTime::new(Weekday::Saturday, 23, 0);
+	assert!(!schedule.alerts_muted(saturday_night));
+	assert_eq!(schedule.sampling_interval(Duration::from_secs(1), saturday_night), Duration::from_secs(10));
+	let friday_night = LocalTime::new(Weekday::Friday, 23, 0);
+	assert!(schedule.alerts_muted(friday_night));
+	assert_eq!(schedule.interval_factor(friday_night), 1);
+
+	assert!(ScheduleWindow::new("24:00", "07:00").is_err());
+	assert!("22:00".parse::<ScheduleWindow>().is_err());
+	let config = NetworkMonitorConfig::builder()
+		.schedule(Schedule::new().with_window(ScheduleWindow::new("01:00", "02:00").unwrap().with_interval_factor(0)))
+		.build();
+	assert!(config.is_err());
+	assert_eq!(Weekday::from_days_since_sunday(0), Weekday::Sunday);
+	assert_eq!(Weekday::Monday.previous(), Weekday::Sunday);
+}
+
 #[test]
 fn test_config_fluent_api() {
 	let config = NetworkMonitorConfig::new()
@@ -138,6 +362,102 @@ fn test_config_fluent_api() {
 	assert_eq!(config.interface_name_filters.len(), 1);
 }
 
+#[test]
+fn test_timeout_and_cancelled_errors() {
+	use network_speed::NetworkError;
+
+	let timeout = NetworkError::Timeout {
+		operation: "measure_speed".to_string(),
+		timeout_ms: 250,
+	};
+	assert!(timeout.is_recoverable());
+	assert_eq!(timeout.error_code(), 1011);
+	assert_eq!(timeout.to_string(), "measure_speed timed out after 250ms");
+
+	assert!(NetworkError::Cancelled.is_recoverable());
+	assert_eq!(NetworkError::Cancelled.error_code(), 1012);
+	assert!(!NetworkError::NoInterfacesFound.is_recoverable());
+}
+
+#[test]
+fn test_structured_error_variants() {
+	use network_speed::NetworkError;
+
+	let poisoned = NetworkError::LockPoisoned { resource: "Monitor" };
+	assert_eq!(poisoned.to_string(), "Monitor mutex poisoned");
+	assert_eq!(poisoned.error_code(), 1013);
+
+	assert_eq!(NetworkError::TaskJoin { panicked: true }.to_string(), "Background task panicked");
+
+	let io: NetworkError = std::io::Error::new(std::io::ErrorKind::BrokenPipe, "closed").into();
+	assert!(matches!(&io, NetworkError::Io(err) if err.kind() == std::io::ErrorKind::BrokenPipe));
+
+	let failed = io.in_provider("/proc/net/dev");
+	assert_eq!(failed.to_string(), "/proc/net/dev failed: closed");
+	assert_eq!((failed.kind(), failed.error_code()), ("provider_failed", 1021));
+	assert!(std::error::Error::source(&failed).is_some());
+	assert!(matches!(NetworkError::InvalidInterface.in_provider("snmp"), NetworkError::InvalidInterface));
+
+	let short = NetworkError::InsufficientSamples { succeeded: 2, required: 3, failed: 1 };
+	assert_eq!(short.to_string(), "only 2 of 3 required samples succeeded (1 failed)");
+	assert!(!short.is_recoverable());
+}
+
+#[test]
+fn test_win32_error_categories() {
+	use network_speed::{ NetworkError, Win32ErrorCategory };
+
+	assert_eq!(Win32ErrorCategory::from_win32_code(5), Win32ErrorCategory::AccessDenied);
+	assert_eq!(Win32ErrorCategory::from_win32_code(50), Win32ErrorCategory::NotSupported);
+	assert_eq!(Win32ErrorCategory::from_win32_code(122), Win32ErrorCategory::Buffer);
+	assert_eq!(Win32ErrorCategory::from_win32_code(31), Win32ErrorCategory::Driver);
+	assert_eq!(Win32ErrorCategory::from_win32_code(9999), Win32ErrorCategory::Other);
+	assert!(Win32ErrorCategory::Buffer.is_transient());
+	assert!(!Win32ErrorCategory::AccessDenied.is_transient());
+
+	assert_eq!(NetworkError::NoInterfacesFound.win32_code(), None);
+	assert_eq!(NetworkError::Cancelled.win32_category(), None);
+
+	let privileges = NetworkError::InsufficientPrivileges { provider: "ETW" };
+	assert_eq!(privileges.to_string(), "ETW requires administrator privileges");
+	assert_eq!(privileges.error_code(), 1019);
+	assert!(privileges.needs_elevation());
+	assert!(!privileges.is_recoverable());
+	assert!(!NetworkError::NoInterfacesFound.needs_elevation());
+}
+
+#[test]
+fn test_interface_kind_conversions() {
+	assert_eq!(InterfaceKind::from(24), InterfaceKind::Loopback);
+	assert_eq!(InterfaceKind::from(71), InterfaceKind::Wifi);
+	assert_eq!(InterfaceKind::from(53), InterfaceKind::Other(53));
+	assert_eq!(u32::from(InterfaceKind::Ethernet), 6);
+	assert_eq!(InterfaceKind::Other(23).to_string(), "PPP (23)");
+	assert_eq!(InterfaceKind::Wifi.to_string(), "Wi-Fi");
+
+	let config = NetworkMonitorConfig::default().add_type_filter(InterfaceKind::Tunnel).add_type_filter(53);
+	assert_eq!(config.interface_type_filters, vec![
+		InterfaceKind::Loopback,
+		InterfaceKind::Tunnel,
+		InterfaceKind::Other(53)
+	]);
+}
+
+#[cfg(feature = "platform")]
+#[test]
+fn test_oper_status_mapping() {
+	use network_speed::OperStatus;
+
+	assert_eq!(OperStatus::from_raw(1), OperStatus::Up);
+	assert_eq!(OperStatus::from_raw(5), OperStatus::Dormant);
+	assert_eq!(OperStatus::from_raw(7), OperStatus::LowerLayerDown);
+	assert_eq!(OperStatus::from_raw(42), OperStatus::Unknown);
+	// The legacy table numbers states differently: 5 is operational, 1 unreachable.
+	assert_eq!(OperStatus::from_legacy(5), OperStatus::Up);
+	assert_eq!(OperStatus::from_legacy(1), OperStatus::Down);
+	assert!(!OperStatus::NotPresent.is_up());
+}
+
 #[test]
 fn test_interface_stats() {
 	let stats = InterfaceStats::new(1000, 2000);
@@ -146,6 +466,177 @@ fn test_interface_stats() {
 	assert_eq!(stats.total_bytes(), 3000);
 }
 
+#[test]
+fn test_interface_stats_delta() {
+	use network_speed::NetworkError;
+
+	let earlier = InterfaceStats::new(u64::MAX - 10, 2_000);
+	let later = InterfaceStats {
+		bytes_sent: 89,
+		bytes_received: 3_000,
+		last_update: earlier.last_update + Duration::from_secs(2),
+		captured_at: earlier.captured_at + Duration::from_secs(2),
+	};
+
+	// The send counter wrapped through u64::MAX.
+	let delta = later.delta(&earlier).unwrap();
+	assert_eq!((delta.bytes_sent, delta.bytes_received), (100, 1_000));
+	assert_eq!(delta.elapsed, Duration::from_secs(2));
+	assert_eq!(delta.total_bytes(), 1_100);
+	assert_eq!(delta.to_speed(later.last_update).download_bytes_per_sec, 500.0);
+
+	// Counters going backwards (a reset) exceed the threshold instead of reporting a spike.
+	assert!(matches!(earlier.delta_within(&later, 1 << 40), Err(NetworkError::CalculationOverflow)));
+	assert_eq!(later.delta(&later).unwrap().to_speed(later.last_update).upload_bytes_per_sec, 0.0);
+}
+
+#[test]
+fn test_compute_rate() {
+	use network_speed::{ compute_precise_rate, compute_rate, NetworkError, RateOptions, WrapStrategy };
+
+	let previous = InterfaceStats::new(1_000, 5_000);
+	let at = |elapsed: Duration, sent: u64, received: u64| InterfaceStats {
+		bytes_sent: sent,
+		bytes_received: received,
+		last_update: previous.last_update + elapsed,
+		captured_at: previous.captured_at + elapsed,
+	};
+	let opts = RateOptions::default();
+	assert_eq!(opts.min_interval, Duration::from_millis(100));
+
+	let speed = compute_rate(&at(Duration::from_secs(2), 3_000, 9_001), &previous, &opts).unwrap();
+	assert_eq!(speed.upload_bytes_per_sec, 1_000);
+	assert_eq!(speed.download_bytes_per_sec, 2_000);
+	let precise = compute_precise_rate(&at(Duration::from_secs(2), 3_000, 9_001), &previous, &opts).unwrap();
+	assert_eq!(precise.download_bytes_per_sec, 2_000.5);
+	assert_eq!(precise.interval, Duration::from_secs(2));
+
+	// Zero-length, too-short, and out-of-order windows.
+	for current in [at(Duration::ZERO, 2_000, 6_000), at(Duration::from_millis(50), 2_000, 6_000)] {
+		assert!(matches!(compute_rate(&current, &previous, &opts), Err(NetworkError::InsufficientTimeElapsed { min_ms: 100, .. })));
+	}
+	assert!(compute_rate(&previous, &at(Duration::from_secs(1), 0, 0), &opts).is_err());
+	let no_minimum = RateOptions { min_interval: Duration::ZERO, ..opts };
+	assert!(compute_rate(&at(Duration::ZERO, 2_000, 6_000), &previous, &no_minimum).is_err());
+
+	// Wrapped counters count; resets beyond the threshold are rejected.
+	let wrapped = InterfaceStats { bytes_sent: u64::MAX, ..previous.clone() };
+	let speed = compute_rate(&at(Duration::from_secs(1), 99, 5_000), &wrapped, &opts).unwrap();
+	assert_eq!(speed.upload_bytes_per_sec, 100);
+	let reset = at(Duration::from_secs(1), 0, 5_000);
+	assert!(matches!(compute_rate(&reset, &previous, &opts), Err(NetworkError::CalculationOverflow)));
+	let strict = RateOptions { wrap_strategy: WrapStrategy::Error { max_delta: 50 }, ..opts };
+	assert!(compute_rate(&at(Duration::from_secs(1), 1_051, 5_000), &previous, &strict).is_err());
+	assert!(compute_rate(&at(Duration::from_secs(1), 1_050, 5_000), &previous, &strict).is_ok());
+}
+
+#[test]
+fn test_network_stats_delta() {
+	use network_speed::{ NetworkStatsDelta, PacketCounters };
+
+	let earlier = PacketCounters {
+		packets_sent: 1_000,
+		packets_received: 5_000,
+		receive_errors: 10,
+		send_discards: 3,
+		receive_discards: 7,
+		..Default::default()
+	};
+	let later = PacketCounters {
+		packets_sent: 2_000,
+		packets_received: 9_995,
+		receive_errors: 15,
+		send_discards: 3,
+		// Reset counter: yields zero rather than wrapping.
+		receive_discards: 0,
+		send_errors: 0,
+	};
+
+	let delta = NetworkStatsDelta::between(&earlier, &later, Duration::from_secs(5));
+	assert_eq!(delta.counters.packets_received, 4_995);
+	assert_eq!(delta.counters.receive_discards, 0);
+	assert_eq!(delta.packets_sent_per_sec(), 200.0);
+	assert_eq!(delta.packets_per_sec(), 1_199.0);
+	assert_eq!(delta.errors_per_sec(), 1.0);
+	assert_eq!(delta.receive_error_ratio(), Some(0.001));
+	assert_eq!(delta.send_error_ratio(), Some(0.0));
+	assert_eq!(delta.discard_ratio(), Some(0.0));
+
+	let idle = NetworkStatsDelta::between(&later, &later, Duration::ZERO);
+	assert_eq!(idle.packets_per_sec(), 0.0);
+	assert_eq!(idle.send_error_ratio(), None);
+	assert_eq!(earlier.saturating_add(&later).packets_sent, 3_000);
+}
+
+#[test]
+fn test_ignore_below_noise_floor() {
+	use network_speed::{ compute_rate, ByteRate, Direction, RateOptions };
+
+	let config = NetworkMonitorConfig::builder().ignore_below_bytes_per_sec(1_024).build().unwrap();
+	let opts = RateOptions::from(&config);
+	assert_eq!(opts.ignore_below_upload, ByteRate::from_bytes_per_sec(1_024));
+	assert_eq!(opts.ignore_below_download, ByteRate::from_bytes_per_sec(1_024));
+	assert_eq!(RateOptions::default().ignore_below_download, ByteRate::ZERO);
+
+	let previous = InterfaceStats::new(0, 0);
+	let current = InterfaceStats {
+		bytes_sent: 200,
+		bytes_received: 2_048,
+		last_update: previous.last_update + Duration::from_secs(1),
+		captured_at: previous.captured_at + Duration::from_secs(1),
+	};
+	// Upload keep-alives fall under the floor; the download is kept as is.
+	let speed = compute_rate(&current, &previous, &opts).unwrap();
+	assert_eq!(speed.upload_bytes_per_sec, 0);
+	assert_eq!(speed.download_bytes_per_sec, 2_048);
+
+	// The floor itself is not noise.
+	let at_floor = InterfaceStats { bytes_sent: 1_024, ..current };
+	assert_eq!(compute_rate(&at_floor, &previous, &opts).unwrap().upload_bytes_per_sec, 1_024);
+	assert_eq!(compute_rate(&at_floor, &previous, &RateOptions::default()).unwrap().upload_bytes_per_sec, 1_024);
+
+	// A per-direction floor overrides the shared one for that direction only.
+	let config = config.with_noise_floor(Direction::Download, ByteRate::from_bps(32_768));
+	assert_eq!(config.noise_floor(Direction::Upload), 1_024);
+	assert_eq!(config.noise_floor(Direction::Download), ByteRate::from_bytes_per_sec(4_096));
+	let speed = compute_rate(&at_floor, &previous, &RateOptions::from(&config)).unwrap();
+	assert_eq!(speed.upload_bytes_per_sec, 1_024);
+	assert_eq!(speed.download_bytes_per_sec, 0);
+}
+
+#[test]
+fn test_wrap_strategies() {
+	use network_speed::{ compute_rate, NetworkError, RateOptions, WrapStrategy };
+
+	let default = WrapStrategy::default();
+	assert_eq!(default.delta(10, 30), Some(20));
+	assert_eq!(default.delta(u64::MAX - 9, 10), Some(20));
+	assert_eq!(default.delta(30, 10), None);
+	assert_eq!(WrapStrategy::ClampToZero.delta(30, 10), Some(0));
+	assert_eq!(WrapStrategy::ClampToZero.delta(10, 30), Some(20));
+	assert_eq!(WrapStrategy::Modular32.delta(u64::from(u32::MAX) - 9, 10), Some(20));
+	assert_eq!(WrapStrategy::Modular64.delta(30, 10), Some(u64::MAX - 19));
+
+	// A reset on the upload counter: the sample fails, reads as zero, or reads as a wrap.
+	let previous = InterfaceStats::new(5_000, 1_000);
+	let current = InterfaceStats {
+		bytes_sent: 100,
+		bytes_received: 3_000,
+		last_update: previous.last_update + Duration::from_secs(1),
+		captured_at: previous.captured_at + Duration::from_secs(1),
+	};
+	let rate = |wrap_strategy| compute_rate(&current, &previous, &RateOptions { wrap_strategy, ..RateOptions::default() });
+	assert!(matches!(rate(WrapStrategy::default()), Err(NetworkError::CalculationOverflow)));
+	let clamped = rate(WrapStrategy::ClampToZero).unwrap();
+	assert_eq!(clamped.upload_bytes_per_sec, 0);
+	assert_eq!(clamped.download_bytes_per_sec, 2_000);
+	let modular = rate(WrapStrategy::Modular32).unwrap();
+	assert_eq!(modular.upload_bytes_per_sec, (1u64 << 32) - 4_900);
+
+	assert!(NetworkMonitorConfig::new().with_wrap_strategy(WrapStrategy::Error { max_delta: 0 }).validate().is_err());
+	assert!(NetworkMonitorConfig::new().with_wrap_strategy(WrapStrategy::ClampToZero).validate().is_ok());
+}
+
 #[test]
 fn test_network_speed_zero() {
 	let speed = NetworkSpeed::zero();
@@ -184,18 +675,917 @@ fn test_speed_formatting_methods() {
 	assert_eq!(speed.download_bits_formatted(), "24.58 Kbps");
 }
 
+#[cfg(feature = "platform")]
 #[test]
 fn test_interface_helpers() {
 	let iface = network_speed::NetworkInterface {
 		index: 1,
-		interface_type: 6,
+		guid: "{00000000-0000-0000-0000-000000000001}".to_string(),
+		interface_type: InterfaceKind::Ethernet,
+		physical_medium: 0,
 		description: "Ethernet Adapter".to_string(),
+		driver_description: String::new(),
 		is_operational: true,
+		oper_status: network_speed::OperStatus::Up,
 		bytes_sent: 1_000,
 		bytes_received: 2_000,
+		packets: Default::default(),
 		speed: 1_000_000,
 	};
 
 	assert_eq!(iface.type_name(), "Ethernet");
 	assert!(iface.formatted_speed().ends_with("Mbps"));
+	assert_eq!(iface.formatted_total(), "2.93 KB");
+}
+
+#[test]
+fn test_format_options_unit_systems() {
+	let si = FormatOptions::bytes().with_unit_system(UnitSystem::Si);
+	let iec = FormatOptions::bytes();
+
+	assert_eq!(format_rate(1_000, &si), "1.00 kB/s");
+	assert_eq!(format_rate(1_024, &iec), "1.00 KiB/s");
+	assert_eq!(format_rate(512, &iec), "512 B/s");
+	assert_eq!(format_rate(1_000, &FormatOptions::bits()), "8.00 Kbps");
+	assert_eq!(
+		format_rate(1_024, &FormatOptions::bits().with_unit_system(UnitSystem::Iec)),
+		"8.00 Kibps"
+	);
+}
+
+#[test]
+fn test_format_options_decimal_places() {
+	let options = FormatOptions::new(UnitSystem::Si, 0, DataUnit::Bytes);
+	assert_eq!(format_rate(1_500_000, &options), "2 MB/s");
+
+	let options = options.with_decimal_places(3);
+	assert_eq!(format_rate(1_500_000, &options), "1.500 MB/s");
+}
+
+#[test]
+fn test_format_options_locale() {
+	let french = FormatOptions::bytes().with_unit_system(UnitSystem::Si).with_locale(Locale::french());
+	assert_eq!(format_rate(1_500_000, &french), "1,50\u{202F}Mo/s");
+	assert_eq!(format_rate(512, &french), "512\u{202F}o/s");
+
+	let german = FormatOptions::bits().with_locale(Locale::for_language_tag("de-DE").unwrap());
+	assert_eq!(format_rate(1_000_000, &german), "8,00 Mbit/s");
+
+	let custom = FormatOptions::bytes().with_locale(Locale::english().with_unit_separator("").with_per_second("ps"));
+	assert_eq!(format_rate(2_048, &custom), "2.00KiBps");
+
+	assert_eq!(Locale::for_language_tag("en_US"), Some(Locale::english()));
+	assert_eq!(Locale::for_language_tag("ja-JP"), None);
+}
+
+#[test]
+fn test_format_template() {
+	let speed = NetworkSpeed::new(1_000_000, 2_000_000);
+	let template = FormatTemplate::parse("[{ts:%H%%}] ↑{up:mbps:.1} ↓{down:MB} Σ{total} {{raw {up:B}}}").unwrap();
+	assert_eq!(
+		template.render_with(&speed, |layout| format!("<{layout}>")),
+		"[<%H%%>] ↑8.0 ↓2.00 Σ2.86 MiB/s {raw 1000000}"
+	);
+
+	let clock = speed.format_template("{ts}").unwrap();
+	assert_eq!(clock.len(), 8);
+	assert_eq!(clock.matches(':').count(), 2);
+	assert_eq!(speed.format_template("{down:autobits:.0}").unwrap(), "16 Mbps");
+
+	for invalid in ["{speed}", "{up:furlongs}", "{up:.x}", "{ts:%Q}", "{up", "up}"] {
+		assert!(FormatTemplate::parse(invalid).is_err(), "{invalid} should be rejected");
+	}
+}
+
+#[test]
+fn test_speed_format_with() {
+	let speed = NetworkSpeed::new(1_000_000, 2_000_000);
+	let formatted = speed.format_with(&FormatOptions::bits().with_decimal_places(1));
+
+	assert_eq!(formatted.upload, "8.0 Mbps");
+	assert_eq!(formatted.download, "16.0 Mbps");
+	assert_eq!(formatted.total, "24.0 Mbps");
+	assert_eq!(formatted.to_string(), "↑ 8.0 Mbps ↓ 16.0 Mbps");
+}
+
+#[test]
+fn test_format_bytes_total() {
+	assert_eq!(format_bytes_total(0), "0 B");
+	assert_eq!(format_bytes_total(1536), "1.50 KB");
+	assert_eq!(format_bytes_total(5 * 1024 * 1024 * 1024), "5.00 GB");
+	assert_eq!(format_bytes_total(3 * (1u64 << 40)), "3.00 TB");
+}
+
+#[test]
+fn test_byte_rate_conversions() {
+	let rate = ByteRate::from_bps(8_000_000);
+	assert_eq!(rate.bytes_per_sec(), 1_000_000);
+	assert_eq!(rate.bits_per_sec(), 8_000_000);
+	assert!((rate.as_mbps() - 8.0).abs() < 0.001);
+	assert_eq!(ByteRate::from_mbps(8.0), rate);
+	assert_eq!(ByteRate::from_mbps(-1.0), ByteRate::ZERO);
+	assert_eq!(ByteRate::MAX.bits_per_sec(), u64::MAX);
+}
+
+#[test]
+fn test_byte_rate_ordering_and_arithmetic() {
+	let slow = ByteRate::from(100);
+	let fast = ByteRate::from(1_000);
+
+	assert!(slow < fast);
+	assert_eq!(fast - slow, ByteRate::from(900));
+	assert_eq!(slow - fast, ByteRate::ZERO);
+	assert_eq!(ByteRate::MAX + fast, ByteRate::MAX);
+	assert_eq!(vec![slow, fast].into_iter().sum::<ByteRate>(), 1_100);
+	assert_eq!(fast.to_string(), "1000 B/s");
+}
+
+#[test]
+fn test_speed_classification() {
+	let thresholds = Thresholds::default();
+
+	assert_eq!(NetworkSpeed::new(10, 10).classify(&thresholds), ActivityLevel::Idle);
+	assert_eq!(NetworkSpeed::new(1024, 0).classify(&thresholds), ActivityLevel::Light);
+	assert_eq!(NetworkSpeed::new(100_000, 100_000).classify(&thresholds), ActivityLevel::Moderate);
+	assert_eq!(NetworkSpeed::new(0, 2_000_000).classify(&thresholds), ActivityLevel::Heavy);
+	assert_eq!(NetworkSpeed::new(0, 50_000_000).classify(&thresholds), ActivityLevel::Saturated);
+
+	// A slow uplink gets its own scale: the same upload is heavy there and light in the total.
+	use network_speed::Direction;
+	let uplink = Thresholds::for_link_speed(ByteRate::from_bps(10_000_000));
+	let speed = NetworkSpeed::new(1_000_000, 0);
+	assert_eq!(speed.classify_in(Direction::Upload, &uplink), ActivityLevel::Heavy);
+	assert_eq!(speed.classify_in(Direction::Download, &thresholds), ActivityLevel::Idle);
+	assert_eq!(speed.classify_in(Direction::Total, &thresholds), speed.classify(&thresholds));
+}
+
+#[test]
+fn test_thresholds_for_link_and_validation() {
+	let thresholds = Thresholds::for_link_speed(ByteRate::from_bps(100_000_000));
+	assert_eq!(thresholds.light, 125_000);
+	assert_eq!(thresholds.saturated, 11_250_000);
+	assert!(thresholds.validate().is_ok());
+
+	let inverted = Thresholds::new(
+		ByteRate::from(10),
+		ByteRate::from(5),
+		ByteRate::from(20),
+		ByteRate::from(30)
+	);
+	assert!(inverted.validate().is_err());
+	assert!(Thresholds::for_link_speed(ByteRate::ZERO).validate().is_err());
+}
+
+#[test]
+fn test_percent_change() {
+	let earlier = NetworkSpeed::new(1_000, 1_000);
+	let later = NetworkSpeed::new(1_500, 1_200);
+
+	assert!((later.percent_change(&earlier).unwrap() - 35.0).abs() < 0.001);
+	assert!((later.upload_percent_change(&earlier).unwrap() - 50.0).abs() < 0.001);
+	assert!((earlier.download_percent_change(&later).unwrap() + 16.666).abs() < 0.01);
+	assert!(later.percent_change(&NetworkSpeed::zero()).is_none());
+}
+
+#[test]
+fn test_bytes_transferred_since() {
+	let earlier = NetworkSpeed::new(1_000, 4_000);
+	let later = NetworkSpeed::new(3_000, 4_000);
+
+	let (sent, received) = later.bytes_transferred_since(&earlier, Duration::from_secs(10));
+	assert_eq!(sent, 20_000);
+	assert_eq!(received, 40_000);
+	assert_eq!(later.bytes_transferred_since(&earlier, Duration::ZERO), (0, 0));
+}
+
+#[test]
+fn test_transfer_eta() {
+	let speed = NetworkSpeed::new(0, 4_000);
+
+	assert_eq!(estimate_transfer_time(10_000, 4_000u64), Some(Duration::from_millis(2_500)));
+	assert_eq!(speed.eta_for(1_000_000), Some(Duration::from_secs(250)));
+	assert_eq!(speed.upload_eta_for(1_000), None);
+	assert_eq!(estimate_transfer_time(u64::MAX, 1u64), Some(Duration::from_secs(u64::MAX)));
+	assert_eq!(format_eta(speed.eta_for(1_000_000)), "4m 10s");
+	assert_eq!(format_eta(speed.upload_eta_for(1_000)), "—");
+}
+
+#[test]
+fn test_precise_speed_keeps_fractions() {
+	use std::time::Instant;
+
+	// 3 bytes over 250 ms is 12 B/s; 1 byte over 2 s is half a byte per second.
+	let speed = PreciseSpeed::from_deltas(3, 0, Duration::from_millis(250), Instant::now());
+	assert!((speed.upload_bytes_per_sec - 12.0).abs() < 1e-9);
+	assert_eq!(speed.download_bytes_per_sec, 0.0);
+
+	let trickle = PreciseSpeed::from_deltas(1, 1, Duration::from_secs(2), Instant::now());
+	assert!((trickle.total_bytes_per_sec() - 1.0).abs() < 1e-9);
+	assert!((trickle.upload_kbps() - 0.004).abs() < 1e-12);
+
+	// The integer view truncates, matching `measure_speed`.
+	let rounded: NetworkSpeed = trickle.into();
+	assert_eq!(rounded.upload_bytes_per_sec, ByteRate::ZERO);
+	assert_eq!(rounded.timestamp, trickle.timestamp);
+
+	assert_eq!(PreciseSpeed::from_deltas(10, 10, Duration::ZERO, Instant::now()).total_bytes_per_sec(), 0.0);
+}
+
+#[test]
+fn test_render_sparkline() {
+	assert_eq!(render_sparkline(&[], 10), "");
+	assert_eq!(render_sparkline(&[0, 0, 0], 10), "▁▁▁");
+	assert_eq!(render_sparkline(&[0, 7, 14], 10), "▁▄█");
+	assert_eq!(render_sparkline(&[1, 2, 3, 4, 5, 6, 7, 8], 3).chars().count(), 3);
+	assert_eq!(render_sparkline(&[0, 0, 8, 8], 2), "▁█");
+}
+
+#[test]
+fn test_checkpoint_age_and_reboot_detection() {
+	let checkpoint = MonitorCheckpoint {
+		bytes_sent: 1_000,
+		bytes_received: 2_000,
+		uptime_ms: 60_000,
+		unix_time_ms: 1_700_000_060_000,
+	};
+	assert_eq!(checkpoint.boot_time_ms(), 1_700_000_000_000);
+
+	// Same boot, 30 s later (a little wall-clock drift is tolerated).
+	assert_eq!(checkpoint.age_at(90_000, 1_700_000_090_500), Some(Duration::from_secs(30)));
+	// Uptime went backwards: the machine rebooted.
+	assert_eq!(checkpoint.age_at(10_000, 1_700_000_100_000), None);
+	// Uptime is larger but the derived boot time moved: rebooted and ran longer than before.
+	assert_eq!(checkpoint.age_at(120_000, 1_700_010_000_000), None);
+}
+
+#[cfg(feature = "platform")]
+#[test]
+fn test_interface_categories() {
+	use network_speed::{ traffic_by_category, DetectionSource, InterfaceCategory, NetworkInterface };
+
+	let iface = |description: &str, interface_type: u32, sent: u64| NetworkInterface {
+		index: 1,
+		guid: String::new(),
+		interface_type: InterfaceKind::from(interface_type),
+		physical_medium: 0,
+		description: description.to_string(),
+		driver_description: String::new(),
+		is_operational: true,
+		oper_status: network_speed::OperStatus::Up,
+		bytes_sent: sent,
+		bytes_received: sent * 2,
+		packets: Default::default(),
+		speed: 1_000_000_000,
+	};
+
+	let interfaces = vec![
+		iface("Intel(R) Ethernet Connection", 6, 100),
+		iface("vEthernet (WSL)", 6, 10),
+		iface("vEthernet (Default Switch)", 6, 20),
+		iface("Hyper-V Virtual Ethernet Adapter #2", 6, 30),
+		iface("WireGuard Tunnel", 53, 5),
+		iface("Bluetooth Device (Personal Area Network)", 6, 1),
+		iface("Loopback Pseudo-Interface 1", 24, 0)
+	];
+
+	let categories: Vec<InterfaceCategory> = interfaces
+		.iter()
+		.map(|iface| iface.category())
+		.collect();
+	assert_eq!(categories, vec![
+		InterfaceCategory::Physical,
+		InterfaceCategory::Container,
+		InterfaceCategory::Container,
+		InterfaceCategory::Container,
+		InterfaceCategory::Virtual,
+		InterfaceCategory::Bluetooth,
+		InterfaceCategory::Loopback
+	]);
+	// Container adapters still count as virtual for the existing exclusion filter.
+	assert!(interfaces[1].is_virtual());
+
+	let totals = traffic_by_category(&interfaces);
+	assert_eq!(totals[&InterfaceCategory::Container], (60, 120));
+	assert_eq!(totals[&InterfaceCategory::Physical], (100, 200));
+	assert_eq!(InterfaceCategory::Container.as_str(), "container");
+
+	// A localized Bluetooth PAN adapter is still found through its NDIS physical medium.
+	let localized = NetworkInterface {
+		physical_medium: 10,
+		..iface("Périphérique réseau personnel", 6, 0)
+	};
+	assert_eq!(localized.category(), InterfaceCategory::Bluetooth);
+	assert_eq!(localized.detection_source(), DetectionSource::PhysicalMedium);
+	assert_eq!(interfaces[5].detection_source(), DetectionSource::Description);
+	assert_eq!(interfaces[6].detection_source(), DetectionSource::InterfaceType);
+	assert_eq!(interfaces[0].detection_source(), DetectionSource::Default);
+}
+
+#[cfg(feature = "platform")]
+fn fixture_adapters() -> Vec<(network_speed::InterfaceCategory, network_speed::NetworkInterface)> {
+	use network_speed::{ InterfaceCategory, NetworkInterface };
+
+	include_str!("fixtures/adapters.tsv")
+		.lines()
+		.filter(|line| !line.starts_with('#') && !line.trim().is_empty())
+		.map(|line| {
+			let fields: Vec<&str> = line.split('\t').collect();
+			let category = match fields[0] {
+				"physical" => InterfaceCategory::Physical,
+				"bluetooth" => InterfaceCategory::Bluetooth,
+				"container" => InterfaceCategory::Container,
+				"virtual" => InterfaceCategory::Virtual,
+				"loopback" => InterfaceCategory::Loopback,
+				other => panic!("unknown category {other}"),
+			};
+			let interface = NetworkInterface {
+				index: 1,
+				guid: fields[5].to_string(),
+				interface_type: InterfaceKind::from(fields[1].parse::<u32>().unwrap()),
+				physical_medium: fields[2].parse().unwrap(),
+				description: fields[3].to_string(),
+				driver_description: fields[4].to_string(),
+				is_operational: true,
+				oper_status: network_speed::OperStatus::Up,
+				bytes_sent: 0,
+				bytes_received: 0,
+				packets: Default::default(),
+				speed: 0,
+			};
+			(category, interface)
+		})
+		.collect()
+}
+
+#[cfg(feature = "platform")]
+#[test]
+fn test_classifier_fixtures() {
+	let adapters = fixture_adapters();
+	assert!(adapters.len() >= 20);
+
+	for (expected, interface) in &adapters {
+		assert_eq!(
+			interface.category(),
+			*expected,
+			"{} / {} ({:?})",
+			interface.description,
+			interface.driver_description,
+			interface.detection_source()
+		);
+	}
+}
+
+#[cfg(feature = "platform")]
+#[test]
+fn test_classifier_user_rules() {
+	use network_speed::{ ClassificationRule, Classifier, DetectionSource, InterfaceCategory, RuleMatcher };
+
+	let adapters = fixture_adapters();
+	let external_switch = &adapters
+		.iter()
+		.find(|(_, interface)| interface.description == "Ethernet 5")
+		.unwrap().1;
+	let vpn = &adapters
+		.iter()
+		.find(|(_, interface)| interface.description == "Ethernet 3")
+		.unwrap().1;
+
+	// The external vSwitch carries the host's real traffic, and a USB NIC is used for a VPN appliance.
+	let classifier = Classifier::new()
+		.with_rule(
+			ClassificationRule::new(
+				RuleMatcher::Guid("aaaaaaaa-bbbb-cccc-dddd-eeeeeeeeeeee".to_string()),
+				InterfaceCategory::Physical
+			)
+		)
+		.with_rule(
+			ClassificationRule::new(RuleMatcher::DriverDescription("usb gbe".to_string()), InterfaceCategory::Virtual)
+		);
+
+	let pinned = classifier.classify(external_switch);
+	assert_eq!(pinned.category, InterfaceCategory::Physical);
+	assert_eq!(pinned.source, DetectionSource::Guid);
+	assert!(pinned.user_rule);
+
+	assert_eq!(classifier.classify(vpn).category, InterfaceCategory::Virtual);
+	assert_eq!(external_switch.category(), InterfaceCategory::Container, "built-ins are unaffected");
+
+	let builtin = classifier.classify(&adapters[0].1);
+	assert!(!builtin.user_rule);
+	assert_eq!(builtin.source, DetectionSource::Default);
+}
+
+#[cfg(feature = "platform")]
+#[test]
+fn test_tunnel_detection() {
+	use network_speed::TunnelAccounting;
+
+	let adapters = fixture_adapters();
+	let tunnels: Vec<&str> = adapters
+		.iter()
+		.filter(|(_, interface)| interface.is_tunnel())
+		.map(|(_, interface)| interface.description.as_str())
+		.collect();
+	assert_eq!(tunnels, vec![
+		"ProtonVPN",
+		"wg0",
+		"LAN-Verbindung* 9",
+		"Conexión de área local* 3",
+		"Соединение 4"
+	]);
+
+	assert_eq!(NetworkMonitorConfig::default().tunnel_accounting, TunnelAccounting::CountBoth);
+	let config = NetworkMonitorConfig::builder().tunnel_accounting(TunnelAccounting::PreferPhysical).build().unwrap();
+	assert_eq!(config.tunnel_accounting, TunnelAccounting::PreferPhysical);
+}
+
+#[cfg(feature = "platform")]
+#[test]
+fn test_select_primary() {
+	use network_speed::{ select_primary, InterfaceCategory, OperStatus };
+
+	let mut interfaces: Vec<_> = fixture_adapters()
+		.into_iter()
+		.enumerate()
+		.map(|(position, (_, mut interface))| {
+			interface.index = position as u32 + 1;
+			interface.bytes_received = 1_000;
+			interface
+		})
+		.collect();
+	assert!(select_primary(&[]).is_none());
+
+	let wifi = interfaces.iter().position(|interface| interface.description == "Wi-Fi 2").unwrap();
+	interfaces[wifi].bytes_received = 50_000;
+	let vpn = interfaces.iter().position(|interface| interface.description == "ProtonVPN").unwrap();
+	interfaces[vpn].bytes_received = 90_000;
+	assert_eq!(select_primary(&interfaces).unwrap().description, "Wi-Fi 2");
+
+	interfaces[wifi].oper_status = OperStatus::Down;
+	let primary = select_primary(&interfaces).unwrap();
+	assert_ne!(primary.description, "Wi-Fi 2");
+	assert_eq!(primary.category(), InterfaceCategory::Physical);
+
+	if cfg!(windows) {
+		if let Some(primary) = network_speed::primary_interface().unwrap() {
+			let found = network_speed::find_by_guid(&primary.guid.to_lowercase()).unwrap().unwrap();
+			assert_eq!(found.index, primary.index);
+			assert!(network_speed::find_interface_by_name(&primary.description).unwrap().is_some());
+		}
+	}
+}
+
+#[cfg(feature = "platform")]
+#[test]
+fn test_rank_interfaces() {
+	use network_speed::{ rank_interfaces, SortBy };
+
+	let earlier: Vec<_> = fixture_adapters()
+		.into_iter()
+		.take(4)
+		.enumerate()
+		.map(|(position, (_, mut interface))| {
+			interface.index = position as u32 + 1;
+			interface.bytes_received = 10_000;
+			interface.speed = 100_000_000 * (position as u64 + 1);
+			interface
+		})
+		.collect();
+	let mut current = earlier.clone();
+	current[1].bytes_received += 500;
+	current[3].bytes_received += 9_000;
+	current[2].index = 99;
+
+	let by_throughput = rank_interfaces(current.clone(), &earlier, SortBy::Throughput);
+	let indices: Vec<u32> = by_throughput.iter().map(|interface| interface.index).collect();
+	assert_eq!(indices, vec![4, 2, 1, 99]);
+
+	let by_speed = rank_interfaces(current.clone(), &[], SortBy::LinkSpeed);
+	assert_eq!(by_speed[0].index, 4);
+
+	let by_name = rank_interfaces(current, &[], SortBy::Name);
+	let names: Vec<String> = by_name.iter().map(|interface| interface.description.to_lowercase()).collect();
+	let mut sorted = names.clone();
+	sorted.sort();
+	assert_eq!(names, sorted);
+
+	if cfg!(windows) {
+		assert!(!network_speed::list_interfaces_sorted(SortBy::Throughput).unwrap().is_empty());
+	}
+}
+
+#[cfg(feature = "platform")]
+#[test]
+fn test_cached_interface_retention() {
+	use network_speed::{ CachedInterface, NetworkInterface };
+	use std::time::Instant;
+
+	let config = NetworkMonitorConfig::builder().interface_retention(Duration::from_secs(30)).build().unwrap();
+	assert_eq!(config.interface_retention, Duration::from_secs(30));
+	assert_eq!(NetworkMonitorConfig::default().interface_retention, Duration::ZERO);
+
+	let now = Instant::now();
+	let entry = |missing_since: Option<Instant>| CachedInterface {
+		interface: NetworkInterface {
+			index: 4,
+			guid: String::new(),
+			interface_type: InterfaceKind::Wifi,
+			physical_medium: 0,
+			description: "Wi-Fi".to_string(),
+			driver_description: String::new(),
+			is_operational: true,
+			oper_status: network_speed::OperStatus::Up,
+			bytes_sent: 0,
+			bytes_received: 0,
+			packets: Default::default(),
+			speed: 0,
+		},
+		last_seen: now,
+		missing_since,
+	};
+
+	let present = entry(None);
+	assert!(present.is_present());
+	assert!(!present.is_expired(now + Duration::from_secs(3600), Duration::ZERO));
+
+	let missing = entry(Some(now));
+	assert!(!missing.is_present());
+	assert!(!missing.is_expired(now + Duration::from_secs(10), config.interface_retention));
+	assert!(missing.is_expired(now + Duration::from_secs(31), config.interface_retention));
+	assert!(missing.is_expired(now + Duration::from_millis(1), Duration::ZERO));
+	assert!(missing.is_expired(now, Duration::ZERO));
+}
+
+#[cfg(feature = "platform")]
+#[test]
+fn test_filter_priority() {
+	use network_speed::{ FilterPriority, InterfaceManager, NetworkInterface };
+
+	let iface = |index: u32, description: &str| NetworkInterface {
+		index,
+		guid: String::new(),
+		interface_type: InterfaceKind::Ethernet,
+		physical_medium: 0,
+		description: description.to_string(),
+		driver_description: String::new(),
+		is_operational: true,
+		oper_status: network_speed::OperStatus::Up,
+		bytes_sent: 0,
+		bytes_received: 0,
+		packets: Default::default(),
+		speed: 0,
+	};
+	let ethernet = iface(1, "Intel(R) Ethernet Connection");
+	let wireguard = iface(2, "WireGuard Tunnel");
+	let vpn = iface(3, "OpenVPN TAP-Windows6");
+
+	let base = NetworkMonitorConfig::builder().include_interface_name_patterns(vec!["wireguard".to_string()]);
+
+	// Default: the include list restricts, so Ethernet is dropped and WireGuard is still virtual.
+	let exclude_wins = InterfaceManager::new(base.build_unchecked());
+	assert!(!exclude_wins.should_include_interface(&ethernet));
+	assert!(!exclude_wins.should_include_interface(&wireguard));
+
+	// "Exclude virtual except the WireGuard tunnel".
+	let include_wins = InterfaceManager::new(
+		NetworkMonitorConfig::builder()
+			.include_interface_name_patterns(vec!["wireguard".to_string()])
+			.filter_priority(FilterPriority::IncludeWins)
+			.build()
+			.unwrap()
+	);
+	assert!(include_wins.should_include_interface(&ethernet));
+	assert!(include_wins.should_include_interface(&wireguard));
+	assert!(!include_wins.should_include_interface(&vpn));
+
+	let by_index = InterfaceManager::new(
+		NetworkMonitorConfig::new()
+			.with_include_interface_indices(vec![3])
+			.with_filter_priority(FilterPriority::IncludeWins)
+	);
+	assert!(by_index.should_include_interface(&vpn));
+
+	let dormant = NetworkInterface {
+		is_operational: false,
+		oper_status: network_speed::OperStatus::Dormant,
+		..ethernet.clone()
+	};
+	assert!(!InterfaceManager::new(NetworkMonitorConfig::default()).should_include_interface(&dormant));
+	let with_dormant = InterfaceManager::new(NetworkMonitorConfig::default().with_include_dormant_interfaces(true));
+	assert!(with_dormant.should_include_interface(&dormant));
+	assert!(!by_index.should_include_interface(&wireguard));
+}
+
+#[cfg(feature = "platform")]
+#[test]
+fn test_filter_reasons() {
+	use network_speed::{ FilterPriority, FilterReason, InterfaceManager, NetworkInterface };
+
+	let iface = |index: u32, description: &str, interface_type: InterfaceKind| NetworkInterface {
+		index,
+		guid: String::new(),
+		interface_type,
+		physical_medium: 0,
+		description: description.to_string(),
+		driver_description: String::new(),
+		is_operational: true,
+		oper_status: network_speed::OperStatus::Up,
+		bytes_sent: 0,
+		bytes_received: 0,
+		packets: Default::default(),
+		speed: 0,
+	};
+	let ethernet = iface(1, "Intel(R) Ethernet Connection", InterfaceKind::Ethernet);
+	let wireguard = iface(2, "WireGuard Tunnel", InterfaceKind::Other(53));
+	let loopback = iface(3, "Loopback Pseudo-Interface 1", InterfaceKind::Loopback);
+	let wwan = iface(4, "Cellular", InterfaceKind::Wwan);
+	let hamachi = iface(5, "Hamachi Network Interface", InterfaceKind::Ethernet);
+
+	let manager = InterfaceManager::new(
+		NetworkMonitorConfig::new()
+			.add_interface_filter("hamachi".to_string())
+			.add_type_filter(InterfaceKind::Wwan)
+	);
+	assert_eq!(manager.filter_reason(&ethernet), FilterReason::Selected);
+	assert_eq!(manager.filter_reason(&wireguard), FilterReason::Virtual);
+	assert_eq!(manager.filter_reason(&loopback), FilterReason::Loopback);
+	assert_eq!(manager.filter_reason(&wwan), FilterReason::InterfaceType(InterfaceKind::Wwan));
+	assert_eq!(manager.filter_reason(&hamachi), FilterReason::NameFilter("hamachi".to_string()));
+	assert_eq!(manager.filter_reason(&hamachi).to_string(), "excluded: name filter \"hamachi\"");
+
+	let include_wins = InterfaceManager::new(
+		NetworkMonitorConfig::new()
+			.with_include_interface_indices(vec![2])
+			.with_filter_priority(FilterPriority::IncludeWins)
+	);
+	assert_eq!(include_wins.filter_reason(&wireguard), FilterReason::IncludeList);
+	assert!(include_wins.filter_reason(&wireguard).is_included());
+
+	let restricted = InterfaceManager::new(NetworkMonitorConfig::new().with_include_interface_indices(vec![2]));
+	assert_eq!(restricted.filter_reason(&ethernet), FilterReason::NotInIncludeList);
+
+	if cfg!(windows) {
+		let decisions = network_speed::NetworkMonitor::new().explain_interfaces().unwrap();
+		assert_eq!(decisions.len(), network_speed::get_interface_count().unwrap());
+	}
+}
+
+#[cfg(feature = "platform")]
+#[test]
+fn test_interface_stack_accounting() {
+	use network_speed::{ InterfaceStack, StackAccounting, StackLink };
+
+	// Team 10 over members 11 and 12; VLAN 20 over NIC 21; zero entries mark stack ends.
+	let stack = InterfaceStack::from_links([
+		StackLink { upper: 0, lower: 10 },
+		StackLink { upper: 10, lower: 11 },
+		StackLink { upper: 10, lower: 12 },
+		StackLink { upper: 20, lower: 21 },
+		StackLink { upper: 21, lower: 0 },
+	]);
+	assert_eq!(stack.links().len(), 3);
+	assert_eq!(stack.children(10).collect::<Vec<_>>(), vec![11, 12]);
+	assert_eq!(stack.parents(21).collect::<Vec<_>>(), vec![20]);
+
+	let selected = [10, 11, 12, 21, 30];
+	assert!(stack.shadowed(&selected, StackAccounting::CountAll).is_empty());
+	assert_eq!(stack.shadowed(&selected, StackAccounting::PreferUpper), vec![11, 12]);
+	assert_eq!(stack.shadowed(&selected, StackAccounting::PreferLower), vec![10]);
+	assert_eq!(stack.shadowed(&[20, 21], StackAccounting::PreferLower), vec![20]);
+
+	// Layers in between that are not selected are followed through.
+	let nested = InterfaceStack::from_links([StackLink { upper: 1, lower: 2 }, StackLink { upper: 2, lower: 3 }]);
+	assert_eq!(nested.shadowed(&[1, 3], StackAccounting::PreferUpper), vec![3]);
+
+	let config = NetworkMonitorConfig::builder().stack_accounting(StackAccounting::PreferLower).build().unwrap();
+	assert_eq!(config.stack_accounting, StackAccounting::PreferLower);
+	assert_eq!(NetworkMonitorConfig::default().stack_accounting, StackAccounting::CountAll);
+}
+
+#[test]
+fn test_traffic_class_usage() {
+	use network_speed::{ TrafficClassCounters, TrafficClassUsage };
+
+	let counters = |class: &str, bytes, packets| TrafficClassCounters {
+		class: class.to_string(),
+		bytes_transmitted: bytes,
+		packets_transmitted: packets,
+	};
+	let earlier = [counters("nic:voice", 1_000, 10), counters("nic:backup", 5_000, 5)];
+	let current = [counters("nic:voice", 4_000, 40), counters("nic:backup", 100, 1), counters("nic:video", 2_000, 2)];
+
+	let breakdown = TrafficClassUsage::between(&earlier, &current, Duration::from_secs(1));
+	let classes: Vec<_> = breakdown.classes
+		.iter()
+		.map(|class| (class.class.as_str(), class.bytes, class.packets))
+		.collect();
+	// Recreated flows count from zero; new flows count in full; sorted by bytes.
+	assert_eq!(classes, vec![("nic:voice", 3_000, 30), ("nic:video", 2_000, 2), ("nic:backup", 0, 0)]);
+
+	assert_eq!(breakdown.prioritized_bytes(), 5_000);
+	assert_eq!(breakdown.best_effort_bytes(20_000), 15_000);
+	assert_eq!(breakdown.best_effort_bytes(1_000), 0);
+	assert_eq!(breakdown.prioritized_share(20_000), Some(0.25));
+	assert_eq!(breakdown.prioritized_share(0), None);
+}
+
+#[cfg(all(feature = "platform", windows))]
+#[test]
+fn test_offload_state_keywords() {
+	use network_speed::OffloadState;
+
+	assert_eq!(OffloadState::from_keyword("0"), Some(OffloadState::Disabled));
+	assert_eq!(OffloadState::from_keyword(" 3 "), Some(OffloadState::TxRxEnabled));
+	assert_eq!(OffloadState::from_keyword("4"), None);
+	assert_eq!(OffloadState::from_keyword(""), None);
+
+	assert!(OffloadState::TxEnabled.transmit() && !OffloadState::TxEnabled.receive());
+	assert!(OffloadState::RxEnabled.receive() && !OffloadState::RxEnabled.transmit());
+	assert!(OffloadState::TxRxEnabled.transmit() && OffloadState::TxRxEnabled.receive());
+}
+
+#[cfg(all(feature = "platform", windows))]
+#[test]
+fn test_neighbor_state_and_mac() {
+	use network_speed::{ format_mac, NeighborState };
+
+	assert_eq!(NeighborState::from_raw(5), NeighborState::Reachable);
+	assert_eq!(NeighborState::from_raw(9), NeighborState::Unknown(9));
+	assert!(NeighborState::Stale.is_resolved());
+	assert!(!NeighborState::Incomplete.is_resolved());
+	assert_eq!(NeighborState::Permanent.as_str(), "permanent");
+
+	assert_eq!(format_mac(&[0x00, 0x1a, 0x2b, 0x3c, 0x4d, 0x5e]).as_deref(), Some("00-1A-2B-3C-4D-5E"));
+	assert_eq!(format_mac(&[0; 6]), None);
+	assert_eq!(format_mac(&[]), None);
+}
+
+#[cfg(feature = "platform")]
+#[test]
+fn test_connection_state_and_speeds() {
+	use network_speed::{ connection_speeds, Connection, ConnectionBytes, ConnectionProtocol, TcpState };
+
+	assert_eq!(TcpState::from_raw(1), TcpState::Closed);
+	assert_eq!(TcpState::from_raw(5), TcpState::Established);
+	assert_eq!(TcpState::from_raw(12), TcpState::DeleteTcb);
+	assert_eq!(TcpState::from_raw(0), TcpState::Unknown(0));
+	assert_eq!(TcpState::from_raw(42), TcpState::Unknown(42));
+	assert_eq!(TcpState::TimeWait.as_str(), "time-wait");
+	assert_eq!(ConnectionProtocol::Udp.as_str(), "udp");
+
+	let connection = |remote: &str, pid, bytes: Option<(u64, u64)>| Connection {
+		protocol: ConnectionProtocol::Tcp,
+		local: "10.0.0.2:50000".parse().unwrap(),
+		remote: Some(remote.parse().unwrap()),
+		state: Some(TcpState::Established),
+		pid,
+		bytes: bytes.map(|(bytes_sent, bytes_received)| ConnectionBytes { bytes_sent, bytes_received }),
+	};
+	let earlier = [
+		connection("1.1.1.1:443", 10, Some((1_000, 10_000))),
+		connection("8.8.8.8:443", 20, Some((0, 0))),
+		connection("9.9.9.9:443", 30, None),
+		connection("4.4.4.4:443", 40, Some((7_000, 7_000))),
+		connection("5.5.5.5:443", 50, Some((0, 0))),
+		connection("6.6.6.6:443", 60, Some((0, 0))),
+	];
+	let later = [
+		connection("1.1.1.1:443", 10, Some((2_000, 30_000))),
+		connection("8.8.8.8:443", 20, Some((500_000, 0))),
+		// Same endpoints, another process: not the same connection.
+		connection("9.9.9.9:443", 31, Some((1, 1))),
+		// Counters went backwards (socket address reused): zero, not a wrapped-around huge rate.
+		connection("4.4.4.4:443", 40, Some((100, 6_000))),
+		// Collection turned off in between: skipped.
+		connection("5.5.5.5:443", 50, None),
+		connection("6.6.6.6:443", 60, Some((2, 0))),
+	];
+
+	let speeds = connection_speeds(&earlier, &later, Duration::from_secs(2));
+	let pids: Vec<u32> = speeds.iter().map(|entry| entry.connection.pid).collect();
+	assert_eq!(pids, [20, 10, 60, 40]);
+	assert_eq!(speeds[0].speed.upload_bytes_per_sec, 250_000.0);
+	assert_eq!(speeds[1].speed.upload_bytes_per_sec, 500.0);
+	assert_eq!(speeds[1].speed.download_bytes_per_sec, 10_000.0);
+	assert_eq!(speeds[2].speed.upload_bytes_per_sec, 1.0);
+	assert_eq!(speeds[3].speed.total_bytes_per_sec(), 0.0);
+
+	assert!(connection_speeds(&earlier, &[], Duration::from_secs(2)).is_empty());
+}
+
+#[cfg(feature = "platform")]
+#[test]
+fn test_top_remotes_from_speeds() {
+	use network_speed::{ Connection, ConnectionProtocol, ConnectionSpeed, PreciseSpeed, TcpState, TopRemotes };
+	use std::time::Instant;
+
+	let speed = |remote: Option<&str>, upload, download| ConnectionSpeed {
+		connection: Connection {
+			protocol: ConnectionProtocol::Tcp,
+			local: "10.0.0.2:50000".parse().unwrap(),
+			remote: remote.map(|remote| remote.parse().unwrap()),
+			state: Some(TcpState::Established),
+			pid: 10,
+			bytes: None,
+		},
+		speed: PreciseSpeed::from_deltas(upload, download, Duration::from_secs(1), Instant::now()),
+	};
+	let speeds = [
+		speed(Some("1.1.1.1:443"), 100, 1_000),
+		speed(Some("1.1.1.1:443"), 100, 1_000),
+		speed(Some("1.1.1.1:80"), 0, 1_500),
+		speed(Some("8.8.8.8:443"), 3_000, 0),
+		speed(Some("9.9.9.9:53"), 10, 10),
+		speed(None, 1_000_000, 0),
+	];
+
+	let top = TopRemotes::from_speeds(&speeds, 2);
+	let endpoints: Vec<_> = top.endpoints.iter().map(|remote| (remote.addr().unwrap(), remote.connections)).collect();
+	assert_eq!(endpoints, [("8.8.8.8:443".parse().unwrap(), 1), ("1.1.1.1:443".parse().unwrap(), 2)]);
+	assert_eq!(top.endpoints[1].speed.upload_bytes_per_sec, 200.0);
+	assert_eq!(top.endpoints[1].speed.download_bytes_per_sec, 2_000.0);
+
+	// Per IP, 1.1.1.1 combines both ports and overtakes 8.8.8.8.
+	assert_eq!(top.ips.len(), 2);
+	assert_eq!(top.ips[0].ip.to_string(), "1.1.1.1");
+	assert_eq!(top.ips[0].port, None);
+	assert_eq!(top.ips[0].connections, 3);
+	assert_eq!(top.ips[0].speed.total_bytes_per_sec(), 3_700.0);
+	assert_eq!(top.ips[1].ip.to_string(), "8.8.8.8");
+
+	assert!(TopRemotes::from_speeds(&speeds, 0).endpoints.is_empty());
+}
+
+#[cfg(feature = "platform")]
+#[test]
+fn test_connection_raw_address_byte_order() {
+	use network_speed::{ ipv4_from_raw, port_from_raw };
+	use std::net::Ipv4Addr;
+
+	// Rows hold both in network byte order, read into a native `u32`.
+	assert_eq!(port_from_raw(u32::from(443u16.to_be())), 443);
+	assert_eq!(port_from_raw(u32::from(0x1f90u16.to_be())), 8080);
+	// Only the low 16 bits carry the port.
+	assert_eq!(port_from_raw(0xabcd_0000 | u32::from(53u16.to_be())), 53);
+
+	assert_eq!(ipv4_from_raw(u32::from_ne_bytes([192, 168, 1, 10])), Ipv4Addr::new(192, 168, 1, 10));
+	assert_eq!(ipv4_from_raw(u32::from_ne_bytes([127, 0, 0, 1])), Ipv4Addr::LOCALHOST);
+}
+
+#[cfg(all(feature = "platform", windows))]
+#[test]
+fn test_route_prefix_match() {
+	use network_speed::Route;
+
+	let route = |destination: &str, prefix_length| Route {
+		destination: destination.parse().unwrap(),
+		prefix_length,
+		next_hop: None,
+		interface_index: 1,
+		metric: 0,
+	};
+
+	let lan = route("192.168.1.0", 24);
+	assert!(lan.contains("192.168.1.42".parse().unwrap()));
+	assert!(!lan.contains("192.168.2.1".parse().unwrap()));
+	assert!(!lan.contains("::1".parse().unwrap()));
+
+	let odd = route("10.0.0.0", 13);
+	assert!(odd.contains("10.7.255.255".parse().unwrap()));
+	assert!(!odd.contains("10.8.0.0".parse().unwrap()));
+
+	let default = route("0.0.0.0", 0);
+	assert!(default.is_default() && default.contains("8.8.8.8".parse().unwrap()));
+	assert!(route("fe80::", 64).contains("fe80::1".parse().unwrap()));
+}
+
+#[cfg(all(feature = "platform", target_os = "linux"))]
+#[test]
+fn test_parse_proc_net_dev() {
+	use network_speed::parse_proc_net_dev;
+
+	let contents = "\
+Inter-|   Receive                                                |  Transmit
+ face |bytes    packets errs drop fifo frame compressed multicast|bytes    packets errs drop fifo colls carrier compressed
+    lo: 40804687    6498    0    0    0     0          0         0 40804687    6498    0    0    0     0       0          0
+  eth0: 33653252    2597    1    2    0     0          0        12   216379    2682    3    4    0     0       0          0
+wlan0:18446744073709551615 7 0 0 0 0 0 0 5 1
+";
+	let devices = parse_proc_net_dev(contents);
+	assert_eq!(devices.len(), 3);
+
+	assert_eq!(devices[0].name, "lo");
+	assert_eq!(devices[0].bytes_received, 40_804_687);
+	assert_eq!(devices[0].bytes_sent, 40_804_687);
+
+	let eth0 = &devices[1];
+	assert_eq!((eth0.bytes_received, eth0.packets_received), (33_653_252, 2_597));
+	assert_eq!((eth0.receive_errors, eth0.receive_drops, eth0.multicast_received), (1, 2, 12));
+	assert_eq!((eth0.bytes_sent, eth0.packets_sent), (216_379, 2_682));
+	assert_eq!((eth0.send_errors, eth0.send_drops), (3, 4));
+
+	// Old kernels print no space after the colon; truncated lines read the missing columns as zero.
+	assert_eq!(devices[2].name, "wlan0");
+	assert_eq!(devices[2].bytes_received, u64::MAX);
+	assert_eq!((devices[2].bytes_sent, devices[2].packets_sent, devices[2].send_drops), (5, 1, 0));
 }